@@ -0,0 +1,83 @@
+use entities::{auth::RoleChange, prelude::*, user};
+use rocket::{http::Status, serde::json::Json, State};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, DatabaseConnection, EntityTrait, QueryOrder, TransactionTrait,
+};
+
+use super::{audit, auth::Admin, error::ApiError};
+
+/// Get all users with their roles, ordered by id
+#[utoipa::path(
+    context_path = "/admin",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Every user", body = [User]),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[get("/user")]
+pub async fn get_users(
+    db: &State<DatabaseConnection>,
+    _admin: Admin,
+) -> Result<(Status, Json<Vec<user::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let users = User::find().order_by_asc(user::Column::Id).all(db).await?;
+
+    info!("Found {} users", users.len());
+    Ok((Status::Ok, Json(users)))
+}
+
+/// Change the role of the user with the given id
+///
+/// This endpoint requires a JSON body containing a [`RoleChange`] and returns the updated
+/// [`user::Model`]. Admins cannot change their own role, so that there is always an admin left.
+#[utoipa::path(
+    context_path = "/admin",
+    tag = "admin",
+    request_body = RoleChange,
+    responses(
+        (status = 200, description = "The updated user", body = User),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+        (status = 409, description = "Admins cannot change their own role", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/user/<id>/role", data = "<change>")]
+pub async fn set_role(
+    db: &State<DatabaseConnection>,
+    admin: Admin,
+    id: i32,
+    change: Json<RoleChange>,
+) -> Result<(Status, Json<user::Model>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    if admin.0.user.id == id {
+        info!("Admin with id {} tried to change their own role", id);
+        return Err(ApiError::Conflict(
+            "Admins cannot change their own role".to_string(),
+        ));
+    }
+
+    let before = User::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("User"))?;
+
+    let mut updated_user: user::ActiveModel = before.clone().into();
+    updated_user.role = ActiveValue::Set(change.role);
+
+    let txn = db.begin().await?;
+
+    let user = updated_user.update(&txn).await?;
+    audit::updated(&txn, Some(&admin.0.user), id, &before, &user).await?;
+
+    txn.commit().await?;
+
+    info!("Set the role of user with id {} to {:?}", id, user.role);
+    Ok((Status::Ok, Json(user)))
+}
@@ -1,104 +1,209 @@
-use chrono::Local;
-use entities::{album, album_artist_mtm, artist, prelude::*, song};
-use rocket::{http::Status, serde::json::Json, State};
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDate;
+use entities::{
+    album, album_artist_mtm, album_cover,
+    album_label::{self, AlbumRelease},
+    artist, genre,
+    pagination::Page,
+    prelude::*,
+    review::RatedAlbum,
+    song, user,
+    validation::Validate,
+};
+use rocket::{
+    form::Form,
+    fs::TempFile,
+    http::{uri::Origin, ContentType, Status},
+    serde::json::Json,
+    tokio::task,
+    State,
+};
 use sea_orm::{
+    sea_query::{Expr, Query},
     ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::{
+    audit,
+    auth::{Admin, Editor},
+    cover::{self, Cover, CoverSize},
+    error::{ApiError, FieldError},
+    genre_api,
+    label_api::{self, LabelLink},
+    query::{fetch_page, DateParam, Direction, PageParams},
+    review_api,
+    storage::{self, read_upload, Storage, StoredFile},
+    trash::{self, Live},
+    version::{stale, IfMatch, WithETag, INITIAL_VERSION},
 };
 
+/// Check the fields of an album
+fn validate(album: &album::Model) -> Result<(), ApiError> {
+    album.validate().map_err(|errors| {
+        info!("Invalid input");
+        ApiError::Validation(errors)
+    })
+}
+
 /// Create a new album for the given artist
 ///
 /// This endpoint requires a JSON body containing an [`album::Model`] without an id.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    request_body = Album,
+    responses(
+        (status = 201, description = "Id of the created album", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Artist not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
 #[post("/<artist_id>", data = "<album>")]
 pub async fn create_album(
     db: &State<DatabaseConnection>,
+    editor: Editor,
     album: Json<album::Model>,
     artist_id: i32,
-) -> Result<(Status, String), (Status, &str)> {
+) -> Result<(Status, String), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let artist = Artist::find_by_id(artist_id)
-        .one(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching artist in DB"))?;
-
-    if album.name.is_empty() || album.date_published > Local::now().date_naive() || artist.is_none()
-    {
-        info!("Invalid input");
-        return Err((Status::BadRequest, "Invalid input"));
+    let artist = Artist::find_live_by_id(artist_id).one(db).await?;
+    if artist.is_none() {
+        info!("Artist not found");
+        return Err(ApiError::not_found("Artist"));
     }
 
+    validate(&album)?;
+
+    // The album and its link to the artist are inserted together or not at all
+    let txn = db.begin().await?;
+
     let new_album = album::ActiveModel {
         name: ActiveValue::Set(album.name.to_owned()),
         date_published: ActiveValue::Set(album.date_published),
         ..Default::default()
     };
 
-    let album = Album::insert(new_album)
-        .exec(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error inserting album in DB"))?;
+    let id = Album::insert(new_album).exec(&txn).await?.last_insert_id;
+    let album = album::Model {
+        id,
+        version: INITIAL_VERSION,
+        ..album.into_inner()
+    };
 
-    info!("Inserted album with id: {}", album.last_insert_id);
+    info!("Inserted album with id: {}", id);
 
     let new_artist_album = album_artist_mtm::ActiveModel {
         artist_id: ActiveValue::Set(artist_id),
-        album_id: ActiveValue::Set(album.last_insert_id),
+        album_id: ActiveValue::Set(id),
+        position: ActiveValue::Set(0),
         ..Default::default()
     };
 
-    let artist_album = AlbumArtistMtm::insert(new_artist_album)
-        .exec(db)
-        .await
-        .map_err(|_| {
-            (
-                Status::InternalServerError,
-                "Error inserting artist_album in DB",
-            )
-        })?;
+    let link_id = AlbumArtistMtm::insert(new_artist_album)
+        .exec(&txn)
+        .await?
+        .last_insert_id;
+    let artist_album = album_artist_mtm::Model {
+        id: link_id,
+        album_id: id,
+        artist_id,
+        position: 0,
+        deleted_at: None,
+    };
 
-    info!(
-        "Inserted album-artist relation with id: {}",
-        artist_album.last_insert_id
-    );
+    audit::created(&txn, Some(&editor.0.user), id, &album).await?;
+    audit::created(&txn, Some(&editor.0.user), link_id, &artist_album).await?;
+
+    txn.commit().await?;
+
+    info!("Inserted album-artist relation with id: {}", link_id);
 
-    Ok((Status::Created, album.last_insert_id.to_string()))
+    Ok((Status::Created, id.to_string()))
 }
 
 /// Get an album by its id
 ///
-/// This endpoint returns an [`album::Model`] with the given id, if it exists.
+/// This endpoint returns a [`RatedAlbum`] with the given id, if it exists: the album along with
+/// the average of its ratings.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    responses(
+        (status = 302, description = "The album", body = RatedAlbum,
+            headers(("ETag" = String, description = "Version of the album"))),
+        (status = 404, description = "Album not found", body = ErrorBody)
+    )
+)]
 #[get("/<id>")]
 pub async fn get_album_by_id(
     db: &State<DatabaseConnection>,
     id: i32,
-) -> Result<(Status, Json<album::Model>), (Status, &str)> {
+) -> Result<(Status, WithETag<Json<RatedAlbum>>), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let album = Album::find_by_id(id)
-        .one(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching album in DB"))?;
+    let album = Album::find_live_by_id(id).one(db).await?;
 
-    let album = album.ok_or((Status::NotFound, "Album not found"))?;
+    let album = album.ok_or_else(|| ApiError::not_found("Album"))?;
+    let ratings = review_api::album_ratings(db, id).await?;
     info!("Found album with id: {}", album.id);
-    Ok((Status::Found, Json(album)))
+    Ok((
+        Status::Found,
+        WithETag(album.version, Json(RatedAlbum { album, ratings })),
+    ))
 }
 
 /// Update an album with the given id
 ///
 /// This endpoint requires a JSON body containing a [`album::Model`] with all of the fields set.
+/// The album must be valid, as on creation.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    request_body = Album,
+    responses(
+        (status = 202, description = "Id of the updated album", body = String,
+            headers(("ETag" = String, description = "New version of the album"))),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Album not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody),
+        (status = 412, description = "Album changed since the given version", body = ErrorBody),
+        (status = 428, description = "If-Match header required", body = ErrorBody)
+    ),
+    params(("If-Match" = String, Header, description = "ETag of the album being changed")),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
 #[put("/<id>", data = "<album>")]
 pub async fn update_album(
     db: &State<DatabaseConnection>,
+    editor: Editor,
+    if_match: IfMatch,
     id: i32,
     album: Json<album::Model>,
-) -> Result<(Status, String), (Status, &str)> {
+) -> Result<(Status, WithETag<String>), ApiError> {
     let db = db as &DatabaseConnection;
 
+    let before = Album::find_live_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Album"))?;
+    if_match.check("Album", before.version)?;
+
+    validate(&album)?;
+
     let updated_album = album::ActiveModel {
         id: ActiveValue::Set(id),
         name: ActiveValue::Set(album.name.to_owned()),
         date_published: ActiveValue::Set(album.date_published.to_owned()),
+        version: ActiveValue::Set(before.version + 1),
         ..Default::default()
     };
 
@@ -107,155 +212,364 @@ pub async fn update_album(
         id, updated_album
     );
 
-    updated_album
-        .update(db)
+    let txn = db.begin().await?;
+
+    let after = Album::update(updated_album)
+        .filter(album::Column::Version.eq(before.version))
+        .exec(&txn)
         .await
-        .map_err(|_| (Status::InternalServerError, "Error updating album"))?;
+        .map_err(stale("Album"))?;
+    audit::updated(&txn, Some(&editor.0.user), id, &before, &after).await?;
+
+    txn.commit().await?;
 
     info!("Updated album with id: {}", id);
 
-    Ok((Status::Accepted, id.to_string()))
+    Ok((Status::Accepted, WithETag(after.version, id.to_string())))
+}
+
+/// Fields of an album to change, every missing field is left as is
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct AlbumPatch {
+    pub name: Option<String>,
+    pub date_published: Option<NaiveDate>,
+}
+
+/// Partially update an album with the given id
+///
+/// This endpoint requires a JSON body containing an [`AlbumPatch`] and returns the updated
+/// [`album::Model`]. The album resulting from the patch must be valid, as on creation.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    request_body = AlbumPatch,
+    responses(
+        (status = 200, description = "The updated album", body = Album,
+            headers(("ETag" = String, description = "New version of the album"))),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Album not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody),
+        (status = 412, description = "Album changed since the given version", body = ErrorBody),
+        (status = 428, description = "If-Match header required", body = ErrorBody)
+    ),
+    params(("If-Match" = String, Header, description = "ETag of the album being changed")),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[patch("/<id>", data = "<patch>")]
+pub async fn patch_album(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    if_match: IfMatch,
+    id: i32,
+    patch: Json<AlbumPatch>,
+) -> Result<(Status, WithETag<Json<album::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let album = Album::find_live_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Album"))?;
+    if_match.check("Album", album.version)?;
+    let patch = patch.into_inner();
+
+    let mut merged = album.clone();
+    if let Some(name) = &patch.name {
+        merged.name = name.to_owned();
+    }
+    if let Some(date_published) = patch.date_published {
+        merged.date_published = date_published;
+    }
+    validate(&merged)?;
+
+    let mut updated_album: album::ActiveModel = album.clone().into();
+    if let Some(name) = patch.name {
+        updated_album.name = ActiveValue::Set(name);
+    }
+    if let Some(date_published) = patch.date_published {
+        updated_album.date_published = ActiveValue::Set(date_published);
+    }
+
+    if !updated_album.is_changed() {
+        info!("Nothing to update for album with id: {}", id);
+        return Ok((Status::Ok, WithETag(merged.version, Json(merged))));
+    }
+
+    updated_album.version = ActiveValue::Set(album.version + 1);
+
+    info!("About to patch album with id {}: {:?}", id, updated_album);
+
+    let txn = db.begin().await?;
+
+    let patched = Album::update(updated_album)
+        .filter(album::Column::Version.eq(album.version))
+        .exec(&txn)
+        .await
+        .map_err(stale("Album"))?;
+    audit::updated(&txn, Some(&editor.0.user), id, &album, &patched).await?;
+
+    txn.commit().await?;
+
+    info!("Patched album with id: {}", id);
+    Ok((Status::Ok, WithETag(patched.version, Json(patched))))
 }
 
-/// Delete an album with the given id
+/// Move an album with the given id to the trash
 ///
-/// This endpoint returns the id of the deleted album, if it exists.
+/// This endpoint returns the id of the deleted album, if it exists. Its songs and its links to
+/// artists go to the trash with it. See [`super::trash_api`] to restore or purge it.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    responses(
+        (status = 200, description = "Id of the deleted album", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody),
+        (status = 404, description = "Album not found", body = ErrorBody),
+        (status = 412, description = "Album changed since the given version", body = ErrorBody),
+        (status = 428, description = "If-Match header required", body = ErrorBody)
+    ),
+    params(("If-Match" = String, Header, description = "ETag of the album being changed")),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
 #[delete("/<id>")]
 pub async fn delete_album(
     db: &State<DatabaseConnection>,
+    admin: Admin,
+    if_match: IfMatch,
     id: i32,
-) -> Result<(Status, String), (Status, &str)> {
+) -> Result<(Status, String), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let res = Album::delete_by_id(id)
-        .exec(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error deleting album"))?;
+    let album = Album::find_live_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Album"))?;
+    if_match.check("Album", album.version)?;
+
+    // The album, its songs and its links are trashed together or not at all
+    let txn = db.begin().await?;
+
+    trash::trash_album(&txn, Some(&admin.0.user), &album).await?;
 
-    match res.rows_affected {
-        1 => {
-            info!("Deleted album with id {}", id);
-            Ok((Status::Ok, id.to_string()))
+    txn.commit().await?;
+
+    info!("Moved album with id {} to the trash", id);
+    Ok((Status::Ok, id.to_string()))
+}
+
+/// Fields the album list can be sorted by
+#[derive(Debug, Clone, Copy, FromFormField, ToSchema)]
+#[schema(rename_all = "snake_case")]
+pub enum AlbumSort {
+    Id,
+    Name,
+    #[field(value = "date_published")]
+    DatePublished,
+}
+
+impl From<AlbumSort> for album::Column {
+    fn from(sort: AlbumSort) -> Self {
+        match sort {
+            AlbumSort::Id => album::Column::Id,
+            AlbumSort::Name => album::Column::Name,
+            AlbumSort::DatePublished => album::Column::DatePublished,
         }
-        _ => Err((Status::NotFound, "Album not found")),
     }
 }
 
 /// Get all albums
 ///
-/// This endpoint returns a list of all albums in the database.
-#[get("/all")]
+/// This endpoint returns a [`Page`] of [`RatedAlbum`]s, each album along with its average rating
+/// and number of ratings, sorted by `sort` (one of `id`, `name` or `date_published`) in the given
+/// `order`. The albums can be filtered by a `published_after`/`published_before` date range
+/// (inclusive, `YYYY-MM-DD`), and by the `label_id` of a label that released them.
+/// See [`PageParams`] for `page` and `per_page`.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    responses(
+        (status = 200, description = "A page of albums", body = AlbumPage)
+    )
+)]
+#[get("/all?<sort>&<order>&<published_after>&<published_before>&<label_id>&<page>&<per_page>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_all_albums(
     db: &State<DatabaseConnection>,
-) -> Result<(Status, Json<Vec<album::Model>>), (Status, &str)> {
+    origin: &Origin<'_>,
+    sort: Option<AlbumSort>,
+    order: Option<Direction>,
+    published_after: Option<DateParam>,
+    published_before: Option<DateParam>,
+    label_id: Option<i32>,
+    page: Option<u64>,
+    per_page: Option<u64>,
+) -> Result<(Status, Json<Page<RatedAlbum>>), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let albums = Album::find()
-        .all(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching albums in DB"))?;
+    let mut query = Album::find_live()
+        .order_by(
+            album::Column::from(sort.unwrap_or(AlbumSort::Id)),
+            order.unwrap_or_default().into(),
+        )
+        .order_by_asc(album::Column::Id);
+    if let Some(DateParam(date)) = published_after {
+        query = query.filter(album::Column::DatePublished.gte(date));
+    }
+    if let Some(DateParam(date)) = published_before {
+        query = query.filter(album::Column::DatePublished.lte(date));
+    }
+    if let Some(label_id) = label_id {
+        query = query.filter(
+            album::Column::Id.in_subquery(
+                Query::select()
+                    .column(album_label::Column::AlbumId)
+                    .from(AlbumLabel)
+                    .and_where(album_label::Column::LabelId.eq(label_id))
+                    .to_owned(),
+            ),
+        );
+    }
 
-    info!("Found {} albums", albums.len());
+    let albums = fetch_page(db, query, PageParams { page, per_page }, origin).await?;
+    let albums = review_api::rate_albums(db, albums).await?;
+
+    info!("Found {} albums", albums.total);
     Ok((Status::Ok, Json(albums)))
 }
 
 /// Add an artist to the album with the given id
 ///
-/// This endpoint requires a JSON body containing a list of artist ids.
+/// This endpoint requires a JSON body containing a list of artist ids. The artists are credited
+/// after those already on the album, in the given order. Artists already on the album are left
+/// where they are, so adding the same artist twice is harmless.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    request_body = Vec<i32>,
+    responses(
+        (status = 201, description = "Id of the album", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Album not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
 #[post("/artist/<id>", data = "<artist_ids>")]
 pub async fn add_artist(
     db: &State<DatabaseConnection>,
+    editor: Editor,
     id: i32,
     artist_ids: Json<Vec<i32>>,
-) -> Result<(Status, String), (Status, &str)> {
+) -> Result<(Status, String), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let album = Album::find_by_id(id)
-        .one(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching album in DB"))?;
+    // Either all of the artists are added, or none of them
+    let txn = db.begin().await?;
 
-    if album.is_none() {
-        info!("Album not found");
-        return Err((Status::NotFound, "Album not found"));
-    }
+    trash::lock_live::<Album, _>(&txn, id, "Album").await?;
 
-    let artist_ids = artist_ids.into_inner();
-    let artists = Artist::find()
+    let mut seen = HashSet::new();
+    let artist_ids: Vec<i32> = artist_ids
+        .into_inner()
+        .into_iter()
+        .filter(|artist_id| seen.insert(*artist_id))
+        .collect();
+    let artists = Artist::find_live()
         .filter(artist::Column::Id.is_in(artist_ids.clone()))
-        .all(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching artists in DB"))?;
+        .all(&txn)
+        .await?;
 
     if artists.len() != artist_ids.len() {
         info!("Not all artists were found");
-        return Err((Status::BadRequest, "Invalid list of artist ids"));
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "artist_ids",
+            "some artists do not exist",
+        )]));
     }
 
+    // Trashed links count too, as restoring them would otherwise credit the artist twice
+    let links = AlbumArtistMtm::find()
+        .filter(album_artist_mtm::Column::AlbumId.eq(id))
+        .all(&txn)
+        .await?;
+    let linked: HashSet<i32> = links.iter().map(|link| link.artist_id).collect();
+    let mut position = links
+        .iter()
+        .map(|link| link.position + 1)
+        .max()
+        .unwrap_or_default();
+
     for artist_id in artist_ids {
+        if linked.contains(&artist_id) {
+            info!("Artist {} is already on the album", artist_id);
+            continue;
+        }
+
         let new_artist_album = album_artist_mtm::ActiveModel {
             artist_id: ActiveValue::Set(artist_id),
             album_id: ActiveValue::Set(id),
+            position: ActiveValue::Set(position),
             ..Default::default()
         };
 
-        let artist_album = AlbumArtistMtm::insert(new_artist_album)
-            .exec(db)
-            .await
-            .map_err(|_| {
-                (
-                    Status::InternalServerError,
-                    "Error inserting artist_album in DB",
-                )
-            })?;
-
-        info!(
-            "Inserted album-artist relation with id: {}",
-            artist_album.last_insert_id
-        );
+        let link_id = AlbumArtistMtm::insert(new_artist_album)
+            .exec(&txn)
+            .await?
+            .last_insert_id;
+        let artist_album = album_artist_mtm::Model {
+            id: link_id,
+            album_id: id,
+            artist_id,
+            position,
+            deleted_at: None,
+        };
+        audit::created(&txn, Some(&editor.0.user), link_id, &artist_album).await?;
+
+        info!("Inserted album-artist relation with id: {}", link_id);
+        position += 1;
     }
 
+    txn.commit().await?;
+
     Ok((Status::Created, id.to_string()))
 }
 
 /// Get all artists for the album with the given id
 ///
-/// This endpoint returns a list of [`artist::Model`] for the album with the given id.
+/// This endpoint returns a list of [`artist::Model`] for the album with the given id, in the
+/// order they are credited, the primary artist first.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    responses(
+        (status = 200, description = "Artists of the album", body = [Artist]),
+        (status = 404, description = "Album not found", body = ErrorBody)
+    )
+)]
 #[get("/artist/<id>")]
 pub async fn get_artists(
     db: &State<DatabaseConnection>,
     id: i32,
-) -> Result<(Status, Json<Vec<artist::Model>>), (Status, &str)> {
+) -> Result<(Status, Json<Vec<artist::Model>>), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let album = Album::find_by_id(id)
-        .one(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching album in DB"))?;
+    let album = Album::find_live_by_id(id).one(db).await?;
 
     if album.is_none() {
         info!("Album not found");
-        return Err((Status::NotFound, "Album not found"));
+        return Err(ApiError::not_found("Album"));
     }
 
-    let artists_ids = AlbumArtistMtm::find()
+    let artists = Artist::find_live()
+        .inner_join(AlbumArtistMtm)
         .filter(album_artist_mtm::Column::AlbumId.eq(id))
+        .filter(album_artist_mtm::Column::DeletedAt.is_null())
+        .order_by_asc(album_artist_mtm::Column::Position)
+        .order_by_asc(album_artist_mtm::Column::Id)
         .all(db)
-        .await
-        .map_err(|_| {
-            (
-                Status::InternalServerError,
-                "Error fetching album-artists in DB",
-            )
-        })?
-        .into_iter()
-        .map(|album_artist| album_artist.artist_id);
-
-    let artists = Artist::find()
-        .filter(artist::Column::Id.is_in(artists_ids))
-        .all(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching artists in DB"))?;
+        .await?;
 
     info!("Found {} artists", artists.len());
     Ok((Status::Ok, Json(artists)))
@@ -264,66 +578,578 @@ pub async fn get_artists(
 /// Remove an artist from the album with the given id
 ///
 /// This endpoint requires a JSON body containing a list of artist ids.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    request_body = Vec<i32>,
+    responses(
+        (status = 200, description = "Id of the album", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Album not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
 #[delete("/artist/<id>", data = "<artist_ids>")]
 pub async fn remove_artist(
     db: &State<DatabaseConnection>,
+    editor: Editor,
     id: i32,
     artist_ids: Json<Vec<i32>>,
-) -> Result<(Status, String), (Status, &str)> {
+) -> Result<(Status, String), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let album = Album::find_by_id(id)
-        .one(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching album in DB"))?;
+    let album = Album::find_live_by_id(id).one(db).await?;
 
     if album.is_none() {
         info!("Album not found");
-        return Err((Status::NotFound, "Album not found"));
+        return Err(ApiError::not_found("Album"));
     }
 
     let artist_ids = artist_ids.into_inner();
-    let artists = Artist::find()
+    let artists = Artist::find_live()
         .filter(artist::Column::Id.is_in(artist_ids.clone()))
         .all(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching artists in DB"))?;
+        .await?;
 
     if artists.len() != artist_ids.len() {
         info!("Not all artists were found");
-        return Err((Status::BadRequest, "Invalid list of artist ids"));
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "artist_ids",
+            "some artists do not exist",
+        )]));
     }
 
-    let res = AlbumArtistMtm::delete_many()
+    let txn = db.begin().await?;
+
+    let links = AlbumArtistMtm::find_live()
         .filter(album_artist_mtm::Column::ArtistId.is_in(artist_ids))
         .filter(album_artist_mtm::Column::AlbumId.eq(id))
-        .exec(db)
-        .await
-        .map_err(|_| {
-            (
-                Status::InternalServerError,
-                "Error deleting artist_album in DB",
-            )
-        })?;
+        .all(&txn)
+        .await?;
+
+    let res = AlbumArtistMtm::delete_many()
+        .filter(album_artist_mtm::Column::Id.is_in(links.iter().map(|link| link.id)))
+        .exec(&txn)
+        .await?;
+    for link in &links {
+        audit::deleted(&txn, Some(&editor.0.user), link.id, link).await?;
+    }
+
+    txn.commit().await?;
 
     info!("Deleted {} album-artist relations", res.rows_affected);
     Ok((Status::Ok, id.to_string()))
 }
 
 /// Get all songs of an album
+///
+/// The songs are sorted by disc, then by track.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    responses(
+        (status = 302, description = "Songs of the album", body = [Song])
+    )
+)]
 #[get("/songs/<id>")]
 pub async fn get_songs(
     db: &State<DatabaseConnection>,
     id: i32,
-) -> Result<(Status, Json<Vec<song::Model>>), (Status, &str)> {
+) -> Result<(Status, Json<Vec<song::Model>>), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let songs = Song::find()
+    let songs = Song::find_live()
         .filter(song::Column::AlbumId.eq(id))
+        .order_by_asc(song::Column::DiscNumber)
+        .order_by_asc(song::Column::TrackNumber)
         .all(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching songs in DB"))?;
+        .await?;
 
     info!("Found {} songs", songs.len());
     Ok((Status::Found, Json(songs)))
 }
+
+/// Reorder the songs of an album
+///
+/// This endpoint requires a JSON body containing the ids of every song of the album, in their
+/// new order. The songs stay on their disc, and are numbered from 1 on each disc in the order
+/// given. It returns the songs of the album, sorted as by [`get_songs`].
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    request_body = Vec<i32>,
+    responses(
+        (status = 200, description = "Songs of the album", body = [Song]),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Album not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/songs/<id>", data = "<song_ids>")]
+pub async fn reorder_songs(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    id: i32,
+    song_ids: Json<Vec<i32>>,
+) -> Result<(Status, Json<Vec<song::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    if Album::find_live_by_id(id).one(&txn).await?.is_none() {
+        info!("Album not found");
+        return Err(ApiError::not_found("Album"));
+    }
+
+    let mut songs: HashMap<i32, song::Model> = Song::find_live()
+        .filter(song::Column::AlbumId.eq(id))
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|song| (song.id, song))
+        .collect();
+
+    let song_ids = song_ids.into_inner();
+    let listed: HashSet<&i32> = song_ids.iter().collect();
+    if song_ids.len() != songs.len()
+        || listed.len() != songs.len()
+        || !listed.iter().all(|song_id| songs.contains_key(song_id))
+    {
+        info!("The songs listed are not those of the album");
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "song_ids",
+            "must list every song of the album once",
+        )]));
+    }
+
+    let mut last_tracks: HashMap<i32, i32> = HashMap::new();
+    let tracklist: Vec<(song::Model, i32)> = song_ids
+        .iter()
+        .filter_map(|song_id| songs.remove(song_id))
+        .map(|song| {
+            let track_number = last_tracks.entry(song.disc_number).or_default();
+            *track_number += 1;
+            (song, *track_number)
+        })
+        .collect();
+    let moved: Vec<i32> = tracklist
+        .iter()
+        .filter(|(song, track_number)| song.track_number != *track_number)
+        .map(|(song, _)| song.id)
+        .collect();
+
+    // The moved songs first leave their track, so that no two songs share one along the way
+    if !moved.is_empty() {
+        Song::update_many()
+            .col_expr(
+                song::Column::TrackNumber,
+                Expr::col(song::Column::TrackNumber).mul(-1),
+            )
+            .filter(song::Column::Id.is_in(moved))
+            .exec(&txn)
+            .await?;
+    }
+
+    let mut reordered = Vec::with_capacity(tracklist.len());
+    for (song, track_number) in tracklist {
+        if song.track_number == track_number {
+            reordered.push(song);
+            continue;
+        }
+
+        let updated_song = song::ActiveModel {
+            id: ActiveValue::Set(song.id),
+            track_number: ActiveValue::Set(track_number),
+            version: ActiveValue::Set(song.version + 1),
+            ..Default::default()
+        };
+        let after = Song::update(updated_song).exec(&txn).await?;
+        audit::updated(&txn, Some(&editor.0.user), song.id, &song, &after).await?;
+        reordered.push(after);
+    }
+
+    txn.commit().await?;
+
+    reordered.sort_by_key(|song| (song.disc_number, song.track_number));
+    info!("Reordered the songs of album {}", id);
+    Ok((Status::Ok, Json(reordered)))
+}
+
+/// Get the genres of an album
+///
+/// This endpoint returns the list of [`genre::Model`] of the album with the given id, sorted by
+/// name.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    responses(
+        (status = 200, description = "Genres of the album", body = [Genre]),
+        (status = 404, description = "Album not found", body = ErrorBody)
+    )
+)]
+#[get("/genres/<id>")]
+pub async fn get_genres(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<Vec<genre::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    if Album::find_live_by_id(id).one(db).await?.is_none() {
+        info!("Album not found");
+        return Err(ApiError::not_found("Album"));
+    }
+
+    let genres = genre_api::album_genres(db, id).await?;
+
+    info!("Found {} genres", genres.len());
+    Ok((Status::Ok, Json(genres)))
+}
+
+/// Set the genres of an album
+///
+/// This endpoint requires a JSON body containing the list of the genre ids the album has from now
+/// on, and returns the list of their [`genre::Model`], sorted by name.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    request_body = Vec<i32>,
+    responses(
+        (status = 200, description = "Genres of the album", body = [Genre]),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Album not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/genres/<id>", data = "<genre_ids>")]
+pub async fn set_genres(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    id: i32,
+    genre_ids: Json<Vec<i32>>,
+) -> Result<(Status, Json<Vec<genre::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    trash::lock_live::<Album, _>(&txn, id, "Album").await?;
+
+    let genre_ids = genre_ids.into_inner().into_iter().collect();
+    let genres = genre_api::set_album_genres(&txn, &editor.0.user, id, genre_ids).await?;
+
+    txn.commit().await?;
+
+    info!("Set {} genres of album with id: {}", genres.len(), id);
+    Ok((Status::Ok, Json(genres)))
+}
+
+/// Get the labels of an album
+///
+/// This endpoint returns the list of [`AlbumRelease`] of the album with the given id, sorted by
+/// the name of the label.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    responses(
+        (status = 200, description = "Labels of the album", body = [AlbumRelease]),
+        (status = 404, description = "Album not found", body = ErrorBody)
+    )
+)]
+#[get("/labels/<id>")]
+pub async fn get_labels(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<Vec<AlbumRelease>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    if Album::find_live_by_id(id).one(db).await?.is_none() {
+        info!("Album not found");
+        return Err(ApiError::not_found("Album"));
+    }
+
+    let labels = label_api::album_labels(db, id).await?;
+
+    info!("Found {} labels", labels.len());
+    Ok((Status::Ok, Json(labels)))
+}
+
+/// Set the labels of an album
+///
+/// This endpoint requires a JSON body containing the list of the [`LabelLink`] the album has from
+/// now on, and returns the list of its [`AlbumRelease`], sorted by the name of the label.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    request_body = Vec<LabelLink>,
+    responses(
+        (status = 200, description = "Labels of the album", body = [AlbumRelease]),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Album not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/labels/<id>", data = "<labels>")]
+pub async fn set_labels(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    id: i32,
+    labels: Json<Vec<LabelLink>>,
+) -> Result<(Status, Json<Vec<AlbumRelease>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    trash::lock_live::<Album, _>(&txn, id, "Album").await?;
+
+    let labels = label_api::set_album_labels(&txn, &editor.0.user, id, labels.into_inner()).await?;
+
+    txn.commit().await?;
+
+    info!("Set {} labels of album with id: {}", labels.len(), id);
+    Ok((Status::Ok, Json(labels)))
+}
+
+/// Form uploading the cover of an album, as documented
+///
+/// The route takes the file of the form whatever the name of its field.
+#[derive(ToSchema)]
+pub struct CoverUpload {
+    /// JPEG, PNG or WebP image
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
+
+/// Upload the cover of an album
+///
+/// This endpoint requires a `multipart/form-data` body with the image in its `file` field. The
+/// image must be a JPEG, PNG or WebP: it is stored as sent, along with a thumbnail of every
+/// [`CoverSize`], and replaces the previous cover of the album.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    request_body(content = CoverUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "The new cover", body = AlbumCover),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Album not found", body = ErrorBody),
+        (status = 422, description = "Not a JPEG, PNG or WebP image", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/cover/<id>", data = "<upload>")]
+pub async fn upload_cover(
+    db: &State<DatabaseConnection>,
+    storage: &State<Box<dyn Storage>>,
+    editor: Editor,
+    id: i32,
+    upload: Form<TempFile<'_>>,
+) -> Result<(Status, Json<album_cover::Model>), ApiError> {
+    let db = db as &DatabaseConnection;
+    let storage = storage.inner().as_ref();
+
+    if Album::find_live_by_id(id).one(db).await?.is_none() {
+        info!("Album not found");
+        return Err(ApiError::not_found("Album"));
+    }
+
+    let bytes = read_upload(&upload).await?;
+    let (bytes, processed) = task::spawn_blocking(move || {
+        let processed = cover::process(&bytes);
+        (bytes, processed)
+    })
+    .await
+    .map_err(|err| {
+        error!("Cover processing failed: {}", err);
+        ApiError::Internal("Cover processing failed".to_string())
+    })?;
+    let cover = processed.map_err(|error| {
+        info!("Invalid cover");
+        ApiError::Validation(vec![error])
+    })?;
+
+    let key = storage::new_key();
+    storage.put(&cover::original_key(&key), &bytes).await?;
+    for (size, thumbnail) in &cover.thumbnails {
+        storage
+            .put(&cover::thumbnail_key(&key, *size), thumbnail)
+            .await?;
+    }
+
+    let (replaced, saved) = match save_cover(db, Some(&editor.0.user), id, &key, &cover).await {
+        Ok(saved) => saved,
+        Err(err) => {
+            remove_cover_files(storage, &key).await;
+            return Err(err);
+        }
+    };
+    if let Some(replaced) = replaced {
+        remove_cover_files(storage, &replaced.key).await;
+    }
+
+    info!("Uploaded cover {} of album {}", key, id);
+    Ok((Status::Created, Json(saved)))
+}
+
+/// Record the cover stored under `key`, returning the one it replaced along with it
+async fn save_cover(
+    db: &DatabaseConnection,
+    user: Option<&user::Model>,
+    album_id: i32,
+    key: &str,
+    cover: &Cover,
+) -> Result<(Option<album_cover::Model>, album_cover::Model), ApiError> {
+    let txn = db.begin().await?;
+
+    let replaced = AlbumCover::find()
+        .filter(album_cover::Column::AlbumId.eq(album_id))
+        .lock_exclusive()
+        .one(&txn)
+        .await?;
+    let new_cover = album_cover::ActiveModel {
+        key: ActiveValue::Set(key.to_string()),
+        content_type: ActiveValue::Set(cover.content_type.to_string()),
+        width: ActiveValue::Set(cover.width as i32),
+        height: ActiveValue::Set(cover.height as i32),
+        ..Default::default()
+    };
+    let saved = match &replaced {
+        Some(replaced) => {
+            let saved = AlbumCover::update(album_cover::ActiveModel {
+                id: ActiveValue::Set(replaced.id),
+                ..new_cover
+            })
+            .exec(&txn)
+            .await?;
+            audit::updated(&txn, user, saved.id, replaced, &saved).await?;
+            saved
+        }
+        None => {
+            let saved = album_cover::ActiveModel {
+                album_id: ActiveValue::Set(album_id),
+                ..new_cover
+            }
+            .insert(&txn)
+            .await?;
+            audit::created(&txn, user, saved.id, &saved).await?;
+            saved
+        }
+    };
+
+    txn.commit().await?;
+    Ok((replaced, saved))
+}
+
+/// Remove the files of a cover, which are only left behind if that fails
+pub async fn remove_cover_files(storage: &dyn Storage, key: &str) {
+    let keys = CoverSize::ALL
+        .into_iter()
+        .map(|size| cover::thumbnail_key(key, size))
+        .chain([cover::original_key(key)]);
+    for key in keys {
+        if let Err(err) = storage.delete(&key).await {
+            warn!("Failed to remove {}: {}", key, err);
+        }
+    }
+}
+
+/// Get the cover of an album
+///
+/// This endpoint returns the image uploaded as the cover of the album or, given a `size`, its
+/// JPEG thumbnail of that size. Its `ETag` changes with each upload, and it may be cached for a
+/// few minutes.
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    responses(
+        (status = 200, description = "The cover", body = String, content_type = "image/*",
+            headers(("ETag" = String, description = "Version of the cover"))),
+        (status = 304, description = "The cover did not change since the `If-None-Match` ETag"),
+        (status = 404, description = "Album without a cover", body = ErrorBody)
+    )
+)]
+#[get("/cover/<id>?<size>")]
+pub async fn get_cover(
+    db: &State<DatabaseConnection>,
+    storage: &State<Box<dyn Storage>>,
+    id: i32,
+    size: Option<CoverSize>,
+) -> Result<StoredFile, ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let cover = AlbumCover::find()
+        .filter(album_cover::Column::AlbumId.eq(id))
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Cover"))?;
+
+    let (key, content_type, etag) = match size {
+        Some(size) => (
+            cover::thumbnail_key(&cover.key, size),
+            ContentType::JPEG,
+            format!("{}-{}", cover.key, size.name()),
+        ),
+        None => (
+            cover::original_key(&cover.key),
+            ContentType::parse_flexible(&cover.content_type).unwrap_or(ContentType::Binary),
+            cover.key.clone(),
+        ),
+    };
+    let Some(bytes) = storage.get(&key).await? else {
+        error!("File {} of the cover of album {} is missing", key, id);
+        return Err(ApiError::not_found("Cover"));
+    };
+
+    Ok(StoredFile {
+        bytes,
+        content_type,
+        etag,
+    })
+}
+
+/// Remove the cover of an album
+#[utoipa::path(
+    context_path = "/album",
+    tag = "album",
+    responses(
+        (status = 200, description = "Id of the album", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Album without a cover", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[delete("/cover/<id>")]
+pub async fn delete_cover(
+    db: &State<DatabaseConnection>,
+    storage: &State<Box<dyn Storage>>,
+    editor: Editor,
+    id: i32,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let cover = AlbumCover::find()
+        .filter(album_cover::Column::AlbumId.eq(id))
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Cover"))?;
+    AlbumCover::delete_by_id(cover.id).exec(&txn).await?;
+    audit::deleted(&txn, Some(&editor.0.user), cover.id, &cover).await?;
+
+    txn.commit().await?;
+
+    remove_cover_files(storage.inner().as_ref(), &cover.key).await;
+
+    info!("Removed the cover of album {}", id);
+    Ok((Status::Ok, id.to_string()))
+}
@@ -1,78 +1,163 @@
-use chrono::Local;
-use entities::{album, album_artist_mtm, artist, prelude::*};
-use rocket::{http::Status, serde::json::Json, State};
+use chrono::NaiveDate;
+use entities::{
+    album, album_artist_mtm, artist, artist_genre,
+    artist_membership::{self, Member},
+    genre,
+    pagination::Page,
+    prelude::*,
+    song,
+    song_credit::{self, Appearance},
+    validation::Validate,
+};
+use rocket::{
+    http::{uri::Origin, Status},
+    serde::json::Json,
+    State,
+};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    sea_query::Query, ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, TransactionTrait,
+};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::{
+    audit,
+    auth::{Admin, Editor},
+    error::ApiError,
+    genre_api,
+    query::{fetch_page, Direction, PageParams},
+    trash::{self, Live},
+    tree,
+    version::{stale, IfMatch, WithETag, INITIAL_VERSION},
 };
 
+/// Check the fields of an artist
+fn validate(artist: &artist::Model) -> Result<(), ApiError> {
+    artist.validate().map_err(|errors| {
+        info!("Invalid input");
+        ApiError::Validation(errors)
+    })
+}
+
 /// Create a new artist
 ///
 /// This endpoint requires a JSON body containing an [`artist::Model`] without an id.
+#[utoipa::path(
+    context_path = "/artist",
+    tag = "artist",
+    request_body = Artist,
+    responses(
+        (status = 201, description = "Id of the created artist", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
 #[post("/", data = "<artist>")]
 pub async fn create_artist(
     db: &State<DatabaseConnection>,
+    editor: Editor,
     artist: Json<artist::Model>,
-) -> Result<(Status, String), (Status, &str)> {
+) -> Result<(Status, String), ApiError> {
     let db = db as &DatabaseConnection;
 
-    if artist.name.is_empty() || artist.date_formed > Local::now().date_naive() {
-        info!("Invalid input");
-        return Err((Status::BadRequest, "Invalid input"));
-    }
+    validate(&artist)?;
 
     let new_artist = artist::ActiveModel {
         name: ActiveValue::Set(artist.name.to_owned()),
         date_formed: ActiveValue::Set(artist.date_formed),
-        genre: ActiveValue::Set(artist.genre.to_owned()),
         ..Default::default()
     };
 
-    let artist = Artist::insert(new_artist)
-        .exec(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error inserting artist in DB"))?;
+    let txn = db.begin().await?;
+
+    let id = Artist::insert(new_artist).exec(&txn).await?.last_insert_id;
+    let artist = artist::Model {
+        id,
+        version: INITIAL_VERSION,
+        ..artist.into_inner()
+    };
+    audit::created(&txn, Some(&editor.0.user), id, &artist).await?;
 
-    info!("Inserted artist with id: {}", artist.last_insert_id);
+    txn.commit().await?;
 
-    Ok((Status::Created, artist.last_insert_id.to_string()))
+    info!("Inserted artist with id: {}", id);
+
+    Ok((Status::Created, id.to_string()))
 }
 
 /// Get an artist by its id
 ///
 /// This endpoint returns an [`artist::Model`] with the given id, if it exists.
+#[utoipa::path(
+    context_path = "/artist",
+    tag = "artist",
+    responses(
+        (status = 302, description = "The artist", body = Artist,
+            headers(("ETag" = String, description = "Version of the artist"))),
+        (status = 404, description = "Artist not found", body = ErrorBody)
+    )
+)]
 #[get("/<id>")]
 pub async fn get_artist_by_id(
     db: &State<DatabaseConnection>,
     id: i32,
-) -> Result<(Status, Json<artist::Model>), (Status, &str)> {
+) -> Result<(Status, WithETag<Json<artist::Model>>), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let artist = Artist::find_by_id(id)
-        .one(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching artist in DB"))?;
+    let artist = Artist::find_live_by_id(id).one(db).await?;
 
-    let artist = artist.ok_or((Status::NotFound, "Artist not found"))?;
+    let artist = artist.ok_or_else(|| ApiError::not_found("Artist"))?;
     info!("Found artist with id: {}", artist.id);
-    Ok((Status::Found, Json(artist)))
+    Ok((Status::Found, WithETag(artist.version, Json(artist))))
 }
 
 /// Update an artist with the given id
 ///
 /// This endpoint requires a JSON body containing a [`artist::Model`] with all of the fields set.
+/// The artist must be valid, as on creation.
+#[utoipa::path(
+    context_path = "/artist",
+    tag = "artist",
+    request_body = Artist,
+    responses(
+        (status = 202, description = "Id of the updated artist", body = String,
+            headers(("ETag" = String, description = "New version of the artist"))),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Artist not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody),
+        (status = 412, description = "Artist changed since the given version", body = ErrorBody),
+        (status = 428, description = "If-Match header required", body = ErrorBody)
+    ),
+    params(("If-Match" = String, Header, description = "ETag of the artist being changed")),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
 #[put("/<id>", data = "<artist>")]
 pub async fn update_artist(
     db: &State<DatabaseConnection>,
+    editor: Editor,
+    if_match: IfMatch,
     id: i32,
     artist: Json<artist::Model>,
-) -> Result<(Status, String), (Status, &str)> {
+) -> Result<(Status, WithETag<String>), ApiError> {
     let db = db as &DatabaseConnection;
 
+    let before = Artist::find_live_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Artist"))?;
+    if_match.check("Artist", before.version)?;
+
+    validate(&artist)?;
+
     let updated_artist = artist::ActiveModel {
         id: ActiveValue::Set(id),
         name: ActiveValue::Set(artist.name.to_owned()),
         date_formed: ActiveValue::Set(artist.date_formed.to_owned()),
-        genre: ActiveValue::Set(artist.genre.to_owned()),
+        version: ActiveValue::Set(before.version + 1),
         ..Default::default()
     };
 
@@ -81,121 +166,425 @@ pub async fn update_artist(
         id, updated_artist
     );
 
-    updated_artist
-        .update(db)
+    let txn = db.begin().await?;
+
+    let after = Artist::update(updated_artist)
+        .filter(artist::Column::Version.eq(before.version))
+        .exec(&txn)
         .await
-        .map_err(|_| (Status::InternalServerError, "Error updating artist"))?;
+        .map_err(stale("Artist"))?;
+    audit::updated(&txn, Some(&editor.0.user), id, &before, &after).await?;
+
+    txn.commit().await?;
 
     info!("Updated artist with id: {}", id);
 
-    Ok((Status::Accepted, id.to_string()))
+    Ok((Status::Accepted, WithETag(after.version, id.to_string())))
+}
+
+/// Fields of an artist to change, every missing field is left as is
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct ArtistPatch {
+    pub name: Option<String>,
+    pub date_formed: Option<NaiveDate>,
+}
+
+/// Partially update an artist with the given id
+///
+/// This endpoint requires a JSON body containing an [`ArtistPatch`] and returns the updated
+/// [`artist::Model`]. The artist resulting from the patch must be valid, as on creation.
+#[utoipa::path(
+    context_path = "/artist",
+    tag = "artist",
+    request_body = ArtistPatch,
+    responses(
+        (status = 200, description = "The updated artist", body = Artist,
+            headers(("ETag" = String, description = "New version of the artist"))),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Artist not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody),
+        (status = 412, description = "Artist changed since the given version", body = ErrorBody),
+        (status = 428, description = "If-Match header required", body = ErrorBody)
+    ),
+    params(("If-Match" = String, Header, description = "ETag of the artist being changed")),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[patch("/<id>", data = "<patch>")]
+pub async fn patch_artist(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    if_match: IfMatch,
+    id: i32,
+    patch: Json<ArtistPatch>,
+) -> Result<(Status, WithETag<Json<artist::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let artist = Artist::find_live_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Artist"))?;
+    if_match.check("Artist", artist.version)?;
+    let patch = patch.into_inner();
+
+    let mut merged = artist.clone();
+    if let Some(name) = &patch.name {
+        merged.name = name.to_owned();
+    }
+    if let Some(date_formed) = patch.date_formed {
+        merged.date_formed = date_formed;
+    }
+    validate(&merged)?;
+
+    let mut updated_artist: artist::ActiveModel = artist.clone().into();
+    if let Some(name) = patch.name {
+        updated_artist.name = ActiveValue::Set(name);
+    }
+    if let Some(date_formed) = patch.date_formed {
+        updated_artist.date_formed = ActiveValue::Set(date_formed);
+    }
+
+    if !updated_artist.is_changed() {
+        info!("Nothing to update for artist with id: {}", id);
+        return Ok((Status::Ok, WithETag(merged.version, Json(merged))));
+    }
+
+    updated_artist.version = ActiveValue::Set(artist.version + 1);
+
+    info!("About to patch artist with id {}: {:?}", id, updated_artist);
+
+    let txn = db.begin().await?;
+
+    let patched = Artist::update(updated_artist)
+        .filter(artist::Column::Version.eq(artist.version))
+        .exec(&txn)
+        .await
+        .map_err(stale("Artist"))?;
+    audit::updated(&txn, Some(&editor.0.user), id, &artist, &patched).await?;
+
+    txn.commit().await?;
+
+    info!("Patched artist with id: {}", id);
+    Ok((Status::Ok, WithETag(patched.version, Json(patched))))
 }
 
-/// Delete an artist with the given id
+/// Move an artist with the given id to the trash
 ///
-/// This endpoint returns the id of the deleted artist, if it exists.
+/// This endpoint returns the id of the deleted artist, if it exists. Its links to albums go to the
+/// trash with it, as do the albums left without any artist and their songs. See
+/// [`super::trash_api`] to restore or purge it.
+#[utoipa::path(
+    context_path = "/artist",
+    tag = "artist",
+    responses(
+        (status = 200, description = "Id of the deleted artist", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody),
+        (status = 404, description = "Artist not found", body = ErrorBody),
+        (status = 412, description = "Artist changed since the given version", body = ErrorBody),
+        (status = 428, description = "If-Match header required", body = ErrorBody)
+    ),
+    params(("If-Match" = String, Header, description = "ETag of the artist being changed")),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
 #[delete("/<id>")]
 pub async fn delete_artist(
     db: &State<DatabaseConnection>,
+    admin: Admin,
+    if_match: IfMatch,
     id: i32,
-) -> Result<(Status, String), (Status, &str)> {
+) -> Result<(Status, String), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let album_ids = AlbumArtistMtm::find()
-        .filter(album_artist_mtm::Column::ArtistId.eq(id))
-        .all(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error finding artist's albums"))?
-        .into_iter()
-        .map(|album_artist| album_artist.album_id);
+    let artist = Artist::find_live_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Artist"))?;
+    if_match.check("Artist", artist.version)?;
 
-    let res = Artist::delete_by_id(id)
-        .exec(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error deleting artist"))?;
+    // The artist and its orphaned albums are trashed together or not at all
+    let txn = db.begin().await?;
 
-    let ret = match res.rows_affected {
-        1 => {
-            info!("Deleted artist with id {}", id);
-            Ok((Status::Ok, id.to_string()))
-        }
-        _ => return Err((Status::NotFound, "Artist not found")),
-    };
+    trash::trash_artist(&txn, Some(&admin.0.user), &artist).await?;
+
+    txn.commit().await?;
+
+    info!("Moved artist with id {} to the trash", id);
+    Ok((Status::Ok, id.to_string()))
+}
+
+/// Fields the artist list can be sorted by
+#[derive(Debug, Clone, Copy, FromFormField, ToSchema)]
+#[schema(rename_all = "snake_case")]
+pub enum ArtistSort {
+    Id,
+    Name,
+    #[field(value = "date_formed")]
+    DateFormed,
+}
 
-    for album_id in album_ids {
-        let no_other_artists = AlbumArtistMtm::find()
-            .filter(album_artist_mtm::Column::AlbumId.eq(album_id))
-            .all(db)
-            .await
-            .map_err(|_| (Status::InternalServerError, "Error finding album-artists"))?
-            .is_empty();
-
-        if no_other_artists {
-            Album::delete_by_id(album_id)
-                .exec(db)
-                .await
-                .map_err(|_| (Status::InternalServerError, "Error deleting albums"))?;
+impl From<ArtistSort> for artist::Column {
+    fn from(sort: ArtistSort) -> Self {
+        match sort {
+            ArtistSort::Id => artist::Column::Id,
+            ArtistSort::Name => artist::Column::Name,
+            ArtistSort::DateFormed => artist::Column::DateFormed,
         }
     }
-
-    ret
 }
 
 /// Get all artists
 ///
-/// This endpoint returns a list of all artists in the database.
-#[get("/all")]
+/// This endpoint returns a [`Page`] of artists, sorted by `sort` (one of `id`, `name` or
+/// `date_formed`) in the given `order`. The artists can be filtered by the name of a `genre`, which
+/// includes the artists of its subgenres.
+/// See [`PageParams`] for `page` and `per_page`.
+#[utoipa::path(
+    context_path = "/artist",
+    tag = "artist",
+    responses(
+        (status = 200, description = "A page of artists", body = ArtistPage),
+        (status = 400, description = "Unknown genre", body = ErrorBody)
+    )
+)]
+#[get("/all?<sort>&<order>&<genre>&<page>&<per_page>")]
 pub async fn get_all_artists(
     db: &State<DatabaseConnection>,
-) -> Result<(Status, Json<Vec<artist::Model>>), (Status, &str)> {
+    origin: &Origin<'_>,
+    sort: Option<ArtistSort>,
+    order: Option<Direction>,
+    genre: Option<&str>,
+    page: Option<u64>,
+    per_page: Option<u64>,
+) -> Result<(Status, Json<Page<artist::Model>>), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let artists = Artist::find()
-        .all(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching artists in DB"))?;
+    let mut query = Artist::find_live()
+        .order_by(
+            artist::Column::from(sort.unwrap_or(ArtistSort::Id)),
+            order.unwrap_or_default().into(),
+        )
+        .order_by_asc(artist::Column::Id);
+    if let Some(name) = genre {
+        let genre = genre_api::find_by_name(db, name)
+            .await?
+            .ok_or_else(|| ApiError::BadRequest(format!("Unknown genre: {}", name)))?;
+        let genres = Genre::find().all(db).await?;
+        query = query.filter(
+            artist::Column::Id.in_subquery(
+                Query::select()
+                    .column(artist_genre::Column::ArtistId)
+                    .from(ArtistGenre)
+                    .and_where(
+                        artist_genre::Column::GenreId
+                            .is_in(tree::with_descendants(&genres, genre.id)),
+                    )
+                    .to_owned(),
+            ),
+        );
+    }
+
+    let artists = fetch_page(db, query, PageParams { page, per_page }, origin).await?;
 
-    info!("Found {} artists", artists.len());
+    info!("Found {} artists", artists.total);
     Ok((Status::Ok, Json(artists)))
 }
 
 /// Get all albums of an artist
+#[utoipa::path(
+    context_path = "/artist",
+    tag = "artist",
+    responses(
+        (status = 200, description = "Albums of the artist", body = [Album]),
+        (status = 404, description = "Artist not found", body = ErrorBody)
+    )
+)]
 #[get("/albums/<id>")]
 pub async fn get_albums(
     db: &State<DatabaseConnection>,
     id: i32,
-) -> Result<(Status, Json<Vec<album::Model>>), (Status, &str)> {
+) -> Result<(Status, Json<Vec<album::Model>>), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let artist = Artist::find_by_id(id)
-        .one(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching artist in DB"))?;
+    let artist = Artist::find_live_by_id(id).one(db).await?;
 
     if artist.is_none() {
         info!("Artist not found");
-        return Err((Status::NotFound, "Artist not found"));
+        return Err(ApiError::not_found("Artist"));
     }
 
-    let album_ids = AlbumArtistMtm::find()
+    let album_ids = AlbumArtistMtm::find_live()
         .filter(album_artist_mtm::Column::ArtistId.eq(id))
         .all(db)
-        .await
-        .map_err(|_| {
-            (
-                Status::InternalServerError,
-                "Error fetching album-artists in DB",
-            )
-        })?
+        .await?
         .into_iter()
         .map(|album_artist| album_artist.album_id);
 
-    let albums = Album::find()
+    let albums = Album::find_live()
         .filter(album::Column::Id.is_in(album_ids))
         .all(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching albums in DB"))?;
+        .await?;
 
     info!("Found {} artists", albums.len());
     Ok((Status::Ok, Json(albums)))
 }
+
+/// Get the genres of an artist
+///
+/// This endpoint returns the list of [`genre::Model`] of the artist with the given id, sorted by
+/// name.
+#[utoipa::path(
+    context_path = "/artist",
+    tag = "artist",
+    responses(
+        (status = 200, description = "Genres of the artist", body = [Genre]),
+        (status = 404, description = "Artist not found", body = ErrorBody)
+    )
+)]
+#[get("/genres/<id>")]
+pub async fn get_genres(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<Vec<genre::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    if Artist::find_live_by_id(id).one(db).await?.is_none() {
+        info!("Artist not found");
+        return Err(ApiError::not_found("Artist"));
+    }
+
+    let genres = genre_api::artist_genres(db, id).await?;
+
+    info!("Found {} genres", genres.len());
+    Ok((Status::Ok, Json(genres)))
+}
+
+/// Set the genres of an artist
+///
+/// This endpoint requires a JSON body containing the list of the genre ids the artist has from now
+/// on, and returns the list of their [`genre::Model`], sorted by name.
+#[utoipa::path(
+    context_path = "/artist",
+    tag = "artist",
+    request_body = Vec<i32>,
+    responses(
+        (status = 200, description = "Genres of the artist", body = [Genre]),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Artist not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/genres/<id>", data = "<genre_ids>")]
+pub async fn set_genres(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    id: i32,
+    genre_ids: Json<Vec<i32>>,
+) -> Result<(Status, Json<Vec<genre::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    trash::lock_live::<Artist, _>(&txn, id, "Artist").await?;
+
+    let genre_ids = genre_ids.into_inner().into_iter().collect();
+    let genres = genre_api::set_artist_genres(&txn, &editor.0.user, id, genre_ids).await?;
+
+    txn.commit().await?;
+
+    info!("Set {} genres of artist with id: {}", genres.len(), id);
+    Ok((Status::Ok, Json(genres)))
+}
+
+/// Get the members of an artist
+///
+/// This endpoint returns the list of [`Member`] of the artist with the given id, past ones
+/// included, by the date they joined. Those whose start is unknown come last.
+#[utoipa::path(
+    context_path = "/artist",
+    tag = "artist",
+    responses(
+        (status = 200, description = "Members of the artist", body = [Member]),
+        (status = 404, description = "Artist not found", body = ErrorBody)
+    )
+)]
+#[get("/members/<id>")]
+pub async fn get_members(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<Vec<Member>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    if Artist::find_live_by_id(id).one(db).await?.is_none() {
+        info!("Artist not found");
+        return Err(ApiError::not_found("Artist"));
+    }
+
+    let members: Vec<Member> = ArtistMembership::find()
+        .find_also_related(Person)
+        .filter(artist_membership::Column::ArtistId.eq(id))
+        .order_by_asc(artist_membership::Column::StartDate)
+        .order_by_asc(artist_membership::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(membership, person)| {
+            Some(Member {
+                membership,
+                person: person?,
+            })
+        })
+        .collect();
+
+    info!("Found {} members", members.len());
+    Ok((Status::Ok, Json(members)))
+}
+
+/// Get the songs an artist appears on
+///
+/// This endpoint returns the list of [`Appearance`] of the artist with the given id, one for each
+/// of its credits, sorted by album and track. Songs in the trash are left out.
+#[utoipa::path(
+    context_path = "/artist",
+    tag = "artist",
+    responses(
+        (status = 200, description = "Songs the artist is credited on", body = [Appearance]),
+        (status = 404, description = "Artist not found", body = ErrorBody)
+    )
+)]
+#[get("/appearances/<id>")]
+pub async fn get_appearances(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<Vec<Appearance>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    if Artist::find_live_by_id(id).one(db).await?.is_none() {
+        info!("Artist not found");
+        return Err(ApiError::not_found("Artist"));
+    }
+
+    let appearances: Vec<Appearance> = SongCredit::find()
+        .find_also_related(Song)
+        .filter(song_credit::Column::ArtistId.eq(id))
+        .filter(song::Column::DeletedAt.is_null())
+        .order_by_asc(song::Column::AlbumId)
+        .order_by_asc(song::Column::DiscNumber)
+        .order_by_asc(song::Column::TrackNumber)
+        .order_by_asc(song_credit::Column::Position)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(credit, song)| {
+            Some(Appearance {
+                credit,
+                song: song?,
+            })
+        })
+        .collect();
+
+    info!("Found {} appearances", appearances.len());
+    Ok((Status::Ok, Json(appearances)))
+}
@@ -0,0 +1,129 @@
+//! Tags of uploaded audio files
+//!
+//! An uploaded song is an MP3, FLAC or Ogg Vorbis file whose ID3 or Vorbis tags name the song, its
+//! album and its artist. They are turned into an [`ImportRow`], so that the artist, album and song
+//! are matched or created as by an import.
+
+use std::io::{Read, Seek};
+
+use chrono::NaiveDate;
+use lofty::{
+    file::{AudioFile, FileType, TaggedFileExt},
+    probe::Probe,
+    tag::{Accessor, ItemKey, Tag},
+};
+
+use super::{error::FieldError, import::ImportRow};
+
+/// An uploaded audio file and what its tags say
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaggedAudio {
+    /// Media type of the file
+    pub content_type: &'static str,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    /// Release date of the album, January 1st when the tags only give the year
+    pub date: Option<NaiveDate>,
+    pub disc_number: Option<i32>,
+    pub track_number: Option<i32>,
+    /// Length of the audio, rounded to the second
+    pub length_secs: i32,
+}
+
+impl TaggedAudio {
+    /// The import row of the song, or which tags it lacks
+    ///
+    /// The tags have no date of formation for the artist, so `artist_date_formed` is used if the
+    /// artist has to be created.
+    pub fn row(&self, artist_date_formed: Option<NaiveDate>) -> Result<ImportRow, Vec<FieldError>> {
+        let missing = [
+            ("artist", &self.artist),
+            ("album", &self.album),
+            ("song", &self.title),
+        ]
+        .into_iter()
+        .filter(|(_, tag)| tag.is_none())
+        .map(|(field, _)| FieldError::new(field, "is missing from the tags"))
+        .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(missing);
+        }
+
+        Ok(ImportRow {
+            artist: self.artist.clone().unwrap_or_default(),
+            artist_date_formed,
+            artist_genre: None,
+            album: self.album.clone(),
+            album_date_published: self.date,
+            song: self.title.clone(),
+            song_length_secs: Some(self.length_secs),
+            song_disc_number: self.disc_number,
+            song_track_number: self.track_number,
+        })
+    }
+}
+
+/// Check that `file` is an MP3, FLAC or Ogg Vorbis file, and read its tags
+///
+/// The format is told by the content of the file, not by its name or the type it was sent with.
+/// Reading a whole file takes a while, so this is better run on a blocking thread.
+pub fn read(file: impl Read + Seek) -> Result<TaggedAudio, FieldError> {
+    let invalid = || FieldError::new("file", "must be an MP3, FLAC or Ogg Vorbis file");
+
+    let file = Probe::new(file)
+        .guess_file_type()
+        .map_err(|_| invalid())?
+        .read()
+        .map_err(|_| invalid())?;
+    let content_type = match file.file_type() {
+        FileType::Mpeg => "audio/mpeg",
+        FileType::Flac => "audio/flac",
+        FileType::Vorbis => "audio/ogg",
+        _ => return Err(invalid()),
+    };
+    let length_secs = file.properties().duration().as_secs_f64().round() as i32;
+
+    let Some(tag) = file.primary_tag().or_else(|| file.first_tag()) else {
+        return Ok(TaggedAudio {
+            content_type,
+            title: None,
+            artist: None,
+            album: None,
+            date: None,
+            disc_number: None,
+            track_number: None,
+            length_secs,
+        });
+    };
+
+    Ok(TaggedAudio {
+        content_type,
+        title: text(tag.title()),
+        artist: text(tag.artist()),
+        album: text(tag.album()),
+        date: date(tag),
+        disc_number: tag.disk().map(|disc| disc as i32),
+        track_number: tag.track().map(|track| track as i32),
+        length_secs,
+    })
+}
+
+/// A text tag, unless it is blank
+fn text(value: Option<impl AsRef<str>>) -> Option<String> {
+    value
+        .map(|value| value.as_ref().trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// The recording date of the tags, or January 1st of their year
+fn date(tag: &Tag) -> Option<NaiveDate> {
+    tag.get_string(&ItemKey::RecordingDate)
+        .and_then(|date| NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok())
+        .or_else(|| NaiveDate::from_ymd_opt(tag.year()? as i32, 1, 1))
+}
+
+/// Storage key of an uploaded audio file
+pub fn audio_key(key: &str) -> String {
+    format!("audio/{}", key)
+}
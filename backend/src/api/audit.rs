@@ -0,0 +1,93 @@
+//! Recording of changes in the audit log
+//!
+//! Every handler changing songs, albums, artists, their links, playlists or users records what it
+//! did, in the same transaction as the change. Sessions are not audited, as their rows hold tokens.
+
+use entities::{audit_log, prelude::*, sea_orm_active_enums::AuditAction, user};
+use rocket::serde::json::{to_value, Value};
+use sea_orm::{ActiveValue, ConnectionTrait, DbErr, EntityName, EntityTrait, ModelTrait};
+use serde::Serialize;
+
+/// Serialize a row for the `before` or `after` snapshot
+fn snapshot<M: Serialize>(model: Option<&M>) -> Result<Option<Value>, DbErr> {
+    model
+        .map(|model| to_value(model).map_err(|err| DbErr::Custom(err.to_string())))
+        .transpose()
+}
+
+async fn record<C, M>(
+    db: &C,
+    user: Option<&user::Model>,
+    action: AuditAction,
+    entity_id: i32,
+    before: Option<&M>,
+    after: Option<&M>,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    M: ModelTrait + Serialize,
+{
+    let entry = audit_log::ActiveModel {
+        entity_type: ActiveValue::Set(M::Entity::default().table_name().to_string()),
+        entity_id: ActiveValue::Set(entity_id),
+        action: ActiveValue::Set(action),
+        before: ActiveValue::Set(snapshot(before)?),
+        after: ActiveValue::Set(snapshot(after)?),
+        user_id: ActiveValue::Set(user.map(|user| user.id)),
+        ..Default::default()
+    };
+
+    AuditLog::insert(entry).exec_without_returning(db).await?;
+    Ok(())
+}
+
+/// Record the creation of the row `after`, with the given id
+pub async fn created<C, M>(
+    db: &C,
+    user: Option<&user::Model>,
+    entity_id: i32,
+    after: &M,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    M: ModelTrait + Serialize,
+{
+    record(db, user, AuditAction::Create, entity_id, None, Some(after)).await
+}
+
+/// Record the update of the row with the given id from `before` to `after`
+pub async fn updated<C, M>(
+    db: &C,
+    user: Option<&user::Model>,
+    entity_id: i32,
+    before: &M,
+    after: &M,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    M: ModelTrait + Serialize,
+{
+    record(
+        db,
+        user,
+        AuditAction::Update,
+        entity_id,
+        Some(before),
+        Some(after),
+    )
+    .await
+}
+
+/// Record the deletion of the row `before`, with the given id
+pub async fn deleted<C, M>(
+    db: &C,
+    user: Option<&user::Model>,
+    entity_id: i32,
+    before: &M,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    M: ModelTrait + Serialize,
+{
+    record(db, user, AuditAction::Delete, entity_id, Some(before), None).await
+}
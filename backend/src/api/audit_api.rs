@@ -0,0 +1,89 @@
+use std::str::FromStr;
+
+use chrono::Duration;
+use entities::{audit_log, pagination::Page, prelude::*, sea_orm_active_enums::AuditAction};
+use rocket::{
+    form::{self, FromFormField, ValueField},
+    http::{uri::Origin, Status},
+    serde::json::Json,
+    State,
+};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use utoipa::ToSchema;
+
+use super::{
+    auth::Admin,
+    error::ApiError,
+    query::{fetch_page, DateParam, PageParams},
+};
+
+/// An [`AuditAction`] given as a query parameter, such as `action=delete`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct ActionParam(pub AuditAction);
+
+#[rocket::async_trait]
+impl<'v> FromFormField<'v> for ActionParam {
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        AuditAction::from_str(field.value)
+            .map(ActionParam)
+            .map_err(|_| form::Error::validation("expected create, update or delete").into())
+    }
+}
+
+/// Get the audit log
+///
+/// This endpoint returns a [`Page`] of audit entries, newest first. The entries can be filtered
+/// by `entity` (the table of the changed rows, such as `album`), `entity_id`, `action` (one of
+/// `create`, `update` or `delete`) and a `from`/`to` date range (inclusive, `YYYY-MM-DD`).
+/// See [`PageParams`] for `page` and `per_page`.
+#[utoipa::path(
+    context_path = "/audit",
+    tag = "audit",
+    responses(
+        (status = 200, description = "A page of audit entries", body = AuditPage),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[get("/?<entity>&<entity_id>&<action>&<from>&<to>&<page>&<per_page>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_audit_log(
+    db: &State<DatabaseConnection>,
+    _admin: Admin,
+    origin: &Origin<'_>,
+    entity: Option<String>,
+    entity_id: Option<i32>,
+    action: Option<ActionParam>,
+    from: Option<DateParam>,
+    to: Option<DateParam>,
+    page: Option<u64>,
+    per_page: Option<u64>,
+) -> Result<(Status, Json<Page<audit_log::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let mut query = AuditLog::find()
+        .order_by_desc(audit_log::Column::CreatedAt)
+        .order_by_desc(audit_log::Column::Id);
+    if let Some(entity) = entity {
+        query = query.filter(audit_log::Column::EntityType.eq(entity));
+    }
+    if let Some(entity_id) = entity_id {
+        query = query.filter(audit_log::Column::EntityId.eq(entity_id));
+    }
+    if let Some(ActionParam(action)) = action {
+        query = query.filter(audit_log::Column::Action.eq(action));
+    }
+    if let Some(DateParam(date)) = from {
+        query = query.filter(audit_log::Column::CreatedAt.gte(date.and_hms_opt(0, 0, 0)));
+    }
+    if let Some(DateParam(date)) = to {
+        let next_day = date + Duration::days(1);
+        query = query.filter(audit_log::Column::CreatedAt.lt(next_day.and_hms_opt(0, 0, 0)));
+    }
+
+    let entries = fetch_page(db, query, PageParams { page, per_page }, origin).await?;
+
+    info!("Found {} audit entries", entries.total);
+    Ok((Status::Ok, Json(entries)))
+}
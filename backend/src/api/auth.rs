@@ -0,0 +1,147 @@
+use chrono::{Duration, Utc};
+use entities::{prelude::*, sea_orm_active_enums::Role, session, user};
+use rand::{distributions::Alphanumeric, Rng};
+use rocket::{
+    http::Status,
+    outcome::Outcome,
+    request::{self, FromRequest},
+    Request, State,
+};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use super::error::ApiError;
+
+/// Name of the cookie holding the session token
+pub const SESSION_COOKIE: &str = "session";
+/// Number of days a session stays valid after logging in
+pub const SESSION_DAYS: i64 = 30;
+/// Length of the random session tokens
+const TOKEN_LENGTH: usize = 48;
+
+/// Generate a new random session token
+pub fn new_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+/// Expiry date of a session opened now
+pub fn session_expiry() -> chrono::NaiveDateTime {
+    (Utc::now() + Duration::days(SESSION_DAYS)).naive_utc()
+}
+
+/// Read the session token from the `session` cookie or the `Authorization: Bearer` header
+fn session_token(request: &Request<'_>) -> Option<String> {
+    if let Some(cookie) = request.cookies().get(SESSION_COOKIE) {
+        return Some(cookie.value().to_string());
+    }
+
+    request
+        .headers()
+        .get_one("Authorization")
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+}
+
+/// The user signed in with the session of the request
+///
+/// Use it as a request guard to reject requests without a valid session with 401 Unauthorized.
+#[derive(Debug, Clone)]
+pub struct CurrentUser {
+    pub user: user::Model,
+    pub session: session::Model,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CurrentUser {
+    type Error = ApiError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let token = match session_token(request) {
+            Some(token) => token,
+            None => {
+                return Outcome::Failure((
+                    Status::Unauthorized,
+                    ApiError::Unauthorized("Sign in required".to_string()),
+                ))
+            }
+        };
+
+        let db = match request.guard::<&State<DatabaseConnection>>().await {
+            Outcome::Success(db) => db as &DatabaseConnection,
+            _ => {
+                return Outcome::Failure((
+                    Status::InternalServerError,
+                    ApiError::Internal("Database unavailable".to_string()),
+                ))
+            }
+        };
+
+        let found = Session::find()
+            .filter(session::Column::Token.eq(token))
+            .filter(session::Column::ExpiresAt.gt(Utc::now().naive_utc()))
+            .find_also_related(User)
+            .one(db)
+            .await;
+
+        match found {
+            Ok(Some((session, Some(user)))) => Outcome::Success(CurrentUser { user, session }),
+            Ok(_) => Outcome::Failure((
+                Status::Unauthorized,
+                ApiError::Unauthorized("Session expired or invalid".to_string()),
+            )),
+            Err(err) => {
+                let err = ApiError::from(err);
+                Outcome::Failure((err.status(), err))
+            }
+        }
+    }
+}
+
+/// Run the [`CurrentUser`] guard, then check that the user has at least the `required` role
+async fn require_role(
+    request: &Request<'_>,
+    required: Role,
+) -> request::Outcome<CurrentUser, ApiError> {
+    match request.guard::<CurrentUser>().await {
+        Outcome::Success(current) if current.user.role >= required => Outcome::Success(current),
+        Outcome::Success(_) => Outcome::Failure((
+            Status::Forbidden,
+            ApiError::Forbidden(format!("The {:?} role is required", required)),
+        )),
+        Outcome::Failure(failure) => Outcome::Failure(failure),
+        Outcome::Forward(()) => Outcome::Forward(()),
+    }
+}
+
+/// A signed in user allowed to create and update songs, albums and artists
+///
+/// Rejects requests without a valid session with 401 and viewers with 403 Forbidden.
+#[derive(Debug, Clone)]
+pub struct Editor(pub CurrentUser);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Editor {
+    type Error = ApiError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        require_role(request, Role::Editor).await.map(Editor)
+    }
+}
+
+/// A signed in user allowed to delete anything and to manage roles
+///
+/// Rejects requests without a valid session with 401 and other users with 403 Forbidden.
+#[derive(Debug, Clone)]
+pub struct Admin(pub CurrentUser);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = ApiError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        require_role(request, Role::Admin).await.map(Admin)
+    }
+}
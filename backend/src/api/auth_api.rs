@@ -0,0 +1,203 @@
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use chrono::Utc;
+use entities::{
+    auth::{Credentials, SessionToken},
+    prelude::*,
+    sea_orm_active_enums::Role,
+    session, user,
+    validation::Validate,
+};
+use rocket::{
+    http::{Cookie, CookieJar, SameSite, Status},
+    serde::json::Json,
+    time, State,
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend,
+    EntityTrait, QueryFilter, Statement, TransactionTrait,
+};
+
+use super::{
+    audit,
+    auth::{new_token, session_expiry, CurrentUser, SESSION_COOKIE, SESSION_DAYS},
+    error::ApiError,
+};
+
+/// Hash a password with Argon2 and a random salt
+fn hash_password(password: &str) -> Result<String, ApiError> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| {
+            error!("Failed to hash password: {}", err);
+            ApiError::Internal("Failed to hash password".to_string())
+        })
+}
+
+/// Check a password against a hash made by [`hash_password`]
+fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .and_then(|hash| Argon2::default().verify_password(password.as_bytes(), &hash))
+        .is_ok()
+}
+
+/// Register a new user
+///
+/// This endpoint requires a JSON body containing [`Credentials`] and returns the new [`user::Model`].
+/// New users are viewers, except the very first one who is an admin.
+#[utoipa::path(
+    context_path = "/auth",
+    tag = "auth",
+    request_body = Credentials,
+    responses(
+        (status = 201, description = "The registered user", body = User),
+        (status = 422, description = "Invalid input or user name taken", body = ErrorBody)
+    )
+)]
+#[post("/register", data = "<credentials>")]
+pub async fn register(
+    db: &State<DatabaseConnection>,
+    credentials: Json<Credentials>,
+) -> Result<(Status, Json<user::Model>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    credentials.validate().map_err(|errors| {
+        info!("Invalid input");
+        ApiError::Validation(errors)
+    })?;
+
+    let password_hash = hash_password(&credentials.password)?;
+
+    let txn = db.begin().await?;
+
+    // Registrations wait for each other, so that two of them cannot both find no user
+    txn.execute(Statement::from_string(
+        DbBackend::Postgres,
+        r#"LOCK TABLE "user" IN SHARE ROW EXCLUSIVE MODE"#.to_string(),
+    ))
+    .await?;
+
+    // Someone has to be able to hand out roles: the first user to register becomes an admin
+    let role = match User::find().one(&txn).await? {
+        Some(_) => Role::Viewer,
+        None => Role::Admin,
+    };
+
+    let new_user = user::ActiveModel {
+        username: ActiveValue::Set(credentials.username.trim().to_string()),
+        password_hash: ActiveValue::Set(password_hash),
+        role: ActiveValue::Set(role),
+        ..Default::default()
+    };
+    let user = new_user.insert(&txn).await?;
+    audit::created(&txn, None, user.id, &user).await?;
+
+    txn.commit().await?;
+
+    info!("Registered user with id: {}", user.id);
+    Ok((Status::Created, Json(user)))
+}
+
+/// Log in with a user name and a password
+///
+/// This endpoint requires a JSON body containing [`Credentials`]. It opens a session, sets its
+/// token as the `session` cookie and returns it as a [`SessionToken`].
+#[utoipa::path(
+    context_path = "/auth",
+    tag = "auth",
+    request_body = Credentials,
+    responses(
+        (status = 200, description = "The new session", body = SessionToken),
+        (status = 401, description = "Wrong user name or password", body = ErrorBody)
+    )
+)]
+#[post("/login", data = "<credentials>")]
+pub async fn login(
+    db: &State<DatabaseConnection>,
+    cookies: &CookieJar<'_>,
+    credentials: Json<Credentials>,
+) -> Result<(Status, Json<SessionToken>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let user = User::find()
+        .filter(user::Column::Username.eq(credentials.username.trim()))
+        .one(db)
+        .await?
+        .filter(|user| verify_password(&credentials.password, &user.password_hash))
+        .ok_or_else(|| ApiError::Unauthorized("Wrong user name or password".to_string()))?;
+
+    // Clean up the expired sessions of the user while we are at it
+    Session::delete_many()
+        .filter(session::Column::UserId.eq(user.id))
+        .filter(session::Column::ExpiresAt.lte(Utc::now().naive_utc()))
+        .exec(db)
+        .await?;
+
+    let token = new_token();
+    let new_session = session::ActiveModel {
+        token: ActiveValue::Set(token.clone()),
+        user_id: ActiveValue::Set(user.id),
+        expires_at: ActiveValue::Set(session_expiry()),
+        ..Default::default()
+    };
+    Session::insert(new_session).exec(db).await?;
+
+    cookies.add(
+        Cookie::build(SESSION_COOKIE, token.clone())
+            .path("/")
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            // Debug builds are served over plain HTTP, where browsers drop secure cookies
+            .secure(!cfg!(debug_assertions))
+            .max_age(time::Duration::days(SESSION_DAYS))
+            .finish(),
+    );
+
+    info!("Logged in user with id: {}", user.id);
+    Ok((Status::Ok, Json(SessionToken { token, user })))
+}
+
+/// Log out, closing the current session
+#[utoipa::path(
+    context_path = "/auth",
+    tag = "auth",
+    responses(
+        (status = 204, description = "The session is closed"),
+        (status = 401, description = "Not signed in", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/logout")]
+pub async fn logout(
+    db: &State<DatabaseConnection>,
+    cookies: &CookieJar<'_>,
+    current: CurrentUser,
+) -> Result<Status, ApiError> {
+    let db = db as &DatabaseConnection;
+
+    Session::delete_by_id(current.session.id).exec(db).await?;
+    cookies.remove(Cookie::named(SESSION_COOKIE));
+
+    info!("Logged out user with id: {}", current.user.id);
+    Ok(Status::NoContent)
+}
+
+/// Get the signed in user
+#[utoipa::path(
+    context_path = "/auth",
+    tag = "auth",
+    responses(
+        (status = 200, description = "The signed in user", body = User),
+        (status = 401, description = "Not signed in", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[get("/me")]
+pub async fn me(current: CurrentUser) -> (Status, Json<user::Model>) {
+    (Status::Ok, Json(current.user))
+}
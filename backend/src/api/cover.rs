@@ -0,0 +1,112 @@
+//! Validation and thumbnails of album covers
+//!
+//! An uploaded cover is stored as it was sent, along with a JPEG thumbnail for each
+//! [`CoverSize`]. The files of a cover are stored under a key of their own, new for each upload,
+//! which also tags them for caching.
+
+use std::io::Cursor;
+
+use image::{codecs::jpeg::JpegEncoder, DynamicImage, ImageFormat};
+use utoipa::ToSchema;
+
+use super::error::FieldError;
+
+/// Quality of the JPEG thumbnails, from 1 to 100
+const THUMBNAIL_QUALITY: u8 = 85;
+
+/// Size of a cover thumbnail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromFormField, ToSchema)]
+#[schema(rename_all = "snake_case")]
+pub enum CoverSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl CoverSize {
+    pub const ALL: [CoverSize; 3] = [CoverSize::Small, CoverSize::Medium, CoverSize::Large];
+
+    /// Largest width and height of the thumbnail, in pixels
+    pub fn pixels(self) -> u32 {
+        match self {
+            CoverSize::Small => 64,
+            CoverSize::Medium => 256,
+            CoverSize::Large => 600,
+        }
+    }
+
+    /// Name of the size, as in the `size` query parameter
+    pub fn name(self) -> &'static str {
+        match self {
+            CoverSize::Small => "small",
+            CoverSize::Medium => "medium",
+            CoverSize::Large => "large",
+        }
+    }
+}
+
+/// An uploaded cover, checked and with its thumbnails
+#[derive(Debug)]
+pub struct Cover {
+    /// Media type of the uploaded image
+    pub content_type: &'static str,
+    pub width: u32,
+    pub height: u32,
+    /// A JPEG for each size
+    pub thumbnails: Vec<(CoverSize, Vec<u8>)>,
+}
+
+/// Check that `bytes` are a JPEG, PNG or WebP image, and make its thumbnails
+///
+/// The format is told by the content of the file, not by its name or the type it was sent with.
+/// Decoding and resizing take a while, so this is better run on a blocking thread.
+pub fn process(bytes: &[u8]) -> Result<Cover, FieldError> {
+    let invalid = || FieldError::new("file", "must be a JPEG, PNG or WebP image");
+
+    let format = image::guess_format(bytes).map_err(|_| invalid())?;
+    let content_type = match format {
+        ImageFormat::Jpeg => "image/jpeg",
+        ImageFormat::Png => "image/png",
+        ImageFormat::WebP => "image/webp",
+        _ => return Err(invalid()),
+    };
+    let image = image::load_from_memory_with_format(bytes, format).map_err(|_| invalid())?;
+
+    let thumbnails = CoverSize::ALL
+        .into_iter()
+        .map(|size| Ok((size, thumbnail(&image, size).map_err(|_| invalid())?)))
+        .collect::<Result<_, FieldError>>()?;
+
+    Ok(Cover {
+        content_type,
+        width: image.width(),
+        height: image.height(),
+        thumbnails,
+    })
+}
+
+/// Encode the image as a JPEG fitting in the size, without ever enlarging it
+fn thumbnail(image: &DynamicImage, size: CoverSize) -> image::ImageResult<Vec<u8>> {
+    let pixels = size.pixels();
+    let resized = if image.width() > pixels || image.height() > pixels {
+        image.thumbnail(pixels, pixels)
+    } else {
+        image.clone()
+    };
+
+    let mut jpeg = Cursor::new(Vec::new());
+    resized
+        .to_rgb8()
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut jpeg, THUMBNAIL_QUALITY))?;
+    Ok(jpeg.into_inner())
+}
+
+/// Storage key of the cover as it was uploaded
+pub fn original_key(key: &str) -> String {
+    format!("covers/{}/original", key)
+}
+
+/// Storage key of a thumbnail of the cover
+pub fn thumbnail_key(key: &str, size: CoverSize) -> String {
+    format!("covers/{}/{}.jpg", key, size.name())
+}
@@ -0,0 +1,198 @@
+pub use entities::validation::FieldError;
+use rocket::{
+    http::Status,
+    response::{self, Responder},
+    serde::json::Json,
+    Request,
+};
+use sea_orm::{DbErr, RuntimeErr};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgDatabaseError;
+use utoipa::ToSchema;
+
+/// Postgres error code of a unique constraint violation
+const UNIQUE_VIOLATION: &str = "23505";
+/// Postgres error code of a foreign key constraint violation
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+
+/// JSON body of every error response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ErrorBody {
+    /// Machine-readable error code, such as `not_found` or `validation_failed`
+    pub code: String,
+    /// Human-readable description of the error
+    pub message: String,
+    /// Per-field validation errors, if any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<FieldError>,
+}
+
+/// Error returned by the API endpoints
+///
+/// Responds with the matching status code and an [`ErrorBody`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// The request is malformed (400)
+    BadRequest(String),
+    /// The request requires a signed in user (401)
+    Unauthorized(String),
+    /// The signed in user is not allowed to do this (403)
+    Forbidden(String),
+    /// The requested entity does not exist (404)
+    NotFound(String),
+    /// The request conflicts with the data in the database (409)
+    Conflict(String),
+    /// The entity changed since the version given in `If-Match` (412)
+    PreconditionFailed(String),
+    /// Some fields of the request are invalid (422)
+    Validation(Vec<FieldError>),
+    /// The request must give the version it changes in `If-Match` (428)
+    PreconditionRequired(String),
+    /// Something went wrong on our side (500)
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn not_found(what: &str) -> Self {
+        ApiError::NotFound(format!("{} not found", what))
+    }
+
+    pub fn status(&self) -> Status {
+        match self {
+            ApiError::BadRequest(_) => Status::BadRequest,
+            ApiError::Unauthorized(_) => Status::Unauthorized,
+            ApiError::Forbidden(_) => Status::Forbidden,
+            ApiError::NotFound(_) => Status::NotFound,
+            ApiError::Conflict(_) => Status::Conflict,
+            ApiError::PreconditionFailed(_) => Status::PreconditionFailed,
+            ApiError::Validation(_) => Status::UnprocessableEntity,
+            ApiError::PreconditionRequired(_) => Status::PreconditionRequired,
+            ApiError::Internal(_) => Status::InternalServerError,
+        }
+    }
+
+    pub fn body(&self) -> ErrorBody {
+        let (code, message, details) = match self {
+            ApiError::BadRequest(message) => ("bad_request", message.as_str(), Vec::new()),
+            ApiError::Unauthorized(message) => ("unauthorized", message.as_str(), Vec::new()),
+            ApiError::Forbidden(message) => ("forbidden", message.as_str(), Vec::new()),
+            ApiError::NotFound(message) => ("not_found", message.as_str(), Vec::new()),
+            ApiError::Conflict(message) => ("conflict", message.as_str(), Vec::new()),
+            ApiError::PreconditionFailed(message) => {
+                ("precondition_failed", message.as_str(), Vec::new())
+            }
+            ApiError::Validation(details) => {
+                ("validation_failed", "Invalid input", details.clone())
+            }
+            ApiError::PreconditionRequired(message) => {
+                ("precondition_required", message.as_str(), Vec::new())
+            }
+            ApiError::Internal(message) => ("internal_error", message.as_str(), Vec::new()),
+        };
+
+        ErrorBody {
+            code: code.to_string(),
+            message: message.to_string(),
+            details,
+        }
+    }
+}
+
+impl From<DbErr> for ApiError {
+    fn from(err: DbErr) -> Self {
+        match &err {
+            DbErr::RecordNotFound(_) => return ApiError::NotFound("Record not found".to_string()),
+            DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Database(db_err)))
+            | DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::Database(db_err))) => {
+                match db_err.code().as_deref() {
+                    Some(FOREIGN_KEY_VIOLATION) => {
+                        return ApiError::Conflict(
+                            "Referenced row does not exist or is still referenced".to_string(),
+                        );
+                    }
+                    Some(UNIQUE_VIOLATION) => {
+                        let field = db_err
+                            .try_downcast_ref::<PgDatabaseError>()
+                            .and_then(|pg_err| pg_err.detail())
+                            .and_then(unique_violation_column)
+                            .unwrap_or("id");
+                        return ApiError::Validation(vec![FieldError::new(
+                            field,
+                            "already exists",
+                        )]);
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+
+        error!("Database error: {}", err);
+        ApiError::Internal("Database error".to_string())
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(err: std::io::Error) -> Self {
+        error!("Storage error: {}", err);
+        ApiError::Internal("Storage error".to_string())
+    }
+}
+
+/// Extract the column from the detail of a unique violation, e.g. `Key (name)=(x) already exists.`
+fn unique_violation_column(detail: &str) -> Option<&str> {
+    let start = detail.find("Key (")? + "Key (".len();
+    let end = start + detail[start..].find(")=")?;
+    Some(&detail[start..end])
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        (self.status(), Json(self.body())).respond_to(request)
+    }
+}
+
+#[catch(401)]
+pub fn unauthorized(_request: &Request<'_>) -> ApiError {
+    ApiError::Unauthorized("Sign in required".to_string())
+}
+
+#[catch(403)]
+pub fn forbidden(_request: &Request<'_>) -> ApiError {
+    ApiError::Forbidden("Permission denied".to_string())
+}
+
+#[catch(404)]
+pub fn not_found(request: &Request<'_>) -> ApiError {
+    ApiError::NotFound(format!(
+        "No route for {} {}",
+        request.method(),
+        request.uri()
+    ))
+}
+
+#[catch(422)]
+pub fn unprocessable_entity(_request: &Request<'_>) -> ApiError {
+    ApiError::Validation(Vec::new())
+}
+
+#[catch(500)]
+pub fn internal_error(_request: &Request<'_>) -> ApiError {
+    ApiError::Internal("Internal server error".to_string())
+}
+
+/// Catch every other error status with the same [`ErrorBody`] shape
+#[catch(default)]
+pub fn default_catcher(status: Status, _request: &Request<'_>) -> (Status, Json<ErrorBody>) {
+    let body = ErrorBody {
+        code: status
+            .reason()
+            .unwrap_or("error")
+            .to_lowercase()
+            .replace(' ', "_"),
+        message: status.reason().unwrap_or("Error").to_string(),
+        details: Vec::new(),
+    };
+
+    (status, Json(body))
+}
@@ -0,0 +1,710 @@
+//! Dumping the catalog and loading a dump back
+//!
+//! Exports read the genres, artists, albums, their links, the songs and their credits, the people
+//! and the labels in one read-only transaction, so that a dump is a consistent snapshot, and
+//! stream the rows from the database cursor as they come. The nested JSON export is the exception
+//! for albums, which are read in batches to gather their links and songs. An NDJSON dump ends with
+//! a trailer counting its rows, so that a dump cut short is told apart from a complete one, and can
+//! be restored into an empty database.
+
+use std::{collections::HashMap, pin::Pin};
+
+use async_stream::try_stream;
+use chrono::NaiveDateTime;
+use entities::{
+    album, album_artist_mtm, album_genre, album_label, artist, artist_genre, artist_membership,
+    genre, label, person, prelude::*, song, song_credit,
+};
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+use rocket::{
+    request::FromParam,
+    serde::json::{from_str, from_value, to_string, Value},
+    tokio::io::{AsyncBufRead, AsyncBufReadExt},
+};
+use sea_orm::{
+    AccessMode, ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend,
+    DbErr, EntityTrait, IdenStatic, IntoActiveModel, IsolationLevel, Iterable, QueryFilter,
+    QueryOrder, QuerySelect, Statement, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::error::ApiError;
+
+/// Albums read at once by the nested JSON export, bounding the memory it uses
+const ALBUM_BATCH: u64 = 500;
+
+/// A table of the catalog, in the order they are dumped and restored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Table {
+    Genre,
+    Artist,
+    Album,
+    AlbumArtistMtm,
+    Song,
+    ArtistGenre,
+    AlbumGenre,
+    Person,
+    ArtistMembership,
+    Label,
+    AlbumLabel,
+    SongCredit,
+}
+
+impl Table {
+    pub const ALL: [Table; 12] = [
+        Table::Genre,
+        Table::Artist,
+        Table::Album,
+        Table::AlbumArtistMtm,
+        Table::Song,
+        Table::ArtistGenre,
+        Table::AlbumGenre,
+        Table::Person,
+        Table::ArtistMembership,
+        Table::Label,
+        Table::AlbumLabel,
+        Table::SongCredit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Table::Genre => "genre",
+            Table::Artist => "artist",
+            Table::Album => "album",
+            Table::AlbumArtistMtm => "album_artist_mtm",
+            Table::Song => "song",
+            Table::ArtistGenre => "artist_genre",
+            Table::AlbumGenre => "album_genre",
+            Table::Person => "person",
+            Table::ArtistMembership => "artist_membership",
+            Table::Label => "label",
+            Table::AlbumLabel => "album_label",
+            Table::SongCredit => "song_credit",
+        }
+    }
+
+    /// Names of the columns of the table, in the order of the fields of its model
+    fn columns(self) -> Vec<String> {
+        match self {
+            Table::Genre => genre::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            Table::Artist => artist::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            Table::Album => album::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            Table::AlbumArtistMtm => album_artist_mtm::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            Table::Song => song::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            Table::ArtistGenre => artist_genre::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            Table::AlbumGenre => album_genre::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            Table::Person => person::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            Table::ArtistMembership => artist_membership::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            Table::Label => label::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            Table::AlbumLabel => album_label::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            Table::SongCredit => song_credit::Column::iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+        }
+    }
+}
+
+impl<'a> FromParam<'a> for Table {
+    type Error = &'a str;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        Table::ALL
+            .into_iter()
+            .find(|table| table.name() == param)
+            .ok_or(param)
+    }
+}
+
+/// A row of any table of the catalog, one line of an NDJSON dump
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "table", content = "row", rename_all = "snake_case")]
+pub enum DumpRow {
+    Genre(genre::Model),
+    Artist(artist::Model),
+    Album(album::Model),
+    AlbumArtistMtm(album_artist_mtm::Model),
+    Song(song::Model),
+    ArtistGenre(artist_genre::Model),
+    AlbumGenre(album_genre::Model),
+    Person(person::Model),
+    ArtistMembership(artist_membership::Model),
+    Label(label::Model),
+    AlbumLabel(album_label::Model),
+    SongCredit(song_credit::Model),
+}
+
+/// An NDJSON line, before its row is read as the model of its table
+#[derive(Deserialize)]
+struct DumpLine {
+    table: Table,
+    row: Value,
+}
+
+/// Id of a dumped genre, person, membership or label, which their models skip on deserialization
+#[derive(Deserialize)]
+struct StoredId {
+    id: i32,
+}
+
+/// Fields of a dumped row that its model skips on deserialization
+#[derive(Deserialize)]
+struct StoredFields {
+    id: i32,
+    version: i32,
+    deleted_at: Option<NaiveDateTime>,
+}
+
+impl DumpRow {
+    pub fn table(&self) -> Table {
+        match self {
+            DumpRow::Genre(_) => Table::Genre,
+            DumpRow::Artist(_) => Table::Artist,
+            DumpRow::Album(_) => Table::Album,
+            DumpRow::AlbumArtistMtm(_) => Table::AlbumArtistMtm,
+            DumpRow::Song(_) => Table::Song,
+            DumpRow::ArtistGenre(_) => Table::ArtistGenre,
+            DumpRow::AlbumGenre(_) => Table::AlbumGenre,
+            DumpRow::Person(_) => Table::Person,
+            DumpRow::ArtistMembership(_) => Table::ArtistMembership,
+            DumpRow::Label(_) => Table::Label,
+            DumpRow::AlbumLabel(_) => Table::AlbumLabel,
+            DumpRow::SongCredit(_) => Table::SongCredit,
+        }
+    }
+
+    /// Read a line of an NDJSON dump, keeping the ids, versions and trash dates
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let parse = || -> Result<Self, rocket::serde::json::serde_json::Error> {
+            let DumpLine { table, row } = from_str(line)?;
+            Ok(match table {
+                Table::Genre => {
+                    let stored: StoredId = from_value(row.clone())?;
+                    DumpRow::Genre(genre::Model {
+                        id: stored.id,
+                        ..from_value(row)?
+                    })
+                }
+                Table::Artist => {
+                    let stored: StoredFields = from_value(row.clone())?;
+                    DumpRow::Artist(artist::Model {
+                        id: stored.id,
+                        version: stored.version,
+                        deleted_at: stored.deleted_at,
+                        ..from_value(row)?
+                    })
+                }
+                Table::Album => {
+                    let stored: StoredFields = from_value(row.clone())?;
+                    DumpRow::Album(album::Model {
+                        id: stored.id,
+                        version: stored.version,
+                        deleted_at: stored.deleted_at,
+                        ..from_value(row)?
+                    })
+                }
+                Table::AlbumArtistMtm => DumpRow::AlbumArtistMtm(from_value(row)?),
+                Table::Song => {
+                    let stored: StoredFields = from_value(row.clone())?;
+                    DumpRow::Song(song::Model {
+                        id: stored.id,
+                        version: stored.version,
+                        deleted_at: stored.deleted_at,
+                        ..from_value(row)?
+                    })
+                }
+                Table::ArtistGenre => DumpRow::ArtistGenre(from_value(row)?),
+                Table::AlbumGenre => DumpRow::AlbumGenre(from_value(row)?),
+                Table::Person => {
+                    let stored: StoredId = from_value(row.clone())?;
+                    DumpRow::Person(person::Model {
+                        id: stored.id,
+                        ..from_value(row)?
+                    })
+                }
+                Table::ArtistMembership => {
+                    let stored: StoredId = from_value(row.clone())?;
+                    DumpRow::ArtistMembership(artist_membership::Model {
+                        id: stored.id,
+                        ..from_value(row)?
+                    })
+                }
+                Table::Label => {
+                    let stored: StoredId = from_value(row.clone())?;
+                    DumpRow::Label(label::Model {
+                        id: stored.id,
+                        ..from_value(row)?
+                    })
+                }
+                Table::AlbumLabel => DumpRow::AlbumLabel(from_value(row)?),
+                Table::SongCredit => DumpRow::SongCredit(from_value(row)?),
+            })
+        };
+        parse().map_err(|err| err.to_string())
+    }
+
+    /// The row as a line of the CSV export of its table
+    fn to_csv(&self) -> Result<String, DbErr> {
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(Vec::new());
+        let written = match self {
+            DumpRow::Genre(genre) => writer.serialize(genre),
+            DumpRow::Artist(artist) => writer.serialize(artist),
+            DumpRow::Album(album) => writer.serialize(album),
+            DumpRow::AlbumArtistMtm(link) => writer.serialize(link),
+            DumpRow::Song(song) => writer.serialize(song),
+            DumpRow::ArtistGenre(link) => writer.serialize(link),
+            DumpRow::AlbumGenre(link) => writer.serialize(link),
+            DumpRow::Person(person) => writer.serialize(person),
+            DumpRow::ArtistMembership(membership) => writer.serialize(membership),
+            DumpRow::Label(label) => writer.serialize(label),
+            DumpRow::AlbumLabel(link) => writer.serialize(link),
+            DumpRow::SongCredit(credit) => writer.serialize(credit),
+        };
+        written.map_err(|err| DbErr::Custom(err.to_string()))?;
+        csv_line(writer)
+    }
+
+    /// Insert the row as it was dumped, id included
+    async fn insert<C: ConnectionTrait>(self, db: &C) -> Result<(), DbErr> {
+        match self {
+            DumpRow::Genre(genre) => {
+                Genre::insert(genre.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+            DumpRow::Artist(artist) => {
+                Artist::insert(artist.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+            DumpRow::Album(album) => {
+                Album::insert(album.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+            DumpRow::AlbumArtistMtm(link) => {
+                AlbumArtistMtm::insert(link.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+            DumpRow::Song(song) => {
+                Song::insert(song.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+            DumpRow::ArtistGenre(link) => {
+                ArtistGenre::insert(link.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+            DumpRow::AlbumGenre(link) => {
+                AlbumGenre::insert(link.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+            DumpRow::Person(person) => {
+                Person::insert(person.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+            DumpRow::ArtistMembership(membership) => {
+                ArtistMembership::insert(membership.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+            DumpRow::Label(label) => {
+                Label::insert(label.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+            DumpRow::AlbumLabel(link) => {
+                AlbumLabel::insert(link.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+            DumpRow::SongCredit(credit) => {
+                SongCredit::insert(credit.into_active_model())
+                    .exec_without_returning(db)
+                    .await?
+            }
+        };
+        Ok(())
+    }
+}
+
+fn csv_line(writer: csv::Writer<Vec<u8>>) -> Result<String, DbErr> {
+    let bytes = writer
+        .into_inner()
+        .map_err(|err| DbErr::Custom(err.to_string()))?;
+    String::from_utf8(bytes).map_err(|err| DbErr::Custom(err.to_string()))
+}
+
+fn json<T: Serialize>(value: &T) -> Result<String, DbErr> {
+    to_string(value).map_err(|err| DbErr::Json(err.to_string()))
+}
+
+/// An album of the nested JSON export, with the links to its artists and its songs
+#[derive(Serialize)]
+struct NestedAlbum {
+    #[serde(flatten)]
+    album: album::Model,
+    links: Vec<album_artist_mtm::Model>,
+    songs: Vec<song::Model>,
+}
+
+/// Start the read-only transaction an export reads its snapshot from
+async fn snapshot(db: &DatabaseConnection) -> Result<DatabaseTransaction, DbErr> {
+    db.begin_with_config(
+        Some(IsolationLevel::RepeatableRead),
+        Some(AccessMode::ReadOnly),
+    )
+    .await
+}
+
+type RowStream<'a> = Pin<Box<dyn Stream<Item = Result<DumpRow, DbErr>> + Send + 'a>>;
+
+/// Every row of an entity, by id, straight from the cursor
+fn rows_of<'a, E, F>(txn: &'a DatabaseTransaction, id: E::Column, wrap: F) -> RowStream<'a>
+where
+    E: EntityTrait,
+    E::Model: Send + Sync + 'a,
+    F: Fn(E::Model) -> DumpRow + Send + 'a,
+{
+    Box::pin(
+        stream::once(E::find().order_by_asc(id).stream(txn))
+            .try_flatten()
+            .map_ok(wrap),
+    )
+}
+
+/// Every row of a table, by id
+fn table_rows(txn: &DatabaseTransaction, table: Table) -> RowStream<'_> {
+    match table {
+        Table::Genre => rows_of::<Genre, _>(txn, genre::Column::Id, DumpRow::Genre),
+        Table::Artist => rows_of::<Artist, _>(txn, artist::Column::Id, DumpRow::Artist),
+        Table::Album => rows_of::<Album, _>(txn, album::Column::Id, DumpRow::Album),
+        Table::AlbumArtistMtm => {
+            rows_of::<AlbumArtistMtm, _>(txn, album_artist_mtm::Column::Id, DumpRow::AlbumArtistMtm)
+        }
+        Table::Song => rows_of::<Song, _>(txn, song::Column::Id, DumpRow::Song),
+        Table::ArtistGenre => {
+            rows_of::<ArtistGenre, _>(txn, artist_genre::Column::Id, DumpRow::ArtistGenre)
+        }
+        Table::AlbumGenre => {
+            rows_of::<AlbumGenre, _>(txn, album_genre::Column::Id, DumpRow::AlbumGenre)
+        }
+        Table::Person => rows_of::<Person, _>(txn, person::Column::Id, DumpRow::Person),
+        Table::ArtistMembership => rows_of::<ArtistMembership, _>(
+            txn,
+            artist_membership::Column::Id,
+            DumpRow::ArtistMembership,
+        ),
+        Table::Label => rows_of::<Label, _>(txn, label::Column::Id, DumpRow::Label),
+        Table::AlbumLabel => {
+            rows_of::<AlbumLabel, _>(txn, album_label::Column::Id, DumpRow::AlbumLabel)
+        }
+        Table::SongCredit => {
+            rows_of::<SongCredit, _>(txn, song_credit::Column::Id, DumpRow::SongCredit)
+        }
+    }
+}
+
+/// The catalog as NDJSON, one [`DumpRow`] per line, tables in the order of [`Table::ALL`]
+///
+/// The last line is a [`DumpTrailer`], only written once every row was.
+pub fn ndjson(db: &DatabaseConnection) -> impl Stream<Item = Result<String, DbErr>> + Send + '_ {
+    try_stream! {
+        let txn = snapshot(db).await?;
+
+        let mut rows = stream::iter(Table::ALL).flat_map(|table| table_rows(&txn, table));
+        let mut counts = RestoredRows::default();
+        while let Some(row) = rows.try_next().await? {
+            counts.count(row.table());
+            let line = json(&row)?;
+            yield format!("{}\n", line);
+        }
+        drop(rows);
+        txn.commit().await?;
+
+        let trailer = json(&DumpTrailer { trailer: counts })?;
+        yield format!("{}\n", trailer);
+    }
+}
+
+/// A table as CSV, with a header line naming its columns
+pub fn csv(
+    db: &DatabaseConnection,
+    table: Table,
+) -> impl Stream<Item = Result<String, DbErr>> + Send + '_ {
+    try_stream! {
+        let txn = snapshot(db).await?;
+
+        let mut header = csv::Writer::from_writer(Vec::new());
+        header
+            .write_record(table.columns())
+            .map_err(|err| DbErr::Custom(err.to_string()))?;
+        let header = csv_line(header)?;
+        yield header;
+
+        let mut rows = table_rows(&txn, table);
+        while let Some(row) = rows.try_next().await? {
+            let line = row.to_csv()?;
+            yield line;
+        }
+        drop(rows);
+        txn.commit().await?;
+    }
+}
+
+/// The catalog as one JSON object, with the artists and the albums along with their songs
+pub fn nested_json(
+    db: &DatabaseConnection,
+) -> impl Stream<Item = Result<String, DbErr>> + Send + '_ {
+    try_stream! {
+        let txn = snapshot(db).await?;
+
+        yield r#"{"artists":["#.to_string();
+        let mut artists = table_rows(&txn, Table::Artist);
+        let mut separator = "";
+        while let Some(row) = artists.try_next().await? {
+            if let DumpRow::Artist(artist) = row {
+                let artist = json(&artist)?;
+                yield format!("{}{}", separator, artist);
+                separator = ",";
+            }
+        }
+        drop(artists);
+
+        yield r#"],"albums":["#.to_string();
+        let mut separator = "";
+        let mut after = 0;
+        loop {
+            let albums = Album::find()
+                .filter(album::Column::Id.gt(after))
+                .order_by_asc(album::Column::Id)
+                .limit(ALBUM_BATCH)
+                .all(&txn)
+                .await?;
+            let Some(last) = albums.last() else {
+                break;
+            };
+            after = last.id;
+
+            let ids: Vec<i32> = albums.iter().map(|album| album.id).collect();
+            let mut links: HashMap<i32, Vec<album_artist_mtm::Model>> = HashMap::new();
+            for link in AlbumArtistMtm::find()
+                .filter(album_artist_mtm::Column::AlbumId.is_in(ids.clone()))
+                .order_by_asc(album_artist_mtm::Column::Id)
+                .all(&txn)
+                .await?
+            {
+                links.entry(link.album_id).or_default().push(link);
+            }
+            let mut songs: HashMap<i32, Vec<song::Model>> = HashMap::new();
+            for song in Song::find()
+                .filter(song::Column::AlbumId.is_in(ids))
+                .order_by_asc(song::Column::Id)
+                .all(&txn)
+                .await?
+            {
+                songs.entry(song.album_id).or_default().push(song);
+            }
+
+            for album in albums {
+                let nested = NestedAlbum {
+                    links: links.remove(&album.id).unwrap_or_default(),
+                    songs: songs.remove(&album.id).unwrap_or_default(),
+                    album,
+                };
+                let nested = json(&nested)?;
+                yield format!("{}{}", separator, nested);
+                separator = ",";
+            }
+        }
+        yield "]}".to_string();
+        txn.commit().await?;
+    }
+}
+
+/// Number of rows restored in each table, or dumped in the trailer of a dump
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RestoredRows {
+    pub genre: u64,
+    pub artist: u64,
+    pub album: u64,
+    pub album_artist_mtm: u64,
+    pub song: u64,
+    pub artist_genre: u64,
+    pub album_genre: u64,
+    pub person: u64,
+    pub artist_membership: u64,
+    pub label: u64,
+    pub album_label: u64,
+    pub song_credit: u64,
+}
+
+impl RestoredRows {
+    fn count(&mut self, table: Table) {
+        match table {
+            Table::Genre => self.genre += 1,
+            Table::Artist => self.artist += 1,
+            Table::Album => self.album += 1,
+            Table::AlbumArtistMtm => self.album_artist_mtm += 1,
+            Table::Song => self.song += 1,
+            Table::ArtistGenre => self.artist_genre += 1,
+            Table::AlbumGenre => self.album_genre += 1,
+            Table::Person => self.person += 1,
+            Table::ArtistMembership => self.artist_membership += 1,
+            Table::Label => self.label += 1,
+            Table::AlbumLabel => self.album_label += 1,
+            Table::SongCredit => self.song_credit += 1,
+        }
+    }
+}
+
+/// The last line of an NDJSON dump, such as `{"trailer":{"genre":3,...}}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpTrailer {
+    /// Number of rows of each table before the trailer
+    pub trailer: RestoredRows,
+}
+
+/// Whether no table of the catalog has any row, trashed ones included
+///
+/// The genres of artists and albums, the memberships of people, the labels of albums and the
+/// credits of songs cannot be there without them.
+async fn catalog_is_empty<C: ConnectionTrait>(db: &C) -> Result<bool, DbErr> {
+    Ok(Genre::find().one(db).await?.is_none()
+        && Artist::find().one(db).await?.is_none()
+        && Album::find().one(db).await?.is_none()
+        && AlbumArtistMtm::find().one(db).await?.is_none()
+        && Song::find().one(db).await?.is_none()
+        && Person::find().one(db).await?.is_none()
+        && Label::find().one(db).await?.is_none())
+}
+
+/// Load an NDJSON dump into an empty database, in one transaction
+///
+/// The rows keep their ids, and the id sequences continue after the largest restored id. The
+/// restored rows are not recorded in the audit log, they are not changes to the catalog. A dump
+/// without its trailer, or with rows its trailer does not count, was cut short and is rejected.
+pub async fn restore<R: AsyncBufRead + Unpin>(
+    db: &DatabaseConnection,
+    mut dump: R,
+    max_bytes: u64,
+) -> Result<RestoredRows, ApiError> {
+    let txn = db.begin().await?;
+
+    if !catalog_is_empty(&txn).await? {
+        info!("Catalog not empty");
+        return Err(ApiError::Conflict(
+            "The database already has a catalog, a dump can only be restored into an empty one"
+                .to_string(),
+        ));
+    }
+
+    // Genres and labels are dumped by id, so a parent may only come after its children
+    txn.execute(Statement::from_string(
+        DbBackend::Postgres,
+        r#"SET CONSTRAINTS "fk-genre-parent_id", "fk-label-parent_id" DEFERRED"#.to_string(),
+    ))
+    .await?;
+
+    let mut restored = RestoredRows::default();
+    let mut trailer = None;
+    let mut line = String::new();
+    let mut read = 0;
+    for number in 1.. {
+        line.clear();
+        let length = dump
+            .read_line(&mut line)
+            .await
+            .map_err(|err| ApiError::BadRequest(format!("Failed to read the dump: {}", err)))?;
+        if length == 0 {
+            break;
+        }
+        read += length as u64;
+        if read >= max_bytes {
+            info!("Dump too large");
+            return Err(ApiError::BadRequest(format!(
+                "The dump is larger than {} bytes",
+                max_bytes
+            )));
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        if trailer.is_some() {
+            info!("Line after the trailer");
+            return Err(ApiError::BadRequest(format!(
+                "Line {}: nothing may follow the trailer of the dump",
+                number
+            )));
+        }
+        if let Ok(DumpTrailer { trailer: counts }) = from_str(&line) {
+            trailer = Some(counts);
+            continue;
+        }
+
+        let row = DumpRow::parse(&line)
+            .map_err(|err| ApiError::BadRequest(format!("Line {}: {}", number, err)))?;
+        restored.count(row.table());
+        row.insert(&txn).await?;
+    }
+
+    match trailer {
+        None => {
+            info!("Dump without a trailer");
+            return Err(ApiError::BadRequest(
+                "The dump has no trailer, it was cut short".to_string(),
+            ));
+        }
+        Some(counts) if counts != restored => {
+            info!("Dump with rows its trailer does not count");
+            return Err(ApiError::BadRequest(
+                "The rows of the dump do not match the counts of its trailer".to_string(),
+            ));
+        }
+        Some(_) => {}
+    }
+
+    for table in Table::ALL {
+        txn.execute(Statement::from_string(
+            DbBackend::Postgres,
+            format!(
+                r#"SELECT setval(pg_get_serial_sequence('"{table}"', 'id'), COALESCE(MAX("id"), 0) + 1, false) FROM "{table}""#,
+                table = table.name()
+            ),
+        ))
+        .await?;
+    }
+
+    txn.commit().await?;
+
+    Ok(restored)
+}
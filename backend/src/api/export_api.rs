@@ -0,0 +1,168 @@
+use futures::{future, Stream, StreamExt};
+use rocket::{
+    data::{Data, ToByteUnit},
+    http::{ContentType, Status},
+    response::stream::TextStream,
+    serde::json::Json,
+    tokio::io::BufReader,
+    State,
+};
+use sea_orm::{DatabaseConnection, DbErr};
+
+use super::{
+    auth::Admin,
+    error::ApiError,
+    export::{self, RestoredRows, Table},
+};
+
+/// Largest accepted dump, in gibibytes, larger ones can be restored with `backend restore`
+const MAX_DUMP_GIB: u64 = 4;
+
+/// Line ending an export that failed, the status of the response being already sent
+const EXPORT_FAILED: &str = "\nERROR: the export failed, it is incomplete\n";
+
+/// Stop an export at its first error, which can only be logged once the response has started
+///
+/// The export then ends with [`EXPORT_FAILED`], so that it cannot be mistaken for a complete one.
+fn until_error<'a, S>(chunks: S) -> TextStream<impl Stream<Item = String> + Send + 'a>
+where
+    S: Stream<Item = Result<String, DbErr>> + Send + 'a,
+{
+    TextStream(chunks.scan(false, |failed, chunk| {
+        if *failed {
+            return future::ready(None);
+        }
+        future::ready(Some(match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                error!("Export failed: {}", err);
+                *failed = true;
+                EXPORT_FAILED.to_string()
+            }
+        }))
+    }))
+}
+
+fn ndjson_type() -> ContentType {
+    ContentType::new("application", "x-ndjson")
+}
+
+/// Export the catalog as JSON
+///
+/// This endpoint streams an object with the `artists`, and the `albums` along with the `links` to
+/// their artists and their `songs`. Trashed rows are included, with their `deleted_at`.
+#[utoipa::path(
+    context_path = "/export",
+    tag = "export",
+    responses(
+        (status = 200, description = "The whole catalog", body = String, content_type = "application/json"),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[get("/json")]
+pub async fn export_json<'a>(
+    db: &'a State<DatabaseConnection>,
+    _admin: Admin,
+) -> (
+    ContentType,
+    TextStream<impl Stream<Item = String> + Send + 'a>,
+) {
+    info!("Exporting the catalog as JSON");
+    (ContentType::JSON, until_error(export::nested_json(db)))
+}
+
+/// Export the catalog as NDJSON
+///
+/// This endpoint streams one line per row, such as `{"table":"artist","row":{...}}`, with the
+/// genres first, then the artists, the albums, the links between them, the songs, the genres of
+/// the artists and albums, the people and their memberships of artists, the labels and their
+/// albums, and the credits of the songs. The last line is a trailer with the number of rows of
+/// each table, such as `{"trailer":{"genre":3,...}}`. This is the format restored by
+/// `/export/restore`.
+#[utoipa::path(
+    context_path = "/export",
+    tag = "export",
+    responses(
+        (status = 200, description = "One row of the catalog per line", body = String, content_type = "application/x-ndjson"),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[get("/ndjson")]
+pub async fn export_ndjson<'a>(
+    db: &'a State<DatabaseConnection>,
+    _admin: Admin,
+) -> (
+    ContentType,
+    TextStream<impl Stream<Item = String> + Send + 'a>,
+) {
+    info!("Exporting the catalog as NDJSON");
+    (ndjson_type(), until_error(export::ndjson(db)))
+}
+
+/// Export a table of the catalog as CSV
+///
+/// This endpoint streams the rows of `table`, one of `genre`, `artist`, `album`,
+/// `album_artist_mtm`, `song`, `artist_genre`, `album_genre`, `person`, `artist_membership`,
+/// `label`, `album_label` or `song_credit`, after a header line naming its columns.
+#[utoipa::path(
+    context_path = "/export",
+    tag = "export",
+    params(("table" = Table, Path, description = "Table to export")),
+    responses(
+        (status = 200, description = "Rows of the table", body = String, content_type = "text/csv"),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody),
+        (status = 404, description = "No such table", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[get("/csv/<table>")]
+pub async fn export_csv<'a>(
+    db: &'a State<DatabaseConnection>,
+    _admin: Admin,
+    table: Table,
+) -> (
+    ContentType,
+    TextStream<impl Stream<Item = String> + Send + 'a>,
+) {
+    info!("Exporting table {} as CSV", table.name());
+    (ContentType::CSV, until_error(export::csv(db, table)))
+}
+
+/// Restore an NDJSON export into an empty database
+///
+/// This endpoint requires a dump from `/export/ndjson`, and returns the number of rows restored
+/// in each table. The rows keep their ids. Nothing is restored if any line is invalid, if the rows
+/// do not match the trailer of the dump, or if the database already has genres, artists, albums,
+/// songs, people or labels, trashed ones included.
+#[utoipa::path(
+    context_path = "/export",
+    tag = "export",
+    request_body(content = String, content_type = "application/x-ndjson"),
+    responses(
+        (status = 201, description = "Rows restored in each table", body = RestoredRows),
+        (status = 400, description = "Malformed dump", body = ErrorBody),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody),
+        (status = 409, description = "The database is not empty", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/restore", data = "<dump>")]
+pub async fn restore(
+    db: &State<DatabaseConnection>,
+    _admin: Admin,
+    dump: Data<'_>,
+) -> Result<(Status, Json<RestoredRows>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let limit = MAX_DUMP_GIB.gibibytes();
+    let restored = export::restore(db, BufReader::new(dump.open(limit)), limit.as_u64()).await?;
+
+    info!("Restored {:?}", restored);
+    Ok((Status::Created, Json(restored)))
+}
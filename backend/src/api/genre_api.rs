@@ -0,0 +1,401 @@
+//! Genres, and the genres of artists and albums
+//!
+//! A genre can belong to a broader parent genre, such as hard rock to rock, so genres form a tree.
+//! Looking artists up by a genre includes those of its subgenres, at any depth. Genre names are
+//! unique regardless of case, and are matched that way.
+
+use std::collections::BTreeSet;
+
+use entities::{album_genre, artist_genre, genre, prelude::*, user, validation::Validate};
+use rocket::{http::Status, serde::json::Json, State};
+use sea_orm::{
+    sea_query::{Expr, Func},
+    ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, TransactionTrait,
+};
+
+use super::{
+    audit,
+    auth::{Admin, Editor},
+    error::{ApiError, FieldError},
+    tree,
+};
+
+/// The genre with the given name, regardless of case
+pub async fn find_by_name<C: ConnectionTrait>(
+    db: &C,
+    name: &str,
+) -> Result<Option<genre::Model>, DbErr> {
+    Genre::find()
+        .filter(
+            Expr::expr(Func::lower(Expr::col((genre::Entity, genre::Column::Name))))
+                .eq(name.trim().to_lowercase()),
+        )
+        .one(db)
+        .await
+}
+
+/// Check a genre given to the genre with the given id, or to a new one when there is none
+///
+/// Besides its fields, its name must not be taken by another of the `genres`, and its parent must
+/// be one of them without being the genre itself or one of its subgenres.
+fn check(genre: &genre::Model, id: Option<i32>, genres: &[genre::Model]) -> Result<(), ApiError> {
+    let mut errors = genre.validate().err().unwrap_or_default();
+
+    let name = genre.name.trim().to_lowercase();
+    if genres
+        .iter()
+        .any(|other| other.name.to_lowercase() == name && Some(other.id) != id)
+    {
+        errors.push(FieldError::new("name", "already exists"));
+    }
+
+    errors.extend(tree::check_parent(
+        genres,
+        id,
+        genre.parent_id,
+        "the genre or one of its subgenres",
+    ));
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        info!("Invalid input");
+        Err(ApiError::Validation(errors))
+    }
+}
+
+/// Create a new genre
+///
+/// This endpoint requires a JSON body containing a [`genre::Model`] without an id.
+#[utoipa::path(
+    context_path = "/genre",
+    tag = "genre",
+    request_body = Genre,
+    responses(
+        (status = 201, description = "Id of the created genre", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/", data = "<genre>")]
+pub async fn create_genre(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    genre: Json<genre::Model>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let genres = Genre::find().all(&txn).await?;
+    check(&genre, None, &genres)?;
+
+    let new_genre = genre::ActiveModel {
+        name: ActiveValue::Set(genre.name.trim().to_string()),
+        parent_id: ActiveValue::Set(genre.parent_id),
+        ..Default::default()
+    };
+    let id = Genre::insert(new_genre).exec(&txn).await?.last_insert_id;
+    let genre = genre::Model {
+        id,
+        name: genre.name.trim().to_string(),
+        parent_id: genre.parent_id,
+    };
+    audit::created(&txn, Some(&editor.0.user), id, &genre).await?;
+
+    txn.commit().await?;
+
+    info!("Inserted genre with id: {}", id);
+    Ok((Status::Created, id.to_string()))
+}
+
+/// Get a genre by its id
+///
+/// This endpoint returns a [`genre::Model`] with the given id, if it exists.
+#[utoipa::path(
+    context_path = "/genre",
+    tag = "genre",
+    responses(
+        (status = 302, description = "The genre", body = Genre),
+        (status = 404, description = "Genre not found", body = ErrorBody)
+    )
+)]
+#[get("/<id>")]
+pub async fn get_genre_by_id(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<genre::Model>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let genre = Genre::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Genre"))?;
+
+    info!("Found genre with id: {}", genre.id);
+    Ok((Status::Found, Json(genre)))
+}
+
+/// Update a genre with the given id
+///
+/// This endpoint requires a JSON body containing a [`genre::Model`] with all of the fields set,
+/// an explicit `null` parent making it a top-level genre.
+#[utoipa::path(
+    context_path = "/genre",
+    tag = "genre",
+    request_body = Genre,
+    responses(
+        (status = 202, description = "Id of the updated genre", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Genre not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/<id>", data = "<genre>")]
+pub async fn update_genre(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    id: i32,
+    genre: Json<genre::Model>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let genres = Genre::find().lock_exclusive().all(&txn).await?;
+    let before = genres
+        .iter()
+        .find(|genre| genre.id == id)
+        .cloned()
+        .ok_or_else(|| ApiError::not_found("Genre"))?;
+    check(&genre, Some(id), &genres)?;
+
+    let updated_genre = genre::ActiveModel {
+        id: ActiveValue::Set(id),
+        name: ActiveValue::Set(genre.name.trim().to_string()),
+        parent_id: ActiveValue::Set(genre.parent_id),
+    };
+    let after = Genre::update(updated_genre).exec(&txn).await?;
+    audit::updated(&txn, Some(&editor.0.user), id, &before, &after).await?;
+
+    txn.commit().await?;
+
+    info!("Updated genre with id: {}", id);
+    Ok((Status::Accepted, id.to_string()))
+}
+
+/// Delete a genre with the given id
+///
+/// This endpoint returns the id of the deleted genre, if it exists. The artists and albums of the
+/// genre lose it. A genre with subgenres cannot be deleted, they have to be moved or deleted first.
+#[utoipa::path(
+    context_path = "/genre",
+    tag = "genre",
+    responses(
+        (status = 200, description = "Id of the deleted genre", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody),
+        (status = 404, description = "Genre not found", body = ErrorBody),
+        (status = 409, description = "The genre has subgenres", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[delete("/<id>")]
+pub async fn delete_genre(
+    db: &State<DatabaseConnection>,
+    admin: Admin,
+    id: i32,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let genre = Genre::find_by_id(id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Genre"))?;
+    let subgenre = Genre::find()
+        .filter(genre::Column::ParentId.eq(id))
+        .one(&txn)
+        .await?;
+    if subgenre.is_some() {
+        info!("Genre {} has subgenres", id);
+        return Err(ApiError::Conflict(
+            "The genre has subgenres, move or delete them first".to_string(),
+        ));
+    }
+
+    // Its links to artists and albums go with it, through the cascade of their foreign keys
+    Genre::delete_by_id(id).exec(&txn).await?;
+    audit::deleted(&txn, Some(&admin.0.user), id, &genre).await?;
+
+    txn.commit().await?;
+
+    info!("Deleted genre with id: {}", id);
+    Ok((Status::Ok, id.to_string()))
+}
+
+/// Get all genres
+///
+/// This endpoint returns every [`genre::Model`], sorted by name. There are few enough of them to
+/// fill a form field at once, so the list is not paginated.
+#[utoipa::path(
+    context_path = "/genre",
+    tag = "genre",
+    responses(
+        (status = 200, description = "Every genre", body = [Genre])
+    )
+)]
+#[get("/all")]
+pub async fn get_all_genres(
+    db: &State<DatabaseConnection>,
+) -> Result<(Status, Json<Vec<genre::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let genres = Genre::find()
+        .order_by_asc(genre::Column::Name)
+        .all(db)
+        .await?;
+
+    info!("Found {} genres", genres.len());
+    Ok((Status::Ok, Json(genres)))
+}
+
+/// The genres with the given ids, sorted by name, or an error if some of them do not exist
+async fn find_genres<C: ConnectionTrait>(
+    db: &C,
+    genre_ids: &BTreeSet<i32>,
+) -> Result<Vec<genre::Model>, ApiError> {
+    let genres = Genre::find()
+        .filter(genre::Column::Id.is_in(genre_ids.iter().copied()))
+        .order_by_asc(genre::Column::Name)
+        .all(db)
+        .await?;
+
+    if genres.len() != genre_ids.len() {
+        info!("Not all genres were found");
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "genre_ids",
+            "some genres do not exist",
+        )]));
+    }
+
+    Ok(genres)
+}
+
+/// Genres of the artist with the given id, sorted by name
+pub async fn artist_genres<C: ConnectionTrait>(
+    db: &C,
+    artist_id: i32,
+) -> Result<Vec<genre::Model>, DbErr> {
+    Genre::find()
+        .inner_join(ArtistGenre)
+        .filter(artist_genre::Column::ArtistId.eq(artist_id))
+        .order_by_asc(genre::Column::Name)
+        .all(db)
+        .await
+}
+
+/// Give the artist with the given id exactly the given genres, returning them sorted by name
+///
+/// Only the links that change are added or removed, and each of them is audited.
+pub async fn set_artist_genres<C: ConnectionTrait>(
+    txn: &C,
+    user: &user::Model,
+    artist_id: i32,
+    genre_ids: BTreeSet<i32>,
+) -> Result<Vec<genre::Model>, ApiError> {
+    let genres = find_genres(txn, &genre_ids).await?;
+
+    let links = ArtistGenre::find()
+        .filter(artist_genre::Column::ArtistId.eq(artist_id))
+        .all(txn)
+        .await?;
+    for link in &links {
+        if !genre_ids.contains(&link.genre_id) {
+            ArtistGenre::delete_by_id(link.id).exec(txn).await?;
+            audit::deleted(txn, Some(user), link.id, link).await?;
+        }
+    }
+    for genre in &genres {
+        if !links.iter().any(|link| link.genre_id == genre.id) {
+            let new_link = artist_genre::ActiveModel {
+                artist_id: ActiveValue::Set(artist_id),
+                genre_id: ActiveValue::Set(genre.id),
+                ..Default::default()
+            };
+            let id = ArtistGenre::insert(new_link)
+                .exec(txn)
+                .await?
+                .last_insert_id;
+            let link = artist_genre::Model {
+                id,
+                artist_id,
+                genre_id: genre.id,
+            };
+            audit::created(txn, Some(user), id, &link).await?;
+        }
+    }
+
+    Ok(genres)
+}
+
+/// Genres of the album with the given id, sorted by name
+pub async fn album_genres<C: ConnectionTrait>(
+    db: &C,
+    album_id: i32,
+) -> Result<Vec<genre::Model>, DbErr> {
+    Genre::find()
+        .inner_join(AlbumGenre)
+        .filter(album_genre::Column::AlbumId.eq(album_id))
+        .order_by_asc(genre::Column::Name)
+        .all(db)
+        .await
+}
+
+/// Give the album with the given id exactly the given genres, returning them sorted by name
+///
+/// Only the links that change are added or removed, and each of them is audited.
+pub async fn set_album_genres<C: ConnectionTrait>(
+    txn: &C,
+    user: &user::Model,
+    album_id: i32,
+    genre_ids: BTreeSet<i32>,
+) -> Result<Vec<genre::Model>, ApiError> {
+    let genres = find_genres(txn, &genre_ids).await?;
+
+    let links = AlbumGenre::find()
+        .filter(album_genre::Column::AlbumId.eq(album_id))
+        .all(txn)
+        .await?;
+    for link in &links {
+        if !genre_ids.contains(&link.genre_id) {
+            AlbumGenre::delete_by_id(link.id).exec(txn).await?;
+            audit::deleted(txn, Some(user), link.id, link).await?;
+        }
+    }
+    for genre in &genres {
+        if !links.iter().any(|link| link.genre_id == genre.id) {
+            let new_link = album_genre::ActiveModel {
+                album_id: ActiveValue::Set(album_id),
+                genre_id: ActiveValue::Set(genre.id),
+                ..Default::default()
+            };
+            let id = AlbumGenre::insert(new_link).exec(txn).await?.last_insert_id;
+            let link = album_genre::Model {
+                id,
+                album_id,
+                genre_id: genre.id,
+            };
+            audit::created(txn, Some(user), id, &link).await?;
+        }
+    }
+
+    Ok(genres)
+}
@@ -0,0 +1,473 @@
+//! Bulk import of artists, albums and songs
+//!
+//! A document is a list of rows, each naming an artist and optionally one of its albums and one
+//! song of that album. Artists, albums and songs are matched by name, and created when missing
+//! with the same validation as the `create_*` endpoints. The whole document is imported in one
+//! transaction, which is only committed when no row was rejected and it is not a dry run.
+
+use chrono::NaiveDate;
+use entities::{
+    album, album_artist_mtm, artist, artist_genre,
+    prelude::*,
+    song, user,
+    validation::{FieldError, Validate},
+};
+use rocket::serde::json::{from_str, from_value, Value};
+use sea_orm::{
+    ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{
+    audit,
+    error::ApiError,
+    genre_api,
+    song_api::{next_track_number, track_taken},
+    trash::Live,
+    version::INITIAL_VERSION,
+};
+
+/// Format of an import document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// A header line with the fields of [`ImportRow`], then one line per row
+    Csv,
+    /// An array of [`ImportRow`]
+    Json,
+}
+
+/// One row of an import document
+///
+/// The dates and the song length are only required to create a missing artist, album or song.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct ImportRow {
+    /// Name of the artist
+    pub artist: String,
+    #[schema(value_type = Option<String>, format = Date)]
+    pub artist_date_formed: Option<NaiveDate>,
+    /// Name of a genre of the artist, such as `rock`, matched regardless of case
+    pub artist_genre: Option<String>,
+    /// Name of an album of the artist
+    pub album: Option<String>,
+    #[schema(value_type = Option<String>, format = Date)]
+    pub album_date_published: Option<NaiveDate>,
+    /// Name of a song of the album
+    pub song: Option<String>,
+    pub song_length_secs: Option<i32>,
+    /// Disc of the song, the first one when not given
+    pub song_disc_number: Option<i32>,
+    /// Track of the song on its disc, the one after the last when not given
+    pub song_track_number: Option<i32>,
+}
+
+/// What the import did with a row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    /// Something named by the row was missing and got created
+    Created,
+    /// Everything named by the row already existed
+    Matched,
+    /// The row is invalid, see its errors
+    Rejected,
+}
+
+/// Outcome of one row of an import
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ImportedRow {
+    /// Number of the row in the document, starting at 1
+    pub row: usize,
+    pub status: ImportStatus,
+    pub artist_id: Option<i32>,
+    pub album_id: Option<i32>,
+    pub song_id: Option<i32>,
+    /// Why the row was rejected, the fields being those of the row
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+impl ImportedRow {
+    /// A row rejected for the given errors
+    pub fn rejected(row: usize, errors: Vec<FieldError>) -> Self {
+        ImportedRow {
+            row,
+            status: ImportStatus::Rejected,
+            artist_id: None,
+            album_id: None,
+            song_id: None,
+            errors,
+        }
+    }
+}
+
+/// Outcome of an import
+///
+/// The ids of a dry run or of an import with rejected rows are those the rows would have had.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ImportReport {
+    /// Whether the changes were written to the database
+    pub committed: bool,
+    pub rows: Vec<ImportedRow>,
+}
+
+impl ImportReport {
+    /// Whether any row was rejected
+    pub fn has_rejections(&self) -> bool {
+        self.rows
+            .iter()
+            .any(|row| row.status == ImportStatus::Rejected)
+    }
+}
+
+/// Parse a document into its rows, a row failing to parse being an error message
+pub fn parse(
+    format: ImportFormat,
+    document: &str,
+) -> Result<Vec<Result<ImportRow, String>>, ApiError> {
+    match format {
+        ImportFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(document.as_bytes());
+            Ok(reader
+                .deserialize()
+                .map(|row| row.map_err(|err| csv_message(&err)))
+                .collect())
+        }
+        ImportFormat::Json => {
+            let rows: Vec<Value> = from_str(document)
+                .map_err(|err| ApiError::BadRequest(format!("Invalid JSON document: {}", err)))?;
+            Ok(rows
+                .into_iter()
+                .map(|row| from_value(row).map_err(|err| err.to_string()))
+                .collect())
+        }
+    }
+}
+
+/// Error message of a CSV record, without its position as the report already gives the row
+fn csv_message(err: &csv::Error) -> String {
+    match err.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => match err.field() {
+            Some(field) => format!("field {}: {}", field + 1, err.kind()),
+            None => err.kind().to_string(),
+        },
+        _ => err.to_string(),
+    }
+}
+
+/// Errors of an entity, renamed after the fields of the row
+fn row_errors(prefix: &str, errors: Vec<FieldError>) -> Vec<FieldError> {
+    errors
+        .into_iter()
+        .map(|error| match error.field.as_str() {
+            "name" => FieldError::new(prefix, error.message),
+            field => FieldError::new(format!("{}_{}", prefix, field), error.message),
+        })
+        .collect()
+}
+
+fn required<T: Copy>(errors: &mut Vec<FieldError>, field: &str, value: Option<T>) -> Option<T> {
+    if value.is_none() {
+        errors.push(FieldError::new(field, "is required to create it"));
+    }
+    value
+}
+
+/// The artist to create for a row, or why it is invalid
+///
+/// `known_genre` tells whether the genre of the row, if any, was found.
+fn new_artist(row: &ImportRow, known_genre: bool) -> Result<artist::Model, Vec<FieldError>> {
+    let mut artist_errors = Vec::new();
+    let date_formed = required(
+        &mut artist_errors,
+        "artist_date_formed",
+        row.artist_date_formed,
+    );
+    if !known_genre {
+        artist_errors.push(FieldError::new("artist_genre", "is not a known genre"));
+    }
+
+    let artist = artist::Model {
+        id: 0,
+        name: row.artist.clone(),
+        date_formed: date_formed.unwrap_or_default(),
+        version: INITIAL_VERSION,
+        deleted_at: None,
+    };
+    if let Err(invalid) = artist.validate() {
+        artist_errors.extend(row_errors("artist", invalid));
+    }
+
+    if artist_errors.is_empty() {
+        Ok(artist)
+    } else {
+        Err(artist_errors)
+    }
+}
+
+/// The album to create for a row, or why it is invalid
+fn new_album(row: &ImportRow, name: &str) -> Result<album::Model, Vec<FieldError>> {
+    let mut album_errors = Vec::new();
+    let date_published = required(
+        &mut album_errors,
+        "album_date_published",
+        row.album_date_published,
+    );
+
+    let album = album::Model {
+        id: 0,
+        name: name.to_string(),
+        date_published: date_published.unwrap_or_default(),
+        version: INITIAL_VERSION,
+        deleted_at: None,
+    };
+    if let Err(invalid) = album.validate() {
+        album_errors.extend(row_errors("album", invalid));
+    }
+
+    if album_errors.is_empty() {
+        Ok(album)
+    } else {
+        Err(album_errors)
+    }
+}
+
+/// The song to create for a row, or why it is invalid
+fn new_song(
+    row: &ImportRow,
+    name: &str,
+    album_id: i32,
+    disc_number: i32,
+    track_number: i32,
+) -> Result<song::Model, Vec<FieldError>> {
+    let mut song_errors = Vec::new();
+    let length_secs = required(&mut song_errors, "song_length_secs", row.song_length_secs);
+
+    let song = song::Model {
+        id: 0,
+        name: name.to_string(),
+        length_secs: length_secs.unwrap_or_default(),
+        album_id,
+        disc_number,
+        track_number,
+        version: INITIAL_VERSION,
+        deleted_at: None,
+    };
+    if let Err(invalid) = song.validate() {
+        song_errors.extend(row_errors("song", invalid));
+    }
+
+    if song_errors.is_empty() {
+        Ok(song)
+    } else {
+        Err(song_errors)
+    }
+}
+
+/// Match or create what a row names, stopping at the first part that is rejected
+pub async fn import_row<C: ConnectionTrait>(
+    db: &C,
+    user: Option<&user::Model>,
+    number: usize,
+    row: &ImportRow,
+) -> Result<ImportedRow, DbErr> {
+    if row.song.is_some() && row.album.is_none() {
+        return Ok(ImportedRow::rejected(
+            number,
+            vec![FieldError::new("album", "is required along with a song")],
+        ));
+    }
+
+    let mut imported = ImportedRow {
+        row: number,
+        status: ImportStatus::Matched,
+        artist_id: None,
+        album_id: None,
+        song_id: None,
+        errors: Vec::new(),
+    };
+
+    let existing_artist = Artist::find_live()
+        .filter(artist::Column::Name.eq(row.artist.as_str()))
+        .one(db)
+        .await?;
+    let artist_id = match existing_artist {
+        Some(artist) => artist.id,
+        None => {
+            let genre = match &row.artist_genre {
+                Some(name) => Some(genre_api::find_by_name(db, name).await?),
+                None => None,
+            };
+            let artist = match new_artist(row, !matches!(genre, Some(None))) {
+                Ok(artist) => artist,
+                Err(errors) => return Ok(ImportedRow::rejected(number, errors)),
+            };
+
+            let new_artist = artist::ActiveModel {
+                name: ActiveValue::Set(artist.name.clone()),
+                date_formed: ActiveValue::Set(artist.date_formed),
+                ..Default::default()
+            };
+            let id = Artist::insert(new_artist).exec(db).await?.last_insert_id;
+            audit::created(db, user, id, &artist::Model { id, ..artist }).await?;
+
+            if let Some(genre) = genre.flatten() {
+                let new_link = artist_genre::ActiveModel {
+                    artist_id: ActiveValue::Set(id),
+                    genre_id: ActiveValue::Set(genre.id),
+                    ..Default::default()
+                };
+                let link_id = ArtistGenre::insert(new_link).exec(db).await?.last_insert_id;
+                let link = artist_genre::Model {
+                    id: link_id,
+                    artist_id: id,
+                    genre_id: genre.id,
+                };
+                audit::created(db, user, link_id, &link).await?;
+            }
+
+            imported.status = ImportStatus::Created;
+            id
+        }
+    };
+    imported.artist_id = Some(artist_id);
+
+    let Some(album_name) = &row.album else {
+        return Ok(imported);
+    };
+    let links = AlbumArtistMtm::find_live()
+        .filter(album_artist_mtm::Column::ArtistId.eq(artist_id))
+        .all(db)
+        .await?;
+    let existing_album = Album::find_live()
+        .filter(album::Column::Id.is_in(links.iter().map(|link| link.album_id)))
+        .filter(album::Column::Name.eq(album_name.as_str()))
+        .one(db)
+        .await?;
+    let album_id = match existing_album {
+        Some(album) => album.id,
+        None => match new_album(row, album_name) {
+            Ok(album) => {
+                let new_album = album::ActiveModel {
+                    name: ActiveValue::Set(album.name.clone()),
+                    date_published: ActiveValue::Set(album.date_published),
+                    ..Default::default()
+                };
+                let id = Album::insert(new_album).exec(db).await?.last_insert_id;
+                audit::created(db, user, id, &album::Model { id, ..album }).await?;
+
+                let new_link = album_artist_mtm::ActiveModel {
+                    artist_id: ActiveValue::Set(artist_id),
+                    album_id: ActiveValue::Set(id),
+                    position: ActiveValue::Set(0),
+                    ..Default::default()
+                };
+                let link_id = AlbumArtistMtm::insert(new_link)
+                    .exec(db)
+                    .await?
+                    .last_insert_id;
+                let link = album_artist_mtm::Model {
+                    id: link_id,
+                    album_id: id,
+                    artist_id,
+                    position: 0,
+                    deleted_at: None,
+                };
+                audit::created(db, user, link_id, &link).await?;
+
+                imported.status = ImportStatus::Created;
+                id
+            }
+            Err(errors) => return Ok(ImportedRow::rejected(number, errors)),
+        },
+    };
+    imported.album_id = Some(album_id);
+
+    let Some(song_name) = &row.song else {
+        return Ok(imported);
+    };
+    let existing_song = Song::find_live()
+        .filter(song::Column::AlbumId.eq(album_id))
+        .filter(song::Column::Name.eq(song_name.as_str()))
+        .one(db)
+        .await?;
+    let song_id = match existing_song {
+        Some(song) => song.id,
+        None => {
+            let disc_number = row.song_disc_number.unwrap_or(1);
+            let track_number = match row.song_track_number {
+                Some(track_number) => track_number,
+                None => next_track_number(db, album_id, disc_number).await?,
+            };
+            match new_song(row, song_name, album_id, disc_number, track_number) {
+                Ok(song)
+                    if row.song_track_number.is_some() && track_taken(db, &song, None).await? =>
+                {
+                    return Ok(ImportedRow::rejected(
+                        number,
+                        vec![FieldError::new(
+                            "song_track_number",
+                            "is already taken on this disc",
+                        )],
+                    ));
+                }
+                Ok(song) => {
+                    let new_song = song::ActiveModel {
+                        name: ActiveValue::Set(song.name.clone()),
+                        length_secs: ActiveValue::Set(song.length_secs),
+                        album_id: ActiveValue::Set(album_id),
+                        disc_number: ActiveValue::Set(song.disc_number),
+                        track_number: ActiveValue::Set(song.track_number),
+                        ..Default::default()
+                    };
+                    let id = Song::insert(new_song).exec(db).await?.last_insert_id;
+                    audit::created(db, user, id, &song::Model { id, ..song }).await?;
+
+                    imported.status = ImportStatus::Created;
+                    id
+                }
+                Err(errors) => return Ok(ImportedRow::rejected(number, errors)),
+            }
+        }
+    };
+    imported.song_id = Some(song_id);
+
+    Ok(imported)
+}
+
+/// Import the rows of a document in one transaction
+///
+/// The transaction is rolled back on a dry run, or when any row is rejected.
+pub async fn import(
+    db: &DatabaseConnection,
+    user: Option<&user::Model>,
+    rows: Vec<Result<ImportRow, String>>,
+    dry_run: bool,
+) -> Result<ImportReport, DbErr> {
+    let txn = db.begin().await?;
+
+    let mut imported = Vec::with_capacity(rows.len());
+    for (index, row) in rows.into_iter().enumerate() {
+        let number = index + 1;
+        imported.push(match row {
+            Ok(row) => import_row(&txn, user, number, &row).await?,
+            Err(message) => ImportedRow::rejected(number, vec![FieldError::new("row", message)]),
+        });
+    }
+
+    let mut report = ImportReport {
+        committed: false,
+        rows: imported,
+    };
+    if dry_run || report.has_rejections() {
+        txn.rollback().await?;
+    } else {
+        txn.commit().await?;
+        report.committed = true;
+    }
+
+    Ok(report)
+}
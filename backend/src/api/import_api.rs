@@ -0,0 +1,92 @@
+use rocket::{
+    data::{Data, ToByteUnit},
+    http::{ContentType, Status},
+    serde::json::Json,
+    State,
+};
+use sea_orm::DatabaseConnection;
+
+use super::{
+    auth::Editor,
+    error::ApiError,
+    import::{self, ImportFormat, ImportReport},
+};
+
+/// Largest accepted import document, in mebibytes
+const MAX_DOCUMENT_MIB: u64 = 16;
+
+/// Format of a document with the given content type, JSON unless it is `text/csv`
+fn format_of(content_type: Option<&ContentType>) -> Result<ImportFormat, ApiError> {
+    match content_type {
+        Some(content_type) if content_type.is_csv() => Ok(ImportFormat::Csv),
+        Some(content_type) if !content_type.is_json() => Err(ApiError::BadRequest(format!(
+            "Expected a JSON or CSV document, got {}",
+            content_type
+        ))),
+        _ => Ok(ImportFormat::Json),
+    }
+}
+
+/// Import artists, albums and songs
+///
+/// This endpoint requires a JSON array of [`ImportRow`], or a CSV document with a header line
+/// naming the same fields, sent as `text/csv`. Artists are matched by name, albums by name among
+/// the albums of the artist and songs by name among the songs of the album; whatever is missing
+/// is created, and validated as in the `create_*` endpoints. It returns an [`ImportReport`] with
+/// the outcome of every row. Nothing is imported if any row is rejected, or when `dry_run=true`.
+#[utoipa::path(
+    context_path = "/import",
+    tag = "import",
+    request_body = [ImportRow],
+    responses(
+        (status = 200, description = "Report of the dry run", body = ImportReport),
+        (status = 201, description = "Report of the import", body = ImportReport),
+        (status = 400, description = "Malformed document", body = ErrorBody),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 422, description = "Some rows were rejected, nothing was imported", body = ImportReport)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/?<dry_run>", data = "<document>")]
+pub async fn import_rows(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    content_type: Option<&ContentType>,
+    dry_run: Option<bool>,
+    document: Data<'_>,
+) -> Result<(Status, Json<ImportReport>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let format = format_of(content_type)?;
+    let document = document
+        .open(MAX_DOCUMENT_MIB.mebibytes())
+        .into_string()
+        .await
+        .map_err(|err| ApiError::BadRequest(format!("Failed to read the document: {}", err)))?;
+    if !document.is_complete() {
+        info!("Import document too large");
+        return Err(ApiError::BadRequest(format!(
+            "The document is larger than {} MiB",
+            MAX_DOCUMENT_MIB
+        )));
+    }
+
+    let rows = import::parse(format, &document)?;
+    let dry_run = dry_run.unwrap_or(false);
+    let report = import::import(db, Some(&editor.0.user), rows, dry_run).await?;
+
+    info!(
+        "Imported {} rows, committed: {}",
+        report.rows.len(),
+        report.committed
+    );
+    let status = if report.has_rejections() {
+        Status::UnprocessableEntity
+    } else if report.committed {
+        Status::Created
+    } else {
+        Status::Ok
+    };
+    Ok((status, Json(report)))
+}
@@ -0,0 +1,424 @@
+//! Record labels, and the labels of albums
+//!
+//! A label can be an imprint or a subsidiary of a parent label, so labels form a tree. An album can
+//! be released by several labels, each under its own catalog number. Albums in the trash keep
+//! their labels, but are left out of the releases of a label until they are restored.
+
+use std::collections::BTreeMap;
+
+use entities::{
+    album,
+    album_label::{self, AlbumRelease, LabelRelease},
+    label,
+    pagination::Page,
+    prelude::*,
+    user,
+    validation::Validate,
+};
+use rocket::{
+    http::{uri::Origin, Status},
+    serde::json::Json,
+    State,
+};
+use sea_orm::{
+    ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::{
+    audit,
+    auth::{Admin, Editor},
+    error::{ApiError, FieldError},
+    query::{fetch_page, PageParams},
+    tree,
+};
+
+/// A label given to an album, with the catalog number the album was released under
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct LabelLink {
+    pub label_id: i32,
+    #[serde(default)]
+    pub catalog_number: Option<String>,
+}
+
+/// Check a label given to the label with the given id, or to a new one when there is none
+///
+/// Besides its fields, its parent must be one of the `labels` without being the label itself or
+/// one of its subsidiaries.
+fn check(label: &label::Model, id: Option<i32>, labels: &[label::Model]) -> Result<(), ApiError> {
+    let mut errors = label.validate().err().unwrap_or_default();
+    errors.extend(tree::check_parent(
+        labels,
+        id,
+        label.parent_id,
+        "the label or one of its subsidiaries",
+    ));
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        info!("Invalid input");
+        Err(ApiError::Validation(errors))
+    }
+}
+
+/// Create a new label
+///
+/// This endpoint requires a JSON body containing a [`label::Model`] without an id.
+#[utoipa::path(
+    context_path = "/label",
+    tag = "label",
+    request_body = Label,
+    responses(
+        (status = 201, description = "Id of the created label", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/", data = "<label>")]
+pub async fn create_label(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    label: Json<label::Model>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let labels = Label::find().all(&txn).await?;
+    check(&label, None, &labels)?;
+
+    let new_label = label::ActiveModel {
+        name: ActiveValue::Set(label.name.clone()),
+        country: ActiveValue::Set(label.country.clone()),
+        founded: ActiveValue::Set(label.founded),
+        parent_id: ActiveValue::Set(label.parent_id),
+        ..Default::default()
+    };
+    let id = Label::insert(new_label).exec(&txn).await?.last_insert_id;
+    let label = label::Model {
+        id,
+        ..label.into_inner()
+    };
+    audit::created(&txn, Some(&editor.0.user), id, &label).await?;
+
+    txn.commit().await?;
+
+    info!("Inserted label with id: {}", id);
+    Ok((Status::Created, id.to_string()))
+}
+
+/// Get a label by its id
+///
+/// This endpoint returns a [`label::Model`] with the given id, if it exists.
+#[utoipa::path(
+    context_path = "/label",
+    tag = "label",
+    responses(
+        (status = 302, description = "The label", body = Label),
+        (status = 404, description = "Label not found", body = ErrorBody)
+    )
+)]
+#[get("/<id>")]
+pub async fn get_label_by_id(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<label::Model>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let label = Label::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Label"))?;
+
+    info!("Found label with id: {}", label.id);
+    Ok((Status::Found, Json(label)))
+}
+
+/// Update a label with the given id
+///
+/// This endpoint requires a JSON body containing a [`label::Model`] with all of the fields set,
+/// an explicit `null` parent making it an independent label.
+#[utoipa::path(
+    context_path = "/label",
+    tag = "label",
+    request_body = Label,
+    responses(
+        (status = 202, description = "Id of the updated label", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Label not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/<id>", data = "<label>")]
+pub async fn update_label(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    id: i32,
+    label: Json<label::Model>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let labels = Label::find().lock_exclusive().all(&txn).await?;
+    let before = labels
+        .iter()
+        .find(|label| label.id == id)
+        .cloned()
+        .ok_or_else(|| ApiError::not_found("Label"))?;
+    check(&label, Some(id), &labels)?;
+
+    let updated_label = label::ActiveModel {
+        id: ActiveValue::Set(id),
+        name: ActiveValue::Set(label.name.clone()),
+        country: ActiveValue::Set(label.country.clone()),
+        founded: ActiveValue::Set(label.founded),
+        parent_id: ActiveValue::Set(label.parent_id),
+    };
+    let after = Label::update(updated_label).exec(&txn).await?;
+    audit::updated(&txn, Some(&editor.0.user), id, &before, &after).await?;
+
+    txn.commit().await?;
+
+    info!("Updated label with id: {}", id);
+    Ok((Status::Accepted, id.to_string()))
+}
+
+/// Delete a label with the given id
+///
+/// This endpoint returns the id of the deleted label, if it exists. The albums of the label lose
+/// it. A label with subsidiaries cannot be deleted, they have to be moved or deleted first.
+#[utoipa::path(
+    context_path = "/label",
+    tag = "label",
+    responses(
+        (status = 200, description = "Id of the deleted label", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody),
+        (status = 404, description = "Label not found", body = ErrorBody),
+        (status = 409, description = "The label has subsidiaries", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[delete("/<id>")]
+pub async fn delete_label(
+    db: &State<DatabaseConnection>,
+    admin: Admin,
+    id: i32,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let label = Label::find_by_id(id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Label"))?;
+    let subsidiary = Label::find()
+        .filter(label::Column::ParentId.eq(id))
+        .one(&txn)
+        .await?;
+    if subsidiary.is_some() {
+        info!("Label {} has subsidiaries", id);
+        return Err(ApiError::Conflict(
+            "The label has subsidiaries, move or delete them first".to_string(),
+        ));
+    }
+
+    // Its links to albums go with it, through the cascade of their foreign key
+    Label::delete_by_id(id).exec(&txn).await?;
+    audit::deleted(&txn, Some(&admin.0.user), id, &label).await?;
+
+    txn.commit().await?;
+
+    info!("Deleted label with id: {}", id);
+    Ok((Status::Ok, id.to_string()))
+}
+
+/// Get all labels
+///
+/// This endpoint returns a [`Page`] of labels sorted by name. See [`PageParams`] for `page` and
+/// `per_page`.
+#[utoipa::path(
+    context_path = "/label",
+    tag = "label",
+    responses(
+        (status = 200, description = "A page of labels", body = LabelPage)
+    )
+)]
+#[get("/all?<page>&<per_page>")]
+pub async fn get_all_labels(
+    db: &State<DatabaseConnection>,
+    origin: &Origin<'_>,
+    page: Option<u64>,
+    per_page: Option<u64>,
+) -> Result<(Status, Json<Page<label::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let query = Label::find()
+        .order_by_asc(label::Column::Name)
+        .order_by_asc(label::Column::Id);
+    let labels = fetch_page(db, query, PageParams { page, per_page }, origin).await?;
+
+    info!("Found {} labels", labels.total);
+    Ok((Status::Ok, Json(labels)))
+}
+
+/// Get the releases of a label
+///
+/// This endpoint returns the list of [`LabelRelease`] of the label with the given id, from the
+/// oldest album to the newest. Albums in the trash are left out.
+#[utoipa::path(
+    context_path = "/label",
+    tag = "label",
+    responses(
+        (status = 200, description = "Releases of the label", body = [LabelRelease]),
+        (status = 404, description = "Label not found", body = ErrorBody)
+    )
+)]
+#[get("/releases/<id>")]
+pub async fn get_releases(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<Vec<LabelRelease>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    if Label::find_by_id(id).one(db).await?.is_none() {
+        info!("Label not found");
+        return Err(ApiError::not_found("Label"));
+    }
+
+    let releases: Vec<LabelRelease> = AlbumLabel::find()
+        .find_also_related(Album)
+        .filter(album_label::Column::LabelId.eq(id))
+        .filter(album::Column::DeletedAt.is_null())
+        .order_by_asc(album::Column::DatePublished)
+        .order_by_asc(album::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(link, album)| {
+            Some(LabelRelease {
+                album: album?,
+                catalog_number: link.catalog_number,
+            })
+        })
+        .collect();
+
+    info!("Found {} releases", releases.len());
+    Ok((Status::Ok, Json(releases)))
+}
+
+/// Labels of the album with the given id, sorted by name
+pub async fn album_labels<C: ConnectionTrait>(
+    db: &C,
+    album_id: i32,
+) -> Result<Vec<AlbumRelease>, DbErr> {
+    Ok(AlbumLabel::find()
+        .find_also_related(Label)
+        .filter(album_label::Column::AlbumId.eq(album_id))
+        .order_by_asc(label::Column::Name)
+        .order_by_asc(label::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(link, label)| {
+            Some(AlbumRelease {
+                label: label?,
+                catalog_number: link.catalog_number,
+            })
+        })
+        .collect())
+}
+
+/// Give the album with the given id exactly the given labels, returning them sorted by name
+///
+/// Catalog numbers are trimmed, a blank one being no catalog number. Only the links that change
+/// are added, updated or removed, and each of them is audited.
+pub async fn set_album_labels<C: ConnectionTrait>(
+    txn: &C,
+    user: &user::Model,
+    album_id: i32,
+    labels: Vec<LabelLink>,
+) -> Result<Vec<AlbumRelease>, ApiError> {
+    let mut catalog_numbers = BTreeMap::new();
+    for link in labels {
+        let catalog_number = link
+            .catalog_number
+            .map(|number| number.trim().to_string())
+            .filter(|number| !number.is_empty());
+        if catalog_numbers
+            .insert(link.label_id, catalog_number)
+            .is_some()
+        {
+            info!("Label {} given twice", link.label_id);
+            return Err(ApiError::Validation(vec![FieldError::new(
+                "label_id",
+                "must not be given twice",
+            )]));
+        }
+    }
+
+    let found = Label::find()
+        .filter(label::Column::Id.is_in(catalog_numbers.keys().copied()))
+        .all(txn)
+        .await?;
+    if found.len() != catalog_numbers.len() {
+        info!("Not all labels were found");
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "label_id",
+            "some labels do not exist",
+        )]));
+    }
+
+    let links = AlbumLabel::find()
+        .filter(album_label::Column::AlbumId.eq(album_id))
+        .all(txn)
+        .await?;
+    for link in &links {
+        match catalog_numbers.get(&link.label_id) {
+            None => {
+                AlbumLabel::delete_by_id(link.id).exec(txn).await?;
+                audit::deleted(txn, Some(user), link.id, link).await?;
+            }
+            Some(catalog_number) if *catalog_number != link.catalog_number => {
+                let updated_link = album_label::ActiveModel {
+                    id: ActiveValue::Set(link.id),
+                    catalog_number: ActiveValue::Set(catalog_number.clone()),
+                    ..Default::default()
+                };
+                let after = AlbumLabel::update(updated_link).exec(txn).await?;
+                audit::updated(txn, Some(user), link.id, link, &after).await?;
+            }
+            Some(_) => {}
+        }
+    }
+    for (&label_id, catalog_number) in &catalog_numbers {
+        if !links.iter().any(|link| link.label_id == label_id) {
+            let new_link = album_label::ActiveModel {
+                album_id: ActiveValue::Set(album_id),
+                label_id: ActiveValue::Set(label_id),
+                catalog_number: ActiveValue::Set(catalog_number.clone()),
+                ..Default::default()
+            };
+            let id = AlbumLabel::insert(new_link).exec(txn).await?.last_insert_id;
+            let link = album_label::Model {
+                id,
+                album_id,
+                label_id,
+                catalog_number: catalog_number.clone(),
+            };
+            audit::created(txn, Some(user), id, &link).await?;
+        }
+    }
+
+    Ok(album_labels(txn, album_id).await?)
+}
@@ -1,3 +1,29 @@
 pub mod song_api;
 pub mod album_api;
-pub mod artist_api;
\ No newline at end of file
+pub mod artist_api;
+pub mod admin_api;
+pub mod audio;
+pub mod audit;
+pub mod audit_api;
+pub mod auth;
+pub mod auth_api;
+pub mod cover;
+pub mod error;
+pub mod export;
+pub mod export_api;
+pub mod genre_api;
+pub mod import;
+pub mod import_api;
+pub mod label_api;
+pub mod openapi;
+pub mod person_api;
+pub mod playlist_api;
+pub mod query;
+pub mod range;
+pub mod review_api;
+pub mod search_api;
+pub mod storage;
+pub mod trash;
+pub mod trash_api;
+pub mod tree;
+pub mod version;
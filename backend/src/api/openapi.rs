@@ -0,0 +1,359 @@
+use entities::{
+    album, album_cover,
+    album_label::{AlbumRelease, LabelRelease},
+    artist,
+    artist_membership::{self, Band, Member},
+    audit_log,
+    auth::{Credentials, RoleChange, SessionToken},
+    genre, label,
+    pagination::{AlbumPage, ArtistPage, AuditPage, LabelPage, PersonPage, PlaylistPage, SongPage},
+    person, playlist,
+    playlist_song::{PlaylistEntry, PlaylistSongs},
+    review::{self, RatedAlbum, RatedSong, Ratings, SignedReview},
+    sea_orm_active_enums::{AuditAction, CreditRole, Role},
+    song,
+    song_credit::{self, Appearance, Credit},
+    user,
+    validation::FieldError,
+};
+use rocket::{response::content::RawHtml, serde::json::Json};
+use utoipa::{
+    openapi::{
+        schema::{KnownFormat, SchemaFormat, SchemaType},
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+        ObjectBuilder, OpenApi as Spec, RefOr, Schema,
+    },
+    Modify, OpenApi,
+};
+
+use super::{
+    admin_api,
+    album_api::{self, AlbumPatch, AlbumSort, CoverUpload},
+    artist_api::{self, ArtistPatch, ArtistSort},
+    audit_api::{self, ActionParam},
+    auth::SESSION_COOKIE,
+    auth_api,
+    cover::CoverSize,
+    error::ErrorBody,
+    export::{RestoredRows, Table},
+    export_api, genre_api,
+    import::{ImportReport, ImportRow, ImportStatus, ImportedRow},
+    import_api,
+    label_api::{self, LabelLink},
+    person_api,
+    playlist_api::{self, NewPlaylistSong, PlaylistMove},
+    query::{DateParam, Direction},
+    review_api,
+    search_api::{self, SearchHit, SearchKind},
+    song_api::{self, CreditLink, SongPatch, SongSort, SongUpload},
+    trash_api::{self, TrashEntry},
+};
+
+/// OpenAPI description of every documented route
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Discography API"),
+    paths(
+        song_api::create_song,
+        song_api::get_song_by_id,
+        song_api::update_song,
+        song_api::patch_song,
+        song_api::delete_song,
+        song_api::get_all_songs,
+        song_api::get_credits,
+        song_api::set_credits,
+        song_api::upload_songs,
+        song_api::stream_song,
+        album_api::create_album,
+        album_api::get_album_by_id,
+        album_api::update_album,
+        album_api::patch_album,
+        album_api::delete_album,
+        album_api::get_all_albums,
+        album_api::add_artist,
+        album_api::get_artists,
+        album_api::remove_artist,
+        album_api::get_songs,
+        album_api::reorder_songs,
+        album_api::get_genres,
+        album_api::set_genres,
+        album_api::get_labels,
+        album_api::set_labels,
+        album_api::upload_cover,
+        album_api::get_cover,
+        album_api::delete_cover,
+        artist_api::create_artist,
+        artist_api::get_artist_by_id,
+        artist_api::update_artist,
+        artist_api::patch_artist,
+        artist_api::delete_artist,
+        artist_api::get_all_artists,
+        artist_api::get_albums,
+        artist_api::get_genres,
+        artist_api::set_genres,
+        artist_api::get_members,
+        artist_api::get_appearances,
+        genre_api::create_genre,
+        genre_api::get_genre_by_id,
+        genre_api::update_genre,
+        genre_api::delete_genre,
+        genre_api::get_all_genres,
+        label_api::create_label,
+        label_api::get_label_by_id,
+        label_api::update_label,
+        label_api::delete_label,
+        label_api::get_all_labels,
+        label_api::get_releases,
+        person_api::create_person,
+        person_api::get_person_by_id,
+        person_api::update_person,
+        person_api::delete_person,
+        person_api::get_all_persons,
+        person_api::get_bands,
+        person_api::create_membership,
+        person_api::update_membership,
+        person_api::delete_membership,
+        search_api::search,
+        auth_api::register,
+        auth_api::login,
+        auth_api::logout,
+        auth_api::me,
+        admin_api::get_users,
+        admin_api::set_role,
+        audit_api::get_audit_log,
+        trash_api::get_trash,
+        trash_api::restore,
+        trash_api::purge,
+        import_api::import_rows,
+        export_api::export_json,
+        export_api::export_ndjson,
+        export_api::export_csv,
+        export_api::restore,
+        playlist_api::create_playlist,
+        playlist_api::get_playlist_by_id,
+        playlist_api::update_playlist,
+        playlist_api::delete_playlist,
+        playlist_api::get_all_playlists,
+        playlist_api::get_playlist_songs,
+        playlist_api::add_song,
+        playlist_api::move_song,
+        playlist_api::remove_song,
+        review_api::create_album_review,
+        review_api::create_song_review,
+        review_api::get_album_reviews,
+        review_api::get_song_reviews,
+        review_api::update_review,
+        review_api::delete_review,
+    ),
+    components(schemas(
+        song::Model,
+        album::Model,
+        album_cover::Model,
+        artist::Model,
+        user::Model,
+        audit_log::Model,
+        playlist::Model,
+        genre::Model,
+        person::Model,
+        artist_membership::Model,
+        label::Model,
+        song_credit::Model,
+        review::Model,
+        Role,
+        AuditAction,
+        CreditRole,
+        SongPage,
+        AlbumPage,
+        ArtistPage,
+        AuditPage,
+        PlaylistPage,
+        PersonPage,
+        LabelPage,
+        SongPatch,
+        AlbumPatch,
+        ArtistPatch,
+        SongSort,
+        AlbumSort,
+        ArtistSort,
+        Direction,
+        DateParam,
+        ActionParam,
+        SearchHit,
+        SearchKind,
+        TrashEntry,
+        ImportRow,
+        ImportReport,
+        ImportedRow,
+        ImportStatus,
+        Table,
+        RestoredRows,
+        PlaylistEntry,
+        PlaylistSongs,
+        NewPlaylistSong,
+        PlaylistMove,
+        Member,
+        Band,
+        LabelLink,
+        AlbumRelease,
+        LabelRelease,
+        CreditLink,
+        Credit,
+        Appearance,
+        SignedReview,
+        Ratings,
+        RatedAlbum,
+        RatedSong,
+        CoverUpload,
+        SongUpload,
+        CoverSize,
+        ErrorBody,
+        FieldError,
+        Credentials,
+        SessionToken,
+        RoleChange,
+    )),
+    modifiers(&ReadOnlyFields, &SessionSecurity)
+)]
+pub struct ApiDoc;
+
+/// Add the `id`, `deleted_at` and `version` of the entities, the `user_id` of playlists and
+/// reviews, and what reviews are of, to their schemas
+///
+/// They are skipped on deserialization, which makes utoipa leave them out altogether even though
+/// they are in every response.
+struct ReadOnlyFields;
+
+impl Modify for ReadOnlyFields {
+    fn modify(&self, openapi: &mut Spec) {
+        let Some(components) = openapi.components.as_mut() else {
+            return;
+        };
+
+        for name in [
+            "Song",
+            "Album",
+            "Artist",
+            "User",
+            "Playlist",
+            "Genre",
+            "Person",
+            "ArtistMembership",
+            "Label",
+            "Review",
+        ] {
+            if let Some(RefOr::T(Schema::Object(object))) = components.schemas.get_mut(name) {
+                let id = ObjectBuilder::new()
+                    .schema_type(SchemaType::Integer)
+                    .format(Some(SchemaFormat::KnownFormat(KnownFormat::Int32)))
+                    .read_only(Some(true))
+                    .description(Some("Id set by the database"));
+                object.properties.insert("id".to_string(), id.into());
+                object.required.insert(0, "id".to_string());
+            }
+        }
+
+        for (name, description) in [
+            ("Playlist", "Id of the user who created the playlist"),
+            ("Review", "Id of the user who wrote the review"),
+        ] {
+            if let Some(RefOr::T(Schema::Object(object))) = components.schemas.get_mut(name) {
+                let user_id = ObjectBuilder::new()
+                    .schema_type(SchemaType::Integer)
+                    .format(Some(SchemaFormat::KnownFormat(KnownFormat::Int32)))
+                    .read_only(Some(true))
+                    .description(Some(description));
+                object
+                    .properties
+                    .insert("user_id".to_string(), user_id.into());
+                object.required.push("user_id".to_string());
+            }
+        }
+
+        if let Some(RefOr::T(Schema::Object(object))) = components.schemas.get_mut("Review") {
+            for (field, description) in [
+                ("album_id", "Id of the album reviewed, unless it is a song"),
+                ("song_id", "Id of the song reviewed, unless it is an album"),
+            ] {
+                let id = ObjectBuilder::new()
+                    .schema_type(SchemaType::Integer)
+                    .format(Some(SchemaFormat::KnownFormat(KnownFormat::Int32)))
+                    .nullable(true)
+                    .read_only(Some(true))
+                    .description(Some(description));
+                object.properties.insert(field.to_string(), id.into());
+            }
+        }
+
+        for name in ["Song", "Album", "Artist"] {
+            if let Some(RefOr::T(Schema::Object(object))) = components.schemas.get_mut(name) {
+                let deleted_at = ObjectBuilder::new()
+                    .schema_type(SchemaType::String)
+                    .format(Some(SchemaFormat::KnownFormat(KnownFormat::DateTime)))
+                    .nullable(true)
+                    .read_only(Some(true))
+                    .description(Some(
+                        "When the row was moved to the trash, set by the database",
+                    ));
+                object
+                    .properties
+                    .insert("deleted_at".to_string(), deleted_at.into());
+
+                let version = ObjectBuilder::new()
+                    .schema_type(SchemaType::Integer)
+                    .format(Some(SchemaFormat::KnownFormat(KnownFormat::Int32)))
+                    .read_only(Some(true))
+                    .description(Some("Number of changes made, sent as the `ETag`"));
+                object
+                    .properties
+                    .insert("version".to_string(), version.into());
+                object.required.push("version".to_string());
+            }
+        }
+    }
+}
+
+/// Declare the session cookie and bearer token used by the protected routes
+struct SessionSecurity;
+
+impl Modify for SessionSecurity {
+    fn modify(&self, openapi: &mut Spec) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+
+        components.add_security_scheme(
+            "session_cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(SESSION_COOKIE))),
+        );
+        components.add_security_scheme(
+            "bearer_token",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// Get the OpenAPI specification of the API
+#[get("/openapi.json")]
+pub fn openapi_json() -> Json<Spec> {
+    Json(ApiDoc::openapi())
+}
+
+/// Browse the OpenAPI specification with RapiDoc
+///
+/// The specification is loaded relative to this page, so that it also works behind the `/api`
+/// prefix of the frontend proxy.
+#[get("/docs")]
+pub fn docs() -> RawHtml<&'static str> {
+    RawHtml(
+        r#"<!doctype html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>Discography API</title>
+    <script type="module" src="https://unpkg.com/rapidoc@9.3.4/dist/rapidoc-min.js"></script>
+</head>
+<body>
+    <rapi-doc spec-url="openapi.json" render-style="read" show-header="false"></rapi-doc>
+</body>
+</html>
+"#,
+    )
+}
@@ -0,0 +1,452 @@
+//! People, and their memberships of artists
+//!
+//! A person can be a member of any number of artists, and of the same one more than once if they
+//! left and joined it again. Memberships are listed by the date they started, the ones without a
+//! known start last. Artists in the trash keep their members, but are left out of the artists of
+//! a person until they are restored.
+
+use entities::{
+    artist,
+    artist_membership::{self, Band},
+    pagination::Page,
+    person,
+    prelude::*,
+    validation::Validate,
+};
+use rocket::{
+    http::{uri::Origin, Status},
+    serde::json::Json,
+    State,
+};
+use sea_orm::{
+    ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, TransactionTrait,
+};
+
+use super::{
+    audit,
+    auth::{Admin, Editor},
+    error::{ApiError, FieldError},
+    query::{fetch_page, PageParams},
+    trash::Live,
+};
+
+/// Check the fields of a person
+fn validate(person: &person::Model) -> Result<(), ApiError> {
+    person.validate().map_err(|errors| {
+        info!("Invalid input");
+        ApiError::Validation(errors)
+    })
+}
+
+/// Check a membership, along with the artist and the person it links
+///
+/// The artist must not be in the trash.
+async fn check_membership<C: ConnectionTrait>(
+    db: &C,
+    membership: &artist_membership::Model,
+) -> Result<(), ApiError> {
+    let mut errors = membership.validate().err().unwrap_or_default();
+
+    if Artist::find_live_by_id(membership.artist_id)
+        .one(db)
+        .await?
+        .is_none()
+    {
+        errors.push(FieldError::new("artist_id", "does not exist"));
+    }
+    if Person::find_by_id(membership.person_id)
+        .one(db)
+        .await?
+        .is_none()
+    {
+        errors.push(FieldError::new("person_id", "does not exist"));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        info!("Invalid input");
+        Err(ApiError::Validation(errors))
+    }
+}
+
+/// Create a new person
+///
+/// This endpoint requires a JSON body containing a [`person::Model`] without an id.
+#[utoipa::path(
+    context_path = "/person",
+    tag = "person",
+    request_body = Person,
+    responses(
+        (status = 201, description = "Id of the created person", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/", data = "<person>")]
+pub async fn create_person(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    person: Json<person::Model>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    validate(&person)?;
+
+    let txn = db.begin().await?;
+
+    let new_person = person::ActiveModel {
+        name: ActiveValue::Set(person.name.clone()),
+        ..Default::default()
+    };
+    let id = Person::insert(new_person).exec(&txn).await?.last_insert_id;
+    let person = person::Model {
+        id,
+        ..person.into_inner()
+    };
+    audit::created(&txn, Some(&editor.0.user), id, &person).await?;
+
+    txn.commit().await?;
+
+    info!("Inserted person with id: {}", id);
+    Ok((Status::Created, id.to_string()))
+}
+
+/// Get a person by their id
+///
+/// This endpoint returns a [`person::Model`] with the given id, if it exists.
+#[utoipa::path(
+    context_path = "/person",
+    tag = "person",
+    responses(
+        (status = 302, description = "The person", body = Person),
+        (status = 404, description = "Person not found", body = ErrorBody)
+    )
+)]
+#[get("/<id>")]
+pub async fn get_person_by_id(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<person::Model>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let person = Person::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Person"))?;
+
+    info!("Found person with id: {}", person.id);
+    Ok((Status::Found, Json(person)))
+}
+
+/// Update a person with the given id
+///
+/// This endpoint requires a JSON body containing a [`person::Model`] with all of the fields set.
+#[utoipa::path(
+    context_path = "/person",
+    tag = "person",
+    request_body = Person,
+    responses(
+        (status = 202, description = "Id of the updated person", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Person not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/<id>", data = "<person>")]
+pub async fn update_person(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    id: i32,
+    person: Json<person::Model>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    validate(&person)?;
+
+    let txn = db.begin().await?;
+
+    let before = Person::find_by_id(id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Person"))?;
+
+    let updated_person = person::ActiveModel {
+        id: ActiveValue::Set(id),
+        name: ActiveValue::Set(person.name.clone()),
+    };
+    let after = Person::update(updated_person).exec(&txn).await?;
+    audit::updated(&txn, Some(&editor.0.user), id, &before, &after).await?;
+
+    txn.commit().await?;
+
+    info!("Updated person with id: {}", id);
+    Ok((Status::Accepted, id.to_string()))
+}
+
+/// Delete a person with the given id
+///
+/// This endpoint returns the id of the deleted person, if they exist. Their memberships are
+/// deleted along with them.
+#[utoipa::path(
+    context_path = "/person",
+    tag = "person",
+    responses(
+        (status = 200, description = "Id of the deleted person", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody),
+        (status = 404, description = "Person not found", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[delete("/<id>")]
+pub async fn delete_person(
+    db: &State<DatabaseConnection>,
+    admin: Admin,
+    id: i32,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let person = Person::find_by_id(id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Person"))?;
+
+    // Their memberships go with them, through the cascade of their foreign key
+    Person::delete_by_id(id).exec(&txn).await?;
+    audit::deleted(&txn, Some(&admin.0.user), id, &person).await?;
+
+    txn.commit().await?;
+
+    info!("Deleted person with id: {}", id);
+    Ok((Status::Ok, id.to_string()))
+}
+
+/// Get all people
+///
+/// This endpoint returns a [`Page`] of people sorted by name. See [`PageParams`] for `page` and
+/// `per_page`.
+#[utoipa::path(
+    context_path = "/person",
+    tag = "person",
+    responses(
+        (status = 200, description = "A page of people", body = PersonPage)
+    )
+)]
+#[get("/all?<page>&<per_page>")]
+pub async fn get_all_persons(
+    db: &State<DatabaseConnection>,
+    origin: &Origin<'_>,
+    page: Option<u64>,
+    per_page: Option<u64>,
+) -> Result<(Status, Json<Page<person::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let query = Person::find()
+        .order_by_asc(person::Column::Name)
+        .order_by_asc(person::Column::Id);
+    let persons = fetch_page(db, query, PageParams { page, per_page }, origin).await?;
+
+    info!("Found {} people", persons.total);
+    Ok((Status::Ok, Json(persons)))
+}
+
+/// Get the artists of a person
+///
+/// This endpoint returns the list of [`Band`] the person with the given id is or was a member of,
+/// by the date they joined. Artists in the trash are left out.
+#[utoipa::path(
+    context_path = "/person",
+    tag = "person",
+    responses(
+        (status = 200, description = "Artists of the person", body = [Band]),
+        (status = 404, description = "Person not found", body = ErrorBody)
+    )
+)]
+#[get("/artists/<id>")]
+pub async fn get_bands(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<Vec<Band>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    if Person::find_by_id(id).one(db).await?.is_none() {
+        info!("Person not found");
+        return Err(ApiError::not_found("Person"));
+    }
+
+    let bands: Vec<Band> = ArtistMembership::find()
+        .find_also_related(Artist)
+        .filter(artist_membership::Column::PersonId.eq(id))
+        .filter(artist::Column::DeletedAt.is_null())
+        .order_by_asc(artist_membership::Column::StartDate)
+        .order_by_asc(artist_membership::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(membership, artist)| {
+            Some(Band {
+                membership,
+                artist: artist?,
+            })
+        })
+        .collect();
+
+    info!("Found {} artists", bands.len());
+    Ok((Status::Ok, Json(bands)))
+}
+
+/// Add a person to the members of an artist
+///
+/// This endpoint requires a JSON body containing an [`artist_membership::Model`] without an id.
+#[utoipa::path(
+    context_path = "/person",
+    tag = "person",
+    request_body = ArtistMembership,
+    responses(
+        (status = 201, description = "Id of the created membership", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/membership", data = "<membership>")]
+pub async fn create_membership(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    membership: Json<artist_membership::Model>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    check_membership(&txn, &membership).await?;
+
+    let new_membership = artist_membership::ActiveModel {
+        artist_id: ActiveValue::Set(membership.artist_id),
+        person_id: ActiveValue::Set(membership.person_id),
+        role: ActiveValue::Set(membership.role.clone()),
+        instrument: ActiveValue::Set(membership.instrument.clone()),
+        start_date: ActiveValue::Set(membership.start_date),
+        end_date: ActiveValue::Set(membership.end_date),
+        ..Default::default()
+    };
+    let id = ArtistMembership::insert(new_membership)
+        .exec(&txn)
+        .await?
+        .last_insert_id;
+    let membership = artist_membership::Model {
+        id,
+        ..membership.into_inner()
+    };
+    audit::created(&txn, Some(&editor.0.user), id, &membership).await?;
+
+    txn.commit().await?;
+
+    info!("Inserted membership with id: {}", id);
+    Ok((Status::Created, id.to_string()))
+}
+
+/// Update a membership with the given id
+///
+/// This endpoint requires a JSON body containing an [`artist_membership::Model`] with all of the
+/// fields set, an explicit `null` `end_date` meaning that the person is still a member.
+#[utoipa::path(
+    context_path = "/person",
+    tag = "person",
+    request_body = ArtistMembership,
+    responses(
+        (status = 202, description = "Id of the updated membership", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Membership not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/membership/<id>", data = "<membership>")]
+pub async fn update_membership(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    id: i32,
+    membership: Json<artist_membership::Model>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let before = ArtistMembership::find_by_id(id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Membership"))?;
+    check_membership(&txn, &membership).await?;
+
+    let updated_membership = artist_membership::ActiveModel {
+        id: ActiveValue::Set(id),
+        artist_id: ActiveValue::Set(membership.artist_id),
+        person_id: ActiveValue::Set(membership.person_id),
+        role: ActiveValue::Set(membership.role.clone()),
+        instrument: ActiveValue::Set(membership.instrument.clone()),
+        start_date: ActiveValue::Set(membership.start_date),
+        end_date: ActiveValue::Set(membership.end_date),
+    };
+    let after = ArtistMembership::update(updated_membership)
+        .exec(&txn)
+        .await?;
+    audit::updated(&txn, Some(&editor.0.user), id, &before, &after).await?;
+
+    txn.commit().await?;
+
+    info!("Updated membership with id: {}", id);
+    Ok((Status::Accepted, id.to_string()))
+}
+
+/// Delete a membership with the given id
+///
+/// This endpoint returns the id of the deleted membership, if it exists. The person and the
+/// artist stay, only the link between them goes.
+#[utoipa::path(
+    context_path = "/person",
+    tag = "person",
+    responses(
+        (status = 200, description = "Id of the deleted membership", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Membership not found", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[delete("/membership/<id>")]
+pub async fn delete_membership(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    id: i32,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let membership = ArtistMembership::find_by_id(id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Membership"))?;
+    ArtistMembership::delete_by_id(id).exec(&txn).await?;
+    audit::deleted(&txn, Some(&editor.0.user), id, &membership).await?;
+
+    txn.commit().await?;
+
+    info!("Deleted membership with id: {}", id);
+    Ok((Status::Ok, id.to_string()))
+}
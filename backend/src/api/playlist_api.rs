@@ -0,0 +1,553 @@
+//! Playlists, curated by any signed in user
+//!
+//! Everyone can browse the playlists, but only the user who created one, or an admin, can
+//! change it. The songs of a playlist are addressed by their 0-based position, which the routes
+//! keep contiguous: adding, moving or removing a song shifts the songs after it.
+
+use entities::{
+    pagination::Page,
+    playlist,
+    playlist_song::{self, PlaylistEntry, PlaylistSongs},
+    prelude::*,
+    sea_orm_active_enums::Role,
+    song, user,
+    validation::Validate,
+};
+use rocket::{
+    http::{uri::Origin, Status},
+    serde::json::Json,
+    State,
+};
+use sea_orm::{
+    sea_query::Expr, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::{
+    audit,
+    auth::CurrentUser,
+    error::{ApiError, FieldError},
+    query::{fetch_page, PageParams},
+    trash::Live,
+};
+
+/// Check the fields of a playlist
+fn validate(playlist: &playlist::Model) -> Result<(), ApiError> {
+    playlist.validate().map_err(|errors| {
+        info!("Invalid input");
+        ApiError::Validation(errors)
+    })
+}
+
+/// Check that the user may change the playlist, being its curator or an admin
+fn check_curator(user: &user::Model, playlist: &playlist::Model) -> Result<(), ApiError> {
+    if playlist.user_id != user.id && user.role < Role::Admin {
+        info!("User {} does not curate playlist {}", user.id, playlist.id);
+        return Err(ApiError::Forbidden(
+            "Only the curator of the playlist can change it".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Find the playlist with the given id for the user to change it
+///
+/// The playlist stays locked until the end of the transaction, so that concurrent changes to its
+/// songs are made one after the other and cannot give two songs the same position.
+async fn lock_playlist<C: ConnectionTrait>(
+    txn: &C,
+    user: &user::Model,
+    id: i32,
+) -> Result<playlist::Model, ApiError> {
+    let playlist = Playlist::find_by_id(id)
+        .lock_exclusive()
+        .one(txn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Playlist"))?;
+    check_curator(user, &playlist)?;
+
+    Ok(playlist)
+}
+
+/// Every song of the playlist, in order
+async fn entries<C: ConnectionTrait>(
+    txn: &C,
+    playlist_id: i32,
+) -> Result<Vec<playlist_song::Model>, DbErr> {
+    PlaylistSong::find()
+        .filter(playlist_song::Column::PlaylistId.eq(playlist_id))
+        .order_by_asc(playlist_song::Column::Position)
+        .all(txn)
+        .await
+}
+
+/// Store the index of every entry that is not at its position
+async fn renumber<C: ConnectionTrait>(
+    txn: &C,
+    entries: &[playlist_song::Model],
+) -> Result<(), DbErr> {
+    for (position, entry) in (0..).zip(entries) {
+        if entry.position != position {
+            PlaylistSong::update_many()
+                .col_expr(playlist_song::Column::Position, Expr::value(position))
+                .filter(playlist_song::Column::Id.eq(entry.id))
+                .exec(txn)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Error of a position out of the `0..=max` range
+fn invalid_position(max: usize) -> ApiError {
+    info!("Invalid position");
+    ApiError::Validation(vec![FieldError::new(
+        "position",
+        format!("must be between 0 and {}", max),
+    )])
+}
+
+/// Error of a position without a song
+fn no_song_at(position: i32) -> ApiError {
+    info!("No song at position {}", position);
+    ApiError::NotFound(format!("No song at position {} of the playlist", position))
+}
+
+/// Create a new playlist
+///
+/// This endpoint requires a JSON body containing a [`playlist::Model`] with only a name. The
+/// playlist is curated by the signed in user.
+#[utoipa::path(
+    context_path = "/playlist",
+    tag = "playlist",
+    request_body = Playlist,
+    responses(
+        (status = 201, description = "Id of the created playlist", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/", data = "<playlist>")]
+pub async fn create_playlist(
+    db: &State<DatabaseConnection>,
+    current: CurrentUser,
+    playlist: Json<playlist::Model>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    validate(&playlist)?;
+
+    let new_playlist = playlist::ActiveModel {
+        name: ActiveValue::Set(playlist.name.to_owned()),
+        user_id: ActiveValue::Set(current.user.id),
+        ..Default::default()
+    };
+
+    let txn = db.begin().await?;
+
+    let id = Playlist::insert(new_playlist)
+        .exec(&txn)
+        .await?
+        .last_insert_id;
+    let playlist = playlist::Model {
+        id,
+        user_id: current.user.id,
+        ..playlist.into_inner()
+    };
+    audit::created(&txn, Some(&current.user), id, &playlist).await?;
+
+    txn.commit().await?;
+
+    info!("Inserted playlist with id: {}", id);
+    Ok((Status::Created, id.to_string()))
+}
+
+/// Get a playlist by its id
+///
+/// This endpoint returns a [`playlist::Model`] with the given id, if it exists. See
+/// [`get_playlist_songs`] for its songs.
+#[utoipa::path(
+    context_path = "/playlist",
+    tag = "playlist",
+    responses(
+        (status = 302, description = "The playlist", body = Playlist),
+        (status = 404, description = "Playlist not found", body = ErrorBody)
+    )
+)]
+#[get("/<id>")]
+pub async fn get_playlist_by_id(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<playlist::Model>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let playlist = Playlist::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Playlist"))?;
+
+    info!("Found playlist with id: {}", playlist.id);
+    Ok((Status::Found, Json(playlist)))
+}
+
+/// Rename a playlist with the given id
+///
+/// This endpoint requires a JSON body containing a [`playlist::Model`] with only a name.
+#[utoipa::path(
+    context_path = "/playlist",
+    tag = "playlist",
+    request_body = Playlist,
+    responses(
+        (status = 202, description = "Id of the updated playlist", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Not the curator of the playlist", body = ErrorBody),
+        (status = 404, description = "Playlist not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/<id>", data = "<playlist>")]
+pub async fn update_playlist(
+    db: &State<DatabaseConnection>,
+    current: CurrentUser,
+    id: i32,
+    playlist: Json<playlist::Model>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    validate(&playlist)?;
+
+    let txn = db.begin().await?;
+
+    let before = lock_playlist(&txn, &current.user, id).await?;
+    let updated_playlist = playlist::ActiveModel {
+        id: ActiveValue::Set(id),
+        name: ActiveValue::Set(playlist.name.to_owned()),
+        ..Default::default()
+    };
+    let after = Playlist::update(updated_playlist).exec(&txn).await?;
+    audit::updated(&txn, Some(&current.user), id, &before, &after).await?;
+
+    txn.commit().await?;
+
+    info!("Updated playlist with id: {}", id);
+    Ok((Status::Accepted, id.to_string()))
+}
+
+/// Delete a playlist with the given id
+///
+/// This endpoint returns the id of the deleted playlist, if it exists. Its songs are only taken
+/// out of it, not deleted.
+#[utoipa::path(
+    context_path = "/playlist",
+    tag = "playlist",
+    responses(
+        (status = 200, description = "Id of the deleted playlist", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Not the curator of the playlist", body = ErrorBody),
+        (status = 404, description = "Playlist not found", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[delete("/<id>")]
+pub async fn delete_playlist(
+    db: &State<DatabaseConnection>,
+    current: CurrentUser,
+    id: i32,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    // The songs of the playlist go with it, through the cascade of their foreign key
+    let playlist = lock_playlist(&txn, &current.user, id).await?;
+    Playlist::delete_by_id(id).exec(&txn).await?;
+    audit::deleted(&txn, Some(&current.user), id, &playlist).await?;
+
+    txn.commit().await?;
+
+    info!("Deleted playlist with id: {}", id);
+    Ok((Status::Ok, id.to_string()))
+}
+
+/// Get all playlists
+///
+/// This endpoint returns a [`Page`] of playlists sorted by id, which can be filtered by the
+/// `user_id` of their curator. See [`PageParams`] for `page` and `per_page`.
+#[utoipa::path(
+    context_path = "/playlist",
+    tag = "playlist",
+    responses(
+        (status = 200, description = "A page of playlists", body = PlaylistPage)
+    )
+)]
+#[get("/all?<user_id>&<page>&<per_page>")]
+pub async fn get_all_playlists(
+    db: &State<DatabaseConnection>,
+    origin: &Origin<'_>,
+    user_id: Option<i32>,
+    page: Option<u64>,
+    per_page: Option<u64>,
+) -> Result<(Status, Json<Page<playlist::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let mut query = Playlist::find().order_by_asc(playlist::Column::Id);
+    if let Some(user_id) = user_id {
+        query = query.filter(playlist::Column::UserId.eq(user_id));
+    }
+
+    let playlists = fetch_page(db, query, PageParams { page, per_page }, origin).await?;
+
+    info!("Found {} playlists", playlists.total);
+    Ok((Status::Ok, Json(playlists)))
+}
+
+/// Get the songs of a playlist
+///
+/// This endpoint returns the [`PlaylistSongs`] of the playlist with the given id, in order, along
+/// with their total length. Songs in the trash are left out, but keep their position, so that
+/// they are back in place if they are restored.
+#[utoipa::path(
+    context_path = "/playlist",
+    tag = "playlist",
+    responses(
+        (status = 200, description = "Songs of the playlist", body = PlaylistSongs),
+        (status = 404, description = "Playlist not found", body = ErrorBody)
+    )
+)]
+#[get("/<id>/songs")]
+pub async fn get_playlist_songs(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<PlaylistSongs>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    if Playlist::find_by_id(id).one(db).await?.is_none() {
+        info!("Playlist not found");
+        return Err(ApiError::not_found("Playlist"));
+    }
+
+    let songs: Vec<PlaylistEntry> = PlaylistSong::find()
+        .find_also_related(Song)
+        .filter(playlist_song::Column::PlaylistId.eq(id))
+        .filter(song::Column::DeletedAt.is_null())
+        .order_by_asc(playlist_song::Column::Position)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(entry, song)| {
+            song.map(|song| PlaylistEntry {
+                position: entry.position,
+                song,
+            })
+        })
+        .collect();
+    let total_length_secs = songs
+        .iter()
+        .map(|entry| i64::from(entry.song.length_secs))
+        .sum();
+
+    info!("Found {} songs", songs.len());
+    Ok((
+        Status::Ok,
+        Json(PlaylistSongs {
+            songs,
+            total_length_secs,
+        }),
+    ))
+}
+
+/// A song to add to a playlist
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct NewPlaylistSong {
+    pub song_id: i32,
+    /// Position to insert the song at, the songs from there on move down by one. The song is
+    /// added at the end if there is none.
+    pub position: Option<i32>,
+}
+
+/// Add a song to the playlist with the given id
+///
+/// This endpoint requires a JSON body containing a [`NewPlaylistSong`]. A song can be added to
+/// the same playlist several times.
+#[utoipa::path(
+    context_path = "/playlist",
+    tag = "playlist",
+    request_body = NewPlaylistSong,
+    responses(
+        (status = 201, description = "Id of the playlist", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Not the curator of the playlist", body = ErrorBody),
+        (status = 404, description = "Playlist not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/<id>/songs", data = "<new_song>")]
+pub async fn add_song(
+    db: &State<DatabaseConnection>,
+    current: CurrentUser,
+    id: i32,
+    new_song: Json<NewPlaylistSong>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    lock_playlist(&txn, &current.user, id).await?;
+    if Song::find_live_by_id(new_song.song_id)
+        .one(&txn)
+        .await?
+        .is_none()
+    {
+        info!("Song not found");
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "song_id",
+            "song does not exist",
+        )]));
+    }
+
+    let mut entries = entries(&txn, id).await?;
+    let position = new_song.position.unwrap_or(entries.len() as i32);
+    if position < 0 || position as usize > entries.len() {
+        return Err(invalid_position(entries.len()));
+    }
+
+    let new_entry = playlist_song::ActiveModel {
+        playlist_id: ActiveValue::Set(id),
+        song_id: ActiveValue::Set(new_song.song_id),
+        position: ActiveValue::Set(position),
+        ..Default::default()
+    };
+    let entry_id = PlaylistSong::insert(new_entry)
+        .exec(&txn)
+        .await?
+        .last_insert_id;
+    let entry = playlist_song::Model {
+        id: entry_id,
+        playlist_id: id,
+        song_id: new_song.song_id,
+        position,
+    };
+    audit::created(&txn, Some(&current.user), entry_id, &entry).await?;
+
+    entries.insert(position as usize, entry);
+    renumber(&txn, &entries).await?;
+
+    txn.commit().await?;
+
+    info!(
+        "Added song {} to playlist {} at {}",
+        new_song.song_id, id, position
+    );
+    Ok((Status::Created, id.to_string()))
+}
+
+/// New position of a song in a playlist
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PlaylistMove {
+    /// Position to move the song to, the songs in between shift by one to make room for it
+    pub position: i32,
+}
+
+/// Move the song at `position` in the playlist with the given id
+///
+/// This endpoint requires a JSON body containing a [`PlaylistMove`].
+#[utoipa::path(
+    context_path = "/playlist",
+    tag = "playlist",
+    request_body = PlaylistMove,
+    responses(
+        (status = 200, description = "Id of the playlist", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Not the curator of the playlist", body = ErrorBody),
+        (status = 404, description = "Playlist or song not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/<id>/songs/<position>", data = "<target>")]
+pub async fn move_song(
+    db: &State<DatabaseConnection>,
+    current: CurrentUser,
+    id: i32,
+    position: i32,
+    target: Json<PlaylistMove>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    lock_playlist(&txn, &current.user, id).await?;
+    let mut entries = entries(&txn, id).await?;
+    let from = usize::try_from(position)
+        .ok()
+        .filter(|&from| from < entries.len())
+        .ok_or_else(|| no_song_at(position))?;
+    let to = usize::try_from(target.position)
+        .ok()
+        .filter(|&to| to < entries.len())
+        .ok_or_else(|| invalid_position(entries.len() - 1))?;
+
+    let entry = entries.remove(from);
+    entries.insert(to, entry.clone());
+    renumber(&txn, &entries).await?;
+    let moved = playlist_song::Model {
+        position: target.position,
+        ..entry.clone()
+    };
+    audit::updated(&txn, Some(&current.user), entry.id, &entry, &moved).await?;
+
+    txn.commit().await?;
+
+    info!("Moved song of playlist {} from {} to {}", id, from, to);
+    Ok((Status::Ok, id.to_string()))
+}
+
+/// Remove the song at `position` from the playlist with the given id
+///
+/// The songs after it move up by one.
+#[utoipa::path(
+    context_path = "/playlist",
+    tag = "playlist",
+    responses(
+        (status = 200, description = "Id of the playlist", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Not the curator of the playlist", body = ErrorBody),
+        (status = 404, description = "Playlist or song not found", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[delete("/<id>/songs/<position>")]
+pub async fn remove_song(
+    db: &State<DatabaseConnection>,
+    current: CurrentUser,
+    id: i32,
+    position: i32,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    lock_playlist(&txn, &current.user, id).await?;
+    let mut entries = entries(&txn, id).await?;
+    let index = usize::try_from(position)
+        .ok()
+        .filter(|&index| index < entries.len())
+        .ok_or_else(|| no_song_at(position))?;
+
+    let entry = entries.remove(index);
+    PlaylistSong::delete_by_id(entry.id).exec(&txn).await?;
+    audit::deleted(&txn, Some(&current.user), entry.id, &entry).await?;
+    renumber(&txn, &entries).await?;
+
+    txn.commit().await?;
+
+    info!("Removed song at {} from playlist {}", position, id);
+    Ok((Status::Ok, id.to_string()))
+}
@@ -0,0 +1,124 @@
+use chrono::NaiveDate;
+use entities::pagination::Page;
+use rocket::{
+    form::{self, FromFormField, ValueField},
+    http::uri::Origin,
+};
+use sea_orm::{
+    DatabaseConnection, DbErr, EntityTrait, FromQueryResult, ItemsAndPagesNumber, Order,
+    PaginatorTrait, Select,
+};
+use utoipa::ToSchema;
+
+/// Number of items per page when `per_page` is not given
+pub const DEFAULT_PER_PAGE: u64 = 50;
+/// Largest accepted value of `per_page`
+pub const MAX_PER_PAGE: u64 = 500;
+
+/// Pagination query parameters shared by the list endpoints
+///
+/// `page` is 1-based and defaults to the first page, `per_page` defaults to
+/// [`DEFAULT_PER_PAGE`] and is capped at [`MAX_PER_PAGE`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PageParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+impl PageParams {
+    fn page(&self) -> u64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    fn per_page(&self) -> u64 {
+        self.per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE)
+    }
+}
+
+/// Sort direction, given as `order=asc` or `order=desc`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromFormField, ToSchema)]
+#[schema(rename_all = "lowercase")]
+pub enum Direction {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl From<Direction> for Order {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Asc => Order::Asc,
+            Direction::Desc => Order::Desc,
+        }
+    }
+}
+
+/// A `YYYY-MM-DD` date given as a query parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct DateParam(pub NaiveDate);
+
+#[rocket::async_trait]
+impl<'v> FromFormField<'v> for DateParam {
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        NaiveDate::parse_from_str(field.value, "%Y-%m-%d")
+            .map(DateParam)
+            .map_err(|_| form::Error::validation("expected a YYYY-MM-DD date").into())
+    }
+}
+
+/// Fetch one page of the given query
+///
+/// The query must be ordered down to a unique column, such as the id, or rows
+/// sorted the same could be repeated or skipped from one page to the next.
+/// The `next` and `prev` links reuse the query string of `origin`, so that
+/// sorting and filtering are kept when following them.
+pub async fn fetch_page<E>(
+    db: &DatabaseConnection,
+    select: Select<E>,
+    params: PageParams,
+    origin: &Origin<'_>,
+) -> Result<Page<E::Model>, DbErr>
+where
+    E: EntityTrait,
+    E::Model: FromQueryResult + Sized + Send + Sync,
+{
+    let page = params.page();
+    let per_page = params.per_page();
+
+    let paginator = select.paginate(db, per_page);
+    let ItemsAndPagesNumber {
+        number_of_items: total,
+        number_of_pages: num_pages,
+    } = paginator.num_items_and_pages().await?;
+    let items = paginator.fetch_page(page - 1).await?;
+
+    Ok(Page {
+        items,
+        total,
+        page,
+        per_page,
+        num_pages,
+        next: (page < num_pages).then(|| page_link(origin, page + 1)),
+        prev: (page > 1).then(|| page_link(origin, (page - 1).min(num_pages.max(1)))),
+    })
+}
+
+/// Build a link to the given page, keeping every other query parameter
+fn page_link(origin: &Origin<'_>, page: u64) -> String {
+    let page = format!("page={}", page);
+    let mut query: Vec<&str> = origin
+        .query()
+        .map(|query| {
+            query
+                .as_str()
+                .split('&')
+                .filter(|segment| !segment.is_empty() && !segment.starts_with("page="))
+                .collect()
+        })
+        .unwrap_or_default();
+    query.push(&page);
+
+    format!("{}?{}", origin.path(), query.join("&"))
+}
@@ -0,0 +1,200 @@
+//! Byte-range requests of stored files
+//!
+//! Players ask for the part of a file they need with a `Range` header, and get it in a 206 Partial
+//! Content response. Only single ranges are served: a request for several gets the whole file, as
+//! HTTP allows.
+
+use std::convert::Infallible;
+
+use rocket::{
+    http::{ContentType, Header, Status},
+    request::{self, FromRequest},
+    response::{self, Responder},
+    tokio::io::Take,
+    Request, Response,
+};
+
+use super::storage::{none_match, StoredReader, MAX_AGE_SECS};
+
+/// Most bytes sent for a range without an end, players asking for the rest as they go
+pub const MAX_OPEN_RANGE: u64 = 1024 * 1024;
+
+/// Range of bytes of a file, both ends included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn length(self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// What to send of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// Nothing, the client has the current version
+    NotModified,
+    Whole,
+    Part(ByteRange),
+    /// Nothing, the range is outside of the file
+    Unsatisfiable,
+}
+
+/// The `Range`, `If-Range` and `If-None-Match` headers of a request
+///
+/// Use it as a request guard, and [`RangeHeaders::select`] what to send of a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeHeaders {
+    range: Option<String>,
+    if_range: Option<String>,
+    if_none_match: Option<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RangeHeaders {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let header = |name| request.headers().get_one(name).map(str::to_string);
+        request::Outcome::Success(RangeHeaders {
+            range: header("Range"),
+            if_range: header("If-Range"),
+            if_none_match: header("If-None-Match"),
+        })
+    }
+}
+
+impl RangeHeaders {
+    /// What to send of a file of `size` bytes tagged with `etag`
+    ///
+    /// The range is ignored when `If-Range` does not have the current tag, as the client would
+    /// mix parts of different versions. A range without an end stops after [`MAX_OPEN_RANGE`]
+    /// bytes.
+    pub fn select(&self, etag: &str, size: u64) -> Selection {
+        let etag = format!("\"{}\"", etag);
+        if self
+            .if_none_match
+            .as_deref()
+            .is_some_and(|header| none_match(header, &etag))
+        {
+            return Selection::NotModified;
+        }
+
+        let Some(range) = &self.range else {
+            return Selection::Whole;
+        };
+        if self
+            .if_range
+            .as_deref()
+            .is_some_and(|if_range| if_range.trim() != etag)
+        {
+            return Selection::Whole;
+        }
+
+        parse_range(range, size)
+    }
+}
+
+/// The part of a file of `size` bytes asked for by a `Range` header
+///
+/// Headers which are not a single range of bytes are ignored, as HTTP allows.
+fn parse_range(header: &str, size: u64) -> Selection {
+    let Some((first, last)) = header
+        .trim()
+        .strip_prefix("bytes=")
+        .filter(|ranges| !ranges.contains(','))
+        .and_then(|range| range.split_once('-'))
+    else {
+        return Selection::Whole;
+    };
+
+    let range = match (first.trim(), last.trim()) {
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(0) => return Selection::Unsatisfiable,
+            Ok(suffix) => ByteRange {
+                start: size.saturating_sub(suffix),
+                end: size.saturating_sub(1),
+            },
+            Err(_) => return Selection::Whole,
+        },
+        (start, "") => match start.parse::<u64>() {
+            Ok(start) if start >= size => return Selection::Unsatisfiable,
+            Ok(start) => ByteRange {
+                start,
+                end: start.saturating_add(MAX_OPEN_RANGE - 1).min(size - 1),
+            },
+            Err(_) => return Selection::Whole,
+        },
+        (start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+            (Ok(start), Ok(end)) if start <= end => ByteRange {
+                start,
+                end: end.min(size.saturating_sub(1)),
+            },
+            _ => return Selection::Whole,
+        },
+    };
+
+    if range.start >= size {
+        Selection::Unsatisfiable
+    } else {
+        Selection::Part(range)
+    }
+}
+
+/// The selected part of a stored file
+pub enum FilePart {
+    NotModified,
+    /// The whole file, sent as it is read
+    Whole(Box<dyn StoredReader>),
+    /// The bytes of a range, also sent as they are read
+    Part(ByteRange, Take<Box<dyn StoredReader>>),
+    Unsatisfiable,
+}
+
+/// A stored file sent by parts, with caching headers
+///
+/// Its `ETag` is the given tag, which `If-None-Match` and `If-Range` are checked against.
+pub struct RangedFile {
+    pub content_type: ContentType,
+    pub etag: String,
+    /// Size of the whole file, in bytes
+    pub size: u64,
+    pub part: FilePart,
+}
+
+impl<'r> Responder<'r, 'static> for RangedFile {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = Response::build();
+        response
+            .raw_header("ETag", format!("\"{}\"", self.etag))
+            .raw_header("Accept-Ranges", "bytes")
+            .header(Header::new(
+                "Cache-Control",
+                format!("public, max-age={}", MAX_AGE_SECS),
+            ));
+
+        match self.part {
+            FilePart::NotModified => response.status(Status::NotModified).ok(),
+            FilePart::Whole(file) => response
+                .sized_body(None, file)
+                .header(self.content_type)
+                .ok(),
+            FilePart::Part(range, file) => response
+                .streamed_body(file)
+                .status(Status::PartialContent)
+                .header(self.content_type)
+                .raw_header(
+                    "Content-Range",
+                    format!("bytes {}-{}/{}", range.start, range.end, self.size),
+                )
+                .ok(),
+            FilePart::Unsatisfiable => response
+                .status(Status::RangeNotSatisfiable)
+                .raw_header("Content-Range", format!("bytes */{}", self.size))
+                .ok(),
+        }
+    }
+}
@@ -0,0 +1,470 @@
+//! Ratings and reviews of albums and songs, written by any signed in user
+//!
+//! Each user rates an album or a song once, from 1 to 10, optionally along with a review written
+//! in markdown, and edits that rating afterwards. Only its author can edit a review, and only its
+//! author or an admin can delete it. The average of the ratings is given along with the albums
+//! and the songs themselves.
+
+use std::collections::HashMap;
+
+use entities::{
+    album,
+    pagination::Page,
+    prelude::*,
+    review::{self, RatedAlbum, RatedSong, Ratings, SignedReview},
+    sea_orm_active_enums::Role,
+    song, user,
+    validation::Validate,
+};
+use rocket::{http::Status, serde::json::Json, State};
+use sea_orm::{
+    sea_query::{Alias, Expr, Func},
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+
+use super::{audit, auth::CurrentUser, error::ApiError, trash::Live};
+
+/// What a review is of
+#[derive(Clone, Copy, Debug)]
+enum Subject {
+    Album(i32),
+    Song(i32),
+}
+
+impl Subject {
+    fn column(self) -> review::Column {
+        match self {
+            Subject::Album(_) => review::Column::AlbumId,
+            Subject::Song(_) => review::Column::SongId,
+        }
+    }
+
+    fn id(self) -> i32 {
+        match self {
+            Subject::Album(id) | Subject::Song(id) => id,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Subject::Album(_) => "Album",
+            Subject::Song(_) => "Song",
+        }
+    }
+
+    /// Check that the album or the song exists and is not in the trash
+    async fn exists<C: ConnectionTrait>(self, db: &C) -> Result<bool, DbErr> {
+        Ok(match self {
+            Subject::Album(id) => Album::find_live_by_id(id).one(db).await?.is_some(),
+            Subject::Song(id) => Song::find_live_by_id(id).one(db).await?.is_some(),
+        })
+    }
+}
+
+/// Check the fields of a review
+fn validate(review: &review::Model) -> Result<(), ApiError> {
+    review.validate().map_err(|errors| {
+        info!("Invalid input");
+        ApiError::Validation(errors)
+    })
+}
+
+/// Text of a review, trimmed, or none if it is blank
+fn body(review: &review::Model) -> Option<String> {
+    review
+        .body
+        .as_deref()
+        .map(str::trim)
+        .filter(|body| !body.is_empty())
+        .map(str::to_string)
+}
+
+/// Find the review with the given id for the user to change it
+///
+/// Its author can always change it, and admins can too when `moderate` is set.
+async fn lock_review<C: ConnectionTrait>(
+    txn: &C,
+    user: &user::Model,
+    id: i32,
+    moderate: bool,
+) -> Result<review::Model, ApiError> {
+    let review = Review::find_by_id(id)
+        .lock_exclusive()
+        .one(txn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Review"))?;
+
+    if review.user_id != user.id && !(moderate && user.role >= Role::Admin) {
+        info!("User {} did not write review {}", user.id, review.id);
+        return Err(ApiError::Forbidden(
+            "Only the author of the review can change it".to_string(),
+        ));
+    }
+
+    Ok(review)
+}
+
+/// Average and number of the ratings of an album or a song
+async fn ratings<C: ConnectionTrait>(db: &C, subject: Subject) -> Result<Ratings, DbErr> {
+    let ratings = Review::find()
+        .select_only()
+        .column_as(
+            Func::avg(Expr::col((review::Entity, review::Column::Rating)))
+                .cast_as(Alias::new("double precision")),
+            "average_rating",
+        )
+        .column_as(
+            Expr::col((review::Entity, review::Column::Id)).count(),
+            "rating_count",
+        )
+        .filter(subject.column().eq(subject.id()))
+        .into_model::<Ratings>()
+        .one(db)
+        .await?;
+
+    Ok(ratings.unwrap_or_default())
+}
+
+/// Average and number of the ratings of an album or a song, along with its id
+#[derive(Debug, FromQueryResult)]
+struct SubjectRatings {
+    subject_id: i32,
+    average_rating: Option<f64>,
+    rating_count: i64,
+}
+
+/// Average and number of the ratings of each of the albums or songs with the given ids, in a
+/// single query grouped by the given column
+///
+/// Those without any rating are left out.
+async fn ratings_of_each<C: ConnectionTrait>(
+    db: &C,
+    column: review::Column,
+    ids: Vec<i32>,
+) -> Result<HashMap<i32, Ratings>, DbErr> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let ratings = Review::find()
+        .select_only()
+        .column_as(column, "subject_id")
+        .column_as(
+            Func::avg(Expr::col((review::Entity, review::Column::Rating)))
+                .cast_as(Alias::new("double precision")),
+            "average_rating",
+        )
+        .column_as(
+            Expr::col((review::Entity, review::Column::Id)).count(),
+            "rating_count",
+        )
+        .filter(column.is_in(ids))
+        .group_by(column)
+        .into_model::<SubjectRatings>()
+        .all(db)
+        .await?;
+
+    Ok(ratings
+        .into_iter()
+        .map(|ratings| {
+            (
+                ratings.subject_id,
+                Ratings {
+                    average_rating: ratings.average_rating,
+                    rating_count: ratings.rating_count,
+                },
+            )
+        })
+        .collect())
+}
+
+/// Average and number of the ratings of the album with the given id
+pub async fn album_ratings<C: ConnectionTrait>(db: &C, album_id: i32) -> Result<Ratings, DbErr> {
+    ratings(db, Subject::Album(album_id)).await
+}
+
+/// Average and number of the ratings of the song with the given id
+pub async fn song_ratings<C: ConnectionTrait>(db: &C, song_id: i32) -> Result<Ratings, DbErr> {
+    ratings(db, Subject::Song(song_id)).await
+}
+
+/// A page of albums, each along with its ratings
+pub async fn rate_albums<C: ConnectionTrait>(
+    db: &C,
+    albums: Page<album::Model>,
+) -> Result<Page<RatedAlbum>, DbErr> {
+    let ids = albums.items.iter().map(|album| album.id).collect();
+    let mut ratings = ratings_of_each(db, review::Column::AlbumId, ids).await?;
+
+    Ok(albums.map(|album| RatedAlbum {
+        ratings: ratings.remove(&album.id).unwrap_or_default(),
+        album,
+    }))
+}
+
+/// A page of songs, each along with its ratings
+pub async fn rate_songs<C: ConnectionTrait>(
+    db: &C,
+    songs: Page<song::Model>,
+) -> Result<Page<RatedSong>, DbErr> {
+    let ids = songs.items.iter().map(|song| song.id).collect();
+    let mut ratings = ratings_of_each(db, review::Column::SongId, ids).await?;
+
+    Ok(songs.map(|song| RatedSong {
+        ratings: ratings.remove(&song.id).unwrap_or_default(),
+        song,
+    }))
+}
+
+/// Rate an album or a song for the first time, in a transaction of its own
+async fn create_review(
+    db: &DatabaseConnection,
+    current: &CurrentUser,
+    subject: Subject,
+    review: review::Model,
+) -> Result<(Status, String), ApiError> {
+    validate(&review)?;
+
+    let txn = db.begin().await?;
+
+    if !subject.exists(&txn).await? {
+        info!("{} not found", subject.name());
+        return Err(ApiError::not_found(subject.name()));
+    }
+
+    let reviewed = Review::find()
+        .filter(subject.column().eq(subject.id()))
+        .filter(review::Column::UserId.eq(current.user.id))
+        .one(&txn)
+        .await?;
+    if let Some(reviewed) = reviewed {
+        info!(
+            "User {} already wrote review {}",
+            current.user.id, reviewed.id
+        );
+        return Err(ApiError::Conflict(format!(
+            "{} already reviewed, edit review {} instead",
+            subject.name(),
+            reviewed.id
+        )));
+    }
+
+    let new_review = review::ActiveModel {
+        user_id: ActiveValue::Set(current.user.id),
+        album_id: ActiveValue::Set(matches!(subject, Subject::Album(_)).then_some(subject.id())),
+        song_id: ActiveValue::Set(matches!(subject, Subject::Song(_)).then_some(subject.id())),
+        rating: ActiveValue::Set(review.rating),
+        body: ActiveValue::Set(body(&review)),
+        ..Default::default()
+    };
+    let review = new_review.insert(&txn).await?;
+    audit::created(&txn, Some(&current.user), review.id, &review).await?;
+
+    txn.commit().await?;
+
+    info!("Inserted review with id: {}", review.id);
+    Ok((Status::Created, review.id.to_string()))
+}
+
+/// The reviews of an album or a song, most recent first
+async fn signed_reviews(
+    db: &DatabaseConnection,
+    subject: Subject,
+) -> Result<(Status, Json<Vec<SignedReview>>), ApiError> {
+    if !subject.exists(db).await? {
+        info!("{} not found", subject.name());
+        return Err(ApiError::not_found(subject.name()));
+    }
+
+    let reviews: Vec<SignedReview> = Review::find()
+        .find_also_related(User)
+        .filter(subject.column().eq(subject.id()))
+        .order_by_desc(review::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(review, user)| {
+            Some(SignedReview {
+                review,
+                username: user?.username,
+            })
+        })
+        .collect();
+
+    info!("Found {} reviews", reviews.len());
+    Ok((Status::Ok, Json(reviews)))
+}
+
+/// Rate and review an album
+///
+/// This endpoint requires a JSON body containing a [`review::Model`] with a rating and, optionally,
+/// the markdown text of the review. Each user reviews an album once.
+#[utoipa::path(
+    context_path = "/review",
+    tag = "review",
+    request_body = Review,
+    responses(
+        (status = 201, description = "Id of the created review", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 404, description = "Album not found", body = ErrorBody),
+        (status = 409, description = "Album already reviewed by the user", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/album/<id>", data = "<review>")]
+pub async fn create_album_review(
+    db: &State<DatabaseConnection>,
+    current: CurrentUser,
+    id: i32,
+    review: Json<review::Model>,
+) -> Result<(Status, String), ApiError> {
+    create_review(db, &current, Subject::Album(id), review.into_inner()).await
+}
+
+/// Rate and review a song
+///
+/// This endpoint requires a JSON body containing a [`review::Model`] with a rating and, optionally,
+/// the markdown text of the review. Each user reviews a song once.
+#[utoipa::path(
+    context_path = "/review",
+    tag = "review",
+    request_body = Review,
+    responses(
+        (status = 201, description = "Id of the created review", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 404, description = "Song not found", body = ErrorBody),
+        (status = 409, description = "Song already reviewed by the user", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/song/<id>", data = "<review>")]
+pub async fn create_song_review(
+    db: &State<DatabaseConnection>,
+    current: CurrentUser,
+    id: i32,
+    review: Json<review::Model>,
+) -> Result<(Status, String), ApiError> {
+    create_review(db, &current, Subject::Song(id), review.into_inner()).await
+}
+
+/// Get the reviews of an album
+///
+/// This endpoint returns a list of [`SignedReview`] for the album with the given id, most recent
+/// first.
+#[utoipa::path(
+    context_path = "/review",
+    tag = "review",
+    responses(
+        (status = 200, description = "Reviews of the album", body = [SignedReview]),
+        (status = 404, description = "Album not found", body = ErrorBody)
+    )
+)]
+#[get("/album/<id>")]
+pub async fn get_album_reviews(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<Vec<SignedReview>>), ApiError> {
+    signed_reviews(db, Subject::Album(id)).await
+}
+
+/// Get the reviews of a song
+///
+/// This endpoint returns a list of [`SignedReview`] for the song with the given id, most recent
+/// first.
+#[utoipa::path(
+    context_path = "/review",
+    tag = "review",
+    responses(
+        (status = 200, description = "Reviews of the song", body = [SignedReview]),
+        (status = 404, description = "Song not found", body = ErrorBody)
+    )
+)]
+#[get("/song/<id>")]
+pub async fn get_song_reviews(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<Vec<SignedReview>>), ApiError> {
+    signed_reviews(db, Subject::Song(id)).await
+}
+
+/// Edit a review with the given id
+///
+/// This endpoint requires a JSON body containing a [`review::Model`] with the new rating and text.
+#[utoipa::path(
+    context_path = "/review",
+    tag = "review",
+    request_body = Review,
+    responses(
+        (status = 202, description = "Id of the updated review", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Not the author of the review", body = ErrorBody),
+        (status = 404, description = "Review not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/<id>", data = "<review>")]
+pub async fn update_review(
+    db: &State<DatabaseConnection>,
+    current: CurrentUser,
+    id: i32,
+    review: Json<review::Model>,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    validate(&review)?;
+
+    let txn = db.begin().await?;
+
+    let before = lock_review(&txn, &current.user, id, false).await?;
+    let updated_review = review::ActiveModel {
+        id: ActiveValue::Set(id),
+        rating: ActiveValue::Set(review.rating),
+        body: ActiveValue::Set(body(&review)),
+        ..Default::default()
+    };
+    let after = Review::update(updated_review).exec(&txn).await?;
+    audit::updated(&txn, Some(&current.user), id, &before, &after).await?;
+
+    txn.commit().await?;
+
+    info!("Updated review with id: {}", id);
+    Ok((Status::Accepted, id.to_string()))
+}
+
+/// Delete a review with the given id
+///
+/// This endpoint returns the id of the deleted review, if it exists.
+#[utoipa::path(
+    context_path = "/review",
+    tag = "review",
+    responses(
+        (status = 200, description = "Id of the deleted review", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Not the author of the review", body = ErrorBody),
+        (status = 404, description = "Review not found", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[delete("/<id>")]
+pub async fn delete_review(
+    db: &State<DatabaseConnection>,
+    current: CurrentUser,
+    id: i32,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let review = lock_review(&txn, &current.user, id, true).await?;
+    Review::delete_by_id(id).exec(&txn).await?;
+    audit::deleted(&txn, Some(&current.user), id, &review).await?;
+
+    txn.commit().await?;
+
+    info!("Deleted review with id: {}", id);
+    Ok((Status::Ok, id.to_string()))
+}
@@ -0,0 +1,159 @@
+use rocket::{http::Status, request::FromParam, serde::json::Json, State};
+use sea_orm::{DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::error::ApiError;
+
+/// Number of hits returned when `limit` is not given
+const DEFAULT_LIMIT: u64 = 20;
+/// Largest accepted value of `limit`
+const MAX_LIMIT: u64 = 100;
+
+/// Kind of entity matched by a search, or found in the trash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, FromFormField, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchKind {
+    Artist,
+    Album,
+    Song,
+}
+
+impl SearchKind {
+    pub const ALL: [SearchKind; 3] = [SearchKind::Artist, SearchKind::Album, SearchKind::Song];
+
+    pub fn table(self) -> &'static str {
+        match self {
+            SearchKind::Artist => "artist",
+            SearchKind::Album => "album",
+            SearchKind::Song => "song",
+        }
+    }
+
+    /// The kind stored in the given table
+    pub fn from_table(table: &str) -> Option<Self> {
+        SearchKind::ALL
+            .into_iter()
+            .find(|kind| kind.table() == table)
+    }
+}
+
+impl<'a> FromParam<'a> for SearchKind {
+    type Error = &'a str;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        SearchKind::from_table(param).ok_or(param)
+    }
+}
+
+/// A single search result
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct SearchHit {
+    pub kind: SearchKind,
+    /// Id of the matched artist, album or song
+    pub id: i32,
+    pub name: String,
+    /// Relevance of the hit, higher is better
+    pub rank: f32,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct SearchRow {
+    kind: String,
+    id: i32,
+    name: String,
+    rank: f32,
+}
+
+impl TryFrom<SearchRow> for SearchHit {
+    type Error = ();
+
+    fn try_from(row: SearchRow) -> Result<Self, Self::Error> {
+        Ok(SearchHit {
+            kind: SearchKind::from_table(&row.kind).ok_or(())?,
+            id: row.id,
+            name: row.name,
+            rank: row.rank,
+        })
+    }
+}
+
+/// Turn free text into a `tsquery` matching every word as a prefix
+///
+/// Anything but letters and digits is dropped, so that user input can never
+/// produce an invalid `tsquery`. Returns `None` if no word is left.
+pub fn to_tsquery(q: &str) -> Option<String> {
+    let terms: Vec<String> = q
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("{}:*", word.to_lowercase()))
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" & "))
+}
+
+/// Search artists, albums and songs by name
+///
+/// This endpoint returns a list of [`SearchHit`] ranked by relevance, optionally restricted to one
+/// `kind` of entity. Every word of `q` must match the start of a word in the name. The search
+/// relies on the full-text indexes on the `name` columns.
+#[utoipa::path(
+    context_path = "/search",
+    tag = "search",
+    responses(
+        (status = 200, description = "Hits ranked by relevance", body = [SearchHit]),
+        (status = 400, description = "Empty search query", body = ErrorBody)
+    )
+)]
+#[get("/?<q>&<kind>&<limit>")]
+pub async fn search(
+    db: &State<DatabaseConnection>,
+    q: &str,
+    kind: Option<SearchKind>,
+    limit: Option<u64>,
+) -> Result<(Status, Json<Vec<SearchHit>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let query =
+        to_tsquery(q).ok_or_else(|| ApiError::BadRequest("Empty search query".to_string()))?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let kinds = match kind {
+        Some(kind) => vec![kind],
+        None => SearchKind::ALL.to_vec(),
+    };
+
+    // The `to_tsvector('simple', "name")` expression must stay in sync with the indexes created
+    // by the `m20261018_000005_create_search_indexes` migration.
+    let selects: Vec<String> = kinds
+        .iter()
+        .map(|kind| {
+            format!(
+                r#"SELECT '{table}' AS kind, "id", "name",
+                    ts_rank(to_tsvector('simple', "name"), to_tsquery('simple', $1)) AS rank
+                FROM "{table}"
+                WHERE to_tsvector('simple', "name") @@ to_tsquery('simple', $1)
+                    AND "deleted_at" IS NULL"#,
+                table = kind.table()
+            )
+        })
+        .collect();
+    let sql = format!(
+        "{} ORDER BY rank DESC, name LIMIT $2",
+        selects.join(" UNION ALL ")
+    );
+
+    let hits = SearchRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        &sql,
+        vec![query.into(), (limit as i64).into()],
+    ))
+    .all(db)
+    .await?
+    .into_iter()
+    .filter_map(|row| SearchHit::try_from(row).ok())
+    .collect::<Vec<_>>();
+
+    info!("Found {} search hits for {:?}", hits.len(), q);
+    Ok((Status::Ok, Json(hits)))
+}
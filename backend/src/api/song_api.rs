@@ -1,132 +1,947 @@
-use entities::{prelude::*, song};
-use rocket::{http::Status, serde::json::Json, State};
-use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection, EntityTrait};
+use std::{collections::HashMap, io};
+
+use entities::{
+    artist,
+    pagination::Page,
+    prelude::*,
+    review::RatedSong,
+    sea_orm_active_enums::CreditRole,
+    song, song_audio,
+    song_credit::{self, Credit},
+    user,
+    validation::Validate,
+};
+use rocket::{
+    form::Form,
+    fs::TempFile,
+    http::{uri::Origin, ContentType, Status},
+    serde::json::Json,
+    tokio::task,
+    State,
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::{
+    audio::{self, TaggedAudio},
+    audit,
+    auth::{Admin, Editor},
+    error::{ApiError, FieldError},
+    import::{self, ImportReport, ImportedRow},
+    query::{fetch_page, DateParam, Direction, PageParams},
+    range::{FilePart, RangeHeaders, RangedFile, Selection},
+    review_api,
+    storage::{self, Storage, Upload},
+    trash::{self, Live},
+    version::{stale, IfMatch, WithETag, INITIAL_VERSION},
+};
+
+/// Check the fields of a song, given whether its album exists and whether its track is taken
+fn validate(song: &song::Model, album_exists: bool, track_taken: bool) -> Result<(), ApiError> {
+    let mut errors = song.validate().err().unwrap_or_default();
+    if !album_exists {
+        errors.push(FieldError::new("album_id", "album does not exist"));
+    }
+    if track_taken {
+        errors.push(FieldError::new(
+            "track_number",
+            "is already taken on this disc",
+        ));
+    }
+    if !errors.is_empty() {
+        info!("Invalid input");
+        return Err(ApiError::Validation(errors));
+    }
+
+    Ok(())
+}
+
+/// Whether another song of the album is at the track and on the disc of `song`
+///
+/// The song with the given id is left out, so that a song can be updated without moving it.
+pub async fn track_taken<C: ConnectionTrait>(
+    db: &C,
+    song: &song::Model,
+    id: Option<i32>,
+) -> Result<bool, DbErr> {
+    let mut query = Song::find_live()
+        .filter(song::Column::AlbumId.eq(song.album_id))
+        .filter(song::Column::DiscNumber.eq(song.disc_number))
+        .filter(song::Column::TrackNumber.eq(song.track_number));
+    if let Some(id) = id {
+        query = query.filter(song::Column::Id.ne(id));
+    }
+
+    Ok(query.one(db).await?.is_some())
+}
+
+/// The track after the last one on the given disc of the album
+///
+/// Songs in the trash are counted too, so that they get their place back when restored.
+pub async fn next_track_number<C: ConnectionTrait>(
+    db: &C,
+    album_id: i32,
+    disc_number: i32,
+) -> Result<i32, DbErr> {
+    let last = Song::find()
+        .filter(song::Column::AlbumId.eq(album_id))
+        .filter(song::Column::DiscNumber.eq(disc_number))
+        .order_by_desc(song::Column::TrackNumber)
+        .one(db)
+        .await?;
+
+    Ok(last.map_or(1, |song| song.track_number + 1))
+}
 
 /// Create a new song
 /// 
-/// This endpoint requires a JSON body containing a [`song::Model`] without an id.
+/// This endpoint requires a JSON body containing a [`song::Model`] without an id. Its track must
+/// not be taken by another song on the same disc of the album; without a track, the song comes
+/// after the last one of its disc.
+#[utoipa::path(
+    context_path = "/song",
+    tag = "song",
+    request_body = Song,
+    responses(
+        (status = 201, description = "Id of the created song", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
 #[post("/", data = "<song>")]
 pub async fn create_song(
     db: &State<DatabaseConnection>,
+    editor: Editor,
     song: Json<song::Model>,
-) -> Result<(Status, String), (Status, &str)> {
+) -> Result<(Status, String), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let album = Album::find_by_id(song.album_id)
-        .one(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching album in DB"))?;
-
-    if song.name.is_empty() || song.length_secs <= 0 || album.is_none() {
-        info!("Invalid input");
-        return Err((Status::BadRequest, "Invalid input"));
+    let mut song = song.into_inner();
+    let album = Album::find_live_by_id(song.album_id).one(db).await?;
+    if song.track_number == 0 {
+        song.track_number = next_track_number(db, song.album_id, song.disc_number).await?;
     }
+    let taken = track_taken(db, &song, None).await?;
+    validate(&song, album.is_some(), taken)?;
 
     let new_song = song::ActiveModel {
         name: ActiveValue::Set(song.name.to_owned()),
         length_secs: ActiveValue::Set(song.length_secs),
         album_id: ActiveValue::Set(song.album_id),
+        disc_number: ActiveValue::Set(song.disc_number),
+        track_number: ActiveValue::Set(song.track_number),
         ..Default::default()
     };
 
-    let song = Song::insert(new_song)
-        .exec(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error inserting song in DB"))?;
+    let txn = db.begin().await?;
+
+    let id = Song::insert(new_song).exec(&txn).await?.last_insert_id;
+    let song = song::Model {
+        id,
+        version: INITIAL_VERSION,
+        ..song
+    };
+    audit::created(&txn, Some(&editor.0.user), id, &song).await?;
+
+    txn.commit().await?;
 
-    info!("Inserted song with id: {}", song.last_insert_id);
-    Ok((Status::Created, song.last_insert_id.to_string()))
+    info!("Inserted song with id: {}", id);
+    Ok((Status::Created, id.to_string()))
 }
 
 /// Get a song by its id
 /// 
-/// This endpoint returns a [`song::Model`] with the given id, if it exists.
+/// This endpoint returns a [`RatedSong`] with the given id, if it exists: the song along with the
+/// average of its ratings.
+#[utoipa::path(
+    context_path = "/song",
+    tag = "song",
+    responses(
+        (status = 302, description = "The song", body = RatedSong,
+            headers(("ETag" = String, description = "Version of the song"))),
+        (status = 404, description = "Song not found", body = ErrorBody)
+    )
+)]
 #[get("/<id>")]
 pub async fn get_song_by_id(
     db: &State<DatabaseConnection>,
     id: i32,
-) -> Result<(Status, Json<song::Model>), (Status, &str)> {
+) -> Result<(Status, WithETag<Json<RatedSong>>), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let song = Song::find_by_id(id)
-        .one(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching song in DB"))?;
+    let song = Song::find_live_by_id(id).one(db).await?;
 
-    let song = song.ok_or((Status::NotFound, "Song not found"))?;
+    let song = song.ok_or_else(|| ApiError::not_found("Song"))?;
+    let ratings = review_api::song_ratings(db, id).await?;
     info!("Found song with id: {}", song.id);
-    Ok((Status::Found, Json(song)))
+    Ok((
+        Status::Found,
+        WithETag(song.version, Json(RatedSong { song, ratings })),
+    ))
 }
 
 /// Update a song with the given id
 /// 
 /// This endpoint requires a JSON body containing a [`song::Model`] with all of the fields set.
+/// The song must be valid, as on creation.
+#[utoipa::path(
+    context_path = "/song",
+    tag = "song",
+    request_body = Song,
+    responses(
+        (status = 202, description = "Id of the updated song", body = String,
+            headers(("ETag" = String, description = "New version of the song"))),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Song not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody),
+        (status = 412, description = "Song changed since the given version", body = ErrorBody),
+        (status = 428, description = "If-Match header required", body = ErrorBody)
+    ),
+    params(("If-Match" = String, Header, description = "ETag of the song being changed")),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
 #[put("/<id>", data = "<song>")]
 pub async fn update_song(
     db: &State<DatabaseConnection>,
+    editor: Editor,
+    if_match: IfMatch,
     id: i32,
     song: Json<song::Model>,
-) -> Result<(Status, String), (Status, &str)> {
+) -> Result<(Status, WithETag<String>), ApiError> {
     let db = db as &DatabaseConnection;
 
+    let before = Song::find_live_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Song"))?;
+    if_match.check("Song", before.version)?;
+
+    let album = Album::find_live_by_id(song.album_id).one(db).await?;
+    let taken = track_taken(db, &song, Some(id)).await?;
+    validate(&song, album.is_some(), taken)?;
+
     let updated_song = song::ActiveModel {
         id: ActiveValue::Set(id),
         name: ActiveValue::Set(song.name.to_owned()),
         length_secs: ActiveValue::Set(song.length_secs),
         album_id: ActiveValue::Set(song.album_id),
+        disc_number: ActiveValue::Set(song.disc_number),
+        track_number: ActiveValue::Set(song.track_number),
+        version: ActiveValue::Set(before.version + 1),
         ..Default::default()
     };
 
     info!("About to update song with id {} to: {:?}", id, updated_song);
 
-    updated_song
-        .update(db)
+    let txn = db.begin().await?;
+
+    let after = Song::update(updated_song)
+        .filter(song::Column::Version.eq(before.version))
+        .exec(&txn)
         .await
-        .map_err(|_| (Status::InternalServerError, "Error updating song"))?;
+        .map_err(stale("Song"))?;
+    audit::updated(&txn, Some(&editor.0.user), id, &before, &after).await?;
+
+    txn.commit().await?;
 
     info!("Updated song with id: {}", id);
 
-    Ok((Status::Accepted, id.to_string()))
+    Ok((Status::Accepted, WithETag(after.version, id.to_string())))
 }
 
-/// Delete a song with the given id
-/// 
-/// This endpoint returns the id of the deleted song, if it exists.
+/// Fields of a song to change, every missing field is left as is
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct SongPatch {
+    pub name: Option<String>,
+    pub length_secs: Option<i32>,
+    pub album_id: Option<i32>,
+    pub disc_number: Option<i32>,
+    pub track_number: Option<i32>,
+}
+
+/// Partially update a song with the given id
+///
+/// This endpoint requires a JSON body containing a [`SongPatch`] and returns the updated
+/// [`song::Model`]. The song resulting from the patch must be valid, as on creation.
+#[utoipa::path(
+    context_path = "/song",
+    tag = "song",
+    request_body = SongPatch,
+    responses(
+        (status = 200, description = "The updated song", body = Song,
+            headers(("ETag" = String, description = "New version of the song"))),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Song not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody),
+        (status = 412, description = "Song changed since the given version", body = ErrorBody),
+        (status = 428, description = "If-Match header required", body = ErrorBody)
+    ),
+    params(("If-Match" = String, Header, description = "ETag of the song being changed")),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[patch("/<id>", data = "<patch>")]
+pub async fn patch_song(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    if_match: IfMatch,
+    id: i32,
+    patch: Json<SongPatch>,
+) -> Result<(Status, WithETag<Json<song::Model>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let song = Song::find_live_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Song"))?;
+    if_match.check("Song", song.version)?;
+    let patch = patch.into_inner();
+
+    let mut merged = song.clone();
+    if let Some(name) = &patch.name {
+        merged.name = name.to_owned();
+    }
+    if let Some(length_secs) = patch.length_secs {
+        merged.length_secs = length_secs;
+    }
+    if let Some(disc_number) = patch.disc_number {
+        merged.disc_number = disc_number;
+    }
+    if let Some(track_number) = patch.track_number {
+        merged.track_number = track_number;
+    }
+    let album_exists = match patch.album_id {
+        Some(album_id) => {
+            merged.album_id = album_id;
+            Album::find_live_by_id(album_id).one(db).await?.is_some()
+        }
+        None => true,
+    };
+    let moved =
+        patch.album_id.is_some() || patch.disc_number.is_some() || patch.track_number.is_some();
+    let taken = moved && track_taken(db, &merged, Some(id)).await?;
+    validate(&merged, album_exists, taken)?;
+
+    let mut updated_song: song::ActiveModel = song.clone().into();
+    if let Some(name) = patch.name {
+        updated_song.name = ActiveValue::Set(name);
+    }
+    if let Some(length_secs) = patch.length_secs {
+        updated_song.length_secs = ActiveValue::Set(length_secs);
+    }
+    if let Some(album_id) = patch.album_id {
+        updated_song.album_id = ActiveValue::Set(album_id);
+    }
+    if let Some(disc_number) = patch.disc_number {
+        updated_song.disc_number = ActiveValue::Set(disc_number);
+    }
+    if let Some(track_number) = patch.track_number {
+        updated_song.track_number = ActiveValue::Set(track_number);
+    }
+
+    if !updated_song.is_changed() {
+        info!("Nothing to update for song with id: {}", id);
+        return Ok((Status::Ok, WithETag(merged.version, Json(merged))));
+    }
+
+    updated_song.version = ActiveValue::Set(song.version + 1);
+
+    info!("About to patch song with id {}: {:?}", id, updated_song);
+
+    let txn = db.begin().await?;
+
+    let patched = Song::update(updated_song)
+        .filter(song::Column::Version.eq(song.version))
+        .exec(&txn)
+        .await
+        .map_err(stale("Song"))?;
+    audit::updated(&txn, Some(&editor.0.user), id, &song, &patched).await?;
+
+    txn.commit().await?;
+
+    info!("Patched song with id: {}", id);
+    Ok((Status::Ok, WithETag(patched.version, Json(patched))))
+}
+
+/// Move a song with the given id to the trash
+///
+/// This endpoint returns the id of the deleted song, if it exists. See [`super::trash_api`] to
+/// restore or purge it.
+#[utoipa::path(
+    context_path = "/song",
+    tag = "song",
+    responses(
+        (status = 200, description = "Id of the deleted song", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody),
+        (status = 404, description = "Song not found", body = ErrorBody),
+        (status = 412, description = "Song changed since the given version", body = ErrorBody),
+        (status = 428, description = "If-Match header required", body = ErrorBody)
+    ),
+    params(("If-Match" = String, Header, description = "ETag of the song being changed")),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
 #[delete("/<id>")]
 pub async fn delete_song(
     db: &State<DatabaseConnection>,
+    admin: Admin,
+    if_match: IfMatch,
     id: i32,
-) -> Result<(Status, String), (Status, &str)> {
+) -> Result<(Status, String), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let res = Song::delete_by_id(id)
-        .exec(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error deleting song"))?;
+    let song = Song::find_live_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Song"))?;
+    if_match.check("Song", song.version)?;
+
+    let txn = db.begin().await?;
+
+    trash::trash_song(&txn, Some(&admin.0.user), &song).await?;
+
+    txn.commit().await?;
+
+    info!("Moved song with id {} to the trash", id);
+    Ok((Status::Ok, id.to_string()))
+}
+
+/// Fields the song list can be sorted by
+#[derive(Debug, Clone, Copy, FromFormField, ToSchema)]
+#[schema(rename_all = "snake_case")]
+pub enum SongSort {
+    Id,
+    Name,
+    #[field(value = "length_secs")]
+    LengthSecs,
+    #[field(value = "album_id")]
+    AlbumId,
+}
 
-    match res.rows_affected {
-        1 => {
-            info!("Deleted song with id {}", id);
-            Ok((Status::Ok, id.to_string()))
+impl From<SongSort> for song::Column {
+    fn from(sort: SongSort) -> Self {
+        match sort {
+            SongSort::Id => song::Column::Id,
+            SongSort::Name => song::Column::Name,
+            SongSort::LengthSecs => song::Column::LengthSecs,
+            SongSort::AlbumId => song::Column::AlbumId,
         }
-        _ => Err((Status::NotFound, "Song not found")),
     }
 }
 
 /// Get all songs
 /// 
-/// This endpoint returns a list of all songs in the database.
-#[get("/all")]
+/// This endpoint returns a [`Page`] of [`RatedSong`]s, each song along with its average rating and
+/// number of ratings, sorted by `sort` (one of `id`, `name`, `length_secs` or `album_id`) in the
+/// given `order`. The songs can be filtered by `album_id` and by a
+/// `min_length_secs`/`max_length_secs` range.
+/// See [`PageParams`] for `page` and `per_page`.
+#[utoipa::path(
+    context_path = "/song",
+    tag = "song",
+    responses(
+        (status = 200, description = "A page of songs", body = SongPage)
+    )
+)]
+#[get("/all?<sort>&<order>&<album_id>&<min_length_secs>&<max_length_secs>&<page>&<per_page>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_all_songs(
     db: &State<DatabaseConnection>,
-) -> Result<(Status, Json<Vec<song::Model>>), (Status, &str)> {
+    origin: &Origin<'_>,
+    sort: Option<SongSort>,
+    order: Option<Direction>,
+    album_id: Option<i32>,
+    min_length_secs: Option<i32>,
+    max_length_secs: Option<i32>,
+    page: Option<u64>,
+    per_page: Option<u64>,
+) -> Result<(Status, Json<Page<RatedSong>>), ApiError> {
     let db = db as &DatabaseConnection;
 
-    let songs = Song::find()
-        .all(db)
-        .await
-        .map_err(|_| (Status::InternalServerError, "Error fetching songs in DB"))?;
+    let mut query = Song::find_live()
+        .order_by(
+            song::Column::from(sort.unwrap_or(SongSort::Id)),
+            order.unwrap_or_default().into(),
+        )
+        .order_by_asc(song::Column::Id);
+    if let Some(album_id) = album_id {
+        query = query.filter(song::Column::AlbumId.eq(album_id));
+    }
+    if let Some(min_length_secs) = min_length_secs {
+        query = query.filter(song::Column::LengthSecs.gte(min_length_secs));
+    }
+    if let Some(max_length_secs) = max_length_secs {
+        query = query.filter(song::Column::LengthSecs.lte(max_length_secs));
+    }
 
-    info!("Found {} songs", songs.len());
+    let songs = fetch_page(db, query, PageParams { page, per_page }, origin).await?;
+    let songs = review_api::rate_songs(db, songs).await?;
+
+    info!("Found {} songs", songs.total);
     Ok((Status::Ok, Json(songs)))
 }
+
+/// An artist credited on a song, in the role they had
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct CreditLink {
+    pub artist_id: i32,
+    pub role: CreditRole,
+    /// Words put before the artist when the credits are written out, such as "feat."
+    #[serde(default)]
+    pub join_phrase: Option<String>,
+}
+
+/// Credits of the song with the given id, in their order
+///
+/// Credits of artists in the trash are left out.
+pub async fn song_credits<C: ConnectionTrait>(db: &C, song_id: i32) -> Result<Vec<Credit>, DbErr> {
+    Ok(SongCredit::find()
+        .find_also_related(Artist)
+        .filter(song_credit::Column::SongId.eq(song_id))
+        .filter(artist::Column::DeletedAt.is_null())
+        .order_by_asc(song_credit::Column::Position)
+        .order_by_asc(song_credit::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(credit, artist)| {
+            Some(Credit {
+                credit,
+                artist: artist?,
+            })
+        })
+        .collect())
+}
+
+/// Give the song with the given id exactly the given credits, in that order, returning them
+///
+/// Join phrases are trimmed, a blank one being no join phrase. Only the credits that change are
+/// added, updated or removed, and each of them is audited.
+pub async fn set_song_credits<C: ConnectionTrait>(
+    txn: &C,
+    user: &user::Model,
+    song_id: i32,
+    credits: Vec<CreditLink>,
+) -> Result<Vec<Credit>, ApiError> {
+    let mut wanted: Vec<song_credit::Model> = Vec::new();
+    for (position, link) in credits.into_iter().enumerate() {
+        if wanted
+            .iter()
+            .any(|credit| credit.artist_id == link.artist_id && credit.role == link.role)
+        {
+            info!(
+                "Artist {} credited twice as {:?}",
+                link.artist_id, link.role
+            );
+            return Err(ApiError::Validation(vec![FieldError::new(
+                "artist_id",
+                "must not be credited twice in the same role",
+            )]));
+        }
+        wanted.push(song_credit::Model {
+            id: 0,
+            song_id,
+            artist_id: link.artist_id,
+            role: link.role,
+            position: position as i32,
+            join_phrase: link
+                .join_phrase
+                .map(|phrase| phrase.trim().to_string())
+                .filter(|phrase| !phrase.is_empty()),
+        });
+    }
+
+    let mut artist_ids: Vec<i32> = wanted.iter().map(|credit| credit.artist_id).collect();
+    artist_ids.sort_unstable();
+    artist_ids.dedup();
+    let found = Artist::find_live()
+        .filter(artist::Column::Id.is_in(artist_ids.clone()))
+        .all(txn)
+        .await?;
+    if found.len() != artist_ids.len() {
+        info!("Not all artists were found");
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "artist_id",
+            "some artists do not exist",
+        )]));
+    }
+
+    let existing = SongCredit::find()
+        .filter(song_credit::Column::SongId.eq(song_id))
+        .all(txn)
+        .await?;
+    for credit in &existing {
+        let same = |other: &&song_credit::Model| {
+            other.artist_id == credit.artist_id && other.role == credit.role
+        };
+        match wanted.iter().find(same) {
+            None => {
+                SongCredit::delete_by_id(credit.id).exec(txn).await?;
+                audit::deleted(txn, Some(user), credit.id, credit).await?;
+            }
+            Some(other)
+                if other.position != credit.position || other.join_phrase != credit.join_phrase =>
+            {
+                let updated_credit = song_credit::ActiveModel {
+                    id: ActiveValue::Set(credit.id),
+                    position: ActiveValue::Set(other.position),
+                    join_phrase: ActiveValue::Set(other.join_phrase.clone()),
+                    ..Default::default()
+                };
+                let after = SongCredit::update(updated_credit).exec(txn).await?;
+                audit::updated(txn, Some(user), credit.id, credit, &after).await?;
+            }
+            Some(_) => {}
+        }
+    }
+    for credit in wanted {
+        if !existing
+            .iter()
+            .any(|other| other.artist_id == credit.artist_id && other.role == credit.role)
+        {
+            let new_credit = song_credit::ActiveModel {
+                song_id: ActiveValue::Set(song_id),
+                artist_id: ActiveValue::Set(credit.artist_id),
+                role: ActiveValue::Set(credit.role),
+                position: ActiveValue::Set(credit.position),
+                join_phrase: ActiveValue::Set(credit.join_phrase.clone()),
+                ..Default::default()
+            };
+            let id = SongCredit::insert(new_credit)
+                .exec(txn)
+                .await?
+                .last_insert_id;
+            let credit = song_credit::Model { id, ..credit };
+            audit::created(txn, Some(user), id, &credit).await?;
+        }
+    }
+
+    Ok(song_credits(txn, song_id).await?)
+}
+
+/// Get the credits of a song
+///
+/// This endpoint returns the list of [`Credit`] of the song with the given id, in their order.
+/// Artists in the trash are left out.
+#[utoipa::path(
+    context_path = "/song",
+    tag = "song",
+    responses(
+        (status = 200, description = "Credits of the song", body = [Credit]),
+        (status = 404, description = "Song not found", body = ErrorBody)
+    )
+)]
+#[get("/<id>/credits")]
+pub async fn get_credits(
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<(Status, Json<Vec<Credit>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    if Song::find_live_by_id(id).one(db).await?.is_none() {
+        info!("Song not found");
+        return Err(ApiError::not_found("Song"));
+    }
+
+    let credits = song_credits(db, id).await?;
+
+    info!("Found {} credits", credits.len());
+    Ok((Status::Ok, Json(credits)))
+}
+
+/// Set the credits of a song
+///
+/// This endpoint requires a JSON body containing the list of the [`CreditLink`] the song has from
+/// now on, in the order they are written out, and returns the list of its [`Credit`]. An artist
+/// can be credited in several roles, but only once in each.
+#[utoipa::path(
+    context_path = "/song",
+    tag = "song",
+    request_body = Vec<CreditLink>,
+    responses(
+        (status = 200, description = "Credits of the song", body = [Credit]),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 404, description = "Song not found", body = ErrorBody),
+        (status = 422, description = "Invalid input", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[put("/<id>/credits", data = "<credits>")]
+pub async fn set_credits(
+    db: &State<DatabaseConnection>,
+    editor: Editor,
+    id: i32,
+    credits: Json<Vec<CreditLink>>,
+) -> Result<(Status, Json<Vec<Credit>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    trash::lock_live::<Song, _>(&txn, id, "Song").await?;
+
+    let credits = set_song_credits(&txn, &editor.0.user, id, credits.into_inner()).await?;
+
+    txn.commit().await?;
+
+    info!("Set {} credits of song with id: {}", credits.len(), id);
+    Ok((Status::Ok, Json(credits)))
+}
+
+/// Form uploading songs, as documented
+#[derive(ToSchema)]
+pub struct SongUpload {
+    /// MP3, FLAC or Ogg Vorbis files
+    #[schema(value_type = Vec<String>)]
+    pub files: Vec<Vec<u8>>,
+}
+
+/// Create songs from audio files
+///
+/// This endpoint requires a `multipart/form-data` body with MP3, FLAC or Ogg Vorbis files, each in
+/// a `files` field. The ID3 or Vorbis tags of each file name its artist, album and song, which are
+/// matched or created as by the import, along with the track, the release date of the album and
+/// the length of the song. The tags have no date of formation for the artists to create, which is
+/// taken from `artist_date_formed` (`YYYY-MM-DD`). Every file is then stored and linked to its
+/// song, replacing the previous audio of a matched song. It returns an [`ImportReport`] with the
+/// outcome of every file, in the order they were sent. Nothing is imported if any file is
+/// rejected, or when `dry_run=true`.
+#[utoipa::path(
+    context_path = "/song",
+    tag = "song",
+    request_body(content = SongUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Report of the dry run", body = ImportReport),
+        (status = 201, description = "Report of the upload", body = ImportReport),
+        (status = 400, description = "No file uploaded", body = ErrorBody),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Editor role required", body = ErrorBody),
+        (status = 422, description = "Some files were rejected, nothing was imported", body = ImportReport)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/upload?<dry_run>&<artist_date_formed>", data = "<form>")]
+pub async fn upload_songs(
+    db: &State<DatabaseConnection>,
+    storage: &State<Box<dyn Storage>>,
+    editor: Editor,
+    dry_run: Option<bool>,
+    artist_date_formed: Option<DateParam>,
+    form: Form<HashMap<String, Vec<TempFile<'_>>>>,
+) -> Result<(Status, Json<ImportReport>), ApiError> {
+    let db = db as &DatabaseConnection;
+    let storage = storage.inner().as_ref();
+    let user = Some(&editor.0.user);
+
+    let files = form.into_inner().remove("files").unwrap_or_default();
+    if files.is_empty() {
+        info!("No audio file uploaded");
+        return Err(ApiError::BadRequest("No file was uploaded".to_string()));
+    }
+    // The files stay where Rocket wrote them, until `files` is dropped
+    let uploads = files.iter().map(Upload::of).collect::<Vec<_>>();
+    let uploads = task::spawn_blocking(move || {
+        uploads
+            .into_iter()
+            .map(|upload| {
+                let tagged = upload.read(|file| audio::read(file))?;
+                Ok((upload, tagged))
+            })
+            .collect::<io::Result<Vec<_>>>()
+    })
+    .await
+    .map_err(|err| {
+        error!("Reading audio tags failed: {}", err);
+        ApiError::Internal("Reading audio tags failed".to_string())
+    })??;
+
+    let artist_date_formed = artist_date_formed.map(|DateParam(date)| date);
+    let txn = db.begin().await?;
+
+    let mut rows = Vec::with_capacity(uploads.len());
+    let mut linked = Vec::new();
+    let mut replaced = Vec::new();
+    for (index, (upload, tagged)) in uploads.iter().enumerate() {
+        let number = index + 1;
+        let row = match tagged {
+            Ok(tagged) => tagged.row(artist_date_formed),
+            Err(error) => Err(vec![error.clone()]),
+        };
+        let imported = match row {
+            Ok(row) => import::import_row(&txn, user, number, &row).await?,
+            Err(errors) => ImportedRow::rejected(number, errors),
+        };
+
+        if let (Some(song_id), Ok(tagged)) = (imported.song_id, tagged) {
+            let key = storage::new_key();
+            let size = upload.size();
+            if let Some(audio) = link_audio(&txn, user, song_id, &key, tagged, size).await? {
+                replaced.push(audio.key);
+            }
+            linked.push((key, upload));
+        }
+        rows.push(imported);
+    }
+
+    let mut report = ImportReport {
+        committed: false,
+        rows,
+    };
+    if dry_run.unwrap_or(false) || report.has_rejections() {
+        txn.rollback().await?;
+    } else {
+        let mut stored = Vec::with_capacity(linked.len());
+        for (key, upload) in linked {
+            if let Err(err) = upload.store(storage, &audio::audio_key(&key)).await {
+                remove_audio_files(storage, &stored).await;
+                return Err(err.into());
+            }
+            stored.push(key);
+        }
+        if let Err(err) = txn.commit().await {
+            remove_audio_files(storage, &stored).await;
+            return Err(err.into());
+        }
+        report.committed = true;
+        remove_audio_files(storage, &replaced).await;
+    }
+
+    info!(
+        "Uploaded {} audio files, committed: {}",
+        report.rows.len(),
+        report.committed
+    );
+    let status = if report.has_rejections() {
+        Status::UnprocessableEntity
+    } else if report.committed {
+        Status::Created
+    } else {
+        Status::Ok
+    };
+    Ok((status, Json(report)))
+}
+
+/// Record the audio file stored under `key` as that of the song, returning the one it replaced
+async fn link_audio<C: ConnectionTrait>(
+    db: &C,
+    user: Option<&user::Model>,
+    song_id: i32,
+    key: &str,
+    tagged: &TaggedAudio,
+    size: u64,
+) -> Result<Option<song_audio::Model>, DbErr> {
+    let replaced = SongAudio::find()
+        .filter(song_audio::Column::SongId.eq(song_id))
+        .lock_exclusive()
+        .one(db)
+        .await?;
+    let new_audio = song_audio::ActiveModel {
+        key: ActiveValue::Set(key.to_string()),
+        content_type: ActiveValue::Set(tagged.content_type.to_string()),
+        size: ActiveValue::Set(size as i64),
+        ..Default::default()
+    };
+    match &replaced {
+        Some(replaced) => {
+            let saved = SongAudio::update(song_audio::ActiveModel {
+                id: ActiveValue::Set(replaced.id),
+                ..new_audio
+            })
+            .exec(db)
+            .await?;
+            audit::updated(db, user, saved.id, replaced, &saved).await?;
+        }
+        None => {
+            let saved = song_audio::ActiveModel {
+                song_id: ActiveValue::Set(song_id),
+                ..new_audio
+            }
+            .insert(db)
+            .await?;
+            audit::created(db, user, saved.id, &saved).await?;
+        }
+    }
+
+    Ok(replaced)
+}
+
+/// Remove stored audio files, which are only left behind if that fails
+pub async fn remove_audio_files(storage: &dyn Storage, keys: &[String]) {
+    for key in keys {
+        let key = audio::audio_key(key);
+        if let Err(err) = storage.delete(&key).await {
+            warn!("Failed to remove {}: {}", key, err);
+        }
+    }
+}
+
+/// Stream the audio of a song
+///
+/// This endpoint returns the audio file uploaded for the song, or the part of it asked for by a
+/// single `bytes` range in the `Range` header, in a 206 Partial Content response. A range without
+/// an end gets at most 1 MiB, players asking for the rest as they go. The `ETag` of the file
+/// changes with each upload: it is checked against `If-None-Match` and `If-Range`.
+#[utoipa::path(
+    context_path = "/song",
+    tag = "song",
+    responses(
+        (status = 200, description = "The audio file", body = String, content_type = "audio/*",
+            headers(("ETag" = String, description = "Version of the audio file"),
+                ("Accept-Ranges" = String, description = "Always `bytes`"))),
+        (status = 206, description = "The requested range of the audio file", body = String, content_type = "audio/*",
+            headers(("Content-Range" = String, description = "Range sent and size of the file"))),
+        (status = 304, description = "The audio file did not change since the `If-None-Match` ETag"),
+        (status = 404, description = "Song without audio", body = ErrorBody),
+        (status = 416, description = "The range is outside of the audio file")
+    )
+)]
+#[get("/<id>/stream")]
+pub async fn stream_song(
+    db: &State<DatabaseConnection>,
+    storage: &State<Box<dyn Storage>>,
+    range: RangeHeaders,
+    id: i32,
+) -> Result<RangedFile, ApiError> {
+    let db = db as &DatabaseConnection;
+
+    if Song::find_live_by_id(id).one(db).await?.is_none() {
+        info!("Song not found");
+        return Err(ApiError::not_found("Song"));
+    }
+    let audio = SongAudio::find()
+        .filter(song_audio::Column::SongId.eq(id))
+        .one(db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Audio"))?;
+
+    let key = audio::audio_key(&audio.key);
+    let size = audio.size as u64;
+    let stored = match range.select(&audio.key, size) {
+        Selection::NotModified => Some(FilePart::NotModified),
+        Selection::Unsatisfiable => Some(FilePart::Unsatisfiable),
+        Selection::Whole => storage.open(&key).await?.map(FilePart::Whole),
+        Selection::Part(range) => storage
+            .open_range(&key, range.start, range.length())
+            .await?
+            .map(|file| FilePart::Part(range, file)),
+    };
+    let Some(part) = stored else {
+        error!("Audio file {} of song {} is missing", key, id);
+        return Err(ApiError::not_found("Audio"));
+    };
+
+    Ok(RangedFile {
+        content_type: ContentType::parse_flexible(&audio.content_type)
+            .unwrap_or(ContentType::Binary),
+        etag: audio.key,
+        size,
+        part,
+    })
+}
@@ -0,0 +1,257 @@
+//! Where uploaded files are kept
+//!
+//! Files are stored under a key, a relative path such as `covers/<key>/original`. The routes only go
+//! through the [`Storage`] trait, managed by Rocket as a [`Box<dyn Storage>`], so that the files
+//! can move elsewhere than the local disk without changing them.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Cursor, SeekFrom},
+    path::{Component, Path, PathBuf},
+};
+
+use rand::{distributions::Alphanumeric, Rng};
+use rocket::{
+    fs::TempFile,
+    http::{ContentType, Header, Status},
+    response::{self, Responder},
+    tokio::{
+        fs,
+        io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, Take},
+    },
+    Request, Response,
+};
+
+/// Length of the random keys of the uploads
+const KEY_LENGTH: usize = 24;
+/// How long browsers may use a stored file without asking whether it changed
+pub const MAX_AGE_SECS: u32 = 300;
+
+/// A place to store files under keys
+#[rocket::async_trait]
+pub trait Storage: Send + Sync {
+    /// Store `bytes` under `key`, replacing what was there
+    async fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+
+    /// Store the local file at `path` under `key`, replacing what was there
+    ///
+    /// The file is copied as it is read, rather than loaded in memory first.
+    async fn put_file(&self, key: &str, path: &Path) -> io::Result<()>;
+
+    /// The bytes stored under `key`, if any
+    async fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// The file stored under `key`, opened to be read as it is sent, if anything is stored there
+    async fn open(&self, key: &str) -> io::Result<Option<Box<dyn StoredReader>>>;
+
+    /// At most `length` bytes stored under `key` from offset `start`, read as they are sent, if
+    /// anything is stored there
+    async fn open_range(
+        &self,
+        key: &str,
+        start: u64,
+        length: u64,
+    ) -> io::Result<Option<Take<Box<dyn StoredReader>>>> {
+        let Some(mut file) = self.open(key).await? else {
+            return Ok(None);
+        };
+        file.seek(SeekFrom::Start(start)).await?;
+        Ok(Some(file.take(length)))
+    }
+
+    /// Remove what is stored under `key`, if anything
+    async fn delete(&self, key: &str) -> io::Result<()>;
+}
+
+/// A stored file being read, which is sent as it is read rather than loaded in memory first
+pub trait StoredReader: AsyncRead + AsyncSeek + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncSeek + Send + Unpin> StoredReader for T {}
+
+/// Files stored in a directory of the local disk
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LocalStorage { root: root.into() }
+    }
+
+    /// Path of the file of `key`, which must stay inside the root
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        let relative = Path::new(key);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid storage key {}", key),
+            ));
+        }
+
+        Ok(self.root.join(relative))
+    }
+}
+
+#[rocket::async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        fs::write(path, bytes).await
+    }
+
+    async fn put_file(&self, key: &str, path: &Path) -> io::Result<()> {
+        let target = self.path(key)?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        fs::copy(path, target).await.map(|_| ())
+    }
+
+    async fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)?).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn open(&self, key: &str) -> io::Result<Option<Box<dyn StoredReader>>> {
+        match fs::File::open(self.path(key)?).await {
+            Ok(file) => Ok(Some(Box::new(file))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)?).await {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Generate the key of a new upload, under which its files are stored
+pub fn new_key() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(KEY_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+/// Read the whole content of an uploaded file
+pub async fn read_upload(file: &TempFile<'_>) -> io::Result<Vec<u8>> {
+    match file {
+        TempFile::Buffered { content } => Ok(content.as_bytes().to_vec()),
+        _ => match file.path() {
+            Some(path) => fs::read(path).await,
+            None => Ok(Vec::new()),
+        },
+    }
+}
+
+/// An uploaded file, left where Rocket put it
+///
+/// Files sent in a form are written to a temporary file on the disk; only the values of fields sent
+/// without a file name are kept in memory, within the limit of such fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Upload {
+    File { path: PathBuf, size: u64 },
+    Buffered(Vec<u8>),
+}
+
+impl Upload {
+    pub fn of(file: &TempFile<'_>) -> Self {
+        match (file, file.path()) {
+            (TempFile::Buffered { content }, _) => Upload::Buffered(content.as_bytes().to_vec()),
+            (_, Some(path)) => Upload::File {
+                path: path.to_path_buf(),
+                size: file.len(),
+            },
+            (_, None) => Upload::Buffered(Vec::new()),
+        }
+    }
+
+    /// Size of the file, in bytes
+    pub fn size(&self) -> u64 {
+        match self {
+            Upload::File { size, .. } => *size,
+            Upload::Buffered(bytes) => bytes.len() as u64,
+        }
+    }
+
+    /// Run `read` on the content of the file, which blocks while reading it
+    pub fn read<T>(&self, read: impl FnOnce(&mut dyn ReadSeek) -> T) -> io::Result<T> {
+        match self {
+            Upload::File { path, .. } => Ok(read(&mut BufReader::new(File::open(path)?))),
+            Upload::Buffered(bytes) => Ok(read(&mut Cursor::new(bytes))),
+        }
+    }
+
+    /// Store the file under `key`
+    pub async fn store(&self, storage: &dyn Storage, key: &str) -> io::Result<()> {
+        match self {
+            Upload::File { path, .. } => storage.put_file(key, path).await,
+            Upload::Buffered(bytes) => storage.put(key, bytes).await,
+        }
+    }
+}
+
+/// A source of bytes which can be read in any order, such as a file
+pub trait ReadSeek: io::Read + io::Seek {}
+
+impl<T: io::Read + io::Seek> ReadSeek for T {}
+
+/// Whether an `If-None-Match` header has the quoted `etag`
+pub fn none_match(header: &str, etag: &str) -> bool {
+    header
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+/// A stored file sent with caching headers
+///
+/// Its `ETag` is the given tag: requests whose `If-None-Match` has it get a 304 Not Modified
+/// without the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredFile {
+    pub bytes: Vec<u8>,
+    pub content_type: ContentType,
+    pub etag: String,
+}
+
+impl<'r> Responder<'r, 'static> for StoredFile {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let etag = format!("\"{}\"", self.etag);
+        let cache_control =
+            Header::new("Cache-Control", format!("public, max-age={}", MAX_AGE_SECS));
+
+        let not_modified = request
+            .headers()
+            .get_one("If-None-Match")
+            .is_some_and(|header| none_match(header, &etag));
+        if not_modified {
+            return Response::build()
+                .status(Status::NotModified)
+                .raw_header("ETag", etag)
+                .header(cache_control)
+                .ok();
+        }
+
+        Response::build_from(self.bytes.respond_to(request)?)
+            .header(self.content_type)
+            .raw_header("ETag", etag)
+            .header(cache_control)
+            .ok()
+    }
+}
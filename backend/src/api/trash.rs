@@ -0,0 +1,343 @@
+//! Moving songs, albums and artists to the trash and back
+//!
+//! The `DELETE` routes only set the `deleted_at` of a row, and every other query skips such rows
+//! through [`Live`]. The timestamp is the start of the transaction, so whatever is trashed along
+//! with a row gets the same one, which is how a restore finds what to bring back with it.
+
+use chrono::NaiveDateTime;
+use entities::{album, album_artist_mtm, artist, prelude::*, song, user};
+use sea_orm::{
+    sea_query::{Expr, SimpleExpr},
+    ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait, PrimaryKeyTrait, QueryFilter,
+    QuerySelect, Select,
+};
+
+use super::{audit, error::ApiError};
+
+/// Queries telling apart the rows in the trash from the others
+pub trait Live: EntityTrait {
+    /// The `deleted_at` column of the entity
+    fn deleted_at() -> Self::Column;
+
+    /// Select every row that is not in the trash
+    fn find_live() -> Select<Self> {
+        Self::find().filter(Self::deleted_at().is_null())
+    }
+
+    /// Select the row with the given id, unless it is in the trash
+    fn find_live_by_id(id: i32) -> Select<Self>
+    where
+        i32: Into<<Self::PrimaryKey as PrimaryKeyTrait>::ValueType>,
+    {
+        Self::find_by_id(id.into()).filter(Self::deleted_at().is_null())
+    }
+
+    /// Select the row with the given id, if it is in the trash
+    fn find_trashed_by_id(id: i32) -> Select<Self>
+    where
+        i32: Into<<Self::PrimaryKey as PrimaryKeyTrait>::ValueType>,
+    {
+        Self::find_by_id(id.into()).filter(Self::deleted_at().is_not_null())
+    }
+}
+
+impl Live for Song {
+    fn deleted_at() -> song::Column {
+        song::Column::DeletedAt
+    }
+}
+
+impl Live for Album {
+    fn deleted_at() -> album::Column {
+        album::Column::DeletedAt
+    }
+}
+
+impl Live for Artist {
+    fn deleted_at() -> artist::Column {
+        artist::Column::DeletedAt
+    }
+}
+
+impl Live for AlbumArtistMtm {
+    fn deleted_at() -> album_artist_mtm::Column {
+        album_artist_mtm::Column::DeletedAt
+    }
+}
+
+/// Lock the row with the given id until the end of the transaction, unless it is in the trash
+///
+/// Handlers replacing what hangs off a row, such as its links or credits, lock the row first, so
+/// that concurrent replacements are made one after the other instead of mixing their rows. `what`
+/// names the row in the error when it is missing.
+pub async fn lock_live<E, C>(txn: &C, id: i32, what: &str) -> Result<E::Model, ApiError>
+where
+    E: Live,
+    C: ConnectionTrait,
+    i32: Into<<E::PrimaryKey as PrimaryKeyTrait>::ValueType>,
+{
+    E::find_live_by_id(id)
+        .lock_exclusive()
+        .one(txn)
+        .await?
+        .ok_or_else(|| {
+            info!("{} not found", what);
+            ApiError::not_found(what)
+        })
+}
+
+/// `deleted_at` value of the rows entering the trash, the same for the whole transaction
+fn trashed() -> SimpleExpr {
+    Expr::cust("CURRENT_TIMESTAMP")
+}
+
+/// `deleted_at` value of the rows leaving the trash
+fn restored() -> SimpleExpr {
+    Expr::value(Option::<NaiveDateTime>::None)
+}
+
+/// Move a song to the trash
+pub async fn trash_song<C: ConnectionTrait>(
+    db: &C,
+    user: Option<&user::Model>,
+    song: &song::Model,
+) -> Result<(), DbErr> {
+    Song::update_many()
+        .col_expr(song::Column::DeletedAt, trashed())
+        .filter(song::Column::Id.eq(song.id))
+        .exec(db)
+        .await?;
+    audit::deleted(db, user, song.id, song).await
+}
+
+/// Move an album to the trash, along with its songs and its links to artists
+pub async fn trash_album<C: ConnectionTrait>(
+    db: &C,
+    user: Option<&user::Model>,
+    album: &album::Model,
+) -> Result<(), DbErr> {
+    let songs = Song::find_live()
+        .filter(song::Column::AlbumId.eq(album.id))
+        .all(db)
+        .await?;
+    let links = AlbumArtistMtm::find_live()
+        .filter(album_artist_mtm::Column::AlbumId.eq(album.id))
+        .all(db)
+        .await?;
+
+    Album::update_many()
+        .col_expr(album::Column::DeletedAt, trashed())
+        .filter(album::Column::Id.eq(album.id))
+        .exec(db)
+        .await?;
+    audit::deleted(db, user, album.id, album).await?;
+
+    Song::update_many()
+        .col_expr(song::Column::DeletedAt, trashed())
+        .filter(song::Column::Id.is_in(songs.iter().map(|song| song.id)))
+        .exec(db)
+        .await?;
+    for song in &songs {
+        audit::deleted(db, user, song.id, song).await?;
+    }
+
+    AlbumArtistMtm::update_many()
+        .col_expr(album_artist_mtm::Column::DeletedAt, trashed())
+        .filter(album_artist_mtm::Column::Id.is_in(links.iter().map(|link| link.id)))
+        .exec(db)
+        .await?;
+    for link in &links {
+        audit::deleted(db, user, link.id, link).await?;
+    }
+
+    Ok(())
+}
+
+/// Move an artist to the trash, along with its links and the albums left without any artist
+pub async fn trash_artist<C: ConnectionTrait>(
+    db: &C,
+    user: Option<&user::Model>,
+    artist: &artist::Model,
+) -> Result<(), DbErr> {
+    let links = AlbumArtistMtm::find_live()
+        .filter(album_artist_mtm::Column::ArtistId.eq(artist.id))
+        .all(db)
+        .await?;
+
+    Artist::update_many()
+        .col_expr(artist::Column::DeletedAt, trashed())
+        .filter(artist::Column::Id.eq(artist.id))
+        .exec(db)
+        .await?;
+    audit::deleted(db, user, artist.id, artist).await?;
+
+    AlbumArtistMtm::update_many()
+        .col_expr(album_artist_mtm::Column::DeletedAt, trashed())
+        .filter(album_artist_mtm::Column::Id.is_in(links.iter().map(|link| link.id)))
+        .exec(db)
+        .await?;
+    for link in &links {
+        audit::deleted(db, user, link.id, link).await?;
+    }
+
+    for link in links {
+        let no_other_artists = AlbumArtistMtm::find_live()
+            .filter(album_artist_mtm::Column::AlbumId.eq(link.album_id))
+            .all(db)
+            .await?
+            .is_empty();
+
+        if no_other_artists {
+            if let Some(album) = Album::find_live_by_id(link.album_id).one(db).await? {
+                trash_album(db, user, &album).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Bring a song back from the trash
+pub async fn restore_song<C: ConnectionTrait>(
+    db: &C,
+    user: Option<&user::Model>,
+    song: &song::Model,
+) -> Result<(), DbErr> {
+    Song::update_many()
+        .col_expr(song::Column::DeletedAt, restored())
+        .filter(song::Column::Id.eq(song.id))
+        .exec(db)
+        .await?;
+
+    let after = song::Model {
+        deleted_at: None,
+        ..song.clone()
+    };
+    audit::updated(db, user, song.id, song, &after).await
+}
+
+/// Bring an album back from the trash, along with the songs and links trashed with it
+///
+/// Links to an artist which is still in the trash stay there, until the artist is restored.
+pub async fn restore_album<C: ConnectionTrait>(
+    db: &C,
+    user: Option<&user::Model>,
+    album: &album::Model,
+) -> Result<(), DbErr> {
+    let Some(at) = album.deleted_at else {
+        return Ok(());
+    };
+
+    Album::update_many()
+        .col_expr(album::Column::DeletedAt, restored())
+        .filter(album::Column::Id.eq(album.id))
+        .exec(db)
+        .await?;
+
+    let after = album::Model {
+        deleted_at: None,
+        ..album.clone()
+    };
+    audit::updated(db, user, album.id, album, &after).await?;
+
+    let songs = Song::find()
+        .filter(song::Column::AlbumId.eq(album.id))
+        .filter(song::Column::DeletedAt.eq(at))
+        .all(db)
+        .await?;
+    Song::update_many()
+        .col_expr(song::Column::DeletedAt, restored())
+        .filter(song::Column::Id.is_in(songs.iter().map(|song| song.id)))
+        .exec(db)
+        .await?;
+    for song in &songs {
+        let after = song::Model {
+            deleted_at: None,
+            ..song.clone()
+        };
+        audit::updated(db, user, song.id, song, &after).await?;
+    }
+
+    let links = AlbumArtistMtm::find()
+        .inner_join(Artist)
+        .filter(album_artist_mtm::Column::AlbumId.eq(album.id))
+        .filter(album_artist_mtm::Column::DeletedAt.eq(at))
+        .filter(artist::Column::DeletedAt.is_null())
+        .all(db)
+        .await?;
+    restore_links(db, user, &links).await
+}
+
+/// Bring an artist back from the trash, along with the links and albums trashed with it
+pub async fn restore_artist<C: ConnectionTrait>(
+    db: &C,
+    user: Option<&user::Model>,
+    artist: &artist::Model,
+) -> Result<(), DbErr> {
+    let Some(at) = artist.deleted_at else {
+        return Ok(());
+    };
+
+    Artist::update_many()
+        .col_expr(artist::Column::DeletedAt, restored())
+        .filter(artist::Column::Id.eq(artist.id))
+        .exec(db)
+        .await?;
+
+    let after = artist::Model {
+        deleted_at: None,
+        ..artist.clone()
+    };
+    audit::updated(db, user, artist.id, artist, &after).await?;
+
+    // Besides those trashed with the artist, the links of albums restored while the artist was
+    // still in the trash come back
+    let links = AlbumArtistMtm::find()
+        .inner_join(Album)
+        .filter(album_artist_mtm::Column::ArtistId.eq(artist.id))
+        .filter(
+            Condition::any()
+                .add(album_artist_mtm::Column::DeletedAt.eq(at))
+                .add(
+                    album_artist_mtm::Column::DeletedAt
+                        .is_not_null()
+                        .and(album::Column::DeletedAt.is_null()),
+                ),
+        )
+        .all(db)
+        .await?;
+    restore_links(db, user, &links).await?;
+
+    let albums = Album::find()
+        .filter(album::Column::Id.is_in(links.iter().map(|link| link.album_id)))
+        .filter(album::Column::DeletedAt.eq(at))
+        .all(db)
+        .await?;
+    for album in &albums {
+        restore_album(db, user, album).await?;
+    }
+
+    Ok(())
+}
+
+/// Bring links between albums and artists back from the trash
+async fn restore_links<C: ConnectionTrait>(
+    db: &C,
+    user: Option<&user::Model>,
+    links: &[album_artist_mtm::Model],
+) -> Result<(), DbErr> {
+    AlbumArtistMtm::update_many()
+        .col_expr(album_artist_mtm::Column::DeletedAt, restored())
+        .filter(album_artist_mtm::Column::Id.is_in(links.iter().map(|link| link.id)))
+        .exec(db)
+        .await?;
+    for link in links {
+        let after = album_artist_mtm::Model {
+            deleted_at: None,
+            ..link.clone()
+        };
+        audit::updated(db, user, link.id, link, &after).await?;
+    }
+
+    Ok(())
+}
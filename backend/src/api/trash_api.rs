@@ -0,0 +1,280 @@
+use chrono::NaiveDateTime;
+use entities::{album_cover, prelude::*, song, song_audio};
+use rocket::{http::Status, serde::json::Json, State};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult, JoinType,
+    QueryFilter, QuerySelect, RelationTrait, Statement, TransactionTrait,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::{
+    album_api::remove_cover_files,
+    audit,
+    auth::Admin,
+    error::ApiError,
+    search_api::SearchKind,
+    song_api::{remove_audio_files, track_taken},
+    storage::Storage,
+    trash::{self, Live},
+};
+
+/// Number of entries returned when `limit` is not given
+const DEFAULT_LIMIT: u64 = 50;
+/// Largest accepted value of `limit`
+const MAX_LIMIT: u64 = 500;
+
+/// An artist, album or song in the trash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TrashEntry {
+    pub kind: SearchKind,
+    /// Id of the trashed artist, album or song
+    pub id: i32,
+    pub name: String,
+    /// When the row was moved to the trash
+    #[schema(value_type = String, format = DateTime)]
+    pub deleted_at: NaiveDateTime,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct TrashRow {
+    kind: String,
+    id: i32,
+    name: String,
+    deleted_at: NaiveDateTime,
+}
+
+impl TryFrom<TrashRow> for TrashEntry {
+    type Error = ();
+
+    fn try_from(row: TrashRow) -> Result<Self, Self::Error> {
+        Ok(TrashEntry {
+            kind: SearchKind::from_table(&row.kind).ok_or(())?,
+            id: row.id,
+            name: row.name,
+            deleted_at: row.deleted_at,
+        })
+    }
+}
+
+/// The row found in the trash, or a 404 naming what is missing
+fn in_trash<M>(model: Option<M>, what: &str) -> Result<M, ApiError> {
+    model.ok_or_else(|| ApiError::NotFound(format!("{} not found in the trash", what)))
+}
+
+/// Get the trash
+///
+/// This endpoint returns a list of [`TrashEntry`], most recently deleted first, optionally
+/// restricted to one `kind` of entity. The songs and albums trashed along with an album or an
+/// artist are listed too.
+#[utoipa::path(
+    context_path = "/trash",
+    tag = "trash",
+    responses(
+        (status = 200, description = "Trashed artists, albums and songs", body = [TrashEntry]),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[get("/?<kind>&<limit>")]
+pub async fn get_trash(
+    db: &State<DatabaseConnection>,
+    _admin: Admin,
+    kind: Option<SearchKind>,
+    limit: Option<u64>,
+) -> Result<(Status, Json<Vec<TrashEntry>>), ApiError> {
+    let db = db as &DatabaseConnection;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let kinds = match kind {
+        Some(kind) => vec![kind],
+        None => SearchKind::ALL.to_vec(),
+    };
+
+    let selects: Vec<String> = kinds
+        .iter()
+        .map(|kind| {
+            format!(
+                r#"SELECT '{table}' AS kind, "id", "name", "deleted_at"
+                FROM "{table}"
+                WHERE "deleted_at" IS NOT NULL"#,
+                table = kind.table()
+            )
+        })
+        .collect();
+    let sql = format!(
+        "{} ORDER BY deleted_at DESC, kind, id LIMIT $1",
+        selects.join(" UNION ALL ")
+    );
+
+    let entries = TrashRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        &sql,
+        vec![(limit as i64).into()],
+    ))
+    .all(db)
+    .await?
+    .into_iter()
+    .filter_map(|row| TrashEntry::try_from(row).ok())
+    .collect::<Vec<_>>();
+
+    info!("Found {} trash entries", entries.len());
+    Ok((Status::Ok, Json(entries)))
+}
+
+/// Restore an artist, album or song from the trash
+///
+/// This endpoint returns the id of the restored row. Everything trashed along with it comes back
+/// too: the links of an artist and its albums left without any artist, the songs and links of an
+/// album. A song can only be restored once its album is out of the trash, and while no other song
+/// took its track.
+#[utoipa::path(
+    context_path = "/trash",
+    tag = "trash",
+    responses(
+        (status = 200, description = "Id of the restored row", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody),
+        (status = 404, description = "Not found in the trash", body = ErrorBody),
+        (status = 409, description = "The album of the song is in the trash or its track is taken", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[post("/<kind>/<id>/restore")]
+pub async fn restore(
+    db: &State<DatabaseConnection>,
+    admin: Admin,
+    kind: SearchKind,
+    id: i32,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+    let user = Some(&admin.0.user);
+
+    match kind {
+        SearchKind::Song => {
+            let song = in_trash(Song::find_trashed_by_id(id).one(db).await?, "Song")?;
+            if Album::find_live_by_id(song.album_id)
+                .one(db)
+                .await?
+                .is_none()
+            {
+                info!("Album of the song is in the trash");
+                return Err(ApiError::Conflict(
+                    "The album of the song is in the trash, restore it first".to_string(),
+                ));
+            }
+            if track_taken(db, &song, None).await? {
+                info!("Track of the song is taken");
+                return Err(ApiError::Conflict(
+                    "Another song took the track of this song, move it first".to_string(),
+                ));
+            }
+
+            let txn = db.begin().await?;
+            trash::restore_song(&txn, user, &song).await?;
+            txn.commit().await?;
+        }
+        SearchKind::Album => {
+            let album = in_trash(Album::find_trashed_by_id(id).one(db).await?, "Album")?;
+
+            let txn = db.begin().await?;
+            trash::restore_album(&txn, user, &album).await?;
+            txn.commit().await?;
+        }
+        SearchKind::Artist => {
+            let artist = in_trash(Artist::find_trashed_by_id(id).one(db).await?, "Artist")?;
+
+            let txn = db.begin().await?;
+            trash::restore_artist(&txn, user, &artist).await?;
+            txn.commit().await?;
+        }
+    }
+
+    info!("Restored {} with id {}", kind.table(), id);
+    Ok((Status::Ok, id.to_string()))
+}
+
+/// Storage keys of the audio files of songs
+fn keys(audio: Vec<song_audio::Model>) -> Vec<String> {
+    audio.into_iter().map(|audio| audio.key).collect()
+}
+
+/// Delete an artist, album or song in the trash for good
+///
+/// This endpoint returns the id of the purged row. The songs of a purged album and the links of a
+/// purged album or artist are deleted with it by the foreign keys, and the cover and audio files
+/// of what is purged are removed from the storage.
+#[utoipa::path(
+    context_path = "/trash",
+    tag = "trash",
+    responses(
+        (status = 200, description = "Id of the purged row", body = String),
+        (status = 401, description = "Not signed in", body = ErrorBody),
+        (status = 403, description = "Admin role required", body = ErrorBody),
+        (status = 404, description = "Not found in the trash", body = ErrorBody)
+    ),
+    security(("session_cookie" = []), ("bearer_token" = []))
+)]
+#[delete("/<kind>/<id>")]
+pub async fn purge(
+    db: &State<DatabaseConnection>,
+    storage: &State<Box<dyn Storage>>,
+    admin: Admin,
+    kind: SearchKind,
+    id: i32,
+) -> Result<(Status, String), ApiError> {
+    let db = db as &DatabaseConnection;
+    let user = Some(&admin.0.user);
+
+    match kind {
+        SearchKind::Song => {
+            let song = in_trash(Song::find_trashed_by_id(id).one(db).await?, "Song")?;
+
+            let txn = db.begin().await?;
+            let audio = SongAudio::find()
+                .filter(song_audio::Column::SongId.eq(id))
+                .all(&txn)
+                .await?;
+            Song::delete_by_id(id).exec(&txn).await?;
+            audit::deleted(&txn, user, id, &song).await?;
+            txn.commit().await?;
+
+            remove_audio_files(storage.inner().as_ref(), &keys(audio)).await;
+        }
+        SearchKind::Album => {
+            let album = in_trash(Album::find_trashed_by_id(id).one(db).await?, "Album")?;
+
+            let txn = db.begin().await?;
+            let cover = AlbumCover::find()
+                .filter(album_cover::Column::AlbumId.eq(id))
+                .one(&txn)
+                .await?;
+            // The songs of the album go with it, trashed or not
+            let audio = SongAudio::find()
+                .join(JoinType::InnerJoin, song_audio::Relation::Song.def())
+                .filter(song::Column::AlbumId.eq(id))
+                .all(&txn)
+                .await?;
+            Album::delete_by_id(id).exec(&txn).await?;
+            audit::deleted(&txn, user, id, &album).await?;
+            txn.commit().await?;
+
+            if let Some(cover) = cover {
+                remove_cover_files(storage.inner().as_ref(), &cover.key).await;
+            }
+            remove_audio_files(storage.inner().as_ref(), &keys(audio)).await;
+        }
+        SearchKind::Artist => {
+            let artist = in_trash(Artist::find_trashed_by_id(id).one(db).await?, "Artist")?;
+
+            let txn = db.begin().await?;
+            Artist::delete_by_id(id).exec(&txn).await?;
+            audit::deleted(&txn, user, id, &artist).await?;
+            txn.commit().await?;
+        }
+    }
+
+    info!("Purged {} with id {}", kind.table(), id);
+    Ok((Status::Ok, id.to_string()))
+}
@@ -0,0 +1,75 @@
+//! Rows forming a tree by pointing to their parent, such as genres and labels
+//!
+//! Moving a row under another is checked against every row of the tree, read in the same
+//! transaction: the parent must exist, and must not be the row itself or one of the rows under it.
+
+use entities::{genre, label};
+
+use super::error::FieldError;
+
+/// A row of a tree
+pub trait Node {
+    fn id(&self) -> i32;
+    fn parent_id(&self) -> Option<i32>;
+}
+
+impl Node for genre::Model {
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+}
+
+impl Node for label::Model {
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+}
+
+/// Ids of the row and of all of the rows under it, at any depth
+pub fn with_descendants<N: Node>(nodes: &[N], id: i32) -> Vec<i32> {
+    let mut ids = vec![id];
+    let mut next = 0;
+    while let Some(&parent_id) = ids.get(next) {
+        ids.extend(
+            nodes
+                .iter()
+                .filter(|node| node.parent_id() == Some(parent_id) && !ids.contains(&node.id()))
+                .map(|node| node.id())
+                .collect::<Vec<_>>(),
+        );
+        next += 1;
+    }
+    ids
+}
+
+/// Check the parent given to the row with the given id, or to a new one when there is none
+///
+/// `nodes` holds every row of the tree. They should stay locked until the end of the transaction
+/// when a row is moved, so that two rows moved at the same time cannot end up under each other.
+/// `descendants` names the row and the rows under it in the error.
+pub fn check_parent<N: Node>(
+    nodes: &[N],
+    id: Option<i32>,
+    parent_id: Option<i32>,
+    descendants: &str,
+) -> Option<FieldError> {
+    let parent_id = parent_id?;
+    if !nodes.iter().any(|node| node.id() == parent_id) {
+        Some(FieldError::new("parent_id", "does not exist"))
+    } else if id.is_some_and(|id| with_descendants(nodes, id).contains(&parent_id)) {
+        Some(FieldError::new(
+            "parent_id",
+            format!("must not be {}", descendants),
+        ))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,91 @@
+//! Optimistic concurrency through the versions of the entities
+//!
+//! Every artist, album and song has a `version`, bumped by each change and sent as the `ETag` of
+//! the responses returning it. The routes changing an entity require the version they were based
+//! on in `If-Match`, so that an editor never silently overwrites the changes of another.
+
+use std::convert::Infallible;
+
+use entities::etag::{etag, ANY};
+use rocket::{
+    request::{self, FromRequest},
+    response::{self, Responder},
+    Request, Response,
+};
+use sea_orm::DbErr;
+
+use super::error::ApiError;
+
+/// Version of a newly inserted row, the default of the `version` columns
+pub const INITIAL_VERSION: i32 = 1;
+
+/// The `If-Match` header of a request
+///
+/// Use it as a request guard, and [`IfMatch::check`] it against the stored version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IfMatch(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IfMatch {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(IfMatch(
+            request.headers().get_one("If-Match").map(str::to_string),
+        ))
+    }
+}
+
+impl IfMatch {
+    /// Check that the header matches the given version of `what`, or `*`
+    ///
+    /// Fails with 428 Precondition Required without the header, and with 412 Precondition Failed
+    /// if none of its entity tags match.
+    pub fn check(&self, what: &str, version: i32) -> Result<(), ApiError> {
+        let Some(header) = &self.0 else {
+            info!("Missing If-Match header");
+            return Err(ApiError::PreconditionRequired(
+                "If-Match header required".to_string(),
+            ));
+        };
+
+        let current = etag(version);
+        if header
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == ANY || tag == current)
+        {
+            Ok(())
+        } else {
+            info!("Stale If-Match header {}, current is {}", header, current);
+            Err(changed(what))
+        }
+    }
+}
+
+/// 412 error of an entity changed by someone else
+fn changed(what: &str) -> ApiError {
+    ApiError::PreconditionFailed(format!("{} was changed by someone else", what))
+}
+
+/// Turn the error of an update filtered on the version into a 412 if no row matched
+///
+/// The version is checked again by the update itself, in case another one slipped in between.
+pub fn stale(what: &str) -> impl Fn(DbErr) -> ApiError + '_ {
+    move |err| match err {
+        DbErr::RecordNotFound(_) => changed(what),
+        err => err.into(),
+    }
+}
+
+/// A response sending the given version as its `ETag`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithETag<R>(pub i32, pub R);
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for WithETag<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        Response::build_from(self.1.respond_to(request)?)
+            .raw_header("ETag", etag(self.0))
+            .ok()
+    }
+}
@@ -0,0 +1,106 @@
+//! Subcommands run instead of the server, such as `backend import discography.csv`
+
+use std::{fs, path::Path};
+
+use rocket::{
+    serde::json::serde_json::to_string_pretty,
+    tokio::{fs::File, io::BufReader},
+};
+use sea_orm::DatabaseConnection;
+
+use crate::api::{
+    export,
+    import::{self, ImportFormat},
+};
+
+const USAGE: &str =
+    "Usage: backend [import <file.csv|file.json> [--dry-run] | restore <file.ndjson>]";
+
+/// Run the subcommand given by `args`, returning the exit code of the process
+pub async fn run(db: &DatabaseConnection, args: &[String]) -> i32 {
+    match args {
+        [command, rest @ ..] if command == "import" => match rest {
+            [file] => import_file(db, Path::new(file), false).await,
+            [file, flag] if flag == "--dry-run" => import_file(db, Path::new(file), true).await,
+            _ => usage(),
+        },
+        [command, file] if command == "restore" => restore_file(db, Path::new(file)).await,
+        _ => usage(),
+    }
+}
+
+fn usage() -> i32 {
+    eprintln!("{}", USAGE);
+    2
+}
+
+/// Import a CSV or JSON document, depending on its extension, and print the report
+async fn import_file(db: &DatabaseConnection, path: &Path, dry_run: bool) -> i32 {
+    let format = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => ImportFormat::Csv,
+        Some("json") => ImportFormat::Json,
+        _ => {
+            eprintln!("Expected a .csv or .json file");
+            return usage();
+        }
+    };
+
+    let document = match fs::read_to_string(path) {
+        Ok(document) => document,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path.display(), err);
+            return 1;
+        }
+    };
+    let rows = match import::parse(format, &document) {
+        Ok(rows) => rows,
+        Err(err) => {
+            eprintln!("{}", err.body().message);
+            return 1;
+        }
+    };
+
+    // There is no signed in user, the audit log records the changes without one
+    let report = match import::import(db, None, rows, dry_run).await {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("Import failed: {}", err);
+            return 1;
+        }
+    };
+
+    println!(
+        "{}",
+        to_string_pretty(&report).expect("Failed to serialize the report")
+    );
+    if report.has_rejections() {
+        1
+    } else {
+        0
+    }
+}
+
+/// Load an NDJSON dump into the empty database and print the number of rows restored
+async fn restore_file(db: &DatabaseConnection, path: &Path) -> i32 {
+    let dump = match File::open(path).await {
+        Ok(dump) => dump,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path.display(), err);
+            return 1;
+        }
+    };
+
+    match export::restore(db, BufReader::new(dump), u64::MAX).await {
+        Ok(restored) => {
+            println!(
+                "{}",
+                to_string_pretty(&restored).expect("Failed to serialize the counts")
+            );
+            0
+        }
+        Err(err) => {
+            eprintln!("Restore failed: {}", err.body().message);
+            1
+        }
+    }
+}
@@ -1,17 +1,23 @@
 pub mod api;
+mod cli;
 mod migrator;
 // pub mod entities;
 
-// #[cfg(test)] mod tests;
+#[cfg(test)]
+mod tests;
 
 #[macro_use]
 extern crate rocket;
 
 use dotenv::dotenv;
-use rocket::Config;
+use api::storage::{LocalStorage, Storage};
+use rocket::{
+    data::{Limits, ToByteUnit},
+    Build, Config, Rocket,
+};
 use sea_orm::{Database, DatabaseConnection};
 use sea_orm_migration::prelude::{MigratorTrait, SchemaManager};
-use std::{env, net::Ipv4Addr};
+use std::{env, net::Ipv4Addr, process};
 
 pub async fn set_up_db() -> DatabaseConnection {
     let url = match env::var("POSTGRES_URL") {
@@ -23,35 +29,28 @@ pub async fn set_up_db() -> DatabaseConnection {
         .expect("Error connecting to database")
 }
 
-#[launch]
-async fn rocket() -> _ {
-    dotenv().ok();
-
-    let db = set_up_db().await;
-
-    let schema_manager = SchemaManager::new(&db);
-    migrator::Migrator::up(&db, None).await.unwrap();
-    assert!(schema_manager.has_table("song").await.unwrap());
-    assert!(schema_manager.has_table("album").await.unwrap());
-    assert!(schema_manager.has_table("artist").await.unwrap());
-    assert!(schema_manager.has_table("album_artist_mtm").await.unwrap());
-
-    let config = Config {
-        port: 8000,
-        address: Ipv4Addr::new(0, 0, 0, 0).into(),
-        ..Config::default()
-    };
+/// Store uploaded files in the directory given by `STORAGE_DIR`, `storage` by default
+pub fn set_up_storage() -> Box<dyn Storage> {
+    let root = env::var("STORAGE_DIR").unwrap_or_else(|_| "storage".to_string());
+    Box::new(LocalStorage::new(root))
+}
 
-    rocket::custom(&config)
-        .manage(db)
+/// Mount every route and catcher of the API
+pub fn mount(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket
         .mount(
             "/song",
             routes![
                 api::song_api::create_song,
                 api::song_api::get_song_by_id,
                 api::song_api::update_song,
+                api::song_api::patch_song,
                 api::song_api::delete_song,
                 api::song_api::get_all_songs,
+                api::song_api::get_credits,
+                api::song_api::set_credits,
+                api::song_api::upload_songs,
+                api::song_api::stream_song,
             ],
         )
         .mount(
@@ -60,12 +59,21 @@ async fn rocket() -> _ {
                 api::album_api::create_album,
                 api::album_api::get_album_by_id,
                 api::album_api::update_album,
+                api::album_api::patch_album,
                 api::album_api::delete_album,
                 api::album_api::get_all_albums,
                 api::album_api::add_artist,
                 api::album_api::get_artists,
                 api::album_api::remove_artist,
                 api::album_api::get_songs,
+                api::album_api::reorder_songs,
+                api::album_api::get_genres,
+                api::album_api::set_genres,
+                api::album_api::get_labels,
+                api::album_api::set_labels,
+                api::album_api::upload_cover,
+                api::album_api::get_cover,
+                api::album_api::delete_cover,
             ],
         )
         .mount(
@@ -74,9 +82,168 @@ async fn rocket() -> _ {
                 api::artist_api::create_artist,
                 api::artist_api::get_artist_by_id,
                 api::artist_api::update_artist,
+                api::artist_api::patch_artist,
                 api::artist_api::delete_artist,
                 api::artist_api::get_all_artists,
                 api::artist_api::get_albums,
+                api::artist_api::get_genres,
+                api::artist_api::set_genres,
+                api::artist_api::get_members,
+                api::artist_api::get_appearances,
+            ],
+        )
+        .mount(
+            "/genre",
+            routes![
+                api::genre_api::create_genre,
+                api::genre_api::get_genre_by_id,
+                api::genre_api::update_genre,
+                api::genre_api::delete_genre,
+                api::genre_api::get_all_genres,
+            ],
+        )
+        .mount(
+            "/label",
+            routes![
+                api::label_api::create_label,
+                api::label_api::get_label_by_id,
+                api::label_api::update_label,
+                api::label_api::delete_label,
+                api::label_api::get_all_labels,
+                api::label_api::get_releases,
+            ],
+        )
+        .mount(
+            "/person",
+            routes![
+                api::person_api::create_person,
+                api::person_api::get_person_by_id,
+                api::person_api::update_person,
+                api::person_api::delete_person,
+                api::person_api::get_all_persons,
+                api::person_api::get_bands,
+                api::person_api::create_membership,
+                api::person_api::update_membership,
+                api::person_api::delete_membership,
+            ],
+        )
+        .mount(
+            "/playlist",
+            routes![
+                api::playlist_api::create_playlist,
+                api::playlist_api::get_playlist_by_id,
+                api::playlist_api::update_playlist,
+                api::playlist_api::delete_playlist,
+                api::playlist_api::get_all_playlists,
+                api::playlist_api::get_playlist_songs,
+                api::playlist_api::add_song,
+                api::playlist_api::move_song,
+                api::playlist_api::remove_song,
+            ],
+        )
+        .mount(
+            "/review",
+            routes![
+                api::review_api::create_album_review,
+                api::review_api::create_song_review,
+                api::review_api::get_album_reviews,
+                api::review_api::get_song_reviews,
+                api::review_api::update_review,
+                api::review_api::delete_review,
             ],
         )
+        .mount("/search", routes![api::search_api::search])
+        .mount(
+            "/auth",
+            routes![
+                api::auth_api::register,
+                api::auth_api::login,
+                api::auth_api::logout,
+                api::auth_api::me,
+            ],
+        )
+        .mount(
+            "/admin",
+            routes![api::admin_api::get_users, api::admin_api::set_role],
+        )
+        .mount("/audit", routes![api::audit_api::get_audit_log])
+        .mount(
+            "/trash",
+            routes![
+                api::trash_api::get_trash,
+                api::trash_api::restore,
+                api::trash_api::purge,
+            ],
+        )
+        .mount("/import", routes![api::import_api::import_rows])
+        .mount(
+            "/export",
+            routes![
+                api::export_api::export_json,
+                api::export_api::export_ndjson,
+                api::export_api::export_csv,
+                api::export_api::restore,
+            ],
+        )
+        .register(
+            "/",
+            catchers![
+                api::error::unauthorized,
+                api::error::forbidden,
+                api::error::not_found,
+                api::error::unprocessable_entity,
+                api::error::internal_error,
+                api::error::default_catcher,
+            ],
+        )
+        .mount("/", routes![api::openapi::openapi_json, api::openapi::docs])
+}
+
+#[rocket::main]
+async fn main() {
+    dotenv().ok();
+
+    let db = set_up_db().await;
+
+    let schema_manager = SchemaManager::new(&db);
+    migrator::Migrator::up(&db, None).await.unwrap();
+    assert!(schema_manager.has_table("song").await.unwrap());
+    assert!(schema_manager.has_table("album").await.unwrap());
+    assert!(schema_manager.has_table("artist").await.unwrap());
+    assert!(schema_manager.has_table("album_artist_mtm").await.unwrap());
+    assert!(schema_manager.has_table("user").await.unwrap());
+    assert!(schema_manager.has_table("session").await.unwrap());
+    assert!(schema_manager.has_table("audit_log").await.unwrap());
+    assert!(schema_manager.has_table("playlist").await.unwrap());
+    assert!(schema_manager.has_table("playlist_song").await.unwrap());
+    assert!(schema_manager.has_table("album_cover").await.unwrap());
+    assert!(schema_manager.has_table("song_audio").await.unwrap());
+    assert!(schema_manager.has_table("genre").await.unwrap());
+    assert!(schema_manager.has_table("artist_genre").await.unwrap());
+    assert!(schema_manager.has_table("album_genre").await.unwrap());
+    assert!(schema_manager.has_table("person").await.unwrap());
+    assert!(schema_manager.has_table("artist_membership").await.unwrap());
+    assert!(schema_manager.has_table("label").await.unwrap());
+    assert!(schema_manager.has_table("album_label").await.unwrap());
+    assert!(schema_manager.has_table("song_credit").await.unwrap());
+    assert!(schema_manager.has_table("review").await.unwrap());
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        process::exit(cli::run(&db, &args).await);
+    }
+
+    let config = Config {
+        port: 8000,
+        address: Ipv4Addr::new(0, 0, 0, 0).into(),
+        limits: Limits::default()
+            .limit("file", 100.mebibytes())
+            .limit("data-form", 500.mebibytes()),
+        ..Config::default()
+    };
+
+    let _ = mount(rocket::custom(&config).manage(db).manage(set_up_storage()))
+        .launch()
+        .await
+        .expect("Failed to launch the server");
 }
@@ -66,6 +66,7 @@ impl MigrationTrait for Migration {
 }
 
 #[derive(Iden)]
+#[allow(clippy::enum_variant_names)]
 pub enum Genre {
     Genre,
     Metal,
@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+/// Tables whose `name` column is indexed for full-text search
+const SEARCHED_TABLES: [&str; 3] = ["artist", "album", "song"];
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000005_create_search_indexes"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sea-query cannot express indexes on expressions, so these are written by hand.
+        // The expression must match the one used by the search endpoint for the index to be used.
+        for table in SEARCHED_TABLES {
+            let sql = format!(
+                r#"CREATE INDEX IF NOT EXISTS "idx-{table}-name-fts" ON "{table}" USING GIN (to_tsvector('simple', "name"))"#
+            );
+            manager
+                .get_connection()
+                .execute(Statement::from_string(manager.get_database_backend(), sql))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in SEARCHED_TABLES {
+            let sql = format!(r#"DROP INDEX IF EXISTS "idx-{table}-name-fts""#);
+            manager
+                .get_connection()
+                .execute(Statement::from_string(manager.get_database_backend(), sql))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000008_add_user_role"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(Role::Role)
+                    .values([Role::Viewer, Role::Editor, Role::Admin])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::Role)
+                            .enumeration(Role::Role, vec![Role::Viewer, Role::Editor, Role::Admin])
+                            .not_null()
+                            .default("viewer"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Someone has to be able to hand out roles: the oldest account becomes an admin
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                r#"UPDATE "user" SET "role" = 'admin' WHERE "id" = (SELECT MIN("id") FROM "user")"#
+                    .to_string(),
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::Role)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(Type::drop().if_exists().name(Role::Role).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[allow(clippy::enum_variant_names)]
+pub enum Role {
+    Role,
+    Viewer,
+    Editor,
+    Admin,
+}
+
+#[derive(Iden)]
+pub enum User {
+    Table,
+    Role,
+}
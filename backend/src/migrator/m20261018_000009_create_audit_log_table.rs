@@ -0,0 +1,132 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+use super::m20261018_000006_create_user_table::User;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000009_create_audit_log_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(AuditAction::AuditAction)
+                    .values([
+                        AuditAction::Create,
+                        AuditAction::Update,
+                        AuditAction::Delete,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .col(
+                        ColumnDef::new(AuditLog::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AuditLog::EntityType).string().not_null())
+                    .col(ColumnDef::new(AuditLog::EntityId).integer().not_null())
+                    .col(
+                        ColumnDef::new(AuditLog::Action)
+                            .enumeration(
+                                AuditAction::AuditAction,
+                                vec![
+                                    AuditAction::Create,
+                                    AuditAction::Update,
+                                    AuditAction::Delete,
+                                ],
+                            )
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AuditLog::Before).json_binary())
+                    .col(ColumnDef::new(AuditLog::After).json_binary())
+                    .col(
+                        ColumnDef::new(AuditLog::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(AuditLog::UserId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-audit_log-user_id")
+                            .from(AuditLog::Table, AuditLog::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The log is filtered by entity and browsed from the newest entries
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-audit_log-entity")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::EntityType)
+                    .col(AuditLog::EntityId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-audit_log-created_at")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().if_exists().table(AuditLog::Table).to_owned())
+            .await?;
+        manager
+            .drop_type(
+                Type::drop()
+                    .if_exists()
+                    .name(AuditAction::AuditAction)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[allow(clippy::enum_variant_names)]
+pub enum AuditAction {
+    AuditAction,
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Iden)]
+pub enum AuditLog {
+    Table,
+    Id,
+    EntityType,
+    EntityId,
+    Action,
+    Before,
+    After,
+    CreatedAt,
+    UserId,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000010_add_deleted_at"
+    }
+}
+
+/// Tables whose rows are moved to the trash instead of being deleted
+const TABLES: [Trashable; 4] = [
+    Trashable::Song,
+    Trashable::Album,
+    Trashable::Artist,
+    Trashable::AlbumArtistMtm,
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in TABLES {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(ColumnDef::new(Trashable::DeletedAt).timestamp().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in TABLES {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Trashable::DeletedAt)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Iden, Clone, Copy)]
+pub enum Trashable {
+    Song,
+    Album,
+    Artist,
+    AlbumArtistMtm,
+    DeletedAt,
+}
@@ -0,0 +1,120 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20230109_000002_create_song_table::Song;
+use super::m20261018_000006_create_user_table::User;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000012_create_playlist_tables"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Playlist::Table)
+                    .col(
+                        ColumnDef::new(Playlist::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Playlist::Name).string().not_null())
+                    .col(ColumnDef::new(Playlist::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-playlist-user_id")
+                            .from(Playlist::Table, Playlist::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PlaylistSong::Table)
+                    .col(
+                        ColumnDef::new(PlaylistSong::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PlaylistSong::PlaylistId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PlaylistSong::SongId).integer().not_null())
+                    .col(ColumnDef::new(PlaylistSong::Position).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-playlist_song-playlist_id")
+                            .from(PlaylistSong::Table, PlaylistSong::PlaylistId)
+                            .to(Playlist::Table, Playlist::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-playlist_song-song_id")
+                            .from(PlaylistSong::Table, PlaylistSong::SongId)
+                            .to(Song::Table, Song::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Not unique, as moving songs shifts the positions of the others one row at a time
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-playlist_song-position")
+                    .table(PlaylistSong::Table)
+                    .col(PlaylistSong::PlaylistId)
+                    .col(PlaylistSong::Position)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(PlaylistSong::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().if_exists().table(Playlist::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Playlist {
+    Table,
+    Id,
+    Name,
+    UserId,
+}
+
+#[derive(Iden)]
+pub enum PlaylistSong {
+    Table,
+    Id,
+    PlaylistId,
+    SongId,
+    Position,
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+use super::m20230109_000002_create_song_table::Song;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000013_add_track_numbers"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Song::Table)
+                    .add_column(
+                        ColumnDef::new(Track::DiscNumber)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .add_column(
+                        ColumnDef::new(Track::TrackNumber)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing songs are numbered on the first disc of their album, in the order they were added
+        let statements = [
+            r#"UPDATE "song" SET "track_number" = "numbered"."track_number" FROM (SELECT "id", ROW_NUMBER() OVER (PARTITION BY "album_id" ORDER BY "id") AS "track_number" FROM "song") AS "numbered" WHERE "song"."id" = "numbered"."id""#,
+            r#"ALTER TABLE "song" ALTER COLUMN "track_number" DROP DEFAULT"#,
+            // Songs in the trash keep their number, and give it up to the songs added in the meantime
+            r#"CREATE UNIQUE INDEX "idx-song-track" ON "song" ("album_id", "disc_number", "track_number") WHERE "deleted_at" IS NULL"#,
+        ];
+        for sql in statements {
+            manager
+                .get_connection()
+                .execute(Statement::from_string(
+                    manager.get_database_backend(),
+                    sql.to_string(),
+                ))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                r#"DROP INDEX IF EXISTS "idx-song-track""#.to_string(),
+            ))
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Song::Table)
+                    .drop_column(Track::DiscNumber)
+                    .drop_column(Track::TrackNumber)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Track {
+    DiscNumber,
+    TrackNumber,
+}
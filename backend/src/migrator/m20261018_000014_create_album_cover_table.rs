@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20230109_000001_create_album_table::Album;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000014_create_album_cover_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AlbumCover::Table)
+                    .col(
+                        ColumnDef::new(AlbumCover::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AlbumCover::AlbumId)
+                            .integer()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(AlbumCover::Key).string().not_null())
+                    .col(ColumnDef::new(AlbumCover::ContentType).string().not_null())
+                    .col(ColumnDef::new(AlbumCover::Width).integer().not_null())
+                    .col(ColumnDef::new(AlbumCover::Height).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-album_cover-album_id")
+                            .from(AlbumCover::Table, AlbumCover::AlbumId)
+                            .to(Album::Table, Album::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(AlbumCover::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum AlbumCover {
+    Table,
+    Id,
+    AlbumId,
+    Key,
+    ContentType,
+    Width,
+    Height,
+}
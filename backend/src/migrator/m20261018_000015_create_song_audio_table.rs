@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20230109_000002_create_song_table::Song;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000015_create_song_audio_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SongAudio::Table)
+                    .col(
+                        ColumnDef::new(SongAudio::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SongAudio::SongId)
+                            .integer()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(SongAudio::Key).string().not_null())
+                    .col(ColumnDef::new(SongAudio::ContentType).string().not_null())
+                    .col(ColumnDef::new(SongAudio::Size).big_integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-song_audio-song_id")
+                            .from(SongAudio::Table, SongAudio::SongId)
+                            .to(Song::Table, Song::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().if_exists().table(SongAudio::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum SongAudio {
+    Table,
+    Id,
+    SongId,
+    Key,
+    ContentType,
+    Size,
+}
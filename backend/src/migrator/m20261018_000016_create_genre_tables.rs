@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000016_create_genre_tables"
+    }
+}
+
+/// Run raw statements in order, the enum conversion having no schema builder equivalent
+async fn execute(manager: &SchemaManager<'_>, statements: &[&str]) -> Result<(), DbErr> {
+    for sql in statements {
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                sql.to_string(),
+            ))
+            .await?;
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        execute(
+            manager,
+            &[
+                // The table takes the name of the enum, which stays until the artists are moved over
+                r#"ALTER TYPE "genre" RENAME TO "old_genre""#,
+                // Restores insert genres by id, so a parent may come after its subgenres
+                r#"CREATE TABLE "genre" (
+                    "id" serial PRIMARY KEY,
+                    "name" varchar NOT NULL,
+                    "parent_id" integer,
+                    CONSTRAINT "fk-genre-parent_id" FOREIGN KEY ("parent_id") REFERENCES "genre" ("id")
+                        DEFERRABLE INITIALLY IMMEDIATE
+                )"#,
+                r#"CREATE UNIQUE INDEX "idx-genre-name" ON "genre" (LOWER("name"))"#,
+                r#"CREATE TABLE "artist_genre" (
+                    "id" serial PRIMARY KEY,
+                    "artist_id" integer NOT NULL,
+                    "genre_id" integer NOT NULL,
+                    CONSTRAINT "fk-artist_genre-artist_id" FOREIGN KEY ("artist_id") REFERENCES "artist" ("id") ON DELETE CASCADE,
+                    CONSTRAINT "fk-artist_genre-genre_id" FOREIGN KEY ("genre_id") REFERENCES "genre" ("id") ON DELETE CASCADE,
+                    CONSTRAINT "idx-artist_genre-pair" UNIQUE ("artist_id", "genre_id")
+                )"#,
+                r#"CREATE TABLE "album_genre" (
+                    "id" serial PRIMARY KEY,
+                    "album_id" integer NOT NULL,
+                    "genre_id" integer NOT NULL,
+                    CONSTRAINT "fk-album_genre-album_id" FOREIGN KEY ("album_id") REFERENCES "album" ("id") ON DELETE CASCADE,
+                    CONSTRAINT "fk-album_genre-genre_id" FOREIGN KEY ("genre_id") REFERENCES "genre" ("id") ON DELETE CASCADE,
+                    CONSTRAINT "idx-album_genre-pair" UNIQUE ("album_id", "genre_id")
+                )"#,
+                // Every genre of the enum stays available, whether an artist has it or not
+                r#"INSERT INTO "genre" ("name") SELECT INITCAP("value"::text) FROM unnest(enum_range(NULL::"old_genre")) AS "value" ORDER BY 1 ON CONFLICT DO NOTHING"#,
+                r#"INSERT INTO "artist_genre" ("artist_id", "genre_id") SELECT "artist"."id", "genre"."id" FROM "artist" JOIN "genre" ON "genre"."name" = INITCAP("artist"."genre"::text) ORDER BY "artist"."id""#,
+                r#"ALTER TABLE "artist" DROP COLUMN "genre""#,
+                r#"DROP TYPE "old_genre""#,
+            ],
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        execute(
+            manager,
+            &[
+                r#"CREATE TYPE "old_genre" AS ENUM ('metal', 'classical', 'rock', 'pop', 'jazz')"#,
+                r#"ALTER TABLE "artist" ADD COLUMN "genre" "old_genre""#,
+                // An artist keeps the first of its genres the enum has, the others are lost
+                r#"UPDATE "artist" SET "genre" = (
+                    SELECT LOWER("genre"."name")::"old_genre" FROM "artist_genre"
+                    JOIN "genre" ON "genre"."id" = "artist_genre"."genre_id"
+                    WHERE "artist_genre"."artist_id" = "artist"."id"
+                        AND LOWER("genre"."name") = ANY(enum_range(NULL::"old_genre")::text[])
+                    ORDER BY "artist_genre"."id" LIMIT 1
+                )"#,
+                r#"DROP TABLE IF EXISTS "album_genre""#,
+                r#"DROP TABLE IF EXISTS "artist_genre""#,
+                r#"DROP TABLE IF EXISTS "genre""#,
+                r#"ALTER TYPE "old_genre" RENAME TO "genre""#,
+            ],
+        )
+        .await
+    }
+}
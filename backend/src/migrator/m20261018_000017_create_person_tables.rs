@@ -0,0 +1,128 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20230109_000003_create_artist_table::Artist;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000017_create_person_tables"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Person::Table)
+                    .col(
+                        ColumnDef::new(Person::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Person::Name).string().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ArtistMembership::Table)
+                    .col(
+                        ColumnDef::new(ArtistMembership::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ArtistMembership::ArtistId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ArtistMembership::PersonId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ArtistMembership::Role).string().not_null())
+                    .col(ColumnDef::new(ArtistMembership::Instrument).string())
+                    .col(ColumnDef::new(ArtistMembership::StartDate).date())
+                    .col(ColumnDef::new(ArtistMembership::EndDate).date())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-artist_membership-artist_id")
+                            .from(ArtistMembership::Table, ArtistMembership::ArtistId)
+                            .to(Artist::Table, Artist::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-artist_membership-person_id")
+                            .from(ArtistMembership::Table, ArtistMembership::PersonId)
+                            .to(Person::Table, Person::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Not unique, as a person can leave an artist and join it again later
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-artist_membership-artist_id")
+                    .table(ArtistMembership::Table)
+                    .col(ArtistMembership::ArtistId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-artist_membership-person_id")
+                    .table(ArtistMembership::Table)
+                    .col(ArtistMembership::PersonId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(ArtistMembership::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().if_exists().table(Person::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Person {
+    Table,
+    Id,
+    Name,
+}
+
+#[derive(Iden)]
+pub enum ArtistMembership {
+    Table,
+    Id,
+    ArtistId,
+    PersonId,
+    Role,
+    Instrument,
+    StartDate,
+    EndDate,
+}
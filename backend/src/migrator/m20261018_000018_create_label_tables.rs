@@ -0,0 +1,133 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+use super::m20230109_000001_create_album_table::Album;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000018_create_label_tables"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Label::Table)
+                    .col(
+                        ColumnDef::new(Label::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Label::Name).string().not_null())
+                    .col(ColumnDef::new(Label::Country).char_len(2))
+                    .col(ColumnDef::new(Label::Founded).date())
+                    .col(ColumnDef::new(Label::ParentId).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Restores insert labels by id, so a parent may come after its subsidiaries, which the
+        // schema builder cannot declare
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                r#"ALTER TABLE "label" ADD CONSTRAINT "fk-label-parent_id" FOREIGN KEY ("parent_id") REFERENCES "label" ("id") DEFERRABLE INITIALLY IMMEDIATE"#
+                    .to_string(),
+            ))
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AlbumLabel::Table)
+                    .col(
+                        ColumnDef::new(AlbumLabel::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AlbumLabel::AlbumId).integer().not_null())
+                    .col(ColumnDef::new(AlbumLabel::LabelId).integer().not_null())
+                    .col(ColumnDef::new(AlbumLabel::CatalogNumber).string())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-album_label-album_id")
+                            .from(AlbumLabel::Table, AlbumLabel::AlbumId)
+                            .to(Album::Table, Album::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-album_label-label_id")
+                            .from(AlbumLabel::Table, AlbumLabel::LabelId)
+                            .to(Label::Table, Label::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-album_label-pair")
+                    .table(AlbumLabel::Table)
+                    .col(AlbumLabel::AlbumId)
+                    .col(AlbumLabel::LabelId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-album_label-label_id")
+                    .table(AlbumLabel::Table)
+                    .col(AlbumLabel::LabelId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(AlbumLabel::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().if_exists().table(Label::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Label {
+    Table,
+    Id,
+    Name,
+    Country,
+    Founded,
+    ParentId,
+}
+
+#[derive(Iden)]
+pub enum AlbumLabel {
+    Table,
+    Id,
+    AlbumId,
+    LabelId,
+    CatalogNumber,
+}
@@ -0,0 +1,141 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+use super::m20230109_000002_create_song_table::Song;
+use super::m20230109_000003_create_artist_table::Artist;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000019_create_song_credit_table"
+    }
+}
+
+fn roles() -> Vec<CreditRole> {
+    vec![
+        CreditRole::Main,
+        CreditRole::Featured,
+        CreditRole::Composer,
+        CreditRole::Lyricist,
+        CreditRole::Producer,
+        CreditRole::Remixer,
+    ]
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(CreditRole::CreditRole)
+                    .values(roles())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SongCredit::Table)
+                    .col(
+                        ColumnDef::new(SongCredit::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SongCredit::SongId).integer().not_null())
+                    .col(ColumnDef::new(SongCredit::ArtistId).integer().not_null())
+                    .col(
+                        ColumnDef::new(SongCredit::Role)
+                            .enumeration(CreditRole::CreditRole, roles())
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SongCredit::Position).integer().not_null())
+                    .col(ColumnDef::new(SongCredit::JoinPhrase).string())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-song_credit-song_id")
+                            .from(SongCredit::Table, SongCredit::SongId)
+                            .to(Song::Table, Song::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-song_credit-artist_id")
+                            .from(SongCredit::Table, SongCredit::ArtistId)
+                            .to(Artist::Table, Artist::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // An artist can have several roles on a song, but each of them only once
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-song_credit-role")
+                    .table(SongCredit::Table)
+                    .col(SongCredit::SongId)
+                    .col(SongCredit::ArtistId)
+                    .col(SongCredit::Role)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-song_credit-artist_id")
+                    .table(SongCredit::Table)
+                    .col(SongCredit::ArtistId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(SongCredit::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(
+                Type::drop()
+                    .if_exists()
+                    .name(CreditRole::CreditRole)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[allow(clippy::enum_variant_names)]
+pub enum CreditRole {
+    CreditRole,
+    Main,
+    Featured,
+    Composer,
+    Lyricist,
+    Producer,
+    Remixer,
+}
+
+#[derive(Iden)]
+pub enum SongCredit {
+    Table,
+    Id,
+    SongId,
+    ArtistId,
+    Role,
+    Position,
+    JoinPhrase,
+}
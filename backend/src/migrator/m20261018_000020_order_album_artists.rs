@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+use super::m20230109_000004_create_album_artist_mtm::AlbumArtistMtm;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000020_order_album_artists"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Of the links between the same album and artist, the oldest one out of the trash is kept
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                r#"DELETE FROM "album_artist_mtm" WHERE "id" IN (SELECT "id" FROM (SELECT "id", ROW_NUMBER() OVER (PARTITION BY "album_id", "artist_id" ORDER BY "deleted_at" IS NOT NULL, "id") AS "rank" FROM "album_artist_mtm") AS "ranked" WHERE "rank" > 1)"#
+                    .to_string(),
+            ))
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AlbumArtistMtm::Table)
+                    .add_column(
+                        ColumnDef::new(Credit::Position)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing artists are ordered in the order they were added to the album
+        let statements = [
+            r#"UPDATE "album_artist_mtm" SET "position" = "ordered"."position" FROM (SELECT "id", ROW_NUMBER() OVER (PARTITION BY "album_id" ORDER BY "id") - 1 AS "position" FROM "album_artist_mtm") AS "ordered" WHERE "album_artist_mtm"."id" = "ordered"."id""#,
+            r#"ALTER TABLE "album_artist_mtm" ALTER COLUMN "position" DROP DEFAULT"#,
+        ];
+        for sql in statements {
+            manager
+                .get_connection()
+                .execute(Statement::from_string(
+                    manager.get_database_backend(),
+                    sql.to_string(),
+                ))
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-album_artist_mtm-pair")
+                    .table(AlbumArtistMtm::Table)
+                    .col(AlbumArtistMtm::AlbumId)
+                    .col(AlbumArtistMtm::ArtistId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-album_artist_mtm-pair")
+                    .table(AlbumArtistMtm::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AlbumArtistMtm::Table)
+                    .drop_column(Credit::Position)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Credit {
+    Position,
+}
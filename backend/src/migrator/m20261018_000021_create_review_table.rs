@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+use super::m20230109_000001_create_album_table::Album;
+use super::m20230109_000002_create_song_table::Song;
+use super::m20261018_000006_create_user_table::User;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000021_create_review_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Review::Table)
+                    .col(
+                        ColumnDef::new(Review::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Review::UserId).integer().not_null())
+                    .col(ColumnDef::new(Review::AlbumId).integer())
+                    .col(ColumnDef::new(Review::SongId).integer())
+                    .col(ColumnDef::new(Review::Rating).integer().not_null())
+                    .col(ColumnDef::new(Review::Body).text())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-review-user_id")
+                            .from(Review::Table, Review::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-review-album_id")
+                            .from(Review::Table, Review::AlbumId)
+                            .to(Album::Table, Album::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-review-song_id")
+                            .from(Review::Table, Review::SongId)
+                            .to(Song::Table, Song::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Ratings go from 1 to 10, and a review is of either an album or a song, never both
+        let statements = [
+            r#"ALTER TABLE "review" ADD CONSTRAINT "chk-review-rating" CHECK ("rating" BETWEEN 1 AND 10)"#,
+            r#"ALTER TABLE "review" ADD CONSTRAINT "chk-review-subject" CHECK (("album_id" IS NULL) <> ("song_id" IS NULL))"#,
+        ];
+        for sql in statements {
+            manager
+                .get_connection()
+                .execute(Statement::from_string(
+                    manager.get_database_backend(),
+                    sql.to_string(),
+                ))
+                .await?;
+        }
+
+        // Each user reviews an album or a song once, and edits that review afterwards
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-review-album_id")
+                    .table(Review::Table)
+                    .col(Review::AlbumId)
+                    .col(Review::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-review-song_id")
+                    .table(Review::Table)
+                    .col(Review::SongId)
+                    .col(Review::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().if_exists().table(Review::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Review {
+    Table,
+    Id,
+    UserId,
+    AlbumId,
+    SongId,
+    Rating,
+    Body,
+}
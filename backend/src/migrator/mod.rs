@@ -4,6 +4,23 @@ mod m20230109_000001_create_album_table;
 mod m20230109_000002_create_song_table;
 mod m20230109_000003_create_artist_table;
 mod m20230109_000004_create_album_artist_mtm;
+mod m20261018_000005_create_search_indexes;
+mod m20261018_000006_create_user_table;
+mod m20261018_000007_create_session_table;
+mod m20261018_000008_add_user_role;
+mod m20261018_000009_create_audit_log_table;
+mod m20261018_000010_add_deleted_at;
+mod m20261018_000011_add_version;
+mod m20261018_000012_create_playlist_tables;
+mod m20261018_000013_add_track_numbers;
+mod m20261018_000014_create_album_cover_table;
+mod m20261018_000015_create_song_audio_table;
+mod m20261018_000016_create_genre_tables;
+mod m20261018_000017_create_person_tables;
+mod m20261018_000018_create_label_tables;
+mod m20261018_000019_create_song_credit_table;
+mod m20261018_000020_order_album_artists;
+mod m20261018_000021_create_review_table;
 
 
 pub struct Migrator;
@@ -16,6 +33,23 @@ impl MigratorTrait for Migrator {
             Box::new(m20230109_000002_create_song_table::Migration),
             Box::new(m20230109_000003_create_artist_table::Migration),
             Box::new(m20230109_000004_create_album_artist_mtm::Migration),
+            Box::new(m20261018_000005_create_search_indexes::Migration),
+            Box::new(m20261018_000006_create_user_table::Migration),
+            Box::new(m20261018_000007_create_session_table::Migration),
+            Box::new(m20261018_000008_add_user_role::Migration),
+            Box::new(m20261018_000009_create_audit_log_table::Migration),
+            Box::new(m20261018_000010_add_deleted_at::Migration),
+            Box::new(m20261018_000011_add_version::Migration),
+            Box::new(m20261018_000012_create_playlist_tables::Migration),
+            Box::new(m20261018_000013_add_track_numbers::Migration),
+            Box::new(m20261018_000014_create_album_cover_table::Migration),
+            Box::new(m20261018_000015_create_song_audio_table::Migration),
+            Box::new(m20261018_000016_create_genre_tables::Migration),
+            Box::new(m20261018_000017_create_person_tables::Migration),
+            Box::new(m20261018_000018_create_label_tables::Migration),
+            Box::new(m20261018_000019_create_song_credit_table::Migration),
+            Box::new(m20261018_000020_order_album_artists::Migration),
+            Box::new(m20261018_000021_create_review_table::Migration),
         ]
     }
 }
\ No newline at end of file
@@ -0,0 +1,86 @@
+use super::{audited, bearer, signed_in, signed_in_as};
+use crate::api::{
+    admin_api::*,
+    error::{self, ErrorBody},
+};
+use entities::{sea_orm_active_enums::Role, user};
+use rocket::{http::Status, local::blocking::Client, Build, Rocket};
+use sea_orm::{DatabaseBackend, MockDatabase};
+
+fn bob(role: Role) -> user::Model {
+    user::Model {
+        id: 2,
+        username: "bob".to_string(),
+        role,
+        password_hash: String::new(),
+    }
+}
+
+fn setup_backend(db: MockDatabase) -> Rocket<Build> {
+    rocket::build()
+        .manage(db.into_connection())
+        .mount("/admin", routes![get_users, set_role])
+        .register("/", catchers![error::forbidden])
+}
+
+#[test]
+fn admins_list_users() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![bob(Role::Viewer)]]);
+    let client = Client::tracked(setup_backend(db)).unwrap();
+
+    let response = client.get("/admin/user").header(bearer()).dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    let users = response.into_json::<Vec<user::Model>>().unwrap();
+    assert_eq!(users[0].username, "bob");
+    assert_eq!(users[0].role, Role::Viewer);
+}
+
+#[test]
+fn editors_cannot_list_users() {
+    let db = signed_in_as(MockDatabase::new(DatabaseBackend::Postgres), Role::Editor);
+    let client = Client::tracked(setup_backend(db)).unwrap();
+
+    let response = client.get("/admin/user").header(bearer()).dispatch();
+
+    assert_eq!(response.status(), Status::Forbidden);
+    assert_eq!(response.into_json::<ErrorBody>().unwrap().code, "forbidden");
+}
+
+#[test]
+fn admins_set_roles() {
+    let db = audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![bob(Role::Viewer)]])
+            .append_query_results(vec![vec![bob(Role::Editor)]]),
+        1,
+    );
+    let client = Client::tracked(setup_backend(db)).unwrap();
+
+    let response = client
+        .put("/admin/user/2/role")
+        .header(bearer())
+        .body(r#"{"role":"Editor"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.into_json::<user::Model>().unwrap().role,
+        Role::Editor
+    );
+}
+
+#[test]
+fn admins_cannot_change_their_own_role() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres));
+    let client = Client::tracked(setup_backend(db)).unwrap();
+
+    let response = client
+        .put("/admin/user/1/role")
+        .header(bearer())
+        .body(r#"{"role":"Viewer"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Conflict);
+}
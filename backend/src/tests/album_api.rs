@@ -0,0 +1,633 @@
+use super::{
+    audited, awesome_band, bearer, if_match, insert_audit, lorem, plain, signed_in, statement,
+    temp_storage, SharedMock,
+};
+use crate::api::album_api::*;
+use chrono::NaiveDate;
+use entities::{album_artist_mtm, album_cover, artist, song};
+use rocket::{
+    http::{ContentType, Header, Status},
+    local::blocking::Client,
+    serde::json::{json, Value as Json},
+};
+use sea_orm::{DatabaseBackend, DbErr, MockDatabase, Statement, Transaction};
+use std::{fs, io::Cursor};
+
+fn second_band() -> artist::Model {
+    artist::Model {
+        id: 2,
+        ..awesome_band()
+    }
+}
+
+fn link(id: i32, album_id: i32, artist_id: i32, position: i32) -> album_artist_mtm::Model {
+    album_artist_mtm::Model {
+        id,
+        album_id,
+        artist_id,
+        position,
+        deleted_at: None,
+    }
+}
+
+fn insert_album() -> Statement {
+    Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        r#"INSERT INTO "album" ("name", "date_published") VALUES ($1, $2) RETURNING "id""#,
+        vec![
+            "Lorem".into(),
+            NaiveDate::from_ymd_opt(2015, 1, 1).unwrap().into(),
+        ],
+    )
+}
+
+fn insert_link(album_id: i32, artist_id: i32, position: i32) -> Statement {
+    Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        r#"INSERT INTO "album_artist_mtm" ("album_id", "artist_id", "position") VALUES ($1, $2, $3) RETURNING "id""#,
+        vec![album_id.into(), artist_id.into(), position.into()],
+    )
+}
+
+fn select_links(album_id: i32) -> Statement {
+    Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" WHERE "album_artist_mtm"."album_id" = $1"#,
+        vec![album_id.into()],
+    )
+}
+
+#[test]
+fn create_album_commits_album_and_artist_link_together() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![awesome_band()]])
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![vec![link(3, 7, 1, 0)]]),
+        2,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/album", routes![create_album]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/album/1")
+        .header(bearer())
+        .body(r#"{"name":"Lorem","date_published":"2015-01-01"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            insert_album(),
+            insert_link(7, 1, 0),
+            insert_audit(
+                "album",
+                7,
+                "create",
+                None,
+                Some(
+                    json!({"id": 7, "name": "Lorem", "date_published": "2015-01-01", "version": 1, "deleted_at": null})
+                ),
+                Some(1),
+            ),
+            insert_audit(
+                "album_artist_mtm",
+                3,
+                "create",
+                None,
+                Some(
+                    json!({"id": 3, "album_id": 7, "artist_id": 1, "position": 0, "deleted_at": null})
+                ),
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn create_album_rolls_back_when_linking_artist_fails() {
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![awesome_band()]])
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_errors(vec![DbErr::Custom("connection lost".to_string())]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/album", routes![create_album]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/album/1")
+        .header(bearer())
+        .body(r#"{"name":"Lorem","date_published":"2015-01-01"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            insert_album(),
+            insert_link(7, 1, 0),
+            plain("ROLLBACK"),
+        ]))
+    );
+}
+
+#[test]
+fn add_artist_rolls_back_when_one_link_fails() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![vec![awesome_band(), second_band()]])
+            .append_query_results(vec![Vec::<album_artist_mtm::Model>::new()])
+            .append_query_results(vec![vec![link(3, 7, 1, 0)]])
+            .append_query_errors(vec![DbErr::Custom("connection lost".to_string())]),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/album", routes![add_artist]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/album/artist/7")
+        .header(bearer())
+        .body("[1, 2]")
+        .dispatch();
+
+    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(
+        db.transaction_log()[1..],
+        [Transaction::many([
+            plain("BEGIN"),
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" WHERE "album"."id" = $1 AND "album"."deleted_at" IS NULL LIMIT $2 FOR UPDATE"#,
+                vec![7i32.into(), 1u64.into()],
+            ),
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" WHERE "artist"."deleted_at" IS NULL AND "artist"."id" IN ($1, $2)"#,
+                vec![1i32.into(), 2i32.into()],
+            ),
+            select_links(7),
+            insert_link(7, 1, 0),
+            insert_audit(
+                "album_artist_mtm",
+                3,
+                "create",
+                None,
+                Some(
+                    json!({"id": 3, "album_id": 7, "artist_id": 1, "position": 0, "deleted_at": null})
+                ),
+                Some(1),
+            ),
+            insert_link(7, 2, 1),
+            plain("ROLLBACK"),
+        ])]
+    );
+}
+
+#[test]
+fn add_artist_appends_only_the_artists_not_yet_on_the_album() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![vec![awesome_band(), second_band()]])
+            .append_query_results(vec![vec![link(3, 7, 1, 0)]])
+            .append_query_results(vec![vec![link(4, 7, 2, 1)]]),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/album", routes![add_artist]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/album/artist/7")
+        .header(bearer())
+        .body("[2, 1, 2]")
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" WHERE "album"."id" = $1 AND "album"."deleted_at" IS NULL LIMIT $2 FOR UPDATE"#,
+                vec![7i32.into(), 1u64.into()],
+            ),
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" WHERE "artist"."deleted_at" IS NULL AND "artist"."id" IN ($1, $2)"#,
+                vec![2i32.into(), 1i32.into()],
+            ),
+            select_links(7),
+            insert_link(7, 2, 1),
+            insert_audit(
+                "album_artist_mtm",
+                4,
+                "create",
+                None,
+                Some(
+                    json!({"id": 4, "album_id": 7, "artist_id": 2, "position": 1, "deleted_at": null})
+                ),
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn get_artists_lists_the_artists_in_credit_order() {
+    let db = SharedMock::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![vec![second_band(), awesome_band()]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/album", routes![get_artists]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/album/artist/7").dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_json::<Json>().unwrap()[0]["id"], json!(2));
+    assert_eq!(
+        db.transaction_log()[1],
+        Transaction::one(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" INNER JOIN "album_artist_mtm" ON "artist"."id" = "album_artist_mtm"."artist_id" WHERE "artist"."deleted_at" IS NULL AND "album_artist_mtm"."album_id" = $1 AND "album_artist_mtm"."deleted_at" IS NULL ORDER BY "album_artist_mtm"."position" ASC, "album_artist_mtm"."id" ASC"#,
+            vec![7i32.into()],
+        ))
+    );
+}
+
+fn track(id: i32, disc_number: i32, track_number: i32) -> song::Model {
+    song::Model {
+        id,
+        name: format!("Song {}", id),
+        length_secs: 120,
+        album_id: 7,
+        disc_number,
+        track_number,
+        version: 1,
+        deleted_at: None,
+    }
+}
+
+#[test]
+fn reorder_songs_numbers_each_disc_in_the_given_order() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![vec![track(4, 1, 1), track(5, 1, 2), track(6, 2, 1)]])
+            .append_query_results(vec![vec![song::Model {
+                version: 2,
+                ..track(5, 1, 1)
+            }]])
+            .append_query_results(vec![vec![song::Model {
+                version: 2,
+                ..track(4, 1, 2)
+            }]]),
+        3,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/album", routes![reorder_songs]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .put("/album/songs/7")
+        .header(bearer())
+        .body("[6, 5, 4]")
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    let tracklist: Vec<(String, i32, i32)> = response
+        .into_json::<Vec<song::Model>>()
+        .unwrap()
+        .into_iter()
+        .map(|song| (song.name, song.disc_number, song.track_number))
+        .collect();
+    assert_eq!(
+        tracklist,
+        vec![
+            ("Song 5".to_string(), 1, 1),
+            ("Song 4".to_string(), 1, 2),
+            ("Song 6".to_string(), 2, 1),
+        ]
+    );
+    let set_track = |id: i32, track_number: i32| {
+        Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"UPDATE "song" SET "track_number" = $1, "version" = $2 WHERE "song"."id" = $3 RETURNING "id", "name", "length_secs", "album_id", "disc_number", "track_number", "version", "deleted_at""#,
+            vec![track_number.into(), 2i32.into(), id.into()],
+        )
+    };
+    let audit_track = |id: i32, before: i32, after: i32| {
+        let song = |track_number: i32, version: i32| json!({"id": id, "name": format!("Song {}", id), "length_secs": 120, "album_id": 7, "disc_number": 1, "track_number": track_number, "version": version, "deleted_at": null});
+        insert_audit(
+            "song",
+            id,
+            "update",
+            Some(song(before, 1)),
+            Some(song(after, 2)),
+            Some(1),
+        )
+    };
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" WHERE "album"."id" = $1 AND "album"."deleted_at" IS NULL LIMIT $2"#,
+                vec![7i32.into(), 1u64.into()],
+            ),
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."deleted_at" IS NULL AND "song"."album_id" = $1"#,
+                vec![7i32.into()],
+            ),
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"UPDATE "song" SET "track_number" = "track_number" * $1 WHERE "song"."id" IN ($2, $3)"#,
+                vec![(-1i32).into(), 5i32.into(), 4i32.into()],
+            ),
+            set_track(5, 1),
+            audit_track(5, 2, 1),
+            set_track(4, 2),
+            audit_track(4, 1, 2),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn reorder_songs_requires_every_song_once() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![lorem()]])
+        .append_query_results(vec![vec![track(4, 1, 1), track(5, 1, 2)]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/album", routes![reorder_songs]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .put("/album/songs/7")
+        .header(bearer())
+        .body("[4, 4]")
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(
+        response.into_json::<rocket::serde::json::Value>().unwrap()["details"],
+        json!([{"field": "song_ids", "message": "must list every song of the album once"}])
+    );
+}
+
+/// A PNG image of the given size
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut png = Cursor::new(Vec::new());
+    image::RgbImage::new(width, height)
+        .write_to(&mut png, image::ImageFormat::Png)
+        .unwrap();
+    png.into_inner()
+}
+
+/// Body of a form sending `file` in its `file` field, along with its content type
+fn multipart(file: &[u8]) -> (ContentType, Vec<u8>) {
+    let mut body = b"--X\r\nContent-Disposition: form-data; name=\"file\"; filename=\"cover.png\"\r\nContent-Type: image/png\r\n\r\n".to_vec();
+    body.extend_from_slice(file);
+    body.extend_from_slice(b"\r\n--X--\r\n");
+    (
+        ContentType::new("multipart", "form-data").with_params(("boundary", "X")),
+        body,
+    )
+}
+
+fn lorem_cover(key: &str) -> album_cover::Model {
+    album_cover::Model {
+        id: 2,
+        album_id: 7,
+        key: key.to_string(),
+        content_type: "image/png".to_string(),
+        width: 800,
+        height: 400,
+    }
+}
+
+#[test]
+fn upload_cover_stores_the_image_and_its_thumbnails() {
+    let (root, storage) = temp_storage();
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![Vec::<album_cover::Model>::new()])
+            .append_query_results(vec![vec![lorem_cover("k3y")]]),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .manage(storage)
+        .mount("/album", routes![upload_cover]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let image = png(800, 400);
+    let (content_type, body) = multipart(&image);
+    let response = client
+        .post("/album/cover/7")
+        .header(bearer())
+        .header(content_type)
+        .body(body)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    // The key of the cover is random, and names its directory
+    let covers: Vec<_> = fs::read_dir(root.join("covers")).unwrap().collect();
+    assert_eq!(covers.len(), 1);
+    let cover = covers[0].as_ref().unwrap().path();
+    let key = cover.file_name().unwrap().to_str().unwrap();
+    assert_eq!(fs::read(cover.join("original")).unwrap(), image);
+    for (size, pixels) in [("small", 64), ("medium", 256), ("large", 600)] {
+        let thumbnail =
+            image::load_from_memory(&fs::read(cover.join(format!("{}.jpg", size))).unwrap())
+                .unwrap();
+        assert_eq!(
+            (thumbnail.width(), thumbnail.height()),
+            (pixels, pixels / 2)
+        );
+    }
+
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "album_cover"."id", "album_cover"."album_id", "album_cover"."key", "album_cover"."content_type", "album_cover"."width", "album_cover"."height" FROM "album_cover" WHERE "album_cover"."album_id" = $1 LIMIT $2 FOR UPDATE"#,
+                vec![7i32.into(), 1u64.into()],
+            ),
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"INSERT INTO "album_cover" ("album_id", "key", "content_type", "width", "height") VALUES ($1, $2, $3, $4, $5) RETURNING "id", "album_id", "key", "content_type", "width", "height""#,
+                vec![
+                    7i32.into(),
+                    key.into(),
+                    "image/png".into(),
+                    800i32.into(),
+                    400i32.into(),
+                ],
+            ),
+            insert_audit(
+                "album_cover",
+                2,
+                "create",
+                None,
+                Some(
+                    json!({"id": 2, "album_id": 7, "key": "k3y", "content_type": "image/png", "width": 800, "height": 400})
+                ),
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn upload_cover_rejects_other_files() {
+    let (root, storage) = temp_storage();
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![lorem()]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .manage(storage)
+        .mount("/album", routes![upload_cover]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let (content_type, body) = multipart(b"GIF89a not a cover");
+    let response = client
+        .post("/album/cover/7")
+        .header(bearer())
+        .header(content_type)
+        .body(body)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(
+        response.into_json::<rocket::serde::json::Value>().unwrap()["details"],
+        json!([{"field": "file", "message": "must be a JPEG, PNG or WebP image"}])
+    );
+    assert!(!root.exists());
+}
+
+#[test]
+fn get_cover_is_not_sent_again_while_unchanged() {
+    let (root, storage) = temp_storage();
+    fs::create_dir_all(root.join("covers/k3y")).unwrap();
+    fs::write(root.join("covers/k3y/small.jpg"), b"thumbnail").unwrap();
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results(vec![vec![lorem_cover("k3y")]])
+        .append_query_results(vec![vec![lorem_cover("k3y")]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .manage(storage)
+        .mount("/album", routes![get_cover]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/album/cover/7?size=small").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.content_type(), Some(ContentType::JPEG));
+    assert_eq!(response.headers().get_one("ETag"), Some(r#""k3y-small""#));
+    assert_eq!(
+        response.headers().get_one("Cache-Control"),
+        Some("public, max-age=300")
+    );
+    assert_eq!(response.into_bytes(), Some(b"thumbnail".to_vec()));
+
+    let response = client
+        .get("/album/cover/7?size=small")
+        .header(Header::new("If-None-Match", r#""k3y-small""#))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotModified);
+    assert_eq!(response.into_bytes(), None);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn delete_album_records_the_songs_and_links_trashed_with_it() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![vec![track(4, 1, 1)]])
+            .append_query_results(vec![vec![link(3, 7, 1, 0)]]),
+        6,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/album", routes![delete_album]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .delete("/album/7")
+        .header(bearer())
+        .header(if_match(1))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."deleted_at" IS NULL AND "song"."album_id" = $1"#,
+                vec![7i32.into()],
+            ),
+            statement(
+                r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" WHERE "album_artist_mtm"."deleted_at" IS NULL AND "album_artist_mtm"."album_id" = $1"#,
+                vec![7i32.into()],
+            ),
+            statement(
+                r#"UPDATE "album" SET "deleted_at" = CURRENT_TIMESTAMP WHERE "album"."id" = $1"#,
+                vec![7i32.into()],
+            ),
+            insert_audit("album", 7, "delete", Some(json!(lorem())), None, Some(1)),
+            statement(
+                r#"UPDATE "song" SET "deleted_at" = CURRENT_TIMESTAMP WHERE "song"."id" IN ($1)"#,
+                vec![4i32.into()],
+            ),
+            insert_audit("song", 4, "delete", Some(json!(track(4, 1, 1))), None, Some(1)),
+            statement(
+                r#"UPDATE "album_artist_mtm" SET "deleted_at" = CURRENT_TIMESTAMP WHERE "album_artist_mtm"."id" IN ($1)"#,
+                vec![3i32.into()],
+            ),
+            insert_audit(
+                "album_artist_mtm",
+                3,
+                "delete",
+                Some(json!(link(3, 7, 1, 0))),
+                None,
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
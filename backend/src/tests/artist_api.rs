@@ -0,0 +1,221 @@
+use super::{
+    audited, awesome_band, bearer, if_match, insert_audit, plain, signed_in, statement, SharedMock,
+};
+use crate::api::artist_api::*;
+use chrono::NaiveDate;
+use entities::{album, album_artist_mtm, artist_genre, genre, song};
+use rocket::{http::Status, local::blocking::Client, serde::json::json};
+use sea_orm::{DatabaseBackend, DbErr, MockDatabase, MockExecResult, Statement, Transaction};
+
+#[test]
+fn delete_artist_rolls_back_when_trashing_an_orphaned_album_fails() {
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![awesome_band()]])
+            .append_query_results(vec![vec![album_artist_mtm::Model {
+                id: 3,
+                album_id: 7,
+                artist_id: 1,
+                position: 0,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![Vec::<album_artist_mtm::Model>::new()])
+            .append_query_results(vec![vec![album::Model {
+                id: 7,
+                name: "Lorem".to_string(),
+                date_published: NaiveDate::from_ymd_opt(2015, 1, 1).unwrap(),
+                version: 1,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![Vec::<song::Model>::new()])
+            .append_query_results(vec![Vec::<album_artist_mtm::Model>::new()])
+            .append_exec_results(
+                (0..4)
+                    .map(|_| MockExecResult {
+                        last_insert_id: 0,
+                        rows_affected: 1,
+                    })
+                    .collect(),
+            )
+            .append_exec_errors(vec![DbErr::Custom("connection lost".to_string())]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/artist", routes![delete_artist]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .delete("/artist/1")
+        .header(bearer())
+        .header(if_match(1))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(
+        db.transaction_log()[1..],
+        [
+            Transaction::one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" WHERE "artist"."id" = $1 AND "artist"."deleted_at" IS NULL LIMIT $2"#,
+                vec![1i32.into(), 1u64.into()],
+            )),
+            Transaction::many([
+                plain("BEGIN"),
+                statement(
+                    r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" WHERE "album_artist_mtm"."deleted_at" IS NULL AND "album_artist_mtm"."artist_id" = $1"#,
+                    vec![1i32.into()],
+                ),
+                statement(
+                    r#"UPDATE "artist" SET "deleted_at" = CURRENT_TIMESTAMP WHERE "artist"."id" = $1"#,
+                    vec![1i32.into()],
+                ),
+                insert_audit(
+                    "artist",
+                    1,
+                    "delete",
+                    Some(json!({
+                        "id": 1,
+                        "name": "Awesome Band",
+                        "date_formed": "2010-01-01",
+                        "version": 1,
+                        "deleted_at": null
+                    })),
+                    None,
+                    Some(1),
+                ),
+                statement(
+                    r#"UPDATE "album_artist_mtm" SET "deleted_at" = CURRENT_TIMESTAMP WHERE "album_artist_mtm"."id" IN ($1)"#,
+                    vec![3i32.into()],
+                ),
+                insert_audit(
+                    "album_artist_mtm",
+                    3,
+                    "delete",
+                    Some(json!({"id": 3, "album_id": 7, "artist_id": 1, "position": 0, "deleted_at": null})),
+                    None,
+                    Some(1),
+                ),
+                statement(
+                    r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" WHERE "album_artist_mtm"."deleted_at" IS NULL AND "album_artist_mtm"."album_id" = $1"#,
+                    vec![7i32.into()],
+                ),
+                Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" WHERE "album"."id" = $1 AND "album"."deleted_at" IS NULL LIMIT $2"#,
+                    vec![7i32.into(), 1u64.into()],
+                ),
+                statement(
+                    r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."deleted_at" IS NULL AND "song"."album_id" = $1"#,
+                    vec![7i32.into()],
+                ),
+                statement(
+                    r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" WHERE "album_artist_mtm"."deleted_at" IS NULL AND "album_artist_mtm"."album_id" = $1"#,
+                    vec![7i32.into()],
+                ),
+                statement(
+                    r#"UPDATE "album" SET "deleted_at" = CURRENT_TIMESTAMP WHERE "album"."id" = $1"#,
+                    vec![7i32.into()],
+                ),
+                plain("ROLLBACK"),
+            ])
+        ]
+    );
+}
+
+#[test]
+fn set_genres_only_changes_the_links_that_differ() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![awesome_band()]])
+            .append_query_results(vec![vec![genre::Model {
+                id: 3,
+                name: "Metal".to_string(),
+                parent_id: None,
+            }]])
+            .append_query_results(vec![vec![
+                artist_genre::Model {
+                    id: 5,
+                    artist_id: 1,
+                    genre_id: 2,
+                },
+                artist_genre::Model {
+                    id: 6,
+                    artist_id: 1,
+                    genre_id: 3,
+                },
+            ]])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }]),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/artist", routes![set_genres]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .put("/artist/genres/1")
+        .header(bearer())
+        .body("[3, 3]")
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.into_string().unwrap(),
+        r#"[{"id":3,"name":"Metal","parent_id":null}]"#
+    );
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" WHERE "artist"."id" = $1 AND "artist"."deleted_at" IS NULL LIMIT $2 FOR UPDATE"#,
+                vec![1i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "genre"."id", "genre"."name", "genre"."parent_id" FROM "genre" WHERE "genre"."id" IN ($1) ORDER BY "genre"."name" ASC"#,
+                vec![3i32.into()],
+            ),
+            statement(
+                r#"SELECT "artist_genre"."id", "artist_genre"."artist_id", "artist_genre"."genre_id" FROM "artist_genre" WHERE "artist_genre"."artist_id" = $1"#,
+                vec![1i32.into()],
+            ),
+            statement(
+                r#"DELETE FROM "artist_genre" WHERE "artist_genre"."id" = $1"#,
+                vec![5i32.into()],
+            ),
+            insert_audit(
+                "artist_genre",
+                5,
+                "delete",
+                Some(json!({"id": 5, "artist_id": 1, "genre_id": 2})),
+                None,
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn set_genres_rejects_unknown_genres() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![awesome_band()]])
+        .append_query_results(vec![Vec::<genre::Model>::new()])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/artist", routes![set_genres]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .put("/artist/genres/1")
+        .header(bearer())
+        .body("[42]")
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+}
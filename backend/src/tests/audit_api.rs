@@ -0,0 +1,66 @@
+use super::{bearer, signed_in, SharedMock};
+use crate::api::audit_api::*;
+use chrono::NaiveDate;
+use entities::{audit_log, pagination::Page, sea_orm_active_enums::AuditAction};
+use rocket::{http::Status, local::blocking::Client, serde::json::json};
+use sea_orm::{DatabaseBackend, MockDatabase, Transaction, Value};
+use std::collections::BTreeMap;
+
+fn deleted_album() -> audit_log::Model {
+    audit_log::Model {
+        id: 12,
+        entity_type: "album".to_string(),
+        entity_id: 7,
+        action: AuditAction::Delete,
+        before: Some(json!({"id": 7, "name": "Lorem", "date_published": "2015-01-01"})),
+        after: None,
+        created_at: NaiveDate::from_ymd_opt(2026, 1, 31)
+            .unwrap()
+            .and_hms_opt(23, 59, 0)
+            .unwrap(),
+        user_id: Some(1),
+    }
+}
+
+#[test]
+fn audit_log_filters_by_entity_action_and_dates() {
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![BTreeMap::from([(
+                "num_items".to_string(),
+                Value::BigInt(Some(1)),
+            )])]])
+            .append_query_results(vec![vec![deleted_album()]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/audit", routes![get_audit_log]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .get("/audit?entity=album&action=delete&from=2026-01-01&to=2026-01-31")
+        .header(bearer())
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    let page = response.into_json::<Page<audit_log::Model>>().unwrap();
+    assert_eq!(page.items[0].before, deleted_album().before);
+
+    let first_day = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+    let day_after = NaiveDate::from_ymd_opt(2026, 2, 1).unwrap();
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "audit_log"."id", "audit_log"."entity_type", "audit_log"."entity_id", CAST("audit_log"."action" AS text), "audit_log"."before", "audit_log"."after", "audit_log"."created_at", "audit_log"."user_id" FROM "audit_log" WHERE "audit_log"."entity_type" = $1 AND "audit_log"."action" = CAST($2 AS audit_action) AND "audit_log"."created_at" >= $3 AND "audit_log"."created_at" < $4 ORDER BY "audit_log"."created_at" DESC, "audit_log"."id" DESC LIMIT $5 OFFSET $6"#,
+            vec![
+                "album".into(),
+                "delete".into(),
+                first_day.and_hms_opt(0, 0, 0).unwrap().into(),
+                day_after.and_hms_opt(0, 0, 0).unwrap().into(),
+                50u64.into(),
+                0u64.into(),
+            ],
+        ))
+    );
+}
@@ -0,0 +1,232 @@
+use super::{audited, bearer, signed_in, signed_in_as, temp_storage, SharedMock};
+use crate::api::{auth_api::*, error::ErrorBody};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHasher, SaltString},
+    Argon2,
+};
+use chrono::NaiveDate;
+use entities::{auth::SessionToken, sea_orm_active_enums::Role, session, user};
+use rocket::{http::Status, local::blocking::Client};
+use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+
+fn alice(password_hash: &str) -> user::Model {
+    user::Model {
+        id: 1,
+        username: "alice".to_string(),
+        role: Role::Admin,
+        password_hash: password_hash.to_string(),
+    }
+}
+
+#[test]
+fn register_stores_a_password_hash() {
+    let db = SharedMock::new(audited(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            .append_query_results(vec![vec![alice("")]]),
+        2,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/auth", routes![register]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/auth/register")
+        .body(r#"{"username":"alice","password":"correct horse"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    let body = response.into_string().unwrap();
+    assert!(!body.contains("password"));
+
+    let log = format!("{:?}", db.transaction_log());
+    assert!(log.contains("$argon2id$"));
+    assert!(!log.contains("correct horse"));
+}
+
+#[test]
+fn only_the_first_user_becomes_an_admin() {
+    let bob = user::Model {
+        id: 2,
+        username: "bob".to_string(),
+        role: Role::Viewer,
+        password_hash: String::new(),
+    };
+    let db = SharedMock::new(audited(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![alice("")]])
+            .append_query_results(vec![vec![bob]]),
+        2,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/auth", routes![register]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/auth/register")
+        .body(r#"{"username":"bob","password":"correct horse"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    let log = format!("{:?}", db.transaction_log());
+    assert!(log.contains(r#"LOCK TABLE \"user\" IN SHARE ROW EXCLUSIVE MODE"#));
+    assert!(log.contains("viewer"));
+    assert!(!log.contains("admin"));
+}
+
+#[test]
+fn register_rejects_short_passwords() {
+    let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+    let rocket = rocket::build().manage(db).mount("/auth", routes![register]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/auth/register")
+        .body(r#"{"username":"alice","password":"short"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    let body = response.into_json::<ErrorBody>().unwrap();
+    assert_eq!(body.details[0].field, "password");
+}
+
+#[test]
+fn login_opens_a_session() {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(b"correct horse", &salt)
+        .unwrap()
+        .to_string();
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results(vec![vec![alice(&hash)]])
+        .append_exec_results(vec![MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 0,
+        }])
+        .append_query_results(vec![vec![session::Model {
+            id: 1,
+            token: String::new(),
+            user_id: 1,
+            expires_at: NaiveDate::from_ymd_opt(2100, 1, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+        }]])
+        .into_connection();
+    let rocket = rocket::build().manage(db).mount("/auth", routes![login]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/auth/login")
+        .body(r#"{"username":"alice","password":"correct horse"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    let cookie = response
+        .cookies()
+        .get("session")
+        .unwrap()
+        .value()
+        .to_string();
+    let session = response.into_json::<SessionToken>().unwrap();
+    assert_eq!(session.token, cookie);
+    assert_eq!(session.user.username, "alice");
+}
+
+#[test]
+fn login_rejects_a_wrong_password() {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(b"correct horse", &salt)
+        .unwrap()
+        .to_string();
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results(vec![vec![alice(&hash)]])
+        .into_connection();
+    let rocket = rocket::build().manage(db).mount("/auth", routes![login]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/auth/login")
+        .body(r#"{"username":"alice","password":"battery staple"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Unauthorized);
+    assert!(response.cookies().get("session").is_none());
+}
+
+#[test]
+fn me_returns_the_signed_in_user() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres)).into_connection();
+    let rocket = rocket::build().manage(db).mount("/auth", routes![me]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/auth/me").header(bearer()).dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.into_json::<user::Model>().unwrap().username,
+        "alice"
+    );
+}
+
+#[test]
+fn write_routes_require_a_session() {
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results(vec![Vec::<(session::Model, user::Model)>::new()])
+        .into_connection();
+    let client = Client::tracked(crate::mount(
+        rocket::build().manage(db).manage(temp_storage().1),
+    ))
+    .unwrap();
+
+    let anonymous = client.delete("/artist/1").dispatch();
+    assert_eq!(anonymous.status(), Status::Unauthorized);
+    assert_eq!(
+        anonymous.into_json::<ErrorBody>().unwrap().code,
+        "unauthorized"
+    );
+
+    let expired = client
+        .post("/song/")
+        .header(bearer())
+        .body(r#"{"name":"My song","length_secs":120,"album_id":1}"#)
+        .dispatch();
+    assert_eq!(expired.status(), Status::Unauthorized);
+}
+
+#[test]
+fn viewers_cannot_write() {
+    let db =
+        signed_in_as(MockDatabase::new(DatabaseBackend::Postgres), Role::Viewer).into_connection();
+    let client = Client::tracked(crate::mount(
+        rocket::build().manage(db).manage(temp_storage().1),
+    ))
+    .unwrap();
+
+    let response = client
+        .post("/song/")
+        .header(bearer())
+        .body(r#"{"name":"My song","length_secs":120,"album_id":1}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Forbidden);
+    assert_eq!(response.into_json::<ErrorBody>().unwrap().code, "forbidden");
+}
+
+#[test]
+fn only_admins_can_delete() {
+    let db =
+        signed_in_as(MockDatabase::new(DatabaseBackend::Postgres), Role::Editor).into_connection();
+    let client = Client::tracked(crate::mount(
+        rocket::build().manage(db).manage(temp_storage().1),
+    ))
+    .unwrap();
+
+    let response = client.delete("/artist/1").header(bearer()).dispatch();
+
+    assert_eq!(response.status(), Status::Forbidden);
+    assert_eq!(response.into_json::<ErrorBody>().unwrap().code, "forbidden");
+}
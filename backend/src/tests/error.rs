@@ -0,0 +1,95 @@
+use super::{bearer, signed_in};
+use crate::api::{
+    error::{self, ErrorBody, FieldError},
+    song_api::*,
+};
+use entities::{album, song};
+use rocket::{http::Status, local::blocking::Client, Build, Rocket};
+use sea_orm::{DatabaseBackend, DbErr, MockDatabase};
+
+fn setup_backend(db: MockDatabase) -> Rocket<Build> {
+    rocket::build()
+        .manage(db.into_connection())
+        .mount("/song", routes![create_song, get_song_by_id])
+        .register(
+            "/",
+            catchers![
+                error::not_found,
+                error::unprocessable_entity,
+                error::internal_error,
+                error::default_catcher,
+            ],
+        )
+}
+
+#[test]
+fn validation_errors_list_every_field() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![Vec::<album::Model>::new()])
+        .append_query_results(vec![Vec::<song::Model>::new()]);
+    let client = Client::tracked(setup_backend(db)).unwrap();
+
+    let response = client
+        .post("/song/")
+        .header(bearer())
+        .body(r#"{"name":"","length_secs":0,"album_id":1,"track_number":1}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    let body = response.into_json::<ErrorBody>().unwrap();
+    assert_eq!(body.code, "validation_failed");
+    assert_eq!(
+        body.details,
+        vec![
+            FieldError::new("name", "must not be empty"),
+            FieldError::new("length_secs", "must be positive"),
+            FieldError::new("album_id", "album does not exist"),
+        ]
+    );
+}
+
+#[test]
+fn database_errors_are_not_leaked() {
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_errors(vec![DbErr::Custom("connection lost".to_string())]);
+    let client = Client::tracked(setup_backend(db)).unwrap();
+
+    let response = client.get("/song/1").dispatch();
+
+    assert_eq!(response.status(), Status::InternalServerError);
+    let body = response.into_json::<ErrorBody>().unwrap();
+    assert_eq!(body.code, "internal_error");
+    assert!(!body.message.contains("connection lost"));
+}
+
+#[test]
+fn rocket_errors_use_the_same_body() {
+    let db = signed_in(signed_in(MockDatabase::new(DatabaseBackend::Postgres)));
+    let client = Client::tracked(setup_backend(db)).unwrap();
+
+    let response = client.get("/nowhere").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.into_json::<ErrorBody>().unwrap().code, "not_found");
+
+    let response = client
+        .post("/song/")
+        .header(bearer())
+        .body(r#"{"name":"x"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(
+        response.into_json::<ErrorBody>().unwrap().code,
+        "validation_failed"
+    );
+
+    let response = client
+        .post("/song/")
+        .header(bearer())
+        .body("not json")
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+    assert_eq!(
+        response.into_json::<ErrorBody>().unwrap().code,
+        "bad_request"
+    );
+}
@@ -0,0 +1,522 @@
+use super::{awesome_band, bearer, lorem, plain, signed_in, statement, SharedMock};
+use crate::api::export_api::*;
+use chrono::{NaiveDate, NaiveDateTime};
+use entities::{
+    album, album_artist_mtm, album_genre, album_label, artist, artist_genre, artist_membership,
+    genre, label, person, sea_orm_active_enums::CreditRole, song, song_credit,
+};
+use rocket::{
+    http::{ContentType, Status},
+    local::blocking::Client,
+};
+use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction};
+
+fn rock() -> genre::Model {
+    genre::Model {
+        id: 2,
+        name: "Rock".to_string(),
+        parent_id: None,
+    }
+}
+
+fn edited_band() -> artist::Model {
+    artist::Model {
+        version: 2,
+        ..awesome_band()
+    }
+}
+
+fn link() -> album_artist_mtm::Model {
+    album_artist_mtm::Model {
+        id: 3,
+        album_id: 7,
+        artist_id: 1,
+        position: 0,
+        deleted_at: None,
+    }
+}
+
+fn awesome_rock() -> artist_genre::Model {
+    artist_genre::Model {
+        id: 5,
+        artist_id: 1,
+        genre_id: 2,
+    }
+}
+
+fn jane() -> person::Model {
+    person::Model {
+        id: 6,
+        name: "Jane Doe".to_string(),
+    }
+}
+
+fn founder() -> artist_membership::Model {
+    artist_membership::Model {
+        id: 8,
+        artist_id: 1,
+        person_id: 6,
+        role: "Founder".to_string(),
+        instrument: Some("Guitar".to_string()),
+        start_date: NaiveDate::from_ymd_opt(2010, 1, 1),
+        end_date: None,
+    }
+}
+
+fn indie() -> label::Model {
+    label::Model {
+        id: 9,
+        name: "Indie Records".to_string(),
+        country: Some("US".to_string()),
+        founded: NaiveDate::from_ymd_opt(2005, 3, 1),
+        parent_id: None,
+    }
+}
+
+fn lorem_release() -> album_label::Model {
+    album_label::Model {
+        id: 10,
+        album_id: 7,
+        label_id: 9,
+        catalog_number: Some("IR-001".to_string()),
+    }
+}
+
+fn featuring() -> song_credit::Model {
+    song_credit::Model {
+        id: 11,
+        song_id: 4,
+        artist_id: 1,
+        role: CreditRole::Featured,
+        position: 0,
+        join_phrase: Some("feat.".to_string()),
+    }
+}
+
+fn my_song() -> song::Model {
+    song::Model {
+        id: 4,
+        name: "My song".to_string(),
+        length_secs: 120,
+        album_id: 7,
+        disc_number: 1,
+        track_number: 1,
+        version: 1,
+        deleted_at: Some(
+            NaiveDate::from_ymd_opt(2026, 10, 1)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+        ),
+    }
+}
+
+const DUMP: &str = r#"{"table":"genre","row":{"id":2,"name":"Rock","parent_id":null}}
+{"table":"artist","row":{"id":1,"name":"Awesome Band","date_formed":"2010-01-01","version":2,"deleted_at":null}}
+{"table":"album","row":{"id":7,"name":"Lorem","date_published":"2015-01-01","version":1,"deleted_at":null}}
+{"table":"album_artist_mtm","row":{"id":3,"album_id":7,"artist_id":1,"position":0,"deleted_at":null}}
+{"table":"song","row":{"id":4,"name":"My song","length_secs":120,"album_id":7,"disc_number":1,"track_number":1,"version":1,"deleted_at":"2026-10-01T12:00:00"}}
+{"table":"artist_genre","row":{"id":5,"artist_id":1,"genre_id":2}}
+{"table":"person","row":{"id":6,"name":"Jane Doe"}}
+{"table":"artist_membership","row":{"id":8,"artist_id":1,"person_id":6,"role":"Founder","instrument":"Guitar","start_date":"2010-01-01","end_date":null}}
+{"table":"label","row":{"id":9,"name":"Indie Records","country":"US","founded":"2005-03-01","parent_id":null}}
+{"table":"album_label","row":{"id":10,"album_id":7,"label_id":9,"catalog_number":"IR-001"}}
+{"table":"song_credit","row":{"id":11,"song_id":4,"artist_id":1,"role":"Featured","position":0,"join_phrase":"feat."}}
+{"trailer":{"genre":1,"artist":1,"album":1,"album_artist_mtm":1,"song":1,"artist_genre":1,"album_genre":0,"person":1,"artist_membership":1,"label":1,"album_label":1,"song_credit":1}}
+"#;
+
+#[test]
+fn export_ndjson_streams_every_table_in_one_transaction() {
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![rock()]])
+            .append_query_results(vec![vec![edited_band()]])
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![vec![link()]])
+            .append_query_results(vec![vec![my_song()]])
+            .append_query_results(vec![vec![awesome_rock()]])
+            .append_query_results(vec![Vec::<album_genre::Model>::new()])
+            .append_query_results(vec![vec![jane()]])
+            .append_query_results(vec![vec![founder()]])
+            .append_query_results(vec![vec![indie()]])
+            .append_query_results(vec![vec![lorem_release()]])
+            .append_query_results(vec![vec![featuring()]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/export", routes![export_ndjson]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/export/ndjson").header(bearer()).dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.content_type(),
+        Some(ContentType::new("application", "x-ndjson"))
+    );
+    assert_eq!(response.into_string().unwrap(), DUMP);
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"SELECT "genre"."id", "genre"."name", "genre"."parent_id" FROM "genre" ORDER BY "genre"."id" ASC"#,
+                vec![],
+            ),
+            statement(
+                r#"SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" ORDER BY "artist"."id" ASC"#,
+                vec![],
+            ),
+            statement(
+                r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" ORDER BY "album"."id" ASC"#,
+                vec![],
+            ),
+            statement(
+                r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" ORDER BY "album_artist_mtm"."id" ASC"#,
+                vec![],
+            ),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" ORDER BY "song"."id" ASC"#,
+                vec![],
+            ),
+            statement(
+                r#"SELECT "artist_genre"."id", "artist_genre"."artist_id", "artist_genre"."genre_id" FROM "artist_genre" ORDER BY "artist_genre"."id" ASC"#,
+                vec![],
+            ),
+            statement(
+                r#"SELECT "album_genre"."id", "album_genre"."album_id", "album_genre"."genre_id" FROM "album_genre" ORDER BY "album_genre"."id" ASC"#,
+                vec![],
+            ),
+            statement(
+                r#"SELECT "person"."id", "person"."name" FROM "person" ORDER BY "person"."id" ASC"#,
+                vec![],
+            ),
+            statement(
+                r#"SELECT "artist_membership"."id", "artist_membership"."artist_id", "artist_membership"."person_id", "artist_membership"."role", "artist_membership"."instrument", "artist_membership"."start_date", "artist_membership"."end_date" FROM "artist_membership" ORDER BY "artist_membership"."id" ASC"#,
+                vec![],
+            ),
+            statement(
+                r#"SELECT "label"."id", "label"."name", "label"."country", "label"."founded", "label"."parent_id" FROM "label" ORDER BY "label"."id" ASC"#,
+                vec![],
+            ),
+            statement(
+                r#"SELECT "album_label"."id", "album_label"."album_id", "album_label"."label_id", "album_label"."catalog_number" FROM "album_label" ORDER BY "album_label"."id" ASC"#,
+                vec![],
+            ),
+            statement(
+                r#"SELECT "song_credit"."id", "song_credit"."song_id", "song_credit"."artist_id", CAST("song_credit"."role" AS text), "song_credit"."position", "song_credit"."join_phrase" FROM "song_credit" ORDER BY "song_credit"."id" ASC"#,
+                vec![],
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn export_json_nests_links_and_songs_in_their_album() {
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![edited_band()]])
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![vec![link()]])
+            .append_query_results(vec![vec![my_song()]])
+            .append_query_results(vec![Vec::<album::Model>::new()]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/export", routes![export_json]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/export/json").header(bearer()).dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.content_type(), Some(ContentType::JSON));
+    assert_eq!(
+        response.into_string().unwrap(),
+        r#"{"artists":[{"id":1,"name":"Awesome Band","date_formed":"2010-01-01","version":2,"deleted_at":null}],"albums":[{"id":7,"name":"Lorem","date_published":"2015-01-01","version":1,"deleted_at":null,"links":[{"id":3,"album_id":7,"artist_id":1,"position":0,"deleted_at":null}],"songs":[{"id":4,"name":"My song","length_secs":120,"album_id":7,"disc_number":1,"track_number":1,"version":1,"deleted_at":"2026-10-01T12:00:00"}]}]}"#
+    );
+}
+
+#[test]
+fn export_csv_starts_with_the_columns() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![edited_band()]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/export", routes![export_csv]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/export/csv/artist").header(bearer()).dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.content_type(), Some(ContentType::CSV));
+    assert_eq!(
+        response.into_string().unwrap(),
+        "id,name,date_formed,version,deleted_at\n1,Awesome Band,2010-01-01,2,\n"
+    );
+}
+
+#[test]
+fn export_csv_ends_with_an_error_line_when_it_fails() {
+    // Without any result, the mock fails the query of the rows
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres)).into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/export", routes![export_csv]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/export/csv/artist").header(bearer()).dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.into_string().unwrap(),
+        "id,name,date_formed,version,deleted_at\n\nERROR: the export failed, it is incomplete\n"
+    );
+}
+
+#[test]
+fn restore_inserts_the_rows_with_their_ids() {
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![Vec::<genre::Model>::new()])
+            .append_query_results(vec![Vec::<artist::Model>::new()])
+            .append_query_results(vec![Vec::<album::Model>::new()])
+            .append_query_results(vec![Vec::<album_artist_mtm::Model>::new()])
+            .append_query_results(vec![Vec::<song::Model>::new()])
+            .append_query_results(vec![Vec::<person::Model>::new()])
+            .append_query_results(vec![Vec::<label::Model>::new()])
+            .append_exec_results(
+                (0..24)
+                    .map(|_| MockExecResult {
+                        last_insert_id: 0,
+                        rows_affected: 1,
+                    })
+                    .collect(),
+            ),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/export", routes![restore]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/export/restore")
+        .header(bearer())
+        .body(DUMP)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(
+        response.into_string().unwrap(),
+        r#"{"genre":1,"artist":1,"album":1,"album_artist_mtm":1,"song":1,"artist_genre":1,"album_genre":0,"person":1,"artist_membership":1,"label":1,"album_label":1,"song_credit":1}"#
+    );
+    let no_date = || Option::<NaiveDateTime>::None.into();
+    let setval = |table: &str| {
+        plain(&format!(
+            r#"SELECT setval(pg_get_serial_sequence('"{table}"', 'id'), COALESCE(MAX("id"), 0) + 1, false) FROM "{table}""#
+        ))
+    };
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"SELECT "genre"."id", "genre"."name", "genre"."parent_id" FROM "genre" LIMIT $1"#,
+                vec![1u64.into()],
+            ),
+            statement(
+                r#"SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" LIMIT $1"#,
+                vec![1u64.into()],
+            ),
+            statement(
+                r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" LIMIT $1"#,
+                vec![1u64.into()],
+            ),
+            statement(
+                r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" LIMIT $1"#,
+                vec![1u64.into()],
+            ),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" LIMIT $1"#,
+                vec![1u64.into()],
+            ),
+            statement(
+                r#"SELECT "person"."id", "person"."name" FROM "person" LIMIT $1"#,
+                vec![1u64.into()],
+            ),
+            statement(
+                r#"SELECT "label"."id", "label"."name", "label"."country", "label"."founded", "label"."parent_id" FROM "label" LIMIT $1"#,
+                vec![1u64.into()],
+            ),
+            plain(r#"SET CONSTRAINTS "fk-genre-parent_id", "fk-label-parent_id" DEFERRED"#),
+            statement(
+                r#"INSERT INTO "genre" ("id", "name", "parent_id") VALUES ($1, $2, $3)"#,
+                vec![2i32.into(), "Rock".into(), Option::<i32>::None.into()],
+            ),
+            statement(
+                r#"INSERT INTO "artist" ("id", "name", "date_formed", "version", "deleted_at") VALUES ($1, $2, $3, $4, $5)"#,
+                vec![
+                    1i32.into(),
+                    "Awesome Band".into(),
+                    NaiveDate::from_ymd_opt(2010, 1, 1).unwrap().into(),
+                    2i32.into(),
+                    no_date(),
+                ],
+            ),
+            statement(
+                r#"INSERT INTO "album" ("id", "name", "date_published", "version", "deleted_at") VALUES ($1, $2, $3, $4, $5)"#,
+                vec![
+                    7i32.into(),
+                    "Lorem".into(),
+                    NaiveDate::from_ymd_opt(2015, 1, 1).unwrap().into(),
+                    1i32.into(),
+                    no_date(),
+                ],
+            ),
+            statement(
+                r#"INSERT INTO "album_artist_mtm" ("id", "album_id", "artist_id", "position", "deleted_at") VALUES ($1, $2, $3, $4, $5)"#,
+                vec![3i32.into(), 7i32.into(), 1i32.into(), 0i32.into(), no_date()],
+            ),
+            statement(
+                r#"INSERT INTO "song" ("id", "name", "length_secs", "album_id", "disc_number", "track_number", "version", "deleted_at") VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+                vec![
+                    4i32.into(),
+                    "My song".into(),
+                    120i32.into(),
+                    7i32.into(),
+                    1i32.into(),
+                    1i32.into(),
+                    1i32.into(),
+                    my_song().deleted_at.into(),
+                ],
+            ),
+            statement(
+                r#"INSERT INTO "artist_genre" ("id", "artist_id", "genre_id") VALUES ($1, $2, $3)"#,
+                vec![5i32.into(), 1i32.into(), 2i32.into()],
+            ),
+            statement(
+                r#"INSERT INTO "person" ("id", "name") VALUES ($1, $2)"#,
+                vec![6i32.into(), "Jane Doe".into()],
+            ),
+            statement(
+                r#"INSERT INTO "artist_membership" ("id", "artist_id", "person_id", "role", "instrument", "start_date", "end_date") VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+                vec![
+                    8i32.into(),
+                    1i32.into(),
+                    6i32.into(),
+                    "Founder".into(),
+                    Some("Guitar").into(),
+                    NaiveDate::from_ymd_opt(2010, 1, 1).into(),
+                    Option::<NaiveDate>::None.into(),
+                ],
+            ),
+            statement(
+                r#"INSERT INTO "label" ("id", "name", "country", "founded", "parent_id") VALUES ($1, $2, $3, $4, $5)"#,
+                vec![
+                    9i32.into(),
+                    "Indie Records".into(),
+                    Some("US").into(),
+                    NaiveDate::from_ymd_opt(2005, 3, 1).into(),
+                    Option::<i32>::None.into(),
+                ],
+            ),
+            statement(
+                r#"INSERT INTO "album_label" ("id", "album_id", "label_id", "catalog_number") VALUES ($1, $2, $3, $4)"#,
+                vec![
+                    10i32.into(),
+                    7i32.into(),
+                    9i32.into(),
+                    Some("IR-001").into()
+                ],
+            ),
+            statement(
+                r#"INSERT INTO "song_credit" ("id", "song_id", "artist_id", "role", "position", "join_phrase") VALUES ($1, $2, $3, CAST($4 AS credit_role), $5, $6)"#,
+                vec![
+                    11i32.into(),
+                    4i32.into(),
+                    1i32.into(),
+                    "featured".into(),
+                    0i32.into(),
+                    Some("feat.").into(),
+                ],
+            ),
+            setval("genre"),
+            setval("artist"),
+            setval("album"),
+            setval("album_artist_mtm"),
+            setval("song"),
+            setval("artist_genre"),
+            setval("album_genre"),
+            setval("person"),
+            setval("artist_membership"),
+            setval("label"),
+            setval("album_label"),
+            setval("song_credit"),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn restore_into_a_database_with_a_catalog_conflicts() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![Vec::<genre::Model>::new()])
+        .append_query_results(vec![vec![edited_band()]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/export", routes![restore]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/export/restore")
+        .header(bearer())
+        .body(DUMP)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Conflict);
+}
+
+#[test]
+fn restore_rejects_a_dump_cut_short() {
+    let catalog_is_empty = |db: MockDatabase| {
+        db.append_query_results(vec![Vec::<genre::Model>::new()])
+            .append_query_results(vec![Vec::<artist::Model>::new()])
+            .append_query_results(vec![Vec::<album::Model>::new()])
+            .append_query_results(vec![Vec::<album_artist_mtm::Model>::new()])
+            .append_query_results(vec![Vec::<song::Model>::new()])
+            .append_query_results(vec![Vec::<person::Model>::new()])
+            .append_query_results(vec![Vec::<label::Model>::new()])
+            .append_exec_results(
+                (0..3)
+                    .map(|_| MockExecResult {
+                        last_insert_id: 0,
+                        rows_affected: 1,
+                    })
+                    .collect(),
+            )
+    };
+    let db = MockDatabase::new(DatabaseBackend::Postgres);
+    let db = catalog_is_empty(signed_in(catalog_is_empty(signed_in(db)))).into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/export", routes![restore]);
+    let client = Client::tracked(rocket).unwrap();
+    let lines: Vec<&str> = DUMP.lines().collect();
+
+    let response = client
+        .post("/export/restore")
+        .header(bearer())
+        .body(lines[..2].join("\n"))
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+
+    // The trailer counts rows which are not there
+    let response = client
+        .post("/export/restore")
+        .header(bearer())
+        .body(format!("{}\n{}", lines[0], lines[lines.len() - 1]))
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
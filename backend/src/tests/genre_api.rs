@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use super::{audited, bearer, insert_audit, plain, signed_in, statement, SharedMock};
+use crate::api::{artist_api::get_all_artists, genre_api::*};
+use entities::{artist, genre};
+use rocket::{
+    http::Status,
+    local::blocking::Client,
+    serde::json::{json, Value as Json},
+};
+use sea_orm::{DatabaseBackend, MockDatabase, Transaction, Value};
+
+fn genre(id: i32, name: &str, parent_id: Option<i32>) -> genre::Model {
+    genre::Model {
+        id,
+        name: name.to_string(),
+        parent_id,
+    }
+}
+
+#[test]
+fn create_genre_records_the_subgenre() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![genre(2, "Rock", None)]])
+            .append_query_results(vec![vec![genre(4, "Hard rock", Some(2))]]),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/genre", routes![create_genre]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/genre")
+        .header(bearer())
+        .body(r#"{"name":" Hard rock ","parent_id":2}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(response.into_string().unwrap(), "4");
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"SELECT "genre"."id", "genre"."name", "genre"."parent_id" FROM "genre""#,
+                vec![],
+            ),
+            statement(
+                r#"INSERT INTO "genre" ("name", "parent_id") VALUES ($1, $2) RETURNING "id""#,
+                vec!["Hard rock".into(), 2i32.into()],
+            ),
+            insert_audit(
+                "genre",
+                4,
+                "create",
+                None,
+                Some(json!({"id": 4, "name": "Hard rock", "parent_id": 2})),
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn create_genre_rejects_a_name_taken_in_another_case() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![genre(2, "Rock", None)]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/genre", routes![create_genre]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/genre")
+        .header(bearer())
+        .body(r#"{"name":"rock"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(
+        response.into_json::<Json>().unwrap()["details"],
+        json!([{"field": "name", "message": "already exists"}])
+    );
+}
+
+#[test]
+fn update_genre_rejects_moving_a_genre_under_its_subgenre() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![
+            genre(2, "Rock", None),
+            genre(4, "Hard rock", Some(2)),
+            genre(5, "Glam metal", Some(4)),
+        ]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/genre", routes![update_genre]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .put("/genre/2")
+        .header(bearer())
+        .body(r#"{"name":"Rock","parent_id":5}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(
+        response.into_json::<Json>().unwrap()["details"],
+        json!([{"field": "parent_id", "message": "must not be the genre or one of its subgenres"}])
+    );
+}
+
+#[test]
+fn delete_genre_with_subgenres_conflicts() {
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![genre(2, "Rock", None)]])
+            .append_query_results(vec![vec![genre(4, "Hard rock", Some(2))]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/genre", routes![delete_genre]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.delete("/genre/2").header(bearer()).dispatch();
+
+    assert_eq!(response.status(), Status::Conflict);
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"SELECT "genre"."id", "genre"."name", "genre"."parent_id" FROM "genre" WHERE "genre"."id" = $1 LIMIT $2 FOR UPDATE"#,
+                vec![2i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "genre"."id", "genre"."name", "genre"."parent_id" FROM "genre" WHERE "genre"."parent_id" = $1 LIMIT $2"#,
+                vec![2i32.into(), 1u64.into()],
+            ),
+            plain("ROLLBACK"),
+        ]))
+    );
+}
+
+#[test]
+fn artists_of_a_genre_include_those_of_its_subgenres() {
+    let db = SharedMock::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![genre(2, "Rock", None)]])
+            .append_query_results(vec![vec![
+                genre(2, "Rock", None),
+                genre(3, "Jazz", None),
+                genre(4, "Hard rock", Some(2)),
+                genre(5, "Glam metal", Some(4)),
+            ]])
+            .append_query_results(vec![vec![BTreeMap::from([(
+                "num_items".to_string(),
+                Value::BigInt(Some(0)),
+            )])]])
+            .append_query_results(vec![Vec::<artist::Model>::new()]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/artist", routes![get_all_artists]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/artist/all?genre=ROCK").dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        db.transaction_log()[..3],
+        [
+            Transaction::one(statement(
+                r#"SELECT "genre"."id", "genre"."name", "genre"."parent_id" FROM "genre" WHERE LOWER("genre"."name") = $1 LIMIT $2"#,
+                vec!["rock".into(), 1u64.into()],
+            )),
+            Transaction::one(statement(
+                r#"SELECT "genre"."id", "genre"."name", "genre"."parent_id" FROM "genre""#,
+                vec![],
+            )),
+            Transaction::one(statement(
+                r#"SELECT COUNT(*) AS num_items FROM (SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" WHERE "artist"."deleted_at" IS NULL AND "artist"."id" IN (SELECT "artist_id" FROM "artist_genre" WHERE "artist_genre"."genre_id" IN ($1, $2, $3)) ORDER BY "artist"."id" ASC, "artist"."id" ASC) AS "sub_query""#,
+                vec![2i32.into(), 4i32.into(), 5i32.into()],
+            )),
+        ]
+    );
+}
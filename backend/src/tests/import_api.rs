@@ -0,0 +1,403 @@
+use super::{audited, awesome_band, bearer, insert_audit, plain, signed_in, statement, SharedMock};
+use crate::api::import_api::*;
+use chrono::NaiveDate;
+use entities::{album, album_artist_mtm, artist, artist_genre, genre, song};
+use rocket::{
+    http::{ContentType, Status},
+    local::blocking::Client,
+    serde::json::{json, Value as Json},
+};
+use sea_orm::{DatabaseBackend, MockDatabase, Statement, Transaction};
+
+fn select_artist(name: &str) -> Statement {
+    statement(
+        r#"SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" WHERE "artist"."deleted_at" IS NULL AND "artist"."name" = $1 LIMIT $2"#,
+        vec![name.into(), 1u64.into()],
+    )
+}
+
+#[test]
+fn import_csv_creates_the_missing_album_and_song() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![awesome_band()]])
+            .append_query_results(vec![Vec::<album_artist_mtm::Model>::new()])
+            .append_query_results(vec![Vec::<album::Model>::new()])
+            .append_query_results(vec![vec![album::Model {
+                id: 7,
+                name: "Lorem".to_string(),
+                date_published: NaiveDate::from_ymd_opt(2015, 1, 1).unwrap(),
+                version: 1,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![vec![album_artist_mtm::Model {
+                id: 3,
+                album_id: 7,
+                artist_id: 1,
+                position: 0,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![Vec::<song::Model>::new()])
+            .append_query_results(vec![Vec::<song::Model>::new()])
+            .append_query_results(vec![vec![song::Model {
+                id: 4,
+                name: "My song".to_string(),
+                length_secs: 120,
+                album_id: 7,
+                disc_number: 1,
+                track_number: 1,
+                version: 1,
+                deleted_at: None,
+            }]]),
+        3,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/import", routes![import_rows]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/import")
+        .header(bearer())
+        .header(ContentType::CSV)
+        .body(
+            "artist,artist_date_formed,artist_genre,album,album_date_published,song,song_length_secs\n\
+             Awesome Band,,,Lorem,2015-01-01,My song,120\n",
+        )
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(
+        response.into_string().unwrap(),
+        r#"{"committed":true,"rows":[{"row":1,"status":"created","artist_id":1,"album_id":7,"song_id":4}]}"#
+    );
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            select_artist("Awesome Band"),
+            statement(
+                r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" WHERE "album_artist_mtm"."deleted_at" IS NULL AND "album_artist_mtm"."artist_id" = $1"#,
+                vec![1i32.into()],
+            ),
+            statement(
+                r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" WHERE "album"."deleted_at" IS NULL AND $1 = $2 AND "album"."name" = $3 LIMIT $4"#,
+                vec![1i32.into(), 2i32.into(), "Lorem".into(), 1u64.into()],
+            ),
+            statement(
+                r#"INSERT INTO "album" ("name", "date_published") VALUES ($1, $2) RETURNING "id""#,
+                vec![
+                    "Lorem".into(),
+                    NaiveDate::from_ymd_opt(2015, 1, 1).unwrap().into(),
+                ],
+            ),
+            insert_audit(
+                "album",
+                7,
+                "create",
+                None,
+                Some(
+                    json!({"id": 7, "name": "Lorem", "date_published": "2015-01-01", "version": 1, "deleted_at": null})
+                ),
+                Some(1),
+            ),
+            statement(
+                r#"INSERT INTO "album_artist_mtm" ("album_id", "artist_id", "position") VALUES ($1, $2, $3) RETURNING "id""#,
+                vec![7i32.into(), 1i32.into(), 0i32.into()],
+            ),
+            insert_audit(
+                "album_artist_mtm",
+                3,
+                "create",
+                None,
+                Some(json!({"id": 3, "album_id": 7, "artist_id": 1, "position": 0, "deleted_at": null})),
+                Some(1),
+            ),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."deleted_at" IS NULL AND "song"."album_id" = $1 AND "song"."name" = $2 LIMIT $3"#,
+                vec![7i32.into(), "My song".into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."album_id" = $1 AND "song"."disc_number" = $2 ORDER BY "song"."track_number" DESC LIMIT $3"#,
+                vec![7i32.into(), 1i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"INSERT INTO "song" ("name", "length_secs", "album_id", "disc_number", "track_number") VALUES ($1, $2, $3, $4, $5) RETURNING "id""#,
+                vec![
+                    "My song".into(),
+                    120i32.into(),
+                    7i32.into(),
+                    1i32.into(),
+                    1i32.into(),
+                ],
+            ),
+            insert_audit(
+                "song",
+                4,
+                "create",
+                None,
+                Some(
+                    json!({"id": 4, "name": "My song", "length_secs": 120, "album_id": 7, "disc_number": 1, "track_number": 1, "version": 1, "deleted_at": null})
+                ),
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn import_with_a_rejected_row_imports_nothing() {
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![awesome_band()]])
+            .append_query_results(vec![Vec::<artist::Model>::new()]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/import", routes![import_rows]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/import")
+        .header(bearer())
+        .header(ContentType::JSON)
+        .body(
+            r#"[
+                {"artist": "Awesome Band"},
+                {"artist": "Awesome Band", "song": "My song"},
+                {"artist": " ", "artist_date_formed": "2100-01-01"},
+                {"artist": "Popular Artist", "song_length_secs": "long"}
+            ]"#,
+        )
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    let report: Json = response.into_json().unwrap();
+    assert_eq!(report["committed"], false);
+    assert_eq!(report["rows"][0]["status"], "matched");
+    assert_eq!(
+        report["rows"][1]["errors"],
+        json!([{"field": "album", "message": "is required along with a song"}])
+    );
+    assert_eq!(
+        report["rows"][2]["errors"],
+        json!([
+            {"field": "artist", "message": "must not be empty"},
+            {"field": "artist_date_formed", "message": "must not be in the future"}
+        ])
+    );
+    assert_eq!(report["rows"][3]["status"], "rejected");
+    assert_eq!(report["rows"][3]["errors"][0]["field"], "row");
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            select_artist("Awesome Band"),
+            select_artist(" "),
+            plain("ROLLBACK"),
+        ]))
+    );
+}
+
+#[test]
+fn import_rejects_a_taken_track() {
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![awesome_band()]])
+            .append_query_results(vec![vec![album_artist_mtm::Model {
+                id: 3,
+                album_id: 7,
+                artist_id: 1,
+                position: 0,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![vec![album::Model {
+                id: 7,
+                name: "Lorem".to_string(),
+                date_published: NaiveDate::from_ymd_opt(2015, 1, 1).unwrap(),
+                version: 1,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![Vec::<song::Model>::new()])
+            .append_query_results(vec![vec![song::Model {
+                id: 4,
+                name: "My song".to_string(),
+                length_secs: 120,
+                album_id: 7,
+                disc_number: 2,
+                track_number: 1,
+                version: 1,
+                deleted_at: None,
+            }]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/import", routes![import_rows]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/import")
+        .header(bearer())
+        .header(ContentType::JSON)
+        .body(
+            r#"[{"artist": "Awesome Band", "album": "Lorem", "song": "Other song", "song_length_secs": 95, "song_disc_number": 2, "song_track_number": 1}]"#,
+        )
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    let report: Json = response.into_json().unwrap();
+    assert_eq!(
+        report["rows"][0]["errors"],
+        json!([{"field": "song_track_number", "message": "is already taken on this disc"}])
+    );
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            select_artist("Awesome Band"),
+            statement(
+                r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" WHERE "album_artist_mtm"."deleted_at" IS NULL AND "album_artist_mtm"."artist_id" = $1"#,
+                vec![1i32.into()],
+            ),
+            statement(
+                r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" WHERE "album"."deleted_at" IS NULL AND "album"."id" IN ($1) AND "album"."name" = $2 LIMIT $3"#,
+                vec![7i32.into(), "Lorem".into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."deleted_at" IS NULL AND "song"."album_id" = $1 AND "song"."name" = $2 LIMIT $3"#,
+                vec![7i32.into(), "Other song".into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."deleted_at" IS NULL AND "song"."album_id" = $1 AND "song"."disc_number" = $2 AND "song"."track_number" = $3 LIMIT $4"#,
+                vec![7i32.into(), 2i32.into(), 1i32.into(), 1u64.into()],
+            ),
+            plain("ROLLBACK"),
+        ]))
+    );
+}
+
+#[test]
+fn import_dry_run_rolls_back() {
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![awesome_band()]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/import", routes![import_rows]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/import?dry_run=true")
+        .header(bearer())
+        .header(ContentType::JSON)
+        .body(r#"[{"artist": "Awesome Band"}]"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.into_string().unwrap(),
+        r#"{"committed":false,"rows":[{"row":1,"status":"matched","artist_id":1,"album_id":null,"song_id":null}]}"#
+    );
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            select_artist("Awesome Band"),
+            plain("ROLLBACK"),
+        ]))
+    );
+}
+
+#[test]
+fn import_links_new_artists_to_their_genre_by_name() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![Vec::<artist::Model>::new()])
+            .append_query_results(vec![vec![genre::Model {
+                id: 2,
+                name: "Rock".to_string(),
+                parent_id: None,
+            }]])
+            .append_query_results(vec![vec![awesome_band()]])
+            .append_query_results(vec![vec![artist_genre::Model {
+                id: 5,
+                artist_id: 1,
+                genre_id: 2,
+            }]])
+            .append_query_results(vec![Vec::<artist::Model>::new()])
+            .append_query_results(vec![Vec::<genre::Model>::new()]),
+        2,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/import", routes![import_rows]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/import")
+        .header(bearer())
+        .header(ContentType::JSON)
+        .body(
+            r#"[
+                {"artist": "Awesome Band", "artist_date_formed": "2010-01-01", "artist_genre": "ROCK"},
+                {"artist": "Popular Artist", "artist_date_formed": "2000-01-01", "artist_genre": "Polka"}
+            ]"#,
+        )
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    let report: Json = response.into_json().unwrap();
+    assert_eq!(report["rows"][0]["status"], "created");
+    assert_eq!(
+        report["rows"][1]["errors"],
+        json!([{"field": "artist_genre", "message": "is not a known genre"}])
+    );
+    let select_genre = |name: &str| {
+        statement(
+            r#"SELECT "genre"."id", "genre"."name", "genre"."parent_id" FROM "genre" WHERE LOWER("genre"."name") = $1 LIMIT $2"#,
+            vec![name.into(), 1u64.into()],
+        )
+    };
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            select_artist("Awesome Band"),
+            select_genre("rock"),
+            statement(
+                r#"INSERT INTO "artist" ("name", "date_formed") VALUES ($1, $2) RETURNING "id""#,
+                vec![
+                    "Awesome Band".into(),
+                    NaiveDate::from_ymd_opt(2010, 1, 1).unwrap().into(),
+                ],
+            ),
+            insert_audit(
+                "artist",
+                1,
+                "create",
+                None,
+                Some(
+                    json!({"id": 1, "name": "Awesome Band", "date_formed": "2010-01-01", "version": 1, "deleted_at": null})
+                ),
+                Some(1),
+            ),
+            statement(
+                r#"INSERT INTO "artist_genre" ("artist_id", "genre_id") VALUES ($1, $2) RETURNING "id""#,
+                vec![1i32.into(), 2i32.into()],
+            ),
+            insert_audit(
+                "artist_genre",
+                5,
+                "create",
+                None,
+                Some(json!({"id": 5, "artist_id": 1, "genre_id": 2})),
+                Some(1),
+            ),
+            select_artist("Popular Artist"),
+            select_genre("polka"),
+            plain("ROLLBACK"),
+        ]))
+    );
+}
@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use super::{audited, bearer, insert_audit, lorem, plain, signed_in, statement, SharedMock};
+use crate::api::{
+    album_api::{get_all_albums, set_labels},
+    label_api::*,
+};
+use entities::{album_label, label};
+use rocket::{
+    http::Status,
+    local::blocking::Client,
+    serde::json::{json, Value as Json},
+};
+use sea_orm::{DatabaseBackend, MockDatabase, Transaction, Value};
+
+fn label(id: i32, name: &str, parent_id: Option<i32>) -> label::Model {
+    label::Model {
+        id,
+        name: name.to_string(),
+        country: Some("US".to_string()),
+        founded: None,
+        parent_id,
+    }
+}
+
+fn release(id: i32, label_id: i32, catalog_number: Option<&str>) -> album_label::Model {
+    album_label::Model {
+        id,
+        album_id: 7,
+        label_id,
+        catalog_number: catalog_number.map(str::to_string),
+    }
+}
+
+#[test]
+fn update_label_rejects_a_subsidiary_as_its_parent() {
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres)).append_query_results(vec![vec![
+            label(9, "Indie Records", None),
+            label(11, "Indie Imprint", Some(9)),
+        ]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/label", routes![update_label]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .put("/label/9")
+        .header(bearer())
+        .body(r#"{"name":"Indie Records","country":"US","parent_id":11}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(
+        response.into_json::<Json>().unwrap()["details"],
+        json!([{"field": "parent_id", "message": "must not be the label or one of its subsidiaries"}])
+    );
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"SELECT "label"."id", "label"."name", "label"."country", "label"."founded", "label"."parent_id" FROM "label" FOR UPDATE"#,
+                vec![],
+            ),
+            plain("ROLLBACK"),
+        ]))
+    );
+}
+
+#[test]
+fn set_labels_only_updates_the_changed_catalog_number() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![vec![
+                label(9, "Indie Records", None),
+                label(12, "Big Records", None),
+            ]])
+            .append_query_results(vec![vec![
+                release(10, 9, Some("IR-001")),
+                release(13, 12, None),
+            ]])
+            .append_query_results(vec![vec![release(10, 9, Some("IR-002"))]])
+            .append_query_results(vec![vec![
+                (release(13, 12, None), label(12, "Big Records", None)),
+                (
+                    release(10, 9, Some("IR-002")),
+                    label(9, "Indie Records", None),
+                ),
+            ]]),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/album", routes![set_labels]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .put("/album/labels/7")
+        .header(bearer())
+        .body(r#"[{"label_id":9,"catalog_number":" IR-002 "},{"label_id":12,"catalog_number":""}]"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    let labels = response.into_json::<Json>().unwrap();
+    assert_eq!(labels[0]["label"]["name"], json!("Big Records"));
+    assert_eq!(labels[1]["catalog_number"], json!("IR-002"));
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" WHERE "album"."id" = $1 AND "album"."deleted_at" IS NULL LIMIT $2 FOR UPDATE"#,
+                vec![7i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "label"."id", "label"."name", "label"."country", "label"."founded", "label"."parent_id" FROM "label" WHERE "label"."id" IN ($1, $2)"#,
+                vec![9i32.into(), 12i32.into()],
+            ),
+            statement(
+                r#"SELECT "album_label"."id", "album_label"."album_id", "album_label"."label_id", "album_label"."catalog_number" FROM "album_label" WHERE "album_label"."album_id" = $1"#,
+                vec![7i32.into()],
+            ),
+            statement(
+                r#"UPDATE "album_label" SET "catalog_number" = $1 WHERE "album_label"."id" = $2 RETURNING "id", "album_id", "label_id", "catalog_number""#,
+                vec![Some("IR-002").into(), 10i32.into()],
+            ),
+            insert_audit(
+                "album_label",
+                10,
+                "update",
+                Some(json!({"id": 10, "album_id": 7, "label_id": 9, "catalog_number": "IR-001"})),
+                Some(json!({"id": 10, "album_id": 7, "label_id": 9, "catalog_number": "IR-002"})),
+                Some(1),
+            ),
+            statement(
+                r#"SELECT "album_label"."id" AS "A_id", "album_label"."album_id" AS "A_album_id", "album_label"."label_id" AS "A_label_id", "album_label"."catalog_number" AS "A_catalog_number", "label"."id" AS "B_id", "label"."name" AS "B_name", "label"."country" AS "B_country", "label"."founded" AS "B_founded", "label"."parent_id" AS "B_parent_id" FROM "album_label" LEFT JOIN "label" ON "album_label"."label_id" = "label"."id" WHERE "album_label"."album_id" = $1 ORDER BY "label"."name" ASC, "label"."id" ASC"#,
+                vec![7i32.into()],
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn get_all_albums_filters_by_label() {
+    let db = SharedMock::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![BTreeMap::from([(
+                "num_items".to_string(),
+                Value::BigInt(Some(1)),
+            )])]])
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![Vec::<BTreeMap<String, Value>>::new()]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/album", routes![get_all_albums]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/album/all?label_id=9").dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.into_json::<Json>().unwrap()["items"][0]["name"],
+        json!("Lorem")
+    );
+    assert_eq!(
+        db.transaction_log()[0],
+        Transaction::one(statement(
+            r#"SELECT COUNT(*) AS num_items FROM (SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" WHERE "album"."deleted_at" IS NULL AND "album"."id" IN (SELECT "album_id" FROM "album_label" WHERE "album_label"."label_id" = $1) ORDER BY "album"."id" ASC, "album"."id" ASC) AS "sub_query""#,
+            vec![9i32.into()],
+        ))
+    );
+}
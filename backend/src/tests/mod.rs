@@ -1 +1,168 @@
+mod admin_api;
+mod album_api;
+mod artist_api;
+mod audit_api;
+mod auth_api;
+mod error;
+mod export_api;
+mod genre_api;
+mod import_api;
+mod label_api;
+mod openapi;
+mod person_api;
+mod playlist_api;
+mod review_api;
+mod search_api;
 mod song_api;
+mod storage;
+mod trash_api;
+mod validation;
+
+use chrono::NaiveDate;
+use entities::{album, artist, etag::etag, sea_orm_active_enums::Role, session, user};
+use rocket::{http::Header, serde::json::Value as Json};
+use sea_orm::{
+    DatabaseBackend, DatabaseConnection, MockDatabase, MockDatabaseConnection, MockExecResult,
+    Statement, Transaction, Value,
+};
+use std::{env, path::PathBuf, sync::Arc};
+
+use crate::api::{
+    auth::new_token,
+    storage::{LocalStorage, Storage},
+};
+
+/// Mock database that stays readable by the test after being handed to Rocket
+pub struct SharedMock(Arc<MockDatabaseConnection>);
+
+impl SharedMock {
+    pub fn new(db: MockDatabase) -> Self {
+        SharedMock(Arc::new(MockDatabaseConnection::new(db)))
+    }
+
+    /// A connection to manage in Rocket
+    pub fn connection(&self) -> DatabaseConnection {
+        DatabaseConnection::MockDatabaseConnection(self.0.clone())
+    }
+
+    /// Take every statement run so far, grouped by transaction
+    pub fn transaction_log(&self) -> Vec<Transaction> {
+        self.connection().into_transaction_log()
+    }
+}
+
+/// Statement without values, such as `BEGIN`
+pub fn plain(sql: &str) -> Statement {
+    Statement::from_string(DatabaseBackend::Postgres, sql.to_string())
+}
+
+/// Statement with the given values
+pub fn statement(sql: &str, values: Vec<Value>) -> Statement {
+    Statement::from_sql_and_values(DatabaseBackend::Postgres, sql, values)
+}
+
+/// Artist 1, found by most tests
+pub fn awesome_band() -> artist::Model {
+    artist::Model {
+        id: 1,
+        name: "Awesome Band".to_string(),
+        date_formed: NaiveDate::from_ymd_opt(2010, 1, 1).unwrap(),
+        version: 1,
+        deleted_at: None,
+    }
+}
+
+/// Album 7, found by most tests
+pub fn lorem() -> album::Model {
+    album::Model {
+        id: 7,
+        name: "Lorem".to_string(),
+        date_published: NaiveDate::from_ymd_opt(2015, 1, 1).unwrap(),
+        version: 1,
+        deleted_at: None,
+    }
+}
+
+/// Storage in a new directory of its own under the temporary directory, also returned
+pub fn temp_storage() -> (PathBuf, Box<dyn Storage>) {
+    let root = env::temp_dir().join(format!("discography-{}", new_token()));
+    (root.clone(), Box::new(LocalStorage::new(root)))
+}
+
+/// Token of the session added by [`signed_in`]
+pub const TOKEN: &str = "s3cr3t";
+
+/// Expect the session lookup of the `CurrentUser` guard, finding a valid session of an admin
+///
+/// It must come before every other result of the mock, as guards run before the handler.
+pub fn signed_in(db: MockDatabase) -> MockDatabase {
+    signed_in_as(db, Role::Admin)
+}
+
+/// Same as [`signed_in`], for a user with the given role
+pub fn signed_in_as(db: MockDatabase, role: Role) -> MockDatabase {
+    db.append_query_results(vec![vec![(
+        session::Model {
+            id: 1,
+            token: TOKEN.to_string(),
+            user_id: 1,
+            expires_at: NaiveDate::from_ymd_opt(2100, 1, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+        },
+        user::Model {
+            id: 1,
+            username: "alice".to_string(),
+            role,
+            password_hash: String::new(),
+        },
+    )]])
+}
+
+/// `Authorization` header sending [`TOKEN`]
+pub fn bearer() -> Header<'static> {
+    Header::new("Authorization", format!("Bearer {}", TOKEN))
+}
+
+/// `If-Match` header sending the given version
+pub fn if_match(version: i32) -> Header<'static> {
+    Header::new("If-Match", etag(version))
+}
+
+/// Expect the given number of audit log entries to be written
+///
+/// Audit entries are written without `RETURNING`, so they only take exec results.
+pub fn audited(db: MockDatabase, entries: usize) -> MockDatabase {
+    db.append_exec_results(
+        (0..entries)
+            .map(|_| MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            })
+            .collect(),
+    )
+}
+
+/// Statement writing an audit log entry, `action` being one of `create`, `update` or `delete`
+pub fn insert_audit(
+    entity_type: &str,
+    entity_id: i32,
+    action: &str,
+    before: Option<Json>,
+    after: Option<Json>,
+    user_id: Option<i32>,
+) -> Statement {
+    Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        r#"INSERT INTO "audit_log" ("entity_type", "entity_id", "action", "before", "after", "user_id") VALUES ($1, $2, CAST($3 AS audit_action), $4, $5, $6)"#,
+        vec![
+            entity_type.into(),
+            entity_id.into(),
+            action.into(),
+            Value::Json(before.map(Box::new)),
+            Value::Json(after.map(Box::new)),
+            user_id.into(),
+        ],
+    )
+}
@@ -0,0 +1,101 @@
+use crate::api::openapi::ApiDoc;
+use rocket::{http::Method, local::blocking::Client, serde::json::Value};
+use utoipa::{openapi::PathItemType, OpenApi};
+
+/// Routes serving the specification itself
+const UNDOCUMENTED: [&str; 2] = ["/openapi.json", "/docs"];
+
+/// Turn a Rocket path such as `/song/<id>/` into an OpenAPI path such as `/song/{id}`
+fn to_spec_path(path: &str) -> String {
+    let path = path.replace('<', "{").replace('>', "}");
+    match path.trim_end_matches('/') {
+        "" => "/".to_string(),
+        path => path.to_string(),
+    }
+}
+
+fn to_path_item_type(method: Method) -> PathItemType {
+    match method {
+        Method::Get => PathItemType::Get,
+        Method::Post => PathItemType::Post,
+        Method::Put => PathItemType::Put,
+        Method::Patch => PathItemType::Patch,
+        Method::Delete => PathItemType::Delete,
+        Method::Head => PathItemType::Head,
+        Method::Options => PathItemType::Options,
+        Method::Trace => PathItemType::Trace,
+        Method::Connect => PathItemType::Connect,
+    }
+}
+
+#[test]
+fn every_mounted_route_is_documented() {
+    let spec = ApiDoc::openapi();
+    let documented: Vec<(String, PathItemType)> = spec
+        .paths
+        .paths
+        .iter()
+        .flat_map(|(path, item)| {
+            item.operations
+                .keys()
+                .map(move |operation| (to_spec_path(path), operation.clone()))
+        })
+        .collect();
+
+    let rocket = crate::mount(rocket::build());
+    let missing: Vec<String> = rocket
+        .routes()
+        .filter(|route| !UNDOCUMENTED.contains(&route.uri.path()))
+        .filter(|route| {
+            let operation = (
+                to_spec_path(route.uri.path()),
+                to_path_item_type(route.method),
+            );
+            !documented.contains(&operation)
+        })
+        .map(|route| format!("{} {}", route.method, route.uri.path()))
+        .collect();
+
+    assert!(
+        missing.is_empty(),
+        "Routes missing from the OpenAPI specification: {:?}",
+        missing
+    );
+}
+
+/// Collect the targets of every `$ref` in the given JSON
+fn references<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::String(reference)) = object.get("$ref") {
+                refs.push(reference);
+            }
+            object.values().for_each(|value| references(value, refs));
+        }
+        Value::Array(array) => array.iter().for_each(|value| references(value, refs)),
+        _ => (),
+    }
+}
+
+#[test]
+fn openapi_json_references_existing_schemas() {
+    let client =
+        Client::tracked(rocket::build().mount("/", routes![crate::api::openapi::openapi_json]))
+            .unwrap();
+
+    let spec = client
+        .get("/openapi.json")
+        .dispatch()
+        .into_json::<Value>()
+        .unwrap();
+    let schemas = &spec["components"]["schemas"];
+    let mut refs = Vec::new();
+    references(&spec, &mut refs);
+
+    assert!(schemas["Genre"].is_object());
+    assert!(schemas["ErrorBody"].is_object());
+    for reference in refs {
+        let name = reference.trim_start_matches("#/components/schemas/");
+        assert!(schemas[name].is_object(), "Unknown schema {}", reference);
+    }
+}
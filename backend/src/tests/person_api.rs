@@ -0,0 +1,184 @@
+use super::{audited, awesome_band, bearer, insert_audit, plain, signed_in, statement, SharedMock};
+use crate::api::{artist_api::get_members, person_api::*};
+use chrono::NaiveDate;
+use entities::{artist, artist_membership, person};
+use rocket::{
+    http::Status,
+    local::blocking::Client,
+    serde::json::{json, Value as Json},
+};
+use sea_orm::{DatabaseBackend, MockDatabase, Transaction};
+
+fn jane() -> person::Model {
+    person::Model {
+        id: 6,
+        name: "Jane Doe".to_string(),
+    }
+}
+
+fn membership(id: i32, start_date: Option<&str>) -> artist_membership::Model {
+    artist_membership::Model {
+        id,
+        artist_id: 1,
+        person_id: 6,
+        role: "Founder".to_string(),
+        instrument: Some("Guitar".to_string()),
+        start_date: start_date.map(|date| date.parse().unwrap()),
+        end_date: None,
+    }
+}
+
+#[test]
+fn create_membership_records_the_link() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![awesome_band()]])
+            .append_query_results(vec![vec![jane()]])
+            .append_query_results(vec![vec![membership(8, Some("2010-01-01"))]]),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/person", routes![create_membership]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/person/membership")
+        .header(bearer())
+        .body(r#"{"artist_id":1,"person_id":6,"role":"Founder","instrument":"Guitar","start_date":"2010-01-01"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(response.into_string().unwrap(), "8");
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" WHERE "artist"."id" = $1 AND "artist"."deleted_at" IS NULL LIMIT $2"#,
+                vec![1i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "person"."id", "person"."name" FROM "person" WHERE "person"."id" = $1 LIMIT $2"#,
+                vec![6i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"INSERT INTO "artist_membership" ("artist_id", "person_id", "role", "instrument", "start_date", "end_date") VALUES ($1, $2, $3, $4, $5, $6) RETURNING "id""#,
+                vec![
+                    1i32.into(),
+                    6i32.into(),
+                    "Founder".into(),
+                    Some("Guitar").into(),
+                    NaiveDate::from_ymd_opt(2010, 1, 1).into(),
+                    Option::<NaiveDate>::None.into(),
+                ],
+            ),
+            insert_audit(
+                "artist_membership",
+                8,
+                "create",
+                None,
+                Some(json!({
+                    "id": 8,
+                    "artist_id": 1,
+                    "person_id": 6,
+                    "role": "Founder",
+                    "instrument": "Guitar",
+                    "start_date": "2010-01-01",
+                    "end_date": null
+                })),
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn create_membership_rejects_a_trashed_artist_and_an_end_before_the_start() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![Vec::<artist::Model>::new()])
+        .append_query_results(vec![vec![jane()]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/person", routes![create_membership]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/person/membership")
+        .header(bearer())
+        .body(r#"{"artist_id":1,"person_id":6,"role":"Founder","start_date":"2010-01-01","end_date":"2009-12-31"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(
+        response.into_json::<Json>().unwrap()["details"],
+        json!([
+            {"field": "end_date", "message": "must not be before the start date"},
+            {"field": "artist_id", "message": "does not exist"}
+        ])
+    );
+}
+
+#[test]
+fn get_members_lists_the_members_by_the_date_they_joined() {
+    let db = SharedMock::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![awesome_band()]])
+            .append_query_results(vec![vec![
+                (membership(8, Some("2010-01-01")), jane()),
+                (membership(9, None), jane()),
+            ]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/artist", routes![get_members]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/artist/members/1").dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    let members = response.into_json::<Json>().unwrap();
+    assert_eq!(members[0]["membership"]["start_date"], json!("2010-01-01"));
+    assert_eq!(members[0]["person"]["name"], json!("Jane Doe"));
+    assert_eq!(members[1]["membership"]["start_date"], json!(null));
+    assert_eq!(
+        db.transaction_log()[1],
+        Transaction::one(statement(
+            r#"SELECT "artist_membership"."id" AS "A_id", "artist_membership"."artist_id" AS "A_artist_id", "artist_membership"."person_id" AS "A_person_id", "artist_membership"."role" AS "A_role", "artist_membership"."instrument" AS "A_instrument", "artist_membership"."start_date" AS "A_start_date", "artist_membership"."end_date" AS "A_end_date", "person"."id" AS "B_id", "person"."name" AS "B_name" FROM "artist_membership" LEFT JOIN "person" ON "artist_membership"."person_id" = "person"."id" WHERE "artist_membership"."artist_id" = $1 ORDER BY "artist_membership"."start_date" ASC, "artist_membership"."id" ASC"#,
+            vec![1i32.into()],
+        ))
+    );
+}
+
+#[test]
+fn get_bands_leaves_out_trashed_artists() {
+    let db = SharedMock::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![jane()]])
+            .append_query_results(vec![vec![(
+                membership(8, Some("2010-01-01")),
+                awesome_band(),
+            )]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/person", routes![get_bands]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/person/artists/6").dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.into_json::<Json>().unwrap()[0]["artist"]["name"],
+        json!("Awesome Band")
+    );
+    assert_eq!(
+        db.transaction_log()[1],
+        Transaction::one(statement(
+            r#"SELECT "artist_membership"."id" AS "A_id", "artist_membership"."artist_id" AS "A_artist_id", "artist_membership"."person_id" AS "A_person_id", "artist_membership"."role" AS "A_role", "artist_membership"."instrument" AS "A_instrument", "artist_membership"."start_date" AS "A_start_date", "artist_membership"."end_date" AS "A_end_date", "artist"."id" AS "B_id", "artist"."name" AS "B_name", "artist"."date_formed" AS "B_date_formed", "artist"."version" AS "B_version", "artist"."deleted_at" AS "B_deleted_at" FROM "artist_membership" LEFT JOIN "artist" ON "artist_membership"."artist_id" = "artist"."id" WHERE "artist_membership"."person_id" = $1 AND "artist"."deleted_at" IS NULL ORDER BY "artist_membership"."start_date" ASC, "artist_membership"."id" ASC"#,
+            vec![6i32.into()],
+        ))
+    );
+}
@@ -0,0 +1,332 @@
+use super::{audited, bearer, insert_audit, plain, signed_in, signed_in_as, statement, SharedMock};
+use crate::api::playlist_api::*;
+use entities::{playlist, playlist_song, sea_orm_active_enums::Role, song};
+use rocket::{http::Status, local::blocking::Client, serde::json::json};
+use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Statement, Transaction};
+
+fn road_trip(user_id: i32) -> playlist::Model {
+    playlist::Model {
+        id: 5,
+        name: "Road trip".to_string(),
+        user_id,
+    }
+}
+
+fn song(id: i32, name: &str, length_secs: i32) -> song::Model {
+    song::Model {
+        id,
+        name: name.to_string(),
+        length_secs,
+        album_id: 7,
+        disc_number: 1,
+        track_number: 1,
+        version: 1,
+        deleted_at: None,
+    }
+}
+
+fn entry(id: i32, song_id: i32, position: i32) -> playlist_song::Model {
+    playlist_song::Model {
+        id,
+        playlist_id: 5,
+        song_id,
+        position,
+    }
+}
+
+/// Expect the given number of positions to be shifted
+fn shifted(db: MockDatabase, entries: usize) -> MockDatabase {
+    db.append_exec_results(
+        (0..entries)
+            .map(|_| MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            })
+            .collect(),
+    )
+}
+
+fn lock_playlist() -> Statement {
+    statement(
+        r#"SELECT "playlist"."id", "playlist"."name", "playlist"."user_id" FROM "playlist" WHERE "playlist"."id" = $1 LIMIT $2 FOR UPDATE"#,
+        vec![5i32.into(), 1u64.into()],
+    )
+}
+
+fn select_entries() -> Statement {
+    statement(
+        r#"SELECT "playlist_song"."id", "playlist_song"."playlist_id", "playlist_song"."song_id", "playlist_song"."position" FROM "playlist_song" WHERE "playlist_song"."playlist_id" = $1 ORDER BY "playlist_song"."position" ASC"#,
+        vec![5i32.into()],
+    )
+}
+
+fn set_position(id: i32, position: i32) -> Statement {
+    statement(
+        r#"UPDATE "playlist_song" SET "position" = $1 WHERE "playlist_song"."id" = $2"#,
+        vec![position.into(), id.into()],
+    )
+}
+
+#[test]
+fn create_playlist_is_curated_by_the_signed_in_user() {
+    let db = SharedMock::new(audited(
+        signed_in_as(MockDatabase::new(DatabaseBackend::Postgres), Role::Viewer)
+            .append_query_results(vec![vec![road_trip(1)]]),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/playlist", routes![create_playlist]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/playlist")
+        .header(bearer())
+        .body(r#"{"name": "Road trip"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(response.into_string().unwrap(), "5");
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"INSERT INTO "playlist" ("name", "user_id") VALUES ($1, $2) RETURNING "id""#,
+                vec!["Road trip".into(), 1i32.into()],
+            ),
+            insert_audit(
+                "playlist",
+                5,
+                "create",
+                None,
+                Some(json!({"id": 5, "name": "Road trip", "user_id": 1})),
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn get_playlist_songs_totals_their_lengths() {
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results(vec![vec![road_trip(1)]])
+        .append_query_results(vec![vec![
+            (entry(1, 4, 0), song(4, "My song", 120)),
+            (entry(2, 6, 2), song(6, "Other song", 95)),
+        ]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/playlist", routes![get_playlist_songs]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/playlist/5/songs").dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.into_json::<rocket::serde::json::Value>().unwrap(),
+        json!({
+            "songs": [
+                {"position": 0, "song": {"id": 4, "name": "My song", "length_secs": 120, "album_id": 7, "disc_number": 1, "track_number": 1, "version": 1, "deleted_at": null}},
+                {"position": 2, "song": {"id": 6, "name": "Other song", "length_secs": 95, "album_id": 7, "disc_number": 1, "track_number": 1, "version": 1, "deleted_at": null}}
+            ],
+            "total_length_secs": 215
+        })
+    );
+}
+
+#[test]
+fn add_song_at_a_position_shifts_the_songs_after_it() {
+    let db = SharedMock::new(shifted(
+        audited(
+            signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+                .append_query_results(vec![vec![road_trip(1)]])
+                .append_query_results(vec![vec![song(6, "Other song", 95)]])
+                .append_query_results(vec![vec![entry(1, 4, 0), entry(2, 4, 1)]])
+                .append_query_results(vec![vec![entry(3, 6, 1)]]),
+            1,
+        ),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/playlist", routes![add_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/playlist/5/songs")
+        .header(bearer())
+        .body(r#"{"song_id": 6, "position": 1}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            lock_playlist(),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."id" = $1 AND "song"."deleted_at" IS NULL LIMIT $2"#,
+                vec![6i32.into(), 1u64.into()],
+            ),
+            select_entries(),
+            statement(
+                r#"INSERT INTO "playlist_song" ("playlist_id", "song_id", "position") VALUES ($1, $2, $3) RETURNING "id""#,
+                vec![5i32.into(), 6i32.into(), 1i32.into()],
+            ),
+            insert_audit(
+                "playlist_song",
+                3,
+                "create",
+                None,
+                Some(json!({"id": 3, "playlist_id": 5, "song_id": 6, "position": 1})),
+                Some(1),
+            ),
+            set_position(2, 2),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn add_song_past_the_end_is_rejected() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![road_trip(1)]])
+        .append_query_results(vec![vec![song(6, "Other song", 95)]])
+        .append_query_results(vec![vec![entry(1, 4, 0)]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/playlist", routes![add_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/playlist/5/songs")
+        .header(bearer())
+        .body(r#"{"song_id": 6, "position": 3}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(
+        response.into_json::<rocket::serde::json::Value>().unwrap()["details"],
+        json!([{"field": "position", "message": "must be between 0 and 1"}])
+    );
+}
+
+#[test]
+fn move_song_shifts_the_songs_in_between() {
+    let db = SharedMock::new(audited(
+        shifted(
+            signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+                .append_query_results(vec![vec![road_trip(1)]])
+                .append_query_results(vec![vec![entry(1, 4, 0), entry(2, 6, 1), entry(3, 8, 2)]]),
+            3,
+        ),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/playlist", routes![move_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .put("/playlist/5/songs/2")
+        .header(bearer())
+        .body(r#"{"position": 0}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            lock_playlist(),
+            select_entries(),
+            set_position(3, 0),
+            set_position(1, 1),
+            set_position(2, 2),
+            insert_audit(
+                "playlist_song",
+                3,
+                "update",
+                Some(json!({"id": 3, "playlist_id": 5, "song_id": 8, "position": 2})),
+                Some(json!({"id": 3, "playlist_id": 5, "song_id": 8, "position": 0})),
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn remove_song_closes_the_gap() {
+    // The delete takes an exec result too
+    let db = SharedMock::new(shifted(
+        audited(
+            signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+                .append_query_results(vec![vec![road_trip(1)]])
+                .append_query_results(vec![vec![entry(1, 4, 0), entry(2, 6, 1), entry(3, 8, 2)]]),
+            1,
+        ),
+        2,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/playlist", routes![remove_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .delete("/playlist/5/songs/1")
+        .header(bearer())
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            lock_playlist(),
+            select_entries(),
+            statement(
+                r#"DELETE FROM "playlist_song" WHERE "playlist_song"."id" = $1"#,
+                vec![2i32.into()],
+            ),
+            insert_audit(
+                "playlist_song",
+                2,
+                "delete",
+                Some(json!({"id": 2, "playlist_id": 5, "song_id": 6, "position": 1})),
+                None,
+                Some(1),
+            ),
+            set_position(3, 1),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn changing_the_playlist_of_another_user_is_forbidden() {
+    let db = SharedMock::new(
+        signed_in_as(MockDatabase::new(DatabaseBackend::Postgres), Role::Editor)
+            .append_query_results(vec![vec![road_trip(2)]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/playlist", routes![delete_playlist]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.delete("/playlist/5").header(bearer()).dispatch();
+
+    assert_eq!(response.status(), Status::Forbidden);
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            lock_playlist(),
+            plain("ROLLBACK"),
+        ]))
+    );
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use super::{audited, bearer, insert_audit, lorem, plain, signed_in, statement, SharedMock};
+use crate::api::{album_api::get_album_by_id, review_api::*};
+use entities::review;
+use rocket::{
+    http::Status,
+    local::blocking::Client,
+    serde::json::{json, Value as Json},
+};
+use sea_orm::{DatabaseBackend, MockDatabase, Transaction, Value};
+
+fn review(id: i32, user_id: i32, body: Option<&str>) -> review::Model {
+    review::Model {
+        id,
+        user_id,
+        album_id: Some(7),
+        song_id: None,
+        rating: 9,
+        body: body.map(str::to_string),
+    }
+}
+
+#[test]
+fn create_album_review_trims_the_text_and_records_it() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![Vec::<review::Model>::new()])
+            .append_query_results(vec![vec![review(5, 1, Some("Great"))]]),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/review", routes![create_album_review]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/review/album/7")
+        .header(bearer())
+        .body(r#"{"rating":9,"body":"  Great  "}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(response.into_string().unwrap(), "5");
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" WHERE "album"."id" = $1 AND "album"."deleted_at" IS NULL LIMIT $2"#,
+                vec![7i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "review"."id", "review"."user_id", "review"."album_id", "review"."song_id", "review"."rating", "review"."body" FROM "review" WHERE "review"."album_id" = $1 AND "review"."user_id" = $2 LIMIT $3"#,
+                vec![7i32.into(), 1i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"INSERT INTO "review" ("user_id", "album_id", "song_id", "rating", "body") VALUES ($1, $2, $3, $4, $5) RETURNING "id", "user_id", "album_id", "song_id", "rating", "body""#,
+                vec![
+                    1i32.into(),
+                    Some(7i32).into(),
+                    Option::<i32>::None.into(),
+                    9i32.into(),
+                    Some("Great").into(),
+                ],
+            ),
+            insert_audit(
+                "review",
+                5,
+                "create",
+                None,
+                Some(json!({
+                    "id": 5,
+                    "user_id": 1,
+                    "album_id": 7,
+                    "song_id": null,
+                    "rating": 9,
+                    "body": "Great"
+                })),
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn update_review_is_reserved_to_its_author_even_for_admins() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![review(5, 2, None)]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/review", routes![update_review]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .put("/review/5")
+        .header(bearer())
+        .body(r#"{"rating":1}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[test]
+fn get_album_by_id_gives_the_average_rating() {
+    let db = SharedMock::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![lorem()]])
+            .append_query_results(vec![vec![BTreeMap::from([
+                ("average_rating".to_string(), Value::Double(Some(8.5))),
+                ("rating_count".to_string(), Value::BigInt(Some(2))),
+            ])]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/album", routes![get_album_by_id]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/album/7").dispatch();
+
+    assert_eq!(response.status(), Status::Found);
+    let album = response.into_json::<Json>().unwrap();
+    assert_eq!(album["name"], json!("Lorem"));
+    assert_eq!(album["average_rating"], json!(8.5));
+    assert_eq!(album["rating_count"], json!(2));
+    assert_eq!(
+        db.transaction_log()[1],
+        Transaction::one(statement(
+            r#"SELECT CAST(AVG("review"."rating") AS double precision) AS "average_rating", COUNT("review"."id") AS "rating_count" FROM "review" WHERE "review"."album_id" = $1 LIMIT $2"#,
+            vec![7i32.into(), 1u64.into()],
+        ))
+    );
+}
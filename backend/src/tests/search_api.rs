@@ -0,0 +1,49 @@
+use crate::api::search_api::*;
+use rocket::{http::Status, local::blocking::Client};
+use sea_orm::{DatabaseBackend, MockDatabase, Value};
+use std::collections::BTreeMap;
+
+#[test]
+fn to_tsquery_matches_word_prefixes() {
+    assert_eq!(to_tsquery("Brown fox"), Some("brown:* & fox:*".to_string()));
+    assert_eq!(to_tsquery("  it's!  "), Some("it:* & s:*".to_string()));
+    assert_eq!(to_tsquery("&|!():*"), None);
+}
+
+#[test]
+fn search_returns_typed_hits() {
+    let row = |kind: &str, id: i32, name: &str, rank: f32| {
+        BTreeMap::from([
+            (
+                "kind".to_string(),
+                Value::String(Some(Box::new(kind.to_string()))),
+            ),
+            ("id".to_string(), Value::Int(Some(id))),
+            (
+                "name".to_string(),
+                Value::String(Some(Box::new(name.to_string()))),
+            ),
+            ("rank".to_string(), Value::Float(Some(rank))),
+        ])
+    };
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results(vec![vec![
+            row("song", 2, "Brown Fox", 0.9),
+            row("album", 1, "Brownies", 0.5),
+        ]])
+        .into_connection();
+
+    let rocket = rocket::build().manage(db).mount("/search", routes![search]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/search?q=brown").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.into_string().unwrap();
+    assert_eq!(
+        body,
+        r#"[{"kind":"song","id":2,"name":"Brown Fox","rank":0.9},{"kind":"album","id":1,"name":"Brownies","rank":0.5}]"#
+    );
+
+    let response = client.get("/search?q=%3F%3F").dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
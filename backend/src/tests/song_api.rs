@@ -1,7 +1,22 @@
-use crate::api::song_api::*;
-use rocket::{local::blocking::Client, Build, Rocket, http::Status};
-use sea_orm::{DatabaseBackend, MockDatabase};
-use entities::song;
+use super::{
+    audited, bearer, if_match, insert_audit, signed_in, statement, temp_storage, SharedMock,
+};
+use crate::api::{
+    artist_api::get_appearances,
+    error::{ErrorBody, FieldError},
+    song_api::*,
+};
+use rocket::{local::blocking::Client, Build, Rocket, http::{ContentType, Header, Status}, serde::json::json};
+use sea_orm::{DatabaseBackend, MockDatabase, Statement, Transaction, Value};
+use chrono::NaiveDate;
+use entities::{
+    album, album_artist_mtm, artist,
+    pagination::Page,
+    review::{RatedSong, Ratings},
+    sea_orm_active_enums::CreditRole,
+    song, song_audio, song_credit,
+};
+use std::{collections::BTreeMap, fs};
 
 // #[ctor::ctor]
 fn setup_backend() -> Rocket<Build> {
@@ -12,8 +27,16 @@ fn setup_backend() -> Rocket<Build> {
                 name: "My song".to_string(),
                 length_secs: 120,
                 album_id: 1,
+                disc_number: 1,
+                track_number: 1,
+                version: 1,
+                deleted_at: None,
             }],
         ])
+        .append_query_results(vec![vec![BTreeMap::from([
+            ("average_rating".to_string(), Value::Double(Some(8.0))),
+            ("rating_count".to_string(), Value::BigInt(Some(2))),
+        ])]])
         .into_connection();
 
     rocket::build().manage(db).mount(
@@ -37,6 +60,7 @@ fn test() {
     let response = req.dispatch();
 
     assert_eq!(response.status(), Status::Found);
+    assert_eq!(response.headers().get_one("ETag"), Some(r#""1""#));
     let json = response.into_json::<song::Model>();
     assert!(json.is_some());
     assert_eq!(json.unwrap(), song::Model {
@@ -44,5 +68,900 @@ fn test() {
         name: "My song".to_string(),
         length_secs: 120,
         album_id: 1,
+        disc_number: 1,
+        track_number: 1,
+        version: 0,
+        deleted_at: None,
     });
 }
+
+#[test]
+fn get_all_songs_paginates() {
+    let db = SharedMock::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![BTreeMap::from([(
+                "num_items".to_string(),
+                Value::BigInt(Some(3)),
+            )])]])
+            .append_query_results(vec![vec![song::Model {
+                id: 3,
+                name: "Jumps Over".to_string(),
+                length_secs: 30,
+                album_id: 1,
+                disc_number: 1,
+                track_number: 1,
+                version: 1,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![vec![BTreeMap::from([
+                ("subject_id".to_string(), Value::Int(Some(3))),
+                ("average_rating".to_string(), Value::Double(Some(7.5))),
+                ("rating_count".to_string(), Value::BigInt(Some(2))),
+            ])]]),
+    );
+
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/song", routes![get_all_songs]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .get("/song/all?sort=length_secs&order=desc&per_page=1&page=2")
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    let page = response.into_json::<Page<RatedSong>>().unwrap();
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(
+        page.items[0].ratings,
+        Ratings {
+            average_rating: Some(7.5),
+            rating_count: 2,
+        }
+    );
+    assert_eq!(page.total, 3);
+    assert_eq!(page.page, 2);
+    assert_eq!(page.num_pages, 3);
+    assert_eq!(
+        page.next.as_deref(),
+        Some("/song/all?sort=length_secs&order=desc&per_page=1&page=3")
+    );
+    assert_eq!(
+        page.prev.as_deref(),
+        Some("/song/all?sort=length_secs&order=desc&per_page=1&page=1")
+    );
+    // The ratings of every song of the page are read at once
+    assert_eq!(
+        db.transaction_log()[2],
+        Transaction::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "review"."song_id" AS "subject_id", CAST(AVG("review"."rating") AS double precision) AS "average_rating", COUNT("review"."id") AS "rating_count" FROM "review" WHERE "review"."song_id" IN ($1) GROUP BY "review"."song_id""#,
+            vec![3i32.into()],
+        )
+    );
+}
+
+#[test]
+fn get_all_songs_breaks_ties_by_id() {
+    let db = SharedMock::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![BTreeMap::from([(
+                "num_items".to_string(),
+                Value::BigInt(Some(2)),
+            )])]])
+            .append_query_results(vec![vec![my_song("Same length", 30)]])
+            .append_query_results(vec![Vec::<BTreeMap<String, Value>>::new()]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/song", routes![get_all_songs]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .get("/song/all?sort=length_secs&per_page=1&page=2")
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        db.transaction_log()[1],
+        Transaction::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."deleted_at" IS NULL ORDER BY "song"."length_secs" ASC, "song"."id" ASC LIMIT $1 OFFSET $2"#,
+            vec![1u64.into(), 1u64.into()],
+        )
+    );
+}
+
+fn my_song(name: &str, length_secs: i32) -> song::Model {
+    song::Model {
+        id: 4,
+        name: name.to_string(),
+        length_secs,
+        album_id: 1,
+        disc_number: 1,
+        track_number: 1,
+        version: 1,
+        deleted_at: None,
+    }
+}
+
+#[test]
+fn patch_song_only_sets_given_fields() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![my_song("My song", 120)]])
+            .append_query_results(vec![vec![song::Model {
+                version: 2,
+                ..my_song("My song", 90)
+            }]]),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/song", routes![patch_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .patch("/song/4")
+        .header(bearer())
+        .header(if_match(1))
+        .body(r#"{"length_secs":90}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.headers().get_one("ETag"), Some(r#""2""#));
+    assert_eq!(
+        response.into_json::<song::Model>().unwrap().length_secs,
+        90
+    );
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            Statement::from_string(DatabaseBackend::Postgres, "BEGIN".to_string()),
+            Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"UPDATE "song" SET "length_secs" = $1, "version" = $2 WHERE "song"."id" = $3 AND "song"."version" = $4 RETURNING "id", "name", "length_secs", "album_id", "disc_number", "track_number", "version", "deleted_at""#,
+                vec![90i32.into(), 2i32.into(), 4i32.into(), 1i32.into()],
+            ),
+            insert_audit(
+                "song",
+                4,
+                "update",
+                Some(json!({"id": 4, "name": "My song", "length_secs": 120, "album_id": 1, "disc_number": 1, "track_number": 1, "version": 1, "deleted_at": null})),
+                Some(json!({"id": 4, "name": "My song", "length_secs": 90, "album_id": 1, "disc_number": 1, "track_number": 1, "version": 2, "deleted_at": null})),
+                Some(1),
+            ),
+            Statement::from_string(DatabaseBackend::Postgres, "COMMIT".to_string()),
+        ]))
+    );
+}
+
+#[test]
+fn patch_song_validates_the_merged_song() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![my_song("My song", 120)]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/song", routes![patch_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .patch("/song/4")
+        .header(bearer())
+        .header(if_match(1))
+        .body(r#"{"name":""}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    let body = response.into_json::<ErrorBody>().unwrap();
+    assert_eq!(body.details, vec![FieldError::new("name", "must not be empty")]);
+}
+
+#[test]
+fn patch_song_returns_not_found() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![Vec::<song::Model>::new()])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/song", routes![patch_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .patch("/song/4")
+        .header(bearer())
+        .body(r#"{"name":"x"}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[test]
+fn patch_song_requires_the_current_version() {
+    let db = signed_in(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![my_song("My song", 120)]]),
+    )
+    .append_query_results(vec![vec![my_song("My song", 120)]])
+    .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/song", routes![patch_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .patch("/song/4")
+        .header(bearer())
+        .body(r#"{"name":"x"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::PreconditionRequired);
+
+    let response = client
+        .patch("/song/4")
+        .header(bearer())
+        .header(if_match(0))
+        .body(r#"{"name":"x"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::PreconditionFailed);
+    let body = response.into_json::<ErrorBody>().unwrap();
+    assert_eq!(body.code, "precondition_failed");
+}
+
+#[test]
+fn create_song_rejects_a_taken_track() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![album::Model {
+            id: 1,
+            name: "Lorem".to_string(),
+            date_published: chrono::NaiveDate::from_ymd_opt(2015, 1, 1).unwrap(),
+            version: 1,
+            deleted_at: None,
+        }]])
+        .append_query_results(vec![vec![my_song("My song", 120)]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/song", routes![create_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/song/")
+        .header(bearer())
+        .body(r#"{"name":"Other song","length_secs":95,"album_id":1,"track_number":1}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    let body = response.into_json::<ErrorBody>().unwrap();
+    assert_eq!(
+        body.details,
+        vec![FieldError::new("track_number", "is already taken on this disc")]
+    );
+}
+
+#[test]
+fn create_song_without_a_track_comes_after_the_last_one_of_its_disc() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![album::Model {
+                id: 1,
+                name: "Lorem".to_string(),
+                date_published: chrono::NaiveDate::from_ymd_opt(2015, 1, 1).unwrap(),
+                version: 1,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![vec![my_song("My song", 120)]])
+            .append_query_results(vec![Vec::<song::Model>::new()])
+            .append_query_results(vec![vec![song::Model {
+                id: 5,
+                ..my_song("Other song", 95)
+            }]]),
+        1,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/song", routes![create_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/song/")
+        .header(bearer())
+        .body(r#"{"name":"Other song","length_secs":95,"album_id":1}"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(response.into_string().unwrap(), "5");
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            Statement::from_string(DatabaseBackend::Postgres, "BEGIN".to_string()),
+            statement(
+                r#"INSERT INTO "song" ("name", "length_secs", "album_id", "disc_number", "track_number") VALUES ($1, $2, $3, $4, $5) RETURNING "id""#,
+                vec!["Other song".into(), 95i32.into(), 1i32.into(), 1i32.into(), 2i32.into()],
+            ),
+            insert_audit(
+                "song",
+                5,
+                "create",
+                None,
+                Some(json!({
+                    "id": 5,
+                    "name": "Other song",
+                    "length_secs": 95,
+                    "album_id": 1,
+                    "disc_number": 1,
+                    "track_number": 2,
+                    "version": 1,
+                    "deleted_at": null
+                })),
+                Some(1),
+            ),
+            Statement::from_string(DatabaseBackend::Postgres, "COMMIT".to_string()),
+        ]))
+    );
+}
+
+/// A FLAC file of `secs` seconds of 44.1 kHz stereo, without any frame, tagged with `comments`
+fn flac(comments: &[&str], secs: u64) -> Vec<u8> {
+    let mut flac = b"fLaC".to_vec();
+    flac.extend_from_slice(&[0, 0, 0, 34]);
+    flac.extend_from_slice(&4096u16.to_be_bytes());
+    flac.extend_from_slice(&4096u16.to_be_bytes());
+    flac.extend_from_slice(&[0; 6]);
+    // Sample rate on 20 bits, channels - 1 on 3, bits per sample - 1 on 5, samples on 36
+    let stream = (44100u64 << 44) | (1 << 41) | (15 << 36) | (secs * 44100);
+    flac.extend_from_slice(&stream.to_be_bytes());
+    flac.extend_from_slice(&[0; 16]);
+
+    let mut block = 0u32.to_le_bytes().to_vec();
+    block.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for comment in comments {
+        block.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        block.extend_from_slice(comment.as_bytes());
+    }
+    flac.push(0x84);
+    flac.extend_from_slice(&(block.len() as u32).to_be_bytes()[1..]);
+    flac.extend_from_slice(&block);
+    flac
+}
+
+/// Body of a form sending each file in a `files` field, along with its content type
+fn multipart(files: &[&[u8]]) -> (ContentType, Vec<u8>) {
+    let mut body = Vec::new();
+    for (index, file) in files.iter().enumerate() {
+        body.extend_from_slice(format!("--X\r\nContent-Disposition: form-data; name=\"files\"; filename=\"{}.flac\"\r\nContent-Type: audio/flac\r\n\r\n", index).as_bytes());
+        body.extend_from_slice(file);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(b"--X--\r\n");
+    (
+        ContentType::new("multipart", "form-data").with_params(("boundary", "X")),
+        body,
+    )
+}
+
+#[test]
+fn upload_songs_creates_the_songs_of_the_tags() {
+    let (root, storage) = temp_storage();
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![artist::Model {
+                id: 1,
+                name: "Awesome Band".to_string(),
+                date_formed: chrono::NaiveDate::from_ymd_opt(2010, 1, 1).unwrap(),
+                version: 1,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![vec![album_artist_mtm::Model {
+                id: 3,
+                album_id: 7,
+                artist_id: 1,
+                position: 0,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![vec![album::Model {
+                id: 7,
+                name: "Lorem".to_string(),
+                date_published: chrono::NaiveDate::from_ymd_opt(2015, 1, 1).unwrap(),
+                version: 1,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![Vec::<song::Model>::new()])
+            .append_query_results(vec![Vec::<song::Model>::new()])
+            .append_query_results(vec![vec![song::Model {
+                id: 4,
+                name: "My song".to_string(),
+                length_secs: 120,
+                album_id: 7,
+                disc_number: 1,
+                track_number: 3,
+                version: 1,
+                deleted_at: None,
+            }]])
+            .append_query_results(vec![Vec::<song_audio::Model>::new()])
+            .append_query_results(vec![vec![song_audio::Model {
+                id: 5,
+                song_id: 4,
+                key: "k3y".to_string(),
+                content_type: "audio/flac".to_string(),
+                size: 100,
+            }]]),
+        2,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .manage(storage)
+        .mount("/song", routes![upload_songs]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let audio = flac(
+        &[
+            "ARTIST=Awesome Band",
+            "ALBUM=Lorem",
+            "TITLE=My song",
+            "TRACKNUMBER=3",
+            "DATE=2015-01-01",
+        ],
+        120,
+    );
+    let (content_type, body) = multipart(&[&audio]);
+    let response = client
+        .post("/song/upload")
+        .header(bearer())
+        .header(content_type)
+        .body(body)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(
+        response.into_string().unwrap(),
+        r#"{"committed":true,"rows":[{"row":1,"status":"created","artist_id":1,"album_id":7,"song_id":4}]}"#
+    );
+    // The key of the audio file is random, and names it
+    let files: Vec<_> = fs::read_dir(root.join("audio")).unwrap().collect();
+    assert_eq!(files.len(), 1);
+    let file = files[0].as_ref().unwrap().path();
+    let key = file.file_name().unwrap().to_str().unwrap();
+    assert_eq!(fs::read(&file).unwrap(), audio);
+
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            Statement::from_string(DatabaseBackend::Postgres, "BEGIN".to_string()),
+            statement(
+                r#"SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" WHERE "artist"."deleted_at" IS NULL AND "artist"."name" = $1 LIMIT $2"#,
+                vec!["Awesome Band".into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" WHERE "album_artist_mtm"."deleted_at" IS NULL AND "album_artist_mtm"."artist_id" = $1"#,
+                vec![1i32.into()],
+            ),
+            statement(
+                r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" WHERE "album"."deleted_at" IS NULL AND "album"."id" IN ($1) AND "album"."name" = $2 LIMIT $3"#,
+                vec![7i32.into(), "Lorem".into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."deleted_at" IS NULL AND "song"."album_id" = $1 AND "song"."name" = $2 LIMIT $3"#,
+                vec![7i32.into(), "My song".into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."deleted_at" IS NULL AND "song"."album_id" = $1 AND "song"."disc_number" = $2 AND "song"."track_number" = $3 LIMIT $4"#,
+                vec![7i32.into(), 1i32.into(), 3i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"INSERT INTO "song" ("name", "length_secs", "album_id", "disc_number", "track_number") VALUES ($1, $2, $3, $4, $5) RETURNING "id""#,
+                vec![
+                    "My song".into(),
+                    120i32.into(),
+                    7i32.into(),
+                    1i32.into(),
+                    3i32.into(),
+                ],
+            ),
+            insert_audit(
+                "song",
+                4,
+                "create",
+                None,
+                Some(
+                    json!({"id": 4, "name": "My song", "length_secs": 120, "album_id": 7, "disc_number": 1, "track_number": 3, "version": 1, "deleted_at": null})
+                ),
+                Some(1),
+            ),
+            statement(
+                r#"SELECT "song_audio"."id", "song_audio"."song_id", "song_audio"."key", "song_audio"."content_type", "song_audio"."size" FROM "song_audio" WHERE "song_audio"."song_id" = $1 LIMIT $2 FOR UPDATE"#,
+                vec![4i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"INSERT INTO "song_audio" ("song_id", "key", "content_type", "size") VALUES ($1, $2, $3, $4) RETURNING "id", "song_id", "key", "content_type", "size""#,
+                vec![
+                    4i32.into(),
+                    key.into(),
+                    "audio/flac".into(),
+                    (audio.len() as i64).into(),
+                ],
+            ),
+            insert_audit(
+                "song_audio",
+                5,
+                "create",
+                None,
+                Some(json!({"id": 5, "song_id": 4, "key": "k3y", "content_type": "audio/flac", "size": 100})),
+                Some(1),
+            ),
+            Statement::from_string(DatabaseBackend::Postgres, "COMMIT".to_string()),
+        ]))
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn upload_songs_rejects_files_without_tags_and_stores_nothing() {
+    let (root, storage) = temp_storage();
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres)).into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .manage(storage)
+        .mount("/song", routes![upload_songs]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let untagged = flac(&[], 120);
+    let (content_type, body) = multipart(&[&untagged, b"not a song"]);
+    let response = client
+        .post("/song/upload")
+        .header(bearer())
+        .header(content_type)
+        .body(body)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(
+        response.into_json::<rocket::serde::json::Value>().unwrap()["rows"],
+        json!([
+            {"row": 1, "status": "rejected", "artist_id": null, "album_id": null, "song_id": null, "errors": [
+                {"field": "artist", "message": "is missing from the tags"},
+                {"field": "album", "message": "is missing from the tags"},
+                {"field": "song", "message": "is missing from the tags"},
+            ]},
+            {"row": 2, "status": "rejected", "artist_id": null, "album_id": null, "song_id": null, "errors": [
+                {"field": "file", "message": "must be an MP3, FLAC or Ogg Vorbis file"},
+            ]},
+        ])
+    );
+    assert!(!root.exists());
+}
+
+/// Mock finding song 4 and its audio, stored under `k3y`, for each of `requests`
+fn streamed(requests: usize) -> MockDatabase {
+    (0..requests).fold(MockDatabase::new(DatabaseBackend::Postgres), |db, _| {
+        db.append_query_results(vec![vec![my_song("My song", 120)]])
+            .append_query_results(vec![vec![song_audio::Model {
+                id: 5,
+                song_id: 4,
+                key: "k3y".to_string(),
+                content_type: "audio/flac".to_string(),
+                size: 10,
+            }]])
+    })
+}
+
+#[test]
+fn stream_song_sends_the_requested_range() {
+    let (root, storage) = temp_storage();
+    fs::create_dir_all(root.join("audio")).unwrap();
+    fs::write(root.join("audio/k3y"), b"0123456789").unwrap();
+    let rocket = rocket::build()
+        .manage(streamed(4).into_connection())
+        .manage(storage)
+        .mount("/song", routes![stream_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/song/4/stream").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.headers().get_one("Accept-Ranges"), Some("bytes"));
+    assert_eq!(response.content_type(), Some(ContentType::new("audio", "flac")));
+    assert_eq!(response.into_bytes().unwrap(), b"0123456789");
+
+    let response = client
+        .get("/song/4/stream")
+        .header(Header::new("Range", "bytes=2-5"))
+        .dispatch();
+    assert_eq!(response.status(), Status::PartialContent);
+    assert_eq!(response.headers().get_one("Content-Range"), Some("bytes 2-5/10"));
+    assert_eq!(response.content_type(), Some(ContentType::new("audio", "flac")));
+    assert_eq!(response.into_bytes().unwrap(), b"2345");
+
+    let response = client
+        .get("/song/4/stream")
+        .header(Header::new("Range", "bytes=-3"))
+        .dispatch();
+    assert_eq!(response.status(), Status::PartialContent);
+    assert_eq!(response.headers().get_one("Content-Range"), Some("bytes 7-9/10"));
+    assert_eq!(response.into_bytes().unwrap(), b"789");
+
+    // However large the range, it is sent as the file is read rather than loaded in memory first
+    let response = client
+        .get("/song/4/stream")
+        .header(Header::new("Range", "bytes=0-104857599"))
+        .dispatch();
+    assert_eq!(response.status(), Status::PartialContent);
+    assert_eq!(response.headers().get_one("Content-Range"), Some("bytes 0-9/10"));
+    assert_eq!(response.body().preset_size(), None);
+    assert_eq!(response.into_bytes().unwrap(), b"0123456789");
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn stream_song_answers_conditional_and_unsatisfiable_requests() {
+    let (root, storage) = temp_storage();
+    fs::create_dir_all(root.join("audio")).unwrap();
+    fs::write(root.join("audio/k3y"), b"0123456789").unwrap();
+    let rocket = rocket::build()
+        .manage(streamed(4).into_connection())
+        .manage(storage)
+        .mount("/song", routes![stream_song]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .get("/song/4/stream")
+        .header(Header::new("If-None-Match", r#""k3y""#))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotModified);
+
+    let response = client
+        .get("/song/4/stream")
+        .header(Header::new("Range", "bytes=10-"))
+        .dispatch();
+    assert_eq!(response.status(), Status::RangeNotSatisfiable);
+    assert_eq!(response.headers().get_one("Content-Range"), Some("bytes */10"));
+
+    let response = client
+        .get("/song/4/stream")
+        .header(Header::new("Range", "bytes=18446744073709551000-"))
+        .dispatch();
+    assert_eq!(response.status(), Status::RangeNotSatisfiable);
+
+    // The range of a replaced file would not match what the client already has
+    let response = client
+        .get("/song/4/stream")
+        .header(Header::new("Range", "bytes=2-5"))
+        .header(Header::new("If-Range", r#""0ld""#))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_bytes().unwrap(), b"0123456789");
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+fn band(id: i32, name: &str) -> artist::Model {
+    artist::Model {
+        id,
+        name: name.to_string(),
+        date_formed: NaiveDate::from_ymd_opt(2010, 1, 1).unwrap(),
+        version: 1,
+        deleted_at: None,
+    }
+}
+
+fn credit(
+    id: i32,
+    artist_id: i32,
+    role: CreditRole,
+    position: i32,
+    join_phrase: Option<&str>,
+) -> song_credit::Model {
+    song_credit::Model {
+        id,
+        song_id: 4,
+        artist_id,
+        role,
+        position,
+        join_phrase: join_phrase.map(str::to_string),
+    }
+}
+
+#[test]
+fn set_credits_only_writes_the_credits_that_change() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![my_song("My song", 120)]])
+            .append_query_results(vec![vec![band(2, "Guest"), band(3, "Producer")]])
+            .append_query_results(vec![vec![
+                credit(20, 1, CreditRole::Composer, 0, None),
+                credit(21, 2, CreditRole::Featured, 1, Some("feat.")),
+                credit(22, 3, CreditRole::Producer, 2, None),
+            ]])
+            .append_query_results(vec![vec![credit(
+                21,
+                2,
+                CreditRole::Featured,
+                0,
+                Some("feat."),
+            )]])
+            .append_query_results(vec![vec![credit(
+                22,
+                3,
+                CreditRole::Producer,
+                1,
+                Some("prod. by"),
+            )]])
+            .append_query_results(vec![vec![credit(23, 3, CreditRole::Remixer, 2, None)]])
+            .append_query_results(vec![vec![
+                (
+                    credit(21, 2, CreditRole::Featured, 0, Some("feat.")),
+                    band(2, "Guest"),
+                ),
+                (
+                    credit(22, 3, CreditRole::Producer, 1, Some("prod. by")),
+                    band(3, "Producer"),
+                ),
+                (
+                    credit(23, 3, CreditRole::Remixer, 2, None),
+                    band(3, "Producer"),
+                ),
+            ]]),
+        5,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/song", routes![set_credits]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .put("/song/4/credits")
+        .header(bearer())
+        .body(r#"[{"artist_id":2,"role":"Featured","join_phrase":" feat. "},{"artist_id":3,"role":"Producer","join_phrase":"prod. by"},{"artist_id":3,"role":"Remixer","join_phrase":" "}]"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    let credits = response.into_json::<rocket::serde::json::Value>().unwrap();
+    assert_eq!(credits[0]["artist"]["name"], json!("Guest"));
+    assert_eq!(credits[2]["credit"]["role"], json!("Remixer"));
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            Statement::from_string(DatabaseBackend::Postgres, "BEGIN".to_string()),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."id" = $1 AND "song"."deleted_at" IS NULL LIMIT $2 FOR UPDATE"#,
+                vec![4i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "artist"."id", "artist"."name", "artist"."date_formed", "artist"."version", "artist"."deleted_at" FROM "artist" WHERE "artist"."deleted_at" IS NULL AND "artist"."id" IN ($1, $2)"#,
+                vec![2i32.into(), 3i32.into()],
+            ),
+            statement(
+                r#"SELECT "song_credit"."id", "song_credit"."song_id", "song_credit"."artist_id", CAST("song_credit"."role" AS text), "song_credit"."position", "song_credit"."join_phrase" FROM "song_credit" WHERE "song_credit"."song_id" = $1"#,
+                vec![4i32.into()],
+            ),
+            statement(
+                r#"DELETE FROM "song_credit" WHERE "song_credit"."id" = $1"#,
+                vec![20i32.into()],
+            ),
+            insert_audit(
+                "song_credit",
+                20,
+                "delete",
+                Some(
+                    json!({"id": 20, "song_id": 4, "artist_id": 1, "role": "Composer", "position": 0, "join_phrase": null})
+                ),
+                None,
+                Some(1),
+            ),
+            statement(
+                r#"UPDATE "song_credit" SET "position" = $1, "join_phrase" = $2 WHERE "song_credit"."id" = $3 RETURNING "id", "song_id", "artist_id", CAST("role" AS text), "position", "join_phrase""#,
+                vec![0i32.into(), Some("feat.").into(), 21i32.into()],
+            ),
+            insert_audit(
+                "song_credit",
+                21,
+                "update",
+                Some(
+                    json!({"id": 21, "song_id": 4, "artist_id": 2, "role": "Featured", "position": 1, "join_phrase": "feat."})
+                ),
+                Some(
+                    json!({"id": 21, "song_id": 4, "artist_id": 2, "role": "Featured", "position": 0, "join_phrase": "feat."})
+                ),
+                Some(1),
+            ),
+            statement(
+                r#"UPDATE "song_credit" SET "position" = $1, "join_phrase" = $2 WHERE "song_credit"."id" = $3 RETURNING "id", "song_id", "artist_id", CAST("role" AS text), "position", "join_phrase""#,
+                vec![1i32.into(), Some("prod. by").into(), 22i32.into()],
+            ),
+            insert_audit(
+                "song_credit",
+                22,
+                "update",
+                Some(
+                    json!({"id": 22, "song_id": 4, "artist_id": 3, "role": "Producer", "position": 2, "join_phrase": null})
+                ),
+                Some(
+                    json!({"id": 22, "song_id": 4, "artist_id": 3, "role": "Producer", "position": 1, "join_phrase": "prod. by"})
+                ),
+                Some(1),
+            ),
+            statement(
+                r#"INSERT INTO "song_credit" ("song_id", "artist_id", "role", "position", "join_phrase") VALUES ($1, $2, CAST($3 AS credit_role), $4, $5) RETURNING "id""#,
+                vec![
+                    4i32.into(),
+                    3i32.into(),
+                    "remixer".into(),
+                    2i32.into(),
+                    Option::<String>::None.into()
+                ],
+            ),
+            insert_audit(
+                "song_credit",
+                23,
+                "create",
+                None,
+                Some(
+                    json!({"id": 23, "song_id": 4, "artist_id": 3, "role": "Remixer", "position": 2, "join_phrase": null})
+                ),
+                Some(1),
+            ),
+            statement(
+                r#"SELECT "song_credit"."id" AS "A_id", "song_credit"."song_id" AS "A_song_id", "song_credit"."artist_id" AS "A_artist_id", CAST("song_credit"."role" AS text) AS "A_role", "song_credit"."position" AS "A_position", "song_credit"."join_phrase" AS "A_join_phrase", "artist"."id" AS "B_id", "artist"."name" AS "B_name", "artist"."date_formed" AS "B_date_formed", "artist"."version" AS "B_version", "artist"."deleted_at" AS "B_deleted_at" FROM "song_credit" LEFT JOIN "artist" ON "song_credit"."artist_id" = "artist"."id" WHERE "song_credit"."song_id" = $1 AND "artist"."deleted_at" IS NULL ORDER BY "song_credit"."position" ASC, "song_credit"."id" ASC"#,
+                vec![4i32.into()],
+            ),
+            Statement::from_string(DatabaseBackend::Postgres, "COMMIT".to_string()),
+        ]))
+    );
+}
+
+#[test]
+fn set_credits_rejects_an_artist_credited_twice_in_a_role() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![my_song("My song", 120)]])
+        .into_connection();
+    let rocket = rocket::build()
+        .manage(db)
+        .mount("/song", routes![set_credits]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .put("/song/4/credits")
+        .header(bearer())
+        .body(r#"[{"artist_id":2,"role":"Featured"},{"artist_id":2,"role":"Composer"},{"artist_id":2,"role":"Featured"}]"#)
+        .dispatch();
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(
+        response.into_json::<ErrorBody>().unwrap().details,
+        vec![FieldError::new(
+            "artist_id",
+            "must not be credited twice in the same role"
+        )]
+    );
+}
+
+#[test]
+fn get_appearances_leaves_out_trashed_songs() {
+    let db = SharedMock::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![band(2, "Guest")]])
+            .append_query_results(vec![vec![(
+                credit(21, 2, CreditRole::Featured, 0, Some("feat.")),
+                my_song("My song", 120),
+            )]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/artist", routes![get_appearances]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.get("/artist/appearances/2").dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    let appearances = response.into_json::<rocket::serde::json::Value>().unwrap();
+    assert_eq!(appearances[0]["song"]["name"], json!("My song"));
+    assert_eq!(appearances[0]["credit"]["join_phrase"], json!("feat."));
+    assert_eq!(
+        db.transaction_log()[1],
+        Transaction::one(statement(
+            r#"SELECT "song_credit"."id" AS "A_id", "song_credit"."song_id" AS "A_song_id", "song_credit"."artist_id" AS "A_artist_id", CAST("song_credit"."role" AS text) AS "A_role", "song_credit"."position" AS "A_position", "song_credit"."join_phrase" AS "A_join_phrase", "song"."id" AS "B_id", "song"."name" AS "B_name", "song"."length_secs" AS "B_length_secs", "song"."album_id" AS "B_album_id", "song"."disc_number" AS "B_disc_number", "song"."track_number" AS "B_track_number", "song"."version" AS "B_version", "song"."deleted_at" AS "B_deleted_at" FROM "song_credit" LEFT JOIN "song" ON "song_credit"."song_id" = "song"."id" WHERE "song_credit"."artist_id" = $1 AND "song"."deleted_at" IS NULL ORDER BY "song"."album_id" ASC, "song"."disc_number" ASC, "song"."track_number" ASC, "song_credit"."position" ASC"#,
+            vec![2i32.into()],
+        ))
+    );
+}
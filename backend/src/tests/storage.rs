@@ -0,0 +1,84 @@
+use super::temp_storage;
+use crate::api::storage::Storage;
+use rocket::tokio::io::AsyncReadExt;
+use std::fs;
+
+#[rocket::async_test]
+async fn local_storage_puts_gets_and_deletes_files() {
+    let (root, storage) = temp_storage();
+
+    storage.put("covers/abc/original", b"cover").await.unwrap();
+    assert_eq!(
+        fs::read(root.join("covers/abc/original")).unwrap(),
+        b"cover"
+    );
+    assert_eq!(
+        storage.get("covers/abc/original").await.unwrap(),
+        Some(b"cover".to_vec())
+    );
+    let mut opened = Vec::new();
+    let mut file = storage.open("covers/abc/original").await.unwrap().unwrap();
+    file.read_to_end(&mut opened).await.unwrap();
+    assert_eq!(opened, b"cover");
+
+    storage.delete("covers/abc/original").await.unwrap();
+    assert_eq!(storage.get("covers/abc/original").await.unwrap(), None);
+    assert!(storage.open("covers/abc/original").await.unwrap().is_none());
+    // Deleting what is not there is not an error
+    storage.delete("covers/abc/original").await.unwrap();
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[rocket::async_test]
+async fn local_storage_copies_local_files() {
+    let (root, storage) = temp_storage();
+    let (source, _) = temp_storage();
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("upload"), b"audio").unwrap();
+
+    storage
+        .put_file("audio/abc", &source.join("upload"))
+        .await
+        .unwrap();
+    assert_eq!(fs::read(root.join("audio/abc")).unwrap(), b"audio");
+    // The uploaded file is left for Rocket to remove
+    assert!(source.join("upload").exists());
+
+    fs::remove_dir_all(root).unwrap();
+    fs::remove_dir_all(source).unwrap();
+}
+
+#[rocket::async_test]
+async fn local_storage_opens_ranges_of_files() {
+    let (root, storage) = temp_storage();
+
+    storage.put("audio/abc", b"0123456789").await.unwrap();
+    assert_eq!(
+        read_range(&*storage, "audio/abc", 2, 4).await,
+        Some(b"2345".to_vec())
+    );
+    // Ranges stop at the end of the file
+    assert_eq!(
+        read_range(&*storage, "audio/abc", 8, 4).await,
+        Some(b"89".to_vec())
+    );
+    assert_eq!(read_range(&*storage, "audio/def", 0, 4).await, None);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+async fn read_range(storage: &dyn Storage, key: &str, start: u64, length: u64) -> Option<Vec<u8>> {
+    let mut file = storage.open_range(key, start, length).await.unwrap()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).await.unwrap();
+    Some(bytes)
+}
+
+#[rocket::async_test]
+async fn local_storage_keys_stay_inside_its_directory() {
+    let (_, storage) = temp_storage();
+
+    assert!(storage.put("../outside", b"x").await.is_err());
+    assert!(storage.get("/etc/passwd").await.is_err());
+}
@@ -0,0 +1,321 @@
+use super::{audited, bearer, insert_audit, plain, signed_in, statement, temp_storage, SharedMock};
+use crate::api::trash_api::*;
+use chrono::{NaiveDate, NaiveDateTime};
+use entities::{album, album_artist_mtm, album_cover, artist, song, song_audio};
+use rocket::{http::Status, local::blocking::Client, serde::json::json};
+use sea_orm::{DatabaseBackend, MockDatabase, Transaction, Value};
+use std::{collections::BTreeMap, fs};
+
+fn deleted_at() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2026, 10, 1)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+}
+
+#[test]
+fn trash_lists_entries_of_the_given_kind() {
+    let row = |kind: &str, id: i32, name: &str| {
+        BTreeMap::from([
+            (
+                "kind".to_string(),
+                Value::String(Some(Box::new(kind.to_string()))),
+            ),
+            ("id".to_string(), Value::Int(Some(id))),
+            (
+                "name".to_string(),
+                Value::String(Some(Box::new(name.to_string()))),
+            ),
+            (
+                "deleted_at".to_string(),
+                Value::ChronoDateTime(Some(Box::new(deleted_at()))),
+            ),
+        ])
+    };
+    let db = SharedMock::new(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![row("album", 7, "Lorem")]]),
+    );
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/trash", routes![get_trash]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .get("/trash?kind=album&limit=10")
+        .header(bearer())
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.into_string().unwrap(),
+        r#"[{"kind":"album","id":7,"name":"Lorem","deleted_at":"2026-10-01T12:00:00"}]"#
+    );
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::one(statement(
+            r#"SELECT 'album' AS kind, "id", "name", "deleted_at"
+                FROM "album"
+                WHERE "deleted_at" IS NOT NULL ORDER BY deleted_at DESC, kind, id LIMIT $1"#,
+            vec![10i64.into()],
+        )))
+    );
+}
+
+#[test]
+fn restore_artist_brings_back_the_albums_trashed_with_it() {
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![artist::Model {
+                id: 1,
+                name: "Awesome Band".to_string(),
+                date_formed: NaiveDate::from_ymd_opt(2010, 1, 1).unwrap(),
+                version: 1,
+                deleted_at: Some(deleted_at()),
+            }]])
+            .append_query_results(vec![vec![album_artist_mtm::Model {
+                id: 3,
+                album_id: 7,
+                artist_id: 1,
+                position: 0,
+                deleted_at: Some(deleted_at()),
+            }]])
+            .append_query_results(vec![vec![album::Model {
+                id: 7,
+                name: "Lorem".to_string(),
+                date_published: NaiveDate::from_ymd_opt(2015, 1, 1).unwrap(),
+                version: 1,
+                deleted_at: Some(deleted_at()),
+            }]])
+            .append_query_results(vec![vec![song::Model {
+                id: 4,
+                name: "Ipsum".to_string(),
+                length_secs: 120,
+                album_id: 7,
+                disc_number: 1,
+                track_number: 1,
+                version: 1,
+                deleted_at: Some(deleted_at()),
+            }]])
+            .append_query_results(vec![Vec::<album_artist_mtm::Model>::new()]),
+        9,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .mount("/trash", routes![restore]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/trash/artist/1/restore")
+        .header(bearer())
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    let at = || Value::from(deleted_at());
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"UPDATE "artist" SET "deleted_at" = $1 WHERE "artist"."id" = $2"#,
+                vec![Option::<NaiveDateTime>::None.into(), 1i32.into()],
+            ),
+            insert_audit(
+                "artist",
+                1,
+                "update",
+                Some(json!({
+                    "id": 1,
+                    "name": "Awesome Band",
+                    "date_formed": "2010-01-01",
+                    "version": 1,
+                    "deleted_at": "2026-10-01T12:00:00"
+                })),
+                Some(json!({
+                    "id": 1,
+                    "name": "Awesome Band",
+                    "date_formed": "2010-01-01",
+                    "version": 1,
+                    "deleted_at": null
+                })),
+                Some(1),
+            ),
+            statement(
+                r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" INNER JOIN "album" ON "album_artist_mtm"."album_id" = "album"."id" WHERE "album_artist_mtm"."artist_id" = $1 AND ("album_artist_mtm"."deleted_at" = $2 OR (("album_artist_mtm"."deleted_at" IS NOT NULL) AND ("album"."deleted_at" IS NULL)))"#,
+                vec![1i32.into(), at()],
+            ),
+            statement(
+                r#"UPDATE "album_artist_mtm" SET "deleted_at" = $1 WHERE "album_artist_mtm"."id" IN ($2)"#,
+                vec![Option::<NaiveDateTime>::None.into(), 3i32.into()],
+            ),
+            insert_audit(
+                "album_artist_mtm",
+                3,
+                "update",
+                Some(
+                    json!({"id": 3, "album_id": 7, "artist_id": 1, "position": 0, "deleted_at": "2026-10-01T12:00:00"})
+                ),
+                Some(json!({"id": 3, "album_id": 7, "artist_id": 1, "position": 0, "deleted_at": null})),
+                Some(1),
+            ),
+            statement(
+                r#"SELECT "album"."id", "album"."name", "album"."date_published", "album"."version", "album"."deleted_at" FROM "album" WHERE "album"."id" IN ($1) AND "album"."deleted_at" = $2"#,
+                vec![7i32.into(), at()],
+            ),
+            statement(
+                r#"UPDATE "album" SET "deleted_at" = $1 WHERE "album"."id" = $2"#,
+                vec![Option::<NaiveDateTime>::None.into(), 7i32.into()],
+            ),
+            insert_audit(
+                "album",
+                7,
+                "update",
+                Some(
+                    json!({"id": 7, "name": "Lorem", "date_published": "2015-01-01", "version": 1, "deleted_at": "2026-10-01T12:00:00"})
+                ),
+                Some(
+                    json!({"id": 7, "name": "Lorem", "date_published": "2015-01-01", "version": 1, "deleted_at": null})
+                ),
+                Some(1),
+            ),
+            statement(
+                r#"SELECT "song"."id", "song"."name", "song"."length_secs", "song"."album_id", "song"."disc_number", "song"."track_number", "song"."version", "song"."deleted_at" FROM "song" WHERE "song"."album_id" = $1 AND "song"."deleted_at" = $2"#,
+                vec![7i32.into(), at()],
+            ),
+            statement(
+                r#"UPDATE "song" SET "deleted_at" = $1 WHERE "song"."id" IN ($2)"#,
+                vec![Option::<NaiveDateTime>::None.into(), 4i32.into()],
+            ),
+            insert_audit(
+                "song",
+                4,
+                "update",
+                Some(
+                    json!({"id": 4, "name": "Ipsum", "length_secs": 120, "album_id": 7, "disc_number": 1, "track_number": 1, "version": 1, "deleted_at": "2026-10-01T12:00:00"})
+                ),
+                Some(
+                    json!({"id": 4, "name": "Ipsum", "length_secs": 120, "album_id": 7, "disc_number": 1, "track_number": 1, "version": 1, "deleted_at": null})
+                ),
+                Some(1),
+            ),
+            // Links to artists still in the trash stay there
+            statement(
+                r#"SELECT "album_artist_mtm"."id", "album_artist_mtm"."album_id", "album_artist_mtm"."artist_id", "album_artist_mtm"."position", "album_artist_mtm"."deleted_at" FROM "album_artist_mtm" INNER JOIN "artist" ON "album_artist_mtm"."artist_id" = "artist"."id" WHERE "album_artist_mtm"."album_id" = $1 AND "album_artist_mtm"."deleted_at" = $2 AND "artist"."deleted_at" IS NULL"#,
+                vec![7i32.into(), at()],
+            ),
+            statement(
+                r#"UPDATE "album_artist_mtm" SET "deleted_at" = $1 WHERE $2 = $3"#,
+                vec![Option::<NaiveDateTime>::None.into(), 1i32.into(), 2i32.into()],
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+}
+
+#[test]
+fn restoring_a_song_of_a_trashed_album_conflicts() {
+    let db = signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+        .append_query_results(vec![vec![song::Model {
+            id: 4,
+            name: "My song".to_string(),
+            length_secs: 120,
+            album_id: 7,
+            disc_number: 1,
+            track_number: 1,
+            version: 1,
+            deleted_at: Some(deleted_at()),
+        }]])
+        .append_query_results(vec![Vec::<album::Model>::new()])
+        .into_connection();
+    let rocket = rocket::build().manage(db).mount("/trash", routes![restore]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/trash/song/4/restore")
+        .header(bearer())
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Conflict);
+}
+
+#[test]
+fn purge_album_removes_the_files_of_its_cover_and_songs() {
+    let (root, storage) = temp_storage();
+    fs::create_dir_all(root.join("covers/k3y")).unwrap();
+    for file in ["original", "small.jpg", "medium.jpg", "large.jpg"] {
+        fs::write(root.join("covers/k3y").join(file), b"image").unwrap();
+    }
+    fs::create_dir_all(root.join("audio")).unwrap();
+    fs::write(root.join("audio/s0ng"), b"audio").unwrap();
+    let db = SharedMock::new(audited(
+        signed_in(MockDatabase::new(DatabaseBackend::Postgres))
+            .append_query_results(vec![vec![album::Model {
+                id: 7,
+                name: "Lorem".to_string(),
+                date_published: NaiveDate::from_ymd_opt(2015, 1, 1).unwrap(),
+                version: 1,
+                deleted_at: Some(deleted_at()),
+            }]])
+            .append_query_results(vec![vec![album_cover::Model {
+                id: 2,
+                album_id: 7,
+                key: "k3y".to_string(),
+                content_type: "image/png".to_string(),
+                width: 800,
+                height: 400,
+            }]])
+            .append_query_results(vec![vec![song_audio::Model {
+                id: 5,
+                song_id: 4,
+                key: "s0ng".to_string(),
+                content_type: "audio/flac".to_string(),
+                size: 5,
+            }]]),
+        2,
+    ));
+    let rocket = rocket::build()
+        .manage(db.connection())
+        .manage(storage)
+        .mount("/trash", routes![purge]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client.delete("/trash/album/7").header(bearer()).dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        db.transaction_log().last(),
+        Some(&Transaction::many([
+            plain("BEGIN"),
+            statement(
+                r#"SELECT "album_cover"."id", "album_cover"."album_id", "album_cover"."key", "album_cover"."content_type", "album_cover"."width", "album_cover"."height" FROM "album_cover" WHERE "album_cover"."album_id" = $1 LIMIT $2"#,
+                vec![7i32.into(), 1u64.into()],
+            ),
+            statement(
+                r#"SELECT "song_audio"."id", "song_audio"."song_id", "song_audio"."key", "song_audio"."content_type", "song_audio"."size" FROM "song_audio" INNER JOIN "song" ON "song_audio"."song_id" = "song"."id" WHERE "song"."album_id" = $1"#,
+                vec![7i32.into()],
+            ),
+            statement(
+                r#"DELETE FROM "album" WHERE "album"."id" = $1"#,
+                vec![7i32.into()],
+            ),
+            insert_audit(
+                "album",
+                7,
+                "delete",
+                Some(json!({
+                    "id": 7,
+                    "name": "Lorem",
+                    "date_published": "2015-01-01",
+                    "version": 1,
+                    "deleted_at": "2026-10-01T12:00:00"
+                })),
+                None,
+                Some(1),
+            ),
+            plain("COMMIT"),
+        ]))
+    );
+    assert_eq!(fs::read_dir(root.join("covers/k3y")).unwrap().count(), 0);
+    assert!(!root.join("audio/s0ng").exists());
+    fs::remove_dir_all(root).unwrap();
+}
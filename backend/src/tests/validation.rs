@@ -0,0 +1,46 @@
+use chrono::Local;
+use entities::{
+    album, song,
+    validation::{message_for, FieldError, Validate},
+};
+
+#[test]
+fn every_invalid_field_is_reported() {
+    let song = song::Model {
+        id: 0,
+        name: " ".to_string(),
+        length_secs: 0,
+        album_id: 1,
+        disc_number: 1,
+        track_number: 0,
+        version: 1,
+        deleted_at: None,
+    };
+
+    assert_eq!(
+        song.validate(),
+        Err(vec![
+            FieldError::new("name", "must not be empty"),
+            FieldError::new("length_secs", "must be positive"),
+            FieldError::new("track_number", "must be positive"),
+        ])
+    );
+}
+
+#[test]
+fn dates_must_not_be_in_the_future() {
+    let album = album::Model {
+        id: 0,
+        name: "Lorem".to_string(),
+        date_published: Local::now().date_naive().succ_opt().unwrap(),
+        version: 1,
+        deleted_at: None,
+    };
+
+    let errors = album.validate().unwrap_err();
+    assert_eq!(
+        message_for(&errors, "date_published"),
+        Some("must not be in the future")
+    );
+    assert_eq!(message_for(&errors, "name"), None);
+}
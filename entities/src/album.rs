@@ -3,16 +3,28 @@
 #[cfg(not(target_family = "wasm"))]
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+#[cfg(not(target_family = "wasm"))]
+use utoipa::ToSchema;
 
 #[cfg_attr(
     not(target_family = "wasm"),
-    derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)
+    derive(
+        Clone,
+        Debug,
+        PartialEq,
+        DeriveEntityModel,
+        Eq,
+        Serialize,
+        Deserialize,
+        ToSchema
+    )
 )]
 #[cfg_attr(
     target_family = "wasm",
     derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)
 )]
 #[cfg_attr(not(target_family = "wasm"), sea_orm(table_name = "album"))]
+#[cfg_attr(not(target_family = "wasm"), schema(as = Album))]
 pub struct Model {
     #[cfg_attr(not(target_family = "wasm"), sea_orm(primary_key))]
     #[cfg_attr(not(target_family = "wasm"), serde(skip_deserializing))]
@@ -21,9 +33,18 @@ pub struct Model {
     pub name: String,
     /// Date of album release
     #[cfg(not(target_family = "wasm"))]
+    #[schema(value_type = String, format = Date)]
     pub date_published: Date,
     #[cfg(target_family = "wasm")]
     pub date_published: String,
+    /// Number of changes made to the album, sent as its `ETag`
+    #[cfg_attr(not(target_family = "wasm"), serde(skip_deserializing))]
+    pub version: i32,
+    /// When the album was moved to the trash, set by the database
+    #[cfg(not(target_family = "wasm"))]
+    #[serde(skip_deserializing)]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub deleted_at: Option<DateTime>,
 }
 
 #[cfg_attr(
@@ -37,6 +58,26 @@ pub enum Relation {
         sea_orm(has_many = "super::album_artist_mtm::Entity")
     )]
     AlbumArtistMtm,
+    #[cfg_attr(
+        not(target_family = "wasm"),
+        sea_orm(has_one = "super::album_cover::Entity")
+    )]
+    AlbumCover,
+    #[cfg_attr(
+        not(target_family = "wasm"),
+        sea_orm(has_many = "super::album_genre::Entity")
+    )]
+    AlbumGenre,
+    #[cfg_attr(
+        not(target_family = "wasm"),
+        sea_orm(has_many = "super::album_label::Entity")
+    )]
+    AlbumLabel,
+    #[cfg_attr(
+        not(target_family = "wasm"),
+        sea_orm(has_many = "super::review::Entity")
+    )]
+    Review,
     #[cfg_attr(not(target_family = "wasm"), sea_orm(has_many = "super::song::Entity"))]
     Song,
 }
@@ -48,6 +89,34 @@ impl Related<super::album_artist_mtm::Entity> for Entity {
     }
 }
 
+#[cfg(not(target_family = "wasm"))]
+impl Related<super::album_cover::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AlbumCover.def()
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Related<super::album_genre::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AlbumGenre.def()
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Related<super::album_label::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AlbumLabel.def()
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Related<super::review::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Review.def()
+    }
+}
+
 #[cfg(not(target_family = "wasm"))]
 impl Related<super::song::Entity> for Entity {
     fn to() -> RelationDef {
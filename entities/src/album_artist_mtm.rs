@@ -2,12 +2,11 @@
 
 #[cfg(not(target_family = "wasm"))]
 use sea_orm::entity::prelude::*;
-#[cfg(target_family = "wasm")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(
     not(target_family = "wasm"),
-    derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)
+    derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)
 )]
 #[cfg_attr(
     target_family = "wasm",
@@ -19,6 +18,10 @@ pub struct Model {
     pub id: i32,
     pub album_id: i32,
     pub artist_id: i32,
+    /// 0-based place of the artist among those of the album, the primary artist first
+    pub position: i32,
+    #[cfg(not(target_family = "wasm"))]
+    pub deleted_at: Option<DateTime>,
 }
 
 #[cfg_attr(
@@ -1,19 +1,30 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.10.6
 
-use super::sea_orm_active_enums::Genre;
 #[cfg(not(target_family = "wasm"))]
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+#[cfg(not(target_family = "wasm"))]
+use utoipa::ToSchema;
 
 #[cfg_attr(
     not(target_family = "wasm"),
-    derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)
+    derive(
+        Clone,
+        Debug,
+        PartialEq,
+        DeriveEntityModel,
+        Eq,
+        Serialize,
+        Deserialize,
+        ToSchema
+    )
 )]
 #[cfg_attr(
     target_family = "wasm",
     derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)
 )]
 #[cfg_attr(not(target_family = "wasm"), sea_orm(table_name = "artist"))]
+#[cfg_attr(not(target_family = "wasm"), schema(as = Artist))]
 pub struct Model {
     #[cfg_attr(not(target_family = "wasm"), sea_orm(primary_key))]
     #[cfg_attr(not(target_family = "wasm"), serde(skip_deserializing))]
@@ -22,11 +33,18 @@ pub struct Model {
     pub name: String,
     /// Date of artist formation
     #[cfg(not(target_family = "wasm"))]
+    #[schema(value_type = String, format = Date)]
     pub date_formed: Date,
     #[cfg(target_family = "wasm")]
     pub date_formed: String,
-    /// Artist genre
-    pub genre: Option<Genre>,
+    /// Number of changes made to the artist, sent as its `ETag`
+    #[cfg_attr(not(target_family = "wasm"), serde(skip_deserializing))]
+    pub version: i32,
+    /// When the artist was moved to the trash, set by the database
+    #[cfg(not(target_family = "wasm"))]
+    #[serde(skip_deserializing)]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub deleted_at: Option<DateTime>,
 }
 
 #[cfg_attr(
@@ -40,6 +58,21 @@ pub enum Relation {
         sea_orm(has_many = "super::album_artist_mtm::Entity")
     )]
     AlbumArtistMtm,
+    #[cfg_attr(
+        not(target_family = "wasm"),
+        sea_orm(has_many = "super::artist_genre::Entity")
+    )]
+    ArtistGenre,
+    #[cfg_attr(
+        not(target_family = "wasm"),
+        sea_orm(has_many = "super::artist_membership::Entity")
+    )]
+    ArtistMembership,
+    #[cfg_attr(
+        not(target_family = "wasm"),
+        sea_orm(has_many = "super::song_credit::Entity")
+    )]
+    SongCredit,
 }
 
 #[cfg(not(target_family = "wasm"))]
@@ -49,5 +82,26 @@ impl Related<super::album_artist_mtm::Entity> for Entity {
     }
 }
 
+#[cfg(not(target_family = "wasm"))]
+impl Related<super::artist_genre::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ArtistGenre.def()
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Related<super::artist_membership::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ArtistMembership.def()
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Related<super::song_credit::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SongCredit.def()
+    }
+}
+
 #[cfg(not(target_family = "wasm"))]
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,32 @@
+//! Bodies of the authentication endpoints, shared between the backend and the frontend
+
+use serde::{Deserialize, Serialize};
+
+use super::{sea_orm_active_enums::Role, user};
+
+/// User name and password sent to register or to log in
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(not(target_family = "wasm"), derive(utoipa::ToSchema))]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// A session opened by logging in
+///
+/// The token is also set as the `session` cookie. Clients that do not keep cookies
+/// send it in an `Authorization: Bearer` header instead.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(not(target_family = "wasm"), derive(utoipa::ToSchema))]
+pub struct SessionToken {
+    pub token: String,
+    #[cfg_attr(not(target_family = "wasm"), schema(value_type = User))]
+    pub user: user::Model,
+}
+
+/// New role given to a user by an admin
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(not(target_family = "wasm"), derive(utoipa::ToSchema))]
+pub struct RoleChange {
+    pub role: Role,
+}
@@ -0,0 +1,12 @@
+//! Versions of the entities as HTTP entity tags
+//!
+//! The backend sends the version of an artist, album or song as its `ETag`, and expects it back in
+//! the `If-Match` header of the requests changing it.
+
+/// Entity tag of the given version
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+/// `If-Match` value matching any version, to overwrite whatever is stored
+pub const ANY: &str = "*";
@@ -3,7 +3,33 @@
 pub mod prelude;
 
 pub mod album;
+#[cfg(not(target_family = "wasm"))]
+pub mod album_cover;
 pub mod album_artist_mtm;
+#[cfg(not(target_family = "wasm"))]
+pub mod album_genre;
+pub mod album_label;
 pub mod artist;
+#[cfg(not(target_family = "wasm"))]
+pub mod artist_genre;
+pub mod artist_membership;
+#[cfg(not(target_family = "wasm"))]
+pub mod audit_log;
+pub mod auth;
+pub mod etag;
+pub mod genre;
+pub mod label;
+pub mod pagination;
+pub mod person;
+pub mod playlist;
+pub mod playlist_song;
+pub mod review;
 pub mod sea_orm_active_enums;
+#[cfg(not(target_family = "wasm"))]
+pub mod session;
 pub mod song;
+#[cfg(not(target_family = "wasm"))]
+pub mod song_audio;
+pub mod song_credit;
+pub mod user;
+pub mod validation;
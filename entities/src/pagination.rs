@@ -0,0 +1,60 @@
+//! Paginated list responses, shared between the backend and the frontend
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_family = "wasm"))]
+use super::{
+    artist::Model as Artist,
+    audit_log::Model as AuditEntry,
+    label::Model as Label,
+    person::Model as Person,
+    playlist::Model as Playlist,
+    review::{RatedAlbum, RatedSong},
+};
+
+/// A single page of results returned by the `/all` list endpoints
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    not(target_family = "wasm"),
+    derive(utoipa::ToSchema),
+    aliases(
+        SongPage = Page<RatedSong>,
+        AlbumPage = Page<RatedAlbum>,
+        ArtistPage = Page<Artist>,
+        AuditPage = Page<AuditEntry>,
+        PlaylistPage = Page<Playlist>,
+        PersonPage = Page<Person>,
+        LabelPage = Page<Label>
+    )
+)]
+pub struct Page<T> {
+    /// Items on this page
+    pub items: Vec<T>,
+    /// Number of items matching the query, across all pages
+    pub total: u64,
+    /// 1-based index of this page
+    pub page: u64,
+    /// Maximum number of items per page
+    pub per_page: u64,
+    /// Number of pages matching the query
+    pub num_pages: u64,
+    /// Link to the next page, if there is one
+    pub next: Option<String>,
+    /// Link to the previous page, if there is one
+    pub prev: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// The same page, with each of its items mapped by `f`
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            page: self.page,
+            per_page: self.per_page,
+            num_pages: self.num_pages,
+            next: self.next,
+            prev: self.prev,
+        }
+    }
+}
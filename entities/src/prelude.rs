@@ -5,14 +5,66 @@ pub use super::album::Entity as Album;
 #[cfg(target_family = "wasm")]
 pub use super::album::Model as Album;
 #[cfg(not(target_family = "wasm"))]
+pub use super::album_cover::Entity as AlbumCover;
+#[cfg(not(target_family = "wasm"))]
 pub use super::album_artist_mtm::Entity as AlbumArtistMtm;
 #[cfg(target_family = "wasm")]
 pub use super::album_artist_mtm::Model as AlbumArtistMtm;
 #[cfg(not(target_family = "wasm"))]
+pub use super::album_genre::Entity as AlbumGenre;
+#[cfg(not(target_family = "wasm"))]
+pub use super::album_label::Entity as AlbumLabel;
+#[cfg(target_family = "wasm")]
+pub use super::album_label::Model as AlbumLabel;
+#[cfg(not(target_family = "wasm"))]
 pub use super::artist::Entity as Artist;
 #[cfg(target_family = "wasm")]
 pub use super::artist::Model as Artist;
 #[cfg(not(target_family = "wasm"))]
+pub use super::artist_genre::Entity as ArtistGenre;
+#[cfg(not(target_family = "wasm"))]
+pub use super::artist_membership::Entity as ArtistMembership;
+#[cfg(target_family = "wasm")]
+pub use super::artist_membership::Model as ArtistMembership;
+#[cfg(not(target_family = "wasm"))]
+pub use super::audit_log::Entity as AuditLog;
+#[cfg(not(target_family = "wasm"))]
+pub use super::genre::Entity as Genre;
+#[cfg(target_family = "wasm")]
+pub use super::genre::Model as Genre;
+#[cfg(not(target_family = "wasm"))]
+pub use super::label::Entity as Label;
+#[cfg(target_family = "wasm")]
+pub use super::label::Model as Label;
+#[cfg(not(target_family = "wasm"))]
+pub use super::person::Entity as Person;
+#[cfg(target_family = "wasm")]
+pub use super::person::Model as Person;
+#[cfg(not(target_family = "wasm"))]
+pub use super::playlist::Entity as Playlist;
+#[cfg(target_family = "wasm")]
+pub use super::playlist::Model as Playlist;
+#[cfg(not(target_family = "wasm"))]
+pub use super::playlist_song::Entity as PlaylistSong;
+#[cfg(target_family = "wasm")]
+pub use super::playlist_song::Model as PlaylistSong;
+#[cfg(not(target_family = "wasm"))]
+pub use super::review::Entity as Review;
+#[cfg(target_family = "wasm")]
+pub use super::review::Model as Review;
+#[cfg(not(target_family = "wasm"))]
 pub use super::song::Entity as Song;
 #[cfg(target_family = "wasm")]
 pub use super::song::Model as Song;
+#[cfg(not(target_family = "wasm"))]
+pub use super::song_audio::Entity as SongAudio;
+#[cfg(not(target_family = "wasm"))]
+pub use super::song_credit::Entity as SongCredit;
+#[cfg(target_family = "wasm")]
+pub use super::song_credit::Model as SongCredit;
+#[cfg(not(target_family = "wasm"))]
+pub use super::session::Entity as Session;
+#[cfg(not(target_family = "wasm"))]
+pub use super::user::Entity as User;
+#[cfg(target_family = "wasm")]
+pub use super::user::Model as User;
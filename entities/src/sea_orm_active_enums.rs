@@ -7,64 +7,164 @@ use std::str::FromStr;
 #[cfg(not(target_family = "wasm"))]
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+#[cfg(not(target_family = "wasm"))]
+use utoipa::ToSchema;
 
+/// Role of a user, each role can do everything the previous ones can
 #[cfg_attr(
     not(target_family = "wasm"),
     derive(
         Debug,
         Clone,
+        Copy,
         PartialEq,
         Eq,
+        PartialOrd,
+        Ord,
         EnumIter,
         DeriveActiveEnum,
         Serialize,
+        Deserialize,
+        ToSchema
+    )
+)]
+#[cfg_attr(
+    target_family = "wasm",
+    derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
         Deserialize
     )
 )]
+#[cfg_attr(
+    not(target_family = "wasm"),
+    sea_orm(rs_type = "String", db_type = "Enum", enum_name = "role")
+)]
+pub enum Role {
+    /// Can browse the database
+    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "viewer"))]
+    Viewer,
+    /// Can also create and update songs, albums and artists
+    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "editor"))]
+    Editor,
+    /// Can also delete anything and manage the roles of users
+    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "admin"))]
+    Admin,
+}
+
+#[cfg(target_family = "wasm")]
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Viewer => write!(f, "Viewer"),
+            Self::Editor => write!(f, "Editor"),
+            Self::Admin => write!(f, "Admin"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "viewer" => Ok(Self::Viewer),
+            "editor" => Ok(Self::Editor),
+            "admin" => Ok(Self::Admin),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Part an artist had in a song
+#[cfg_attr(
+    not(target_family = "wasm"),
+    derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        EnumIter,
+        DeriveActiveEnum,
+        Serialize,
+        Deserialize,
+        ToSchema
+    )
+)]
 #[cfg_attr(
     target_family = "wasm",
-    derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)
+    derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)
 )]
 #[cfg_attr(
     not(target_family = "wasm"),
-    sea_orm(rs_type = "String", db_type = "Enum", enum_name = "genre")
+    sea_orm(rs_type = "String", db_type = "Enum", enum_name = "credit_role")
 )]
-pub enum Genre {
-    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "metal"))]
-    Metal,
-    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "classical"))]
-    Classical,
-    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "jazz"))]
-    Jazz,
-    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "pop"))]
-    Pop,
-    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "rock"))]
-    Rock,
+pub enum CreditRole {
+    /// Performs the song, for songs by other artists than those of their album
+    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "main"))]
+    Main,
+    /// Performs on the song as a guest
+    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "featured"))]
+    Featured,
+    /// Wrote the music
+    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "composer"))]
+    Composer,
+    /// Wrote the words
+    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "lyricist"))]
+    Lyricist,
+    /// Produced the recording
+    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "producer"))]
+    Producer,
+    /// Made the remix
+    #[cfg_attr(not(target_family = "wasm"), sea_orm(string_value = "remixer"))]
+    Remixer,
 }
 
 #[cfg(target_family = "wasm")]
-impl Display for Genre {
+impl Display for CreditRole {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Metal => write!(f, "Metal"),
-            Self::Classical => write!(f, "Classical"),
-            Self::Jazz => write!(f, "Jazz"),
-            Self::Pop => write!(f, "Pop"),
-            Self::Rock => write!(f, "Rock"),
+            Self::Main => write!(f, "Artist"),
+            Self::Featured => write!(f, "Featured artist"),
+            Self::Composer => write!(f, "Composer"),
+            Self::Lyricist => write!(f, "Lyricist"),
+            Self::Producer => write!(f, "Producer"),
+            Self::Remixer => write!(f, "Remixer"),
         }
     }
 }
 
-impl FromStr for Genre {
+/// Kind of change recorded in the audit log
+#[cfg(not(target_family = "wasm"))]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "audit_action")]
+pub enum AuditAction {
+    #[sea_orm(string_value = "create")]
+    Create,
+    #[sea_orm(string_value = "update")]
+    Update,
+    #[sea_orm(string_value = "delete")]
+    Delete,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl FromStr for AuditAction {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.trim().to_lowercase().as_str() {
-            "metal" => Ok(Self::Metal),
-            "classical" => Ok(Self::Classical),
-            "jazz" => Ok(Self::Jazz),
-            "pop" => Ok(Self::Pop),
-            "rock" => Ok(Self::Rock),
+            "create" => Ok(Self::Create),
+            "update" => Ok(Self::Update),
+            "delete" => Ok(Self::Delete),
             _ => Err(()),
         }
     }
@@ -3,16 +3,28 @@
 #[cfg(not(target_family = "wasm"))]
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+#[cfg(not(target_family = "wasm"))]
+use utoipa::ToSchema;
 
 #[cfg_attr(
     not(target_family = "wasm"),
-    derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)
+    derive(
+        Clone,
+        Debug,
+        PartialEq,
+        DeriveEntityModel,
+        Eq,
+        Serialize,
+        Deserialize,
+        ToSchema
+    )
 )]
 #[cfg_attr(
     target_family = "wasm",
     derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)
 )]
 #[cfg_attr(not(target_family = "wasm"), sea_orm(table_name = "song"))]
+#[cfg_attr(not(target_family = "wasm"), schema(as = Song))]
 pub struct Model {
     #[cfg_attr(not(target_family = "wasm"), sea_orm(primary_key))]
     #[cfg_attr(not(target_family = "wasm"), serde(skip_deserializing))]
@@ -22,6 +34,25 @@ pub struct Model {
     /// Non-zero, positive song length in seconds
     pub length_secs: i32,
     pub album_id: i32,
+    /// Disc of the album the song is on, from 1, the first one if left out
+    #[serde(default = "first_disc")]
+    pub disc_number: i32,
+    /// Place of the song on its disc, from 1, unique among the songs of the disc, the track after
+    /// the last one of the disc if left out on creation
+    #[serde(default)]
+    pub track_number: i32,
+    /// Number of changes made to the song, sent as its `ETag`
+    #[cfg_attr(not(target_family = "wasm"), serde(skip_deserializing))]
+    pub version: i32,
+    /// When the song was moved to the trash, set by the database
+    #[cfg(not(target_family = "wasm"))]
+    #[serde(skip_deserializing)]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub deleted_at: Option<DateTime>,
+}
+
+fn first_disc() -> i32 {
+    1
 }
 
 #[cfg_attr(
@@ -41,6 +72,26 @@ pub enum Relation {
         )
     )]
     Album,
+    #[cfg_attr(
+        not(target_family = "wasm"),
+        sea_orm(has_many = "super::playlist_song::Entity")
+    )]
+    PlaylistSong,
+    #[cfg_attr(
+        not(target_family = "wasm"),
+        sea_orm(has_many = "super::review::Entity")
+    )]
+    Review,
+    #[cfg_attr(
+        not(target_family = "wasm"),
+        sea_orm(has_one = "super::song_audio::Entity")
+    )]
+    SongAudio,
+    #[cfg_attr(
+        not(target_family = "wasm"),
+        sea_orm(has_many = "super::song_credit::Entity")
+    )]
+    SongCredit,
 }
 
 #[cfg(not(target_family = "wasm"))]
@@ -50,5 +101,33 @@ impl Related<super::album::Entity> for Entity {
     }
 }
 
+#[cfg(not(target_family = "wasm"))]
+impl Related<super::playlist_song::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PlaylistSong.def()
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Related<super::review::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Review.def()
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Related<super::song_audio::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SongAudio.def()
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Related<super::song_credit::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SongCredit.def()
+    }
+}
+
 #[cfg(not(target_family = "wasm"))]
 impl ActiveModelBehavior for ActiveModel {}
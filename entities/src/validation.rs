@@ -0,0 +1,223 @@
+//! Validation rules shared between the backend and the frontend
+//!
+//! The backend rejects invalid entities with these errors, and the frontend
+//! uses the same rules to show them next to the form fields before submitting.
+
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    album, artist, artist_membership, auth::Credentials, genre, label, person, playlist, review,
+    song,
+};
+
+/// Shortest accepted password
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// A validation error on a single field
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(not(target_family = "wasm"), derive(utoipa::ToSchema))]
+pub struct FieldError {
+    /// Name of the field that failed validation
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        FieldError {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Find the first error message for the given field
+pub fn message_for<'a>(errors: &'a [FieldError], field: &str) -> Option<&'a str> {
+    errors
+        .iter()
+        .find(|error| error.field == field)
+        .map(|error| error.message.as_str())
+}
+
+/// An entity whose fields can be checked on their own, without the database
+pub trait Validate {
+    /// Check every field, returning all of the errors found
+    fn validate(&self) -> Result<(), Vec<FieldError>>;
+}
+
+fn non_empty(errors: &mut Vec<FieldError>, field: &str, value: &str) {
+    if value.trim().is_empty() {
+        errors.push(FieldError::new(field, "must not be empty"));
+    }
+}
+
+fn positive(errors: &mut Vec<FieldError>, field: &str, value: i32) {
+    if value <= 0 {
+        errors.push(FieldError::new(field, "must be positive"));
+    }
+}
+
+fn not_in_future(errors: &mut Vec<FieldError>, field: &str, date: NaiveDate) {
+    if date > Local::now().date_naive() {
+        errors.push(FieldError::new(field, "must not be in the future"));
+    }
+}
+
+/// Dates are kept as `YYYY-MM-DD` strings on the frontend
+#[cfg(target_family = "wasm")]
+fn date(errors: &mut Vec<FieldError>, field: &str, value: &str) -> Option<NaiveDate> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok();
+    if date.is_none() {
+        errors.push(FieldError::new(field, "must be a valid date"));
+    }
+    date
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn date(_errors: &mut Vec<FieldError>, _field: &str, value: &NaiveDate) -> Option<NaiveDate> {
+    Some(*value)
+}
+
+fn into_result(errors: Vec<FieldError>) -> Result<(), Vec<FieldError>> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl Validate for song::Model {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        non_empty(&mut errors, "name", &self.name);
+        positive(&mut errors, "length_secs", self.length_secs);
+        positive(&mut errors, "disc_number", self.disc_number);
+        positive(&mut errors, "track_number", self.track_number);
+        into_result(errors)
+    }
+}
+
+impl Validate for album::Model {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        non_empty(&mut errors, "name", &self.name);
+        if let Some(date_published) = date(&mut errors, "date_published", &self.date_published) {
+            not_in_future(&mut errors, "date_published", date_published);
+        }
+        into_result(errors)
+    }
+}
+
+impl Validate for artist::Model {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        non_empty(&mut errors, "name", &self.name);
+        if let Some(date_formed) = date(&mut errors, "date_formed", &self.date_formed) {
+            not_in_future(&mut errors, "date_formed", date_formed);
+        }
+        into_result(errors)
+    }
+}
+
+impl Validate for genre::Model {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        non_empty(&mut errors, "name", &self.name);
+        into_result(errors)
+    }
+}
+
+impl Validate for label::Model {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        non_empty(&mut errors, "name", &self.name);
+        if let Some(country) = &self.country {
+            if country.len() != 2 || !country.chars().all(|c| c.is_ascii_uppercase()) {
+                errors.push(FieldError::new(
+                    "country",
+                    "must be a two-letter country code",
+                ));
+            }
+        }
+        if let Some(founded) = self
+            .founded
+            .as_ref()
+            .and_then(|founded| date(&mut errors, "founded", founded))
+        {
+            not_in_future(&mut errors, "founded", founded);
+        }
+        into_result(errors)
+    }
+}
+
+impl Validate for person::Model {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        non_empty(&mut errors, "name", &self.name);
+        into_result(errors)
+    }
+}
+
+impl Validate for artist_membership::Model {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        non_empty(&mut errors, "role", &self.role);
+        if let Some(instrument) = &self.instrument {
+            non_empty(&mut errors, "instrument", instrument);
+        }
+        let start_date = self
+            .start_date
+            .as_ref()
+            .and_then(|start_date| date(&mut errors, "start_date", start_date));
+        let end_date = self
+            .end_date
+            .as_ref()
+            .and_then(|end_date| date(&mut errors, "end_date", end_date));
+        if let Some(start_date) = start_date {
+            not_in_future(&mut errors, "start_date", start_date);
+        }
+        if let Some(end_date) = end_date {
+            not_in_future(&mut errors, "end_date", end_date);
+            if start_date.is_some_and(|start_date| end_date < start_date) {
+                errors.push(FieldError::new(
+                    "end_date",
+                    "must not be before the start date",
+                ));
+            }
+        }
+        into_result(errors)
+    }
+}
+
+impl Validate for playlist::Model {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        non_empty(&mut errors, "name", &self.name);
+        into_result(errors)
+    }
+}
+
+impl Validate for review::Model {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        if !(1..=10).contains(&self.rating) {
+            errors.push(FieldError::new("rating", "must be between 1 and 10"));
+        }
+        into_result(errors)
+    }
+}
+
+impl Validate for Credentials {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        non_empty(&mut errors, "username", &self.username);
+        if self.password.chars().count() < MIN_PASSWORD_LENGTH {
+            errors.push(FieldError::new(
+                "password",
+                format!("must be at least {} characters", MIN_PASSWORD_LENGTH),
+            ));
+        }
+        into_result(errors)
+    }
+}
@@ -1,14 +1,19 @@
 use entities::album::Model as Album;
+use entities::album_label::AlbumRelease;
 use entities::artist::Model as Artist;
+use entities::etag::etag;
+use entities::review::{RatedAlbum, Ratings};
+use entities::sea_orm_active_enums::Role;
 use entities::song::Model as Song;
+use entities::user::Model as User;
 use gloo_net::http::Request;
 use stylist::yew::use_style;
 use wasm_bindgen::JsCast;
-use web_sys::HtmlElement;
+use web_sys::{FormData, HtmlElement, HtmlInputElement};
 use yew::prelude::*;
 use yew_router::prelude::{use_navigator, Link};
 
-use crate::components::SongView;
+use crate::components::{PlayQueue, Reviews, SongView};
 use crate::router::Route;
 use crate::theme::Theme;
 
@@ -25,14 +30,26 @@ pub fn album_view(props: &Props) -> Html {
         id: props.id,
         name: String::new(),
         date_published: String::new(),
+        version: 0,
     });
 
+    let ratings = use_state(Ratings::default);
+    // Bumped after each review, to fetch the new average rating
+    let ratings_version = use_state(|| 0);
+
     let songs = use_state(|| Vec::new());
 
     let artists = use_state(|| Vec::new());
 
+    let labels = use_state(Vec::<AlbumRelease>::new);
+
     let full = use_state(|| props.full);
 
+    // Albums without a cover answer 404, after which the image is left out
+    let has_cover = use_state(|| true);
+    // Bumped after each upload, to fetch the new cover
+    let cover_version = use_state(|| 0);
+
     let navigator = use_navigator().unwrap();
     let edit = {
         let navigator = navigator.clone();
@@ -41,6 +58,7 @@ pub fn album_view(props: &Props) -> Html {
     };
     let delete = {
         let id = album.id;
+        let version = album.version;
         Callback::from(move |event: MouseEvent| {
             let window = web_sys::window().unwrap();
             let confirm = window
@@ -65,6 +83,7 @@ pub fn album_view(props: &Props) -> Html {
 
             wasm_bindgen_futures::spawn_local(async move {
                 Request::delete(&format!("/api/album/{}", id))
+                    .header("If-Match", &etag(version))
                     .send()
                     .await
                     .expect("Failed to send request to delete album");
@@ -75,6 +94,7 @@ pub fn album_view(props: &Props) -> Html {
     {
         let id = props.id;
         let album = album.clone();
+        let ratings = ratings.clone();
         use_effect_with_deps(
             move |_| {
                 wasm_bindgen_futures::spawn_local(async move {
@@ -82,14 +102,15 @@ pub fn album_view(props: &Props) -> Html {
                         .send()
                         .await
                         .expect("Failed to fetch album")
-                        .json::<Album>()
+                        .json::<RatedAlbum>()
                         .await
                         .expect("Failed to parse album");
 
-                    album.set(resp);
+                    album.set(resp.album);
+                    ratings.set(resp.ratings);
                 })
             },
-            (),
+            *ratings_version,
         );
     }
 
@@ -135,11 +156,93 @@ pub fn album_view(props: &Props) -> Html {
         );
     }
 
+    {
+        let album_id = props.id;
+        let labels = labels.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let resp = Request::get(&format!("/api/album/labels/{}", album_id))
+                        .send()
+                        .await
+                        .expect("Failed to fetch labels")
+                        .json::<Vec<AlbumRelease>>()
+                        .await
+                        .expect("Failed to parse labels");
+
+                    labels.set(resp);
+                })
+            },
+            (),
+        );
+    }
+
+    let multi_disc = songs
+        .iter()
+        .any(|song: &Song| song.disc_number != songs[0].disc_number);
+
+    let upload_cover = {
+        let id = props.id;
+        let has_cover = has_cover.clone();
+        let cover_version = cover_version.clone();
+        Callback::from(move |event: Event| {
+            let input = event.target().unwrap().unchecked_into::<HtmlInputElement>();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            let form = FormData::new().unwrap();
+            form.append_with_blob("file", &file).unwrap();
+
+            let has_cover = has_cover.clone();
+            let cover_version = cover_version.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let resp = Request::post(&format!("/api/album/cover/{}", id))
+                    .body(form)
+                    .send()
+                    .await
+                    .expect("Failed to send request to upload cover");
+
+                if resp.ok() {
+                    has_cover.set(true);
+                    cover_version.set(*cover_version + 1);
+                } else {
+                    let window = web_sys::window().unwrap();
+                    let _ =
+                        window.alert_with_message("The cover must be a JPEG, PNG or WebP image");
+                }
+            });
+        })
+    };
+    let hide_cover = {
+        let has_cover = has_cover.clone();
+        Callback::from(move |_| has_cover.set(false))
+    };
+
+    let queue = use_context::<UseStateHandle<PlayQueue>>().expect("No context found");
+    let play = {
+        let queue = queue.clone();
+        let songs = songs.clone();
+        Callback::from(move |_| queue.set(PlayQueue::of((*songs).clone())))
+    };
+    let add_to_queue = {
+        let songs = songs.clone();
+        Callback::from(move |_| queue.set(queue.with(&songs)))
+    };
+
+    let reviewed = {
+        let ratings_version = ratings_version.clone();
+        Callback::from(move |_| ratings_version.set(*ratings_version + 1))
+    };
+
     let toggle_full = {
         let full = full.clone();
         Callback::from(move |_| full.set(!*full))
     };
 
+    let role = use_context::<UseStateHandle<Option<User>>>()
+        .expect("No context found")
+        .as_ref()
+        .map(|user| user.role);
     let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
     let theme_style = theme.get_theme();
 
@@ -189,6 +292,10 @@ pub fn album_view(props: &Props) -> Html {
             button:hover {
                 cursor: pointer;
             }
+
+            img {
+                border-radius: 10px;
+            }
         "#,
         surface = theme_style.surface,
         on_surface = theme_style.on_surface,
@@ -267,11 +374,35 @@ pub fn album_view(props: &Props) -> Html {
     html! {
         <div class={style}>
             <div>
+                if *has_cover {
+                    <img
+                        src={ format!("/api/album/cover/{}?size={}&v={}", props.id, if *full { "large" } else { "small" }, *cover_version) }
+                        alt="Cover"
+                        onerror={ hide_cover }
+                    />
+                }
                 <Link<Route> classes={ title_style } to={ Route::Album { id: props.id } }>{ &album.name }</Link<Route>>
                 <p>{ &album.date_published }</p>
+                if let Some(average) = ratings.average_rating {
+                    <p>{ format!("{:.1}/10 from {} ratings", average, ratings.rating_count) }</p>
+                }
                 <span>
-                    <button onclick={ edit } class={ edit_style }>{ "Edit" }</button>
-                    <button onclick={ delete } class={ delete_style }>{ "Delete" }</button>
+                    if role >= Some(Role::Editor) {
+                        <button onclick={ edit } class={ edit_style }>{ "Edit" }</button>
+                    }
+                    if role >= Some(Role::Admin) {
+                        <button onclick={ delete } class={ delete_style }>{ "Delete" }</button>
+                    }
+                    if role >= Some(Role::Editor) && *full {
+                        <label>
+                            { "Cover " }
+                            <input type="file" accept="image/jpeg,image/png,image/webp" onchange={ upload_cover } />
+                        </label>
+                    }
+                    if !songs.is_empty() {
+                        <button onclick={ play } class={ show_style.clone() }>{ "Play" }</button>
+                        <button onclick={ add_to_queue } class={ show_style.clone() }>{ "Queue" }</button>
+                    }
                     <button onclick={ toggle_full } class={ show_style }>
                         { if *full { "Hide songs" } else { "Show songs" } }
                     </button>
@@ -285,15 +416,43 @@ pub fn album_view(props: &Props) -> Html {
                         <ArtistTag artist_id={ id } album_id={ props.id } artist_name={ artist.name.clone() } />
                     }
                 })}
-                <Link<Route> to={Route::AlbumArtist { album_id: props.id } } classes={ add_artist_style }>{ "+" }</Link<Route>>
+                if role >= Some(Role::Editor) {
+                    <Link<Route> to={Route::AlbumArtist { album_id: props.id } } classes={ add_artist_style }>{ "+" }</Link<Route>>
+                }
             </ul>
+            if *full && !labels.is_empty() {
+                <p>
+                    { "Released by " }
+                    {for labels.iter().map(|release| html! {
+                        <span>
+                            <Link<Route> to={ Route::Label { id: release.label.id } }>{ &release.label.name }</Link<Route>>
+                            if let Some(catalog_number) = &release.catalog_number {
+                                { format!("({})", catalog_number) }
+                            }
+                        </span>
+                    })}
+                </p>
+            }
             if *full {
                 <ul>
-                    {for songs.iter().map(|song| html! {
-                        <SongView id={ song.id } />
+                    {for songs.iter().enumerate().map(|(index, song)| {
+                        // Songs come sorted by disc, so a new disc starts where the disc changes
+                        let new_disc = index == 0 || songs[index - 1].disc_number != song.disc_number;
+
+                        html! {
+                            <>
+                                if multi_disc && new_disc {
+                                    <h3>{ format!("Disc {}", song.disc_number) }</h3>
+                                }
+                                <SongView id={ song.id } numbered=true />
+                            </>
+                        }
                     })}
-                    <Link<Route> classes={ create_song_style } to={ Route::SongCreate { album_id: props.id } }>{ "+" }</Link<Route>>
+                    if role >= Some(Role::Editor) {
+                        <Link<Route> classes={ create_song_style } to={ Route::SongCreate { album_id: props.id } }>{ "+" }</Link<Route>>
+                    }
                 </ul>
+                <Reviews album_id={ props.id } onchange={ reviewed } />
             }
         </div>
     }
@@ -334,6 +493,10 @@ fn artist_tag(props: &ArtistTagProps) -> Html {
         })
     };
 
+    let role = use_context::<UseStateHandle<Option<User>>>()
+        .expect("No context found")
+        .as_ref()
+        .map(|user| user.role);
     let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
     let theme_style = theme.get_theme();
 
@@ -361,7 +524,9 @@ fn artist_tag(props: &ArtistTagProps) -> Html {
     html! {
         <span class={ style }>
             <Link<Route> to={Route::Artist { id: props.artist_id }} >{ &*props.artist_name }</Link<Route>>
-            <button onclick={ remove_artist } class={ x_style }>{ "X" }</button>
+            if role >= Some(Role::Editor) {
+                <button onclick={ remove_artist } class={ x_style }>{ "X" }</button>
+            }
         </span>
     }
 }
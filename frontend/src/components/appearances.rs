@@ -0,0 +1,72 @@
+use entities::song_credit::Appearance;
+use gloo_net::http::Request;
+use stylist::yew::use_style;
+use yew::prelude::*;
+
+use crate::components::SongView;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub artist_id: i32,
+}
+
+/// Songs an artist is credited on, each with the part the artist had in it
+#[function_component(Appearances)]
+pub fn appearances(props: &Props) -> Html {
+    let appearances = use_state(Vec::<Appearance>::new);
+
+    {
+        let id = props.artist_id;
+        let appearances = appearances.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let resp = Request::get(&format!("/api/artist/appearances/{}", id))
+                        .send()
+                        .await
+                        .expect("Failed to send request")
+                        .json::<Vec<Appearance>>()
+                        .await
+                        .expect("Failed to parse response");
+
+                    appearances.set(resp);
+                })
+            },
+            (),
+        );
+    }
+
+    let style = use_style!(
+        r#"
+            width: 90%;
+
+            ul {
+                padding: 0;
+                list-style: none;
+            }
+
+            small {
+                display: block;
+                margin-left: 20px;
+            }
+        "#,
+    );
+
+    if appearances.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        <div class={ style }>
+            <h3>{ "Appears on" }</h3>
+            <ul>
+                {for appearances.iter().map(|appearance| html! {
+                    <li>
+                        <small>{ appearance.credit.role.to_string() }</small>
+                        <SongView id={ appearance.song.id } />
+                    </li>
+                })}
+            </ul>
+        </div>
+    }
+}
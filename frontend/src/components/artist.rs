@@ -1,5 +1,9 @@
 use entities::album::Model as Album;
 use entities::artist::Model as Artist;
+use entities::etag::etag;
+use entities::genre::Model as Genre;
+use entities::sea_orm_active_enums::Role;
+use entities::user::Model as User;
 use gloo_net::http::Request;
 use stylist::yew::use_style;
 use wasm_bindgen::JsCast;
@@ -7,7 +11,11 @@ use web_sys::HtmlElement;
 use yew::prelude::*;
 use yew_router::prelude::{use_navigator, Link};
 
-use crate::{components::AlbumView, router::Route, theme::Theme};
+use crate::{
+    components::{AlbumView, Appearances, MemberTimeline},
+    router::Route,
+    theme::Theme,
+};
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {
@@ -21,10 +29,12 @@ pub fn artist_view(props: &Props) -> Html {
     let artist = use_state(|| Artist {
         id: 0,
         name: "Artist name".to_string(),
-        genre: None,
         date_formed: "0000-00-00".to_string(),
+        version: 0,
     });
 
+    let genres = use_state(Vec::<Genre>::new);
+
     let albums = use_state(|| Vec::new());
 
     let full = use_state(|| props.full);
@@ -37,6 +47,7 @@ pub fn artist_view(props: &Props) -> Html {
     };
     let delete = {
         let id = artist.id;
+        let version = artist.version;
         Callback::from(move |event: MouseEvent| {
             let window = web_sys::window().unwrap();
             let confirm = window
@@ -61,6 +72,7 @@ pub fn artist_view(props: &Props) -> Html {
 
             wasm_bindgen_futures::spawn_local(async move {
                 Request::delete(&format!("/api/artist/{}", id))
+                    .header("If-Match", &etag(version))
                     .send()
                     .await
                     .expect("Failed to send request to delete artist");
@@ -89,6 +101,27 @@ pub fn artist_view(props: &Props) -> Html {
         );
     }
 
+    {
+        let id = props.id;
+        let genres = genres.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let resp = Request::get(&format!("/api/artist/genres/{}", id))
+                        .send()
+                        .await
+                        .expect("Failed to send request")
+                        .json::<Vec<Genre>>()
+                        .await
+                        .expect("Failed to parse response");
+
+                    genres.set(resp);
+                })
+            },
+            (),
+        );
+    }
+
     {
         let id = props.id;
         let albums = albums.clone();
@@ -115,6 +148,10 @@ pub fn artist_view(props: &Props) -> Html {
         Callback::from(move |_| full.set(!*full))
     };
 
+    let role = use_context::<UseStateHandle<Option<User>>>()
+        .expect("No context found")
+        .as_ref()
+        .map(|user| user.role);
     let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
     let theme_style = theme.get_theme();
 
@@ -230,24 +267,45 @@ pub fn artist_view(props: &Props) -> Html {
         <div class={ style }>
             <div>
                 <Link<Route> classes={ title_style } to={ Route::Artist { id: props.id } }>{ &*artist.name }</Link<Route>>
-                <p>{ "Genre: " } { &*artist.genre.as_ref().map_or("Unknown".to_string(), |g| g.to_string()) }</p>
+                <p>{ "Genres: " } { genre_names(&genres) }</p>
                 <p>{ "Date formed: " } { &*artist.date_formed }</p>
                 <span>
-                    <button onclick={ edit } class={ edit_style }>{ "Edit" }</button>
-                    <button onclick={ delete } class={ delete_style }>{ "Delete" }</button>
+                    if role >= Some(Role::Editor) {
+                        <button onclick={ edit } class={ edit_style }>{ "Edit" }</button>
+                    }
+                    if role >= Some(Role::Admin) {
+                        <button onclick={ delete } class={ delete_style }>{ "Delete" }</button>
+                    }
                     <button onclick={ toggle_full } class={ show_style }>
                         { if *full { "Hide albums" } else { "Show albums" } }
                     </button>
                 </span>
             </div>
+            if props.full {
+                <MemberTimeline artist_id={ props.id } />
+                <Appearances artist_id={ props.id } />
+            }
             if *full {
                 <ul>
                     {for albums.iter().map(|album| html! {
                         <AlbumView id={ album.id } full={ props.full } />
                     })}
-                    <Link<Route> classes={ create_album_style } to={ Route::AlbumCreate { artist_id: props.id } }>{ "+" }</Link<Route>>
+                    if role >= Some(Role::Editor) {
+                        <Link<Route> classes={ create_album_style } to={ Route::AlbumCreate { artist_id: props.id } }>{ "+" }</Link<Route>>
+                    }
                 </ul>
             }
         </div>
     }
 }
+
+fn genre_names(genres: &[Genre]) -> String {
+    if genres.is_empty() {
+        return "Unknown".to_string();
+    }
+    genres
+        .iter()
+        .map(|genre| genre.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
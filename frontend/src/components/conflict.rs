@@ -0,0 +1,64 @@
+use entities::validation::FieldError;
+use gloo_net::http::Response;
+use serde::Deserialize;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// What is being edited, such as "song"
+    pub what: AttrValue,
+    pub onreload: Callback<MouseEvent>,
+    pub onoverwrite: Callback<MouseEvent>,
+}
+
+/// Shown instead of an edit form when someone else saved the same entity in the meantime
+#[function_component(ConflictNotice)]
+pub fn conflict_notice(props: &Props) -> Html {
+    html! {
+        <>
+            <p>{ format!("This {} was changed by someone else while you were editing it.", props.what) }</p>
+            <p>{ "Reload it to see their changes, or overwrite them with yours." }</p>
+            <div>
+                <button onclick={ props.onreload.clone() }>{ "Reload" }</button>
+                <button onclick={ props.onoverwrite.clone() }>{ "Overwrite" }</button>
+            </div>
+        </>
+    }
+}
+
+/// What became of a request saving an entity edited in a form
+pub enum SaveOutcome {
+    Saved,
+    /// Someone else saved the entity in the meantime
+    Conflict,
+    /// The API refused the changes, for the reasons to show in the form
+    Rejected(Vec<FieldError>),
+}
+
+/// The parts of an API error body shown in a form
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+    #[serde(default)]
+    details: Vec<FieldError>,
+}
+
+/// Read the response to a request saving an entity
+///
+/// A rejection gives the field errors of the response, or its message under the `form` field when
+/// it has none, as when the user is not allowed to make the change.
+pub async fn save_outcome(resp: Response) -> SaveOutcome {
+    if resp.ok() {
+        return SaveOutcome::Saved;
+    }
+    if resp.status() == 412 {
+        return SaveOutcome::Conflict;
+    }
+
+    let errors = match resp.json::<ErrorBody>().await {
+        Ok(body) if !body.details.is_empty() => body.details,
+        Ok(body) => vec![FieldError::new("form", body.message)],
+        Err(_) => vec![FieldError::new("form", "Something went wrong, try again")],
+    };
+    SaveOutcome::Rejected(errors)
+}
@@ -0,0 +1,46 @@
+use entities::validation::{message_for, FieldError, Validate};
+use stylist::yew::use_style;
+use yew::prelude::*;
+
+use crate::theme::Theme;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub errors: Vec<FieldError>,
+    pub field: &'static str,
+}
+
+/// Error message shown under a form field, if the field is invalid
+#[function_component(FieldErrorText)]
+pub fn field_error_text(props: &Props) -> Html {
+    let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
+    let theme_style = theme.get_theme();
+
+    let style = use_style!(
+        r#"
+            display: block;
+            margin-left: calc(15vw + 10px);
+            font-size: 0.8em;
+            color: ${error};
+        "#,
+        error = theme_style.error
+    );
+
+    match message_for(&props.errors, props.field) {
+        Some(message) => html! { <span class={ style }>{ message }</span> },
+        None => html! {},
+    }
+}
+
+/// Validate `model` again after `field` changed, keeping the errors of the other fields
+pub fn revalidate<T: Validate>(model: &T, field: &str, errors: &[FieldError]) -> Vec<FieldError> {
+    let mut errors: Vec<FieldError> = errors
+        .iter()
+        .filter(|error| error.field != field)
+        .cloned()
+        .collect();
+    if let Err(new_errors) = model.validate() {
+        errors.extend(new_errors.into_iter().filter(|error| error.field == field));
+    }
+    errors
+}
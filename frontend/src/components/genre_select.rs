@@ -0,0 +1,83 @@
+use entities::genre::Model as Genre;
+use gloo_net::http::Request;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlOptionElement, HtmlSelectElement};
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub selected: Vec<i32>,
+    pub onchange: Callback<Vec<i32>>,
+}
+
+/// Multi-select listing every genre, subgenres indented under their parent
+#[function_component(GenreSelect)]
+pub fn genre_select(props: &Props) -> Html {
+    let genres = use_state(Vec::<Genre>::new);
+
+    {
+        let genres = genres.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let resp = Request::get("/api/genre/all")
+                        .send()
+                        .await
+                        .expect("Failed to send request")
+                        .json::<Vec<Genre>>()
+                        .await
+                        .expect("Failed to parse response");
+
+                    genres.set(resp);
+                })
+            },
+            (),
+        );
+    }
+
+    let onchange = {
+        let onchange = props.onchange.clone();
+        Callback::from(move |event: Event| {
+            let options = event
+                .target()
+                .unwrap()
+                .unchecked_into::<HtmlSelectElement>()
+                .selected_options();
+
+            let selected = (0..options.length())
+                .filter_map(|i| options.item(i))
+                .filter_map(|option| {
+                    option
+                        .unchecked_into::<HtmlOptionElement>()
+                        .value()
+                        .parse::<i32>()
+                        .ok()
+                })
+                .collect();
+            onchange.emit(selected)
+        })
+    };
+
+    html! {
+        <select multiple=true { onchange }>
+            {for tree(&genres, None, 0).into_iter().map(|(genre, depth)| html! {
+                <option value={ genre.id.to_string() } selected={ props.selected.contains(&genre.id) }>
+                    { format!("{}{}", "\u{a0}\u{a0}".repeat(depth), genre.name) }
+                </option>
+            })}
+        </select>
+    }
+}
+
+/// Genres below `parent_id` in display order, each paired with its depth
+fn tree(genres: &[Genre], parent_id: Option<i32>, depth: usize) -> Vec<(&Genre, usize)> {
+    genres
+        .iter()
+        .filter(|genre| genre.parent_id == parent_id)
+        .flat_map(|genre| {
+            let mut rows = vec![(genre, depth)];
+            rows.extend(tree(genres, Some(genre.id), depth + 1));
+            rows
+        })
+        .collect()
+}
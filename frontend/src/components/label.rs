@@ -0,0 +1,160 @@
+use entities::album_label::LabelRelease;
+use entities::label::Model as Label;
+use gloo_net::http::Request;
+use stylist::yew::use_style;
+use yew::prelude::*;
+use yew_router::prelude::Link;
+
+use crate::router::Route;
+use crate::theme::Theme;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub id: i32,
+}
+
+/// A label with its releases, grouped by the year they were published
+#[function_component(LabelView)]
+pub fn label_view(props: &Props) -> Html {
+    let label = use_state(|| None::<Label>);
+    let parent = use_state(|| None::<Label>);
+    let releases = use_state(Vec::<LabelRelease>::new);
+
+    {
+        let label = label.clone();
+        let parent = parent.clone();
+        let releases = releases.clone();
+        use_effect_with_deps(
+            move |id: &i32| {
+                let id = *id;
+                wasm_bindgen_futures::spawn_local(async move {
+                    let resp = Request::get(&format!("/api/label/{}", id))
+                        .send()
+                        .await
+                        .expect("Failed to fetch label")
+                        .json::<Label>()
+                        .await
+                        .expect("Failed to parse label");
+
+                    parent.set(None);
+                    if let Some(parent_id) = resp.parent_id {
+                        let resp = Request::get(&format!("/api/label/{}", parent_id))
+                            .send()
+                            .await
+                            .expect("Failed to fetch parent label")
+                            .json::<Label>()
+                            .await
+                            .expect("Failed to parse parent label");
+                        parent.set(Some(resp));
+                    }
+                    label.set(Some(resp));
+
+                    let resp = Request::get(&format!("/api/label/releases/{}", id))
+                        .send()
+                        .await
+                        .expect("Failed to fetch releases")
+                        .json::<Vec<LabelRelease>>()
+                        .await
+                        .expect("Failed to parse releases");
+
+                    releases.set(resp);
+                })
+            },
+            props.id,
+        );
+    }
+
+    let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
+    let theme_style = theme.get_theme();
+
+    let style = use_style!(
+        r#"
+            background: ${surface};
+            color: ${on_surface};
+            border-radius: 50px;
+            border: 1px solid ${outline};
+            padding: 10px 40px;
+
+            h1 {
+                margin-bottom: 0;
+            }
+
+            a {
+                color: ${on_surface};
+            }
+
+            ul {
+                list-style: none;
+                padding: 0;
+            }
+
+            li {
+                margin: 5px 0;
+            }
+
+            small {
+                margin-left: 10px;
+            }
+        "#,
+        surface = theme_style.surface,
+        on_surface = theme_style.on_surface,
+        outline = theme_style.outline,
+    );
+
+    let Some(label) = &*label else {
+        return html! {};
+    };
+
+    let details = [
+        label.country.clone(),
+        label
+            .founded
+            .as_ref()
+            .map(|founded| format!("founded {}", founded)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(", ");
+
+    // Releases come sorted by date, so a new year starts where the year changes
+    let mut years: Vec<(&str, Vec<&LabelRelease>)> = Vec::new();
+    for release in releases.iter() {
+        let year = release.album.date_published.get(..4).unwrap_or_default();
+        match years.last_mut() {
+            Some((last, group)) if *last == year => group.push(release),
+            _ => years.push((year, vec![release])),
+        }
+    }
+
+    html! {
+        <div class={ style }>
+            <h1>{ &label.name }</h1>
+            <p>
+                { details }
+                if let Some(parent) = &*parent {
+                    { " — part of " }
+                    <Link<Route> to={ Route::Label { id: parent.id } }>{ &parent.name }</Link<Route>>
+                }
+            </p>
+            if releases.is_empty() {
+                <p>{ "No releases yet" }</p>
+            }
+            {for years.into_iter().map(|(year, group)| html! {
+                <>
+                    <h3>{ year }</h3>
+                    <ul>
+                        {for group.into_iter().map(|release| html! {
+                            <li>
+                                <Link<Route> to={ Route::Album { id: release.album.id } }>{ &release.album.name }</Link<Route>>
+                                if let Some(catalog_number) = &release.catalog_number {
+                                    <small>{ catalog_number }</small>
+                                }
+                            </li>
+                        })}
+                    </ul>
+                </>
+            })}
+        </div>
+    }
+}
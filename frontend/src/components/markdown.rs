@@ -0,0 +1,84 @@
+use markdown::{tokenize, Block, ListItem, Span};
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub text: String,
+}
+
+/// Markdown written by users, such as reviews, rendered without any HTML of their own
+///
+/// The text is parsed by the `markdown` crate, but turned into nodes here rather than into an HTML
+/// string: every bit of text is escaped as any other, and links and images are only kept when
+/// their URL is safe to follow.
+#[function_component(Markdown)]
+pub fn markdown_view(props: &Props) -> Html {
+    html! {
+        <>
+            {for tokenize(&props.text).iter().map(block)}
+        </>
+    }
+}
+
+/// Whether a URL is relative, or uses a scheme that cannot run scripts
+fn safe_url(url: &str) -> bool {
+    // Browsers ignore whitespace and control characters within a scheme, as in "java\tscript:"
+    let url = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    match url.find([':', '/', '?', '#']) {
+        Some(index) if url[index..].starts_with(':') => {
+            ["http:", "https:", "mailto:"]
+                .iter()
+                .any(|scheme| url.starts_with(scheme))
+        }
+        _ => true,
+    }
+}
+
+fn block(block: &Block) -> Html {
+    match block {
+        Block::Header(spans, level) => html! {
+            <@{ format!("h{}", (*level).clamp(1, 6)) }>{for spans.iter().map(span)}</@>
+        },
+        Block::Paragraph(spans) => html! { <p>{for spans.iter().map(span)}</p> },
+        Block::Blockquote(blocks) => html! {
+            <blockquote>{for blocks.iter().map(self::block)}</blockquote>
+        },
+        Block::CodeBlock(_, code) => html! { <pre><code>{ code }</code></pre> },
+        Block::OrderedList(items, kind) => html! {
+            <ol type={ kind.0.clone() }>{for items.iter().map(item)}</ol>
+        },
+        Block::UnorderedList(items) => html! { <ul>{for items.iter().map(item)}</ul> },
+        Block::Raw(text) => html! { <p>{ text }</p> },
+        Block::Hr => html! { <hr /> },
+    }
+}
+
+fn item(item: &ListItem) -> Html {
+    match item {
+        ListItem::Simple(spans) => html! { <li>{for spans.iter().map(span)}</li> },
+        ListItem::Paragraph(blocks) => html! { <li>{for blocks.iter().map(block)}</li> },
+    }
+}
+
+fn span(span: &Span) -> Html {
+    match span {
+        Span::Break => html! { <br /> },
+        Span::Text(text) => html! { { text } },
+        Span::Code(code) => html! { <code>{ code }</code> },
+        Span::Link(text, url, title) if safe_url(url) => html! {
+            <a href={ url.clone() } title={ title.clone() } rel="nofollow noopener noreferrer">{ text }</a>
+        },
+        Span::Link(text, _, _) => html! { { text } },
+        Span::Image(alt, url, title) if safe_url(url) => html! {
+            <img src={ url.clone() } alt={ alt.clone() } title={ title.clone() } />
+        },
+        Span::Image(alt, _, _) => html! { { alt } },
+        Span::Emphasis(spans) => html! { <em>{for spans.iter().map(self::span)}</em> },
+        Span::Strong(spans) => html! { <strong>{for spans.iter().map(self::span)}</strong> },
+    }
+}
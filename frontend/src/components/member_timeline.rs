@@ -0,0 +1,136 @@
+use entities::artist_membership::Member;
+use gloo_net::http::Request;
+use stylist::yew::use_style;
+use yew::prelude::*;
+
+use crate::theme::Theme;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub artist_id: i32,
+}
+
+/// Year of a `YYYY-MM-DD` date
+fn year(date: &str) -> Option<i32> {
+    date.get(..4)?.parse().ok()
+}
+
+/// Members of an artist, each with a bar spanning the years they were in it
+///
+/// The timeline runs from the earliest known start to this year. Members whose start is unknown
+/// are listed without a bar, and those who did not leave have their bar reach the end.
+#[function_component(MemberTimeline)]
+pub fn member_timeline(props: &Props) -> Html {
+    let members = use_state(Vec::<Member>::new);
+
+    {
+        let id = props.artist_id;
+        let members = members.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let resp = Request::get(&format!("/api/artist/members/{}", id))
+                        .send()
+                        .await
+                        .expect("Failed to send request")
+                        .json::<Vec<Member>>()
+                        .await
+                        .expect("Failed to parse response");
+
+                    members.set(resp);
+                })
+            },
+            (),
+        );
+    }
+
+    let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
+    let theme_style = theme.get_theme();
+
+    let style = use_style!(
+        r#"
+            width: 90%;
+            padding: 0;
+            list-style: none;
+
+            li {
+                display: flex;
+                align-items: center;
+                margin: 5px 0;
+            }
+
+            .who {
+                width: 30%;
+            }
+
+            .track {
+                position: relative;
+                flex-grow: 1;
+                height: 1em;
+                border-bottom: 1px solid ${outline};
+            }
+
+            .bar {
+                position: absolute;
+                height: 100%;
+                border-radius: 5px;
+                background-color: ${primary};
+            }
+
+            .years {
+                width: 15%;
+                text-align: right;
+                font-size: 0.8em;
+            }
+        "#,
+        outline = theme_style.outline,
+        primary = theme_style.primary,
+    );
+
+    if members.is_empty() {
+        return html! {};
+    }
+
+    let this_year = js_sys::Date::new_0().get_full_year() as i32;
+    let first_year = members
+        .iter()
+        .filter_map(|member| member.membership.start_date.as_deref().and_then(year))
+        .min()
+        .unwrap_or(this_year);
+    let span = (this_year - first_year + 1) as f64;
+
+    html! {
+        <ul class={ style }>
+            {for members.iter().map(|member| {
+                let membership = &member.membership;
+                let start = membership.start_date.as_deref().and_then(year);
+                let end = membership.end_date.as_deref().and_then(year).unwrap_or(this_year);
+                let part = match &membership.instrument {
+                    Some(instrument) => format!("{}, {}", membership.role, instrument),
+                    None => membership.role.clone(),
+                };
+                let years = match (start, &membership.end_date) {
+                    (Some(start), Some(_)) => format!("{} – {}", start, end),
+                    (Some(start), None) => format!("{} – present", start),
+                    (None, _) => "Unknown".to_string(),
+                };
+
+                html! {
+                    <li>
+                        <span class="who">{ &*member.person.name } <small>{ format!(" ({})", part) }</small></span>
+                        <span class="track">
+                            if let Some(start) = start {
+                                <span class="bar" style={ format!(
+                                    "left: {:.2}%; width: {:.2}%;",
+                                    (start - first_year) as f64 / span * 100.0,
+                                    (end - start + 1).max(1) as f64 / span * 100.0,
+                                ) } />
+                            }
+                        </span>
+                        <span class="years">{ years }</span>
+                    </li>
+                }
+            })}
+        </ul>
+    }
+}
@@ -1,9 +1,29 @@
 mod album;
+mod appearances;
 mod artist;
+mod conflict;
+mod field_error;
+mod genre_select;
+mod label;
+mod markdown;
+mod member_timeline;
 mod navbar;
+mod player;
+mod playlist;
+mod reviews;
 mod song;
 
 pub use album::AlbumView;
+pub use appearances::Appearances;
 pub use artist::ArtistView;
+pub use conflict::{save_outcome, ConflictNotice, SaveOutcome};
+pub use field_error::{revalidate, FieldErrorText};
+pub use genre_select::GenreSelect;
+pub use label::LabelView;
+pub use markdown::Markdown;
+pub use member_timeline::MemberTimeline;
 pub use navbar::Navbar;
+pub use player::{PlayQueue, Player};
+pub use playlist::PlaylistView;
+pub use reviews::Reviews;
 pub use song::SongView;
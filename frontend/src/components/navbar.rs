@@ -1,12 +1,33 @@
+use entities::user::Model as User;
+use gloo_net::http::Request;
 use stylist::yew::use_style;
 use yew::prelude::*;
-use yew_router::prelude::Link;
+use yew_router::prelude::{use_navigator, Link};
 
 use crate::{router::Route, theme::Theme};
 
 #[function_component(Navbar)]
 pub fn navbar() -> Html {
     let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
+    let user = use_context::<UseStateHandle<Option<User>>>().expect("No context found");
+
+    let logout = {
+        let user = user.clone();
+        let navigator = use_navigator().unwrap();
+        Callback::from(move |_| {
+            let user = user.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                Request::post("/api/auth/logout")
+                    .send()
+                    .await
+                    .expect("Failed to send request to log out");
+
+                user.set(None);
+            });
+
+            navigator.push(&Route::Home);
+        })
+    };
 
     let toggle_theme = {
         let theme = theme.clone();
@@ -75,6 +96,10 @@ pub fn navbar() -> Html {
                     margin: 0;
                 }
 
+                li span {
+                    margin-right: 10px;
+                }
+
                 a {
                     text-decoration: none;
                     background: ${primary_container};
@@ -120,6 +145,18 @@ pub fn navbar() -> Html {
                 <li><Link<Route> to={Route::Songs}>{ "Songs" }</Link<Route>></li>
                 <li><Link<Route> to={Route::Albums}>{ "Albums" }</Link<Route>></li>
                 <li><Link<Route> to={Route::Artists}>{ "Artists" }</Link<Route>></li>
+                <li><Link<Route> to={Route::Playlists}>{ "Playlists" }</Link<Route>></li>
+                {match &*user {
+                    Some(user) => html! {
+                        <li>
+                            <span>{ &user.username }</span>
+                            <button onclick={ logout }>{ "Log out" }</button>
+                        </li>
+                    },
+                    None => html! {
+                        <li><Link<Route> to={Route::Login}>{ "Log in" }</Link<Route>></li>
+                    },
+                }}
                 <button onclick={ toggle_theme }>
                     {match *theme {
                         Theme::Light => "Light",
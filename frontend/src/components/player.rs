@@ -0,0 +1,172 @@
+use entities::song::Model as Song;
+use stylist::yew::use_style;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlInputElement, HtmlMediaElement};
+use yew::prelude::*;
+
+use crate::theme::Theme;
+
+/// Songs to play in order, shared by the player and the tracklists filling it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayQueue {
+    pub songs: Vec<Song>,
+    /// Index of the song playing, or about to
+    pub current: usize,
+}
+
+impl PlayQueue {
+    /// A queue playing `songs` from the first one
+    pub fn of(songs: Vec<Song>) -> Self {
+        PlayQueue { songs, current: 0 }
+    }
+
+    /// The same queue with `songs` added at the end
+    pub fn with(&self, songs: &[Song]) -> Self {
+        let mut queue = self.clone();
+        queue.songs.extend_from_slice(songs);
+        queue
+    }
+
+    /// The same queue playing another song, if there is one at `index`
+    pub fn at(&self, index: usize) -> Option<Self> {
+        (index < self.songs.len()).then(|| PlayQueue {
+            songs: self.songs.clone(),
+            current: index,
+        })
+    }
+}
+
+/// Player of the queue, kept at the bottom of the page across routes
+#[function_component(Player)]
+pub fn player() -> Html {
+    let queue = use_context::<UseStateHandle<PlayQueue>>().expect("No context found");
+    let audio = use_node_ref();
+    let playing = use_state(|| false);
+    let position = use_state(|| 0.0);
+
+    let media = {
+        let audio = audio.clone();
+        move || audio.cast::<HtmlMediaElement>()
+    };
+
+    let toggle = {
+        let media = media.clone();
+        Callback::from(move |_| {
+            let Some(media) = media() else {
+                return;
+            };
+            if media.paused() {
+                let _ = media.play();
+            } else {
+                let _ = media.pause();
+            }
+        })
+    };
+    let skip = |offset: isize| {
+        let queue = queue.clone();
+        Callback::from(move |_: ()| {
+            let index = queue.current as isize + offset;
+            if let Some(moved) = usize::try_from(index).ok().and_then(|index| queue.at(index)) {
+                queue.set(moved);
+            }
+        })
+    };
+    let seek = {
+        let media = media.clone();
+        Callback::from(move |event: InputEvent| {
+            let input = event.target().unwrap().unchecked_into::<HtmlInputElement>();
+            if let (Some(media), Ok(secs)) = (media(), input.value().parse::<f64>()) {
+                media.set_current_time(secs);
+            }
+        })
+    };
+    let update_position = {
+        let position = position.clone();
+        Callback::from(move |event: Event| {
+            let media = event.target().unwrap().unchecked_into::<HtmlMediaElement>();
+            position.set(media.current_time());
+        })
+    };
+    let set_playing = |value: bool| {
+        let playing = playing.clone();
+        Callback::from(move |_: Event| playing.set(value))
+    };
+
+    let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
+    let theme_style = theme.get_theme();
+
+    let style = use_style!(
+        r#"
+            position: fixed;
+            bottom: 0;
+            left: 0;
+            right: 0;
+            display: flex;
+            align-items: center;
+            gap: 10px;
+            padding: 10px 20px;
+            background-color: ${surface};
+            color: ${on_surface};
+            border-top: 1px solid ${outline};
+
+            button {
+                background-color: ${surface};
+                color: ${on_surface};
+                border: 2px solid ${outline};
+                border-radius: 10px;
+                padding: 5px 10px;
+            }
+
+            button:hover {
+                background-color: ${primary};
+                color: ${on_primary};
+                cursor: pointer;
+            }
+
+            input {
+                flex-grow: 1;
+            }
+        "#,
+        surface = theme_style.surface,
+        on_surface = theme_style.on_surface,
+        outline = theme_style.outline,
+        primary = theme_style.primary,
+        on_primary = theme_style.on_primary,
+    );
+
+    let Some(song) = queue.songs.get(queue.current) else {
+        return html! {};
+    };
+
+    html! {
+        <div class={ style }>
+            // Songs without audio fail to load, and are skipped
+            <audio
+                ref={ audio }
+                src={ format!("/api/song/{}/stream", song.id) }
+                autoplay=true
+                ontimeupdate={ update_position }
+                onplay={ set_playing(true) }
+                onpause={ set_playing(false) }
+                onended={ skip(1).reform(|_: Event| ()) }
+                onerror={ skip(1).reform(|_: Event| ()) }
+            />
+            <button onclick={ skip(-1).reform(|_: MouseEvent| ()) }>{ "Previous" }</button>
+            <button onclick={ toggle }>{ if *playing { "Pause" } else { "Play" } }</button>
+            <button onclick={ skip(1).reform(|_: MouseEvent| ()) }>{ "Next" }</button>
+            <span>{ format!("{} ({}/{})", song.name, queue.current + 1, queue.songs.len()) }</span>
+            <input
+                type="range"
+                min="0"
+                max={ song.length_secs.to_string() }
+                value={ (*position as i32).to_string() }
+                oninput={ seek }
+            />
+            <span>{ format!("{} / {}", minsec(*position as i32), minsec(song.length_secs)) }</span>
+        </div>
+    }
+}
+
+fn minsec(secs: i32) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
@@ -0,0 +1,260 @@
+use entities::playlist::Model as Playlist;
+use entities::playlist_song::{PlaylistEntry, PlaylistSongs};
+use entities::sea_orm_active_enums::Role;
+use entities::user::Model as User;
+use gloo_net::http::Request;
+use serde::Serialize;
+use stylist::yew::use_style;
+use yew::prelude::*;
+use yew_router::prelude::use_navigator;
+
+use crate::router::Route;
+use crate::theme::Theme;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub id: i32,
+}
+
+/// Body of a request moving a song of the playlist
+#[derive(Serialize)]
+struct PlaylistMove {
+    position: i32,
+}
+
+/// Move the song at `from` to the position `to`
+async fn move_song(id: i32, from: i32, to: i32) {
+    Request::put(&format!("/api/playlist/{}/songs/{}", id, from))
+        .json(&PlaylistMove { position: to })
+        .expect("Failed to serialize move")
+        .send()
+        .await
+        .expect("Failed to send request to move song");
+}
+
+#[function_component(PlaylistView)]
+pub fn playlist_view(props: &Props) -> Html {
+    let playlist = use_state(|| Playlist {
+        id: props.id,
+        name: String::new(),
+        user_id: 0,
+    });
+    let songs = use_state(|| PlaylistSongs {
+        songs: Vec::new(),
+        total_length_secs: 0,
+    });
+    // Bumped after every change to the songs, to fetch them again
+    let changes = use_state(|| 0);
+
+    {
+        let id = props.id;
+        let playlist = playlist.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let resp = Request::get(&format!("/api/playlist/{}", id))
+                        .send()
+                        .await
+                        .expect("Failed to fetch playlist")
+                        .json::<Playlist>()
+                        .await
+                        .expect("Failed to parse playlist");
+
+                    playlist.set(resp);
+                })
+            },
+            (),
+        );
+    }
+
+    {
+        let id = props.id;
+        let songs = songs.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let resp = Request::get(&format!("/api/playlist/{}/songs", id))
+                        .send()
+                        .await
+                        .expect("Failed to fetch songs")
+                        .json::<PlaylistSongs>()
+                        .await
+                        .expect("Failed to parse songs");
+
+                    songs.set(resp);
+                })
+            },
+            *changes,
+        );
+    }
+
+    let navigator = use_navigator().unwrap();
+    let delete = {
+        let id = props.id;
+        Callback::from(move |_| {
+            let window = web_sys::window().unwrap();
+            let confirm = window
+                .confirm_with_message("Press OK to delete this playlist")
+                .unwrap_or(false);
+
+            if !confirm {
+                return;
+            }
+
+            let navigator = navigator.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                Request::delete(&format!("/api/playlist/{}", id))
+                    .send()
+                    .await
+                    .expect("Failed to send request to delete playlist");
+
+                navigator.push(&Route::Playlists);
+            });
+        })
+    };
+
+    // Songs in the trash are not listed, so a song moves to the position of its visible neighbour
+    let move_to = {
+        let id = props.id;
+        let changes = changes.clone();
+        Callback::from(move |(from, to): (i32, i32)| {
+            let changes = changes.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                move_song(id, from, to).await;
+                changes.set(*changes + 1);
+            });
+        })
+    };
+    let remove = {
+        let id = props.id;
+        let changes = changes.clone();
+        Callback::from(move |position: i32| {
+            let changes = changes.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                Request::delete(&format!("/api/playlist/{}/songs/{}", id, position))
+                    .send()
+                    .await
+                    .expect("Failed to send request to remove song");
+
+                changes.set(*changes + 1);
+            });
+        })
+    };
+
+    let curates = use_context::<UseStateHandle<Option<User>>>()
+        .expect("No context found")
+        .as_ref()
+        .map_or(false, |user| {
+            user.id == playlist.user_id || user.role >= Role::Admin
+        });
+    let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
+    let theme_style = theme.get_theme();
+
+    let style = use_style!(
+        r#"
+            background: ${surface};
+            color: ${on_surface};
+            border-radius: 50px;
+            border: 1px solid ${outline};
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            padding: 10px 0;
+
+            h1 {
+                font-size: 2em;
+            }
+
+            ol {
+                width: 90%;
+            }
+
+            li {
+                display: flex;
+                justify-content: space-between;
+                align-items: baseline;
+                margin-bottom: 5px;
+            }
+
+            button {
+                background-color: ${surface};
+                color: ${on_surface};
+                border: 2px solid ${outline};
+                border-radius: 10px;
+                padding: 5px 10px;
+                margin: 0 5px;
+            }
+
+            button:hover {
+                background-color: ${primary};
+                color: ${on_primary};
+                cursor: pointer;
+            }
+        "#,
+        surface = theme_style.surface,
+        on_surface = theme_style.on_surface,
+        outline = theme_style.outline,
+        primary = theme_style.primary,
+        on_primary = theme_style.on_primary,
+    );
+
+    let delete_style = use_style!(
+        r#"
+            :hover {
+                background-color: ${error};
+                color: ${on_error};
+            }
+        "#,
+        error = theme_style.error,
+        on_error = theme_style.on_error,
+    );
+
+    let entries = &songs.songs;
+
+    html! {
+        <div class={ style }>
+            <h1>{ &playlist.name }</h1>
+            <p>{ format!("{} songs, {}", entries.len(), duration(songs.total_length_secs)) }</p>
+            if curates {
+                <button onclick={ delete } class={ delete_style }>{ "Delete playlist" }</button>
+            }
+            <ol>
+                {for entries.iter().enumerate().map(|(index, entry)| {
+                    let PlaylistEntry { position, song } = entry;
+                    let position = *position;
+                    let previous = index.checked_sub(1).map(|index| entries[index].position);
+                    let next = entries.get(index + 1).map(|entry| entry.position);
+
+                    html! {
+                        <li>
+                            <span>{ format!("{}. {}", index + 1, song.name) }</span>
+                            <span>
+                                { duration(song.length_secs.into()) }
+                                if curates {
+                                    if let Some(to) = previous {
+                                        <button onclick={ move_to.reform(move |_| (position, to)) }>{ "Up" }</button>
+                                    }
+                                    if let Some(to) = next {
+                                        <button onclick={ move_to.reform(move |_| (position, to)) }>{ "Down" }</button>
+                                    }
+                                    <button onclick={ remove.reform(move |_| position) }>{ "Remove" }</button>
+                                }
+                            </span>
+                        </li>
+                    }
+                })}
+            </ol>
+        </div>
+    }
+}
+
+/// Format a number of seconds as `m:ss`, or `h:mm:ss` from an hour on
+fn duration(secs: i64) -> String {
+    let (hours, mins, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, mins, secs)
+    } else {
+        format!("{}:{:02}", mins, secs)
+    }
+}
@@ -0,0 +1,270 @@
+use entities::review::{Model as Review, SignedReview};
+use entities::sea_orm_active_enums::Role;
+use entities::user::Model as User;
+use entities::validation::{message_for, FieldError};
+use gloo_net::http::Request;
+use stylist::yew::use_style;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+use crate::components::Markdown;
+use crate::theme::Theme;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub album_id: i32,
+    /// Called after the signed in user submits, edits or deletes a review
+    #[prop_or_default]
+    pub onchange: Callback<()>,
+}
+
+/// Reviews of an album, most recent first, with a form to rate and review it when signed in
+///
+/// The form starts from the review of the signed in user, if they already wrote one, and edits it
+/// rather than submitting another.
+#[function_component(Reviews)]
+pub fn reviews(props: &Props) -> Html {
+    let reviews = use_state(Vec::<SignedReview>::new);
+    // Bumped after each change, to fetch the reviews again
+    let reload = use_state(|| 0);
+    let draft = use_state(|| Review {
+        id: 0,
+        user_id: 0,
+        album_id: Some(props.album_id),
+        song_id: None,
+        rating: 10,
+        body: None,
+    });
+    let errors = use_state(Vec::<FieldError>::new);
+
+    let user = use_context::<UseStateHandle<Option<User>>>().expect("No context found");
+    let user_id = user.as_ref().map(|user| user.id);
+
+    {
+        let id = props.album_id;
+        let reviews = reviews.clone();
+        let draft = draft.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let resp = Request::get(&format!("/api/review/album/{}", id))
+                        .send()
+                        .await
+                        .expect("Failed to fetch reviews")
+                        .json::<Vec<SignedReview>>()
+                        .await
+                        .expect("Failed to parse reviews");
+
+                    if let Some(own) = resp
+                        .iter()
+                        .find(|review| Some(review.review.user_id) == user_id)
+                    {
+                        draft.set(own.review.clone());
+                    }
+                    reviews.set(resp);
+                })
+            },
+            (*reload, user_id),
+        );
+    }
+
+    let rating_onchange = {
+        let draft = draft.clone();
+        Callback::from(move |event: Event| {
+            let rating = event
+                .target()
+                .unwrap()
+                .unchecked_into::<HtmlSelectElement>()
+                .value();
+
+            draft.set(Review {
+                rating: rating.parse().unwrap_or_default(),
+                ..(*draft).clone()
+            });
+        })
+    };
+
+    let body_onchange = {
+        let draft = draft.clone();
+        Callback::from(move |event: Event| {
+            let body = event
+                .target()
+                .unwrap()
+                .unchecked_into::<HtmlTextAreaElement>()
+                .value();
+
+            draft.set(Review {
+                body: Some(body),
+                ..(*draft).clone()
+            });
+        })
+    };
+
+    let submit = {
+        let id = props.album_id;
+        let draft = draft.clone();
+        let errors = errors.clone();
+        let reload = reload.clone();
+        let onchange = props.onchange.clone();
+        Callback::from(move |_| {
+            let draft = draft.clone();
+            let errors = errors.clone();
+            let reload = reload.clone();
+            let onchange = onchange.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                // A review already written is edited, as each user reviews an album once
+                let request = if draft.id == 0 {
+                    Request::post(&format!("/api/review/album/{}", id))
+                } else {
+                    Request::put(&format!("/api/review/{}", draft.id))
+                };
+                let resp = request
+                    .json(&*draft)
+                    .expect("Failed to serialize review")
+                    .send()
+                    .await
+                    .expect("Failed to send request to save review");
+
+                if resp.ok() {
+                    errors.set(Vec::new());
+                    reload.set(*reload + 1);
+                    onchange.emit(());
+                } else {
+                    errors.set(vec![FieldError::new(
+                        "rating",
+                        "could not be saved, try again",
+                    )]);
+                }
+            });
+        })
+    };
+
+    let delete = {
+        let draft = draft.clone();
+        let reload = reload.clone();
+        let onchange = props.onchange.clone();
+        Callback::from(move |id: i32| {
+            let window = web_sys::window().unwrap();
+            let confirm = window
+                .confirm_with_message("Press OK to delete this review")
+                .unwrap_or(false);
+
+            if !confirm {
+                return;
+            }
+
+            // Once their review is gone, the user starts a new one
+            if draft.id == id {
+                draft.set(Review {
+                    id: 0,
+                    body: None,
+                    ..(*draft).clone()
+                });
+            }
+
+            let reload = reload.clone();
+            let onchange = onchange.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                Request::delete(&format!("/api/review/{}", id))
+                    .send()
+                    .await
+                    .expect("Failed to send request to delete review");
+
+                reload.set(*reload + 1);
+                onchange.emit(());
+            });
+        })
+    };
+
+    let role = user.as_ref().map(|user| user.role);
+    let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
+    let theme_style = theme.get_theme();
+
+    let style = use_style!(
+        r#"
+            width: 90%;
+            align-self: center;
+
+            ul {
+                list-style: none;
+                padding: 0;
+            }
+
+            li {
+                border-top: 1px solid ${outline};
+                padding: 5px 0;
+            }
+
+            .review-form {
+                display: flex;
+                flex-direction: column;
+                gap: 5px;
+            }
+
+            textarea {
+                min-height: 5em;
+            }
+
+            small {
+                color: ${error};
+            }
+        "#,
+        outline = theme_style.outline,
+        error = theme_style.error,
+    );
+
+    html! {
+        <div class={ style }>
+            <h3>{ "Reviews" }</h3>
+            if user_id.is_some() {
+                <div class="review-form">
+                    <label>
+                        { "Rating " }
+                        <select onchange={ rating_onchange }>
+                            {for (1..=10).rev().map(|rating| html! {
+                                <option value={ rating.to_string() } selected={ draft.rating == rating }>
+                                    { rating }
+                                </option>
+                            })}
+                        </select>
+                    </label>
+                    <textarea
+                        placeholder="Write a review, in markdown"
+                        value={ draft.body.clone().unwrap_or_default() }
+                        onchange={ body_onchange }
+                    />
+                    if let Some(message) = message_for(&errors, "rating") {
+                        <small>{ message }</small>
+                    }
+                    <button onclick={ submit }>
+                        { if draft.id == 0 { "Submit review" } else { "Edit review" } }
+                    </button>
+                </div>
+            }
+            if reviews.is_empty() {
+                <p>{ "No reviews yet" }</p>
+            }
+            <ul>
+                {for reviews.iter().map(|review| {
+                    let id = review.review.id;
+                    let can_delete = Some(review.review.user_id) == user_id || role >= Some(Role::Admin);
+                    let delete = delete.clone();
+
+                    html! {
+                        <li>
+                            <strong>{ &review.username }</strong>
+                            { format!(" rated it {}/10", review.review.rating) }
+                            if can_delete {
+                                <button onclick={ Callback::from(move |_| delete.emit(id)) }>{ "Delete" }</button>
+                            }
+                            if let Some(body) = &review.review.body {
+                                <Markdown text={ body.clone() } />
+                            }
+                        </li>
+                    }
+                })}
+            </ul>
+        </div>
+    }
+}
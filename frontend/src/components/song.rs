@@ -1,5 +1,10 @@
 use entities::album::Model as Album;
+use entities::etag::etag;
+use entities::review::{RatedSong, Ratings};
+use entities::sea_orm_active_enums::Role;
 use entities::song::Model as Song;
+use entities::song_credit::Credit;
+use entities::user::Model as User;
 use gloo_net::http::Request;
 use stylist::yew::use_style;
 use wasm_bindgen::JsCast;
@@ -12,6 +17,9 @@ use crate::{router::Route, theme::Theme};
 #[derive(Properties, PartialEq)]
 pub struct Props {
     pub id: i32,
+    /// Whether to show the track number before the name, as in a tracklist
+    #[prop_or(false)]
+    pub numbered: bool,
 }
 
 #[function_component(SongView)]
@@ -21,8 +29,13 @@ pub fn song_view(props: &Props) -> Html {
         name: String::new(),
         length_secs: 0,
         album_id: 0,
+        disc_number: 1,
+        track_number: 0,
+        version: 0,
     });
+    let ratings = use_state(Ratings::default);
     let album_name = use_state(|| String::new());
+    let credits = use_state(Vec::<Credit>::new);
 
     let navigator = use_navigator().unwrap();
     let edit = {
@@ -32,6 +45,7 @@ pub fn song_view(props: &Props) -> Html {
     };
     let delete = {
         let id = song.id;
+        let version = song.version;
         Callback::from(move |event: MouseEvent| {
             let window = web_sys::window().unwrap();
             let confirm = window
@@ -54,6 +68,7 @@ pub fn song_view(props: &Props) -> Html {
 
             wasm_bindgen_futures::spawn_local(async move {
                 Request::delete(&format!("/api/song/{}", id))
+                    .header("If-Match", &etag(version))
                     .send()
                     .await
                     .expect("Failed to send request to delete song");
@@ -64,6 +79,7 @@ pub fn song_view(props: &Props) -> Html {
     {
         let id = props.id;
         let song = song.clone();
+        let ratings = ratings.clone();
         let album_name = album_name.clone();
         use_effect_with_deps(
             move |_| {
@@ -72,12 +88,13 @@ pub fn song_view(props: &Props) -> Html {
                         .send()
                         .await
                         .expect("Failed to send request to fetch song")
-                        .json::<Song>()
+                        .json::<RatedSong>()
                         .await
                         .expect("Failed to parse response into song");
 
-                    let album_id = resp.album_id;
-                    song.set(resp);
+                    let album_id = resp.song.album_id;
+                    song.set(resp.song);
+                    ratings.set(resp.ratings);
 
                     let resp = Request::get(&format!("/api/album/{}", album_id))
                         .send()
@@ -94,6 +111,31 @@ pub fn song_view(props: &Props) -> Html {
         );
     }
 
+    {
+        let id = props.id;
+        let credits = credits.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let resp = Request::get(&format!("/api/song/{}/credits", id))
+                        .send()
+                        .await
+                        .expect("Failed to send request to fetch credits")
+                        .json::<Vec<Credit>>()
+                        .await
+                        .expect("Failed to parse response into credits");
+
+                    credits.set(resp);
+                })
+            },
+            (),
+        );
+    }
+
+    let role = use_context::<UseStateHandle<Option<User>>>()
+        .expect("No context found")
+        .as_ref()
+        .map(|user| user.role);
     let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
     let theme_style = theme.get_theme();
 
@@ -162,13 +204,42 @@ pub fn song_view(props: &Props) -> Html {
 
     html! {
         <div class={ style }>
-            <h1>{ &song.name }</h1>
+            if props.numbered {
+                <h1>{ format!("{}. {}", song.track_number, song.name) }</h1>
+            } else {
+                <h1>{ &song.name }</h1>
+            }
+            if !credits.is_empty() {
+                <p>
+                    {for credits.iter().map(|credit| {
+                        // Without a join phrase, the role says what the artist did
+                        let before = match &credit.credit.join_phrase {
+                            Some(phrase) => format!(" {} ", phrase),
+                            None => format!(" {}: ", credit.credit.role),
+                        };
+
+                        html! {
+                            <>
+                                { before }
+                                <Link<Route> to={ Route::Artist { id: credit.artist.id } }>{ &credit.artist.name }</Link<Route>>
+                            </>
+                        }
+                    })}
+                </p>
+            }
             <p>{ &*sec_to_minsec(song.length_secs) }</p>
+            if let Some(average) = ratings.average_rating {
+                <p>{ format!("{:.1}/10 from {} ratings", average, ratings.rating_count) }</p>
+            }
             <Link<Route> to={ Route::Artist { id: song.album_id } }>{ &*album_name }</Link<Route>>
-            <span>
-                <button onclick={ edit } class={ edit_style }>{ "Edit" }</button>
-                <button onclick={ delete } class={ delete_style }>{ "Delete" }</button>
-            </span>
+            if role >= Some(Role::Editor) {
+                <span>
+                    <button onclick={ edit } class={ edit_style }>{ "Edit" }</button>
+                    if role >= Some(Role::Admin) {
+                        <button onclick={ delete } class={ delete_style }>{ "Delete" }</button>
+                    }
+                </span>
+            }
         </div>
     }
 }
@@ -3,17 +3,40 @@ mod pages;
 mod router;
 mod theme;
 
+use entities::user::Model as User;
+use gloo_net::http::Request;
 use router::{switch, Route};
 use stylist::yew::use_style;
 use theme::Theme;
 use yew::prelude::*;
 use yew_router::prelude::*;
 
-use components::Navbar;
+use components::{Navbar, PlayQueue, Player};
 
 #[function_component(App)]
 pub fn app() -> Html {
     let theme = use_state(|| Theme::Dark);
+    let user = use_state(|| None::<User>);
+    let queue = use_state(PlayQueue::default);
+
+    {
+        let user = user.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let resp = Request::get("/api/auth/me")
+                        .send()
+                        .await
+                        .expect("Failed to send request");
+
+                    if resp.ok() {
+                        user.set(resp.json::<User>().await.ok());
+                    }
+                })
+            },
+            (),
+        );
+    }
 
     let style = {
         let theme = theme.clone();
@@ -35,6 +58,7 @@ pub fn app() -> Html {
     let window_style = use_style!(
         r#"
             margin: 10px;
+            padding-bottom: 60px;
         "#
     );
 
@@ -42,10 +66,15 @@ pub fn app() -> Html {
         <div class={style}>
             <BrowserRouter>
                 <ContextProvider<UseStateHandle<Theme>> context={ theme }>
+                <ContextProvider<UseStateHandle<Option<User>>> context={ user }>
+                <ContextProvider<UseStateHandle<PlayQueue>> context={ queue }>
                     <Navbar />
                     <div class={ window_style }>
                         <Switch<Route> render={ switch } />
                     </div>
+                    <Player />
+                </ContextProvider<UseStateHandle<PlayQueue>>>
+                </ContextProvider<UseStateHandle<Option<User>>>>
                 </ContextProvider<UseStateHandle<Theme>>>
             </BrowserRouter>
         </div>
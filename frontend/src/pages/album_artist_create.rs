@@ -1,4 +1,4 @@
-use entities::artist::Model as Artist;
+use entities::{artist::Model as Artist, pagination::Page};
 use gloo_net::http::Request;
 use stylist::yew::use_style;
 use wasm_bindgen::JsCast;
@@ -23,15 +23,23 @@ pub fn album_artist_create(props: &Props) -> Html {
         use_effect_with_deps(
             move |_| {
                 wasm_bindgen_futures::spawn_local(async move {
-                    let resp = Request::get("/api/artist/all")
-                        .send()
-                        .await
-                        .expect("Failed to fetch artists")
-                        .json::<Vec<Artist>>()
-                        .await
-                        .expect("Failed to parse artists");
-
-                    artists.set(resp);
+                    let mut all = Vec::new();
+                    let mut next = Some("/artist/all?per_page=500".to_string());
+
+                    while let Some(link) = next {
+                        let resp = Request::get(&format!("/api{}", link))
+                            .send()
+                            .await
+                            .expect("Failed to fetch artists")
+                            .json::<Page<Artist>>()
+                            .await
+                            .expect("Failed to parse artists");
+
+                        all.extend(resp.items);
+                        next = resp.next;
+                    }
+
+                    artists.set(all);
                 })
             },
             (),
@@ -1,6 +1,9 @@
 use std::ops::Deref;
 
-use entities::album::Model as Album;
+use entities::{
+    album::Model as Album,
+    validation::{FieldError, Validate},
+};
 use gloo_net::http::Request;
 use stylist::yew::use_style;
 use wasm_bindgen::JsCast;
@@ -8,7 +11,10 @@ use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew_router::prelude::use_navigator;
 
-use crate::theme::Theme;
+use crate::{
+    components::{revalidate, FieldErrorText},
+    theme::Theme,
+};
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
@@ -21,13 +27,21 @@ pub fn album_create(props: &Props) -> Html {
         id: 0,
         name: String::new(),
         date_published: String::new(),
+        version: 0,
     });
+    let errors = use_state(Vec::<FieldError>::new);
 
     let create = {
         let artist_id = props.artist_id;
         let album = album.clone();
         let navigator = use_navigator().unwrap();
+        let errors = errors.clone();
         Callback::from(move |_| {
+            if let Err(new_errors) = album.validate() {
+                errors.set(new_errors);
+                return;
+            }
+
             let album = album.clone();
             wasm_bindgen_futures::spawn_local(async move {
                 Request::post(&format!("/api/album/{}", artist_id))
@@ -45,6 +59,7 @@ pub fn album_create(props: &Props) -> Html {
 
     let name_onchange = {
         let album = album.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let name = event
                 .target()
@@ -52,15 +67,18 @@ pub fn album_create(props: &Props) -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value();
 
-            album.set(Album {
+            let new_album = Album {
                 name,
                 ..album.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_album, "name", &errors));
+            album.set(new_album)
         })
     };
 
     let date_onchange = {
         let album = album.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let date_published = event
                 .target()
@@ -68,10 +86,12 @@ pub fn album_create(props: &Props) -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value();
 
-            album.set(Album {
+            let new_album = Album {
                 date_published,
                 ..album.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_album, "date_published", &errors));
+            album.set(new_album)
         })
     };
 
@@ -133,10 +153,12 @@ pub fn album_create(props: &Props) -> Html {
             <div>
                 <label>{ "Name" }</label>
                 <input type="text" onchange={ name_onchange } />
+                <FieldErrorText errors={ (*errors).clone() } field="name" />
             </div>
             <div>
                 <label>{ "Date published" }</label>
                 <input type="date" onchange={ date_onchange } />
+                <FieldErrorText errors={ (*errors).clone() } field="date_published" />
             </div>
             <button onclick={ create }>{ "Create" }</button>
         </div>
@@ -1,6 +1,10 @@
 use std::ops::Deref;
 
-use entities::album::Model as Album;
+use entities::{
+    album::Model as Album,
+    etag::{etag, ANY},
+    validation::{FieldError, Validate},
+};
 use gloo_net::http::Request;
 use stylist::yew::use_style;
 use wasm_bindgen::JsCast;
@@ -8,20 +12,50 @@ use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew_router::prelude::use_navigator;
 
-use crate::theme::Theme;
+use crate::{
+    components::{revalidate, save_outcome, ConflictNotice, FieldErrorText, SaveOutcome},
+    theme::Theme,
+};
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
     pub id: i32,
 }
 
+/// Fetch the album being edited
+async fn fetch_album(id: i32) -> Album {
+    Request::get(&format!("/api/album/{}", id))
+        .send()
+        .await
+        .expect("Failed to send request to fetch album")
+        .json::<Album>()
+        .await
+        .expect("Failed to parse response into album")
+}
+
+/// Save the album unless it no longer matches `if_match`
+async fn put_album(album: &Album, if_match: &str) -> SaveOutcome {
+    let resp = Request::put(&format!("/api/album/{}", album.id))
+        .header("If-Match", if_match)
+        .json(album)
+        .expect("Failed to serialize album")
+        .send()
+        .await
+        .expect("Failed to send request to save album");
+
+    save_outcome(resp).await
+}
+
 #[function_component(AlbumEdit)]
 pub fn album_edit(props: &Props) -> Html {
     let album = use_state(|| Album {
         id: props.id,
         name: String::new(),
         date_published: String::new(),
+        version: 0,
     });
+    let errors = use_state(Vec::<FieldError>::new);
+    let conflict = use_state(|| false);
 
     {
         let id = props.id;
@@ -29,15 +63,7 @@ pub fn album_edit(props: &Props) -> Html {
         use_effect_with_deps(
             move |_| {
                 wasm_bindgen_futures::spawn_local(async move {
-                    let resp = Request::get(&format!("/api/album/{}", id))
-                        .send()
-                        .await
-                        .expect("Failed to send request to fetch album")
-                        .json::<Album>()
-                        .await
-                        .expect("Failed to parse response into album");
-
-                    album.set(resp);
+                    album.set(fetch_album(id).await);
                 })
             },
             (),
@@ -45,27 +71,78 @@ pub fn album_edit(props: &Props) -> Html {
     }
 
     let save = {
-        let id = props.id;
         let album = album.clone();
         let navigator = use_navigator().unwrap();
+        let errors = errors.clone();
+        let conflict = conflict.clone();
         Callback::from(move |_| {
+            if let Err(new_errors) = album.validate() {
+                errors.set(new_errors);
+                return;
+            }
+
             let album = album.clone();
+            let navigator = navigator.clone();
+            let errors = errors.clone();
+            let conflict = conflict.clone();
             wasm_bindgen_futures::spawn_local(async move {
-                Request::put(&format!("/api/album/{}", id))
-                    .json(&*album)
-                    .expect("Failed to serialize album")
-                    .send()
-                    .await
-                    .expect("Failed to send request to save album");
+                match put_album(&album, &etag(album.version)).await {
+                    SaveOutcome::Saved => {
+                        navigator.back();
+                    }
+                    SaveOutcome::Conflict => conflict.set(true),
+                    SaveOutcome::Rejected(new_errors) => errors.set(new_errors),
+                }
             });
+        })
+    };
 
+    let reload = {
+        let id = props.id;
+        let album = album.clone();
+        let errors = errors.clone();
+        let conflict = conflict.clone();
+        Callback::from(move |_| {
+            let album = album.clone();
+            let errors = errors.clone();
+            let conflict = conflict.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                album.set(fetch_album(id).await);
+                errors.set(Vec::new());
+                conflict.set(false);
+            });
+        })
+    };
+
+    let overwrite = {
+        let album = album.clone();
+        let navigator = use_navigator().unwrap();
+        let errors = errors.clone();
+        let conflict = conflict.clone();
+        Callback::from(move |_| {
+            let album = album.clone();
             let navigator = navigator.clone();
-            navigator.back();
+            let errors = errors.clone();
+            let conflict = conflict.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match put_album(&album, ANY).await {
+                    SaveOutcome::Saved => {
+                        navigator.back();
+                    }
+                    SaveOutcome::Conflict => conflict.set(true),
+                    // Back to the form, to show why the changes were refused
+                    SaveOutcome::Rejected(new_errors) => {
+                        errors.set(new_errors);
+                        conflict.set(false);
+                    }
+                }
+            });
         })
     };
 
     let name_onchange = {
         let album = album.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let name = event
                 .target()
@@ -73,15 +150,18 @@ pub fn album_edit(props: &Props) -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value();
 
-            album.set(Album {
+            let new_album = Album {
                 name,
                 ..album.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_album, "name", &errors));
+            album.set(new_album)
         })
     };
 
     let date_onchange = {
         let album = album.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let date_published = event
                 .target()
@@ -89,10 +169,12 @@ pub fn album_edit(props: &Props) -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value();
 
-            album.set(Album {
+            let new_album = Album {
                 date_published,
                 ..album.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_album, "date_published", &errors));
+            album.set(new_album)
         })
     };
 
@@ -149,16 +231,27 @@ pub fn album_edit(props: &Props) -> Html {
         on_primary = theme_style.on_primary
     );
 
+    if *conflict {
+        return html! {
+            <div class={ style }>
+                <ConflictNotice what="album" onreload={ reload } onoverwrite={ overwrite } />
+            </div>
+        };
+    }
+
     html! {
         <div class={ style }>
             <div>
                 <label>{"Name"}</label>
                 <input type="text" value={album.name.clone()} onchange={name_onchange} />
+                <FieldErrorText errors={ (*errors).clone() } field="name" />
             </div>
             <div>
                 <label>{"Date published"}</label>
                 <input type="date" value={album.date_published.clone()} onchange={date_onchange} />
+                <FieldErrorText errors={ (*errors).clone() } field="date_published" />
             </div>
+            <FieldErrorText errors={ (*errors).clone() } field="form" />
             <button onclick={save}>{"Save"}</button>
         </div>
     }
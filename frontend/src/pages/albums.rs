@@ -1,37 +1,77 @@
-use crate::components::AlbumView;
-use entities::album::Model as Album;
+use crate::{components::AlbumView, theme::Theme};
+use entities::{album::Model as Album, pagination::Page};
 use gloo_net::http::Request;
+use stylist::yew::use_style;
 use yew::prelude::*;
 
 #[function_component(AlbumsPage)]
 pub fn albums_page() -> Html {
     let albums = use_state(|| Vec::new());
+    let next = use_state(|| Some("/album/all".to_string()));
 
-    {
+    let load_more = {
         let albums = albums.clone();
-        use_effect_with_deps(
-            move |_| {
-                wasm_bindgen_futures::spawn_local(async move {
-                    let resp = Request::get("/api/album/all")
-                        .send()
-                        .await
-                        .expect("Failed to fetch albums")
-                        .json::<Vec<Album>>()
-                        .await
-                        .expect("Failed to parse albums");
-
-                    albums.set(resp);
-                })
-            },
-            (),
-        );
+        let next = next.clone();
+        Callback::from(move |_| {
+            let Some(link) = (*next).clone() else {
+                return;
+            };
+            let albums = albums.clone();
+            let next = next.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let resp = Request::get(&format!("/api{}", link))
+                    .send()
+                    .await
+                    .expect("Failed to fetch albums")
+                    .json::<Page<Album>>()
+                    .await
+                    .expect("Failed to parse albums");
+
+                let mut all = (*albums).clone();
+                all.extend(resp.items);
+                albums.set(all);
+                next.set(resp.next);
+            });
+        })
+    };
+
+    {
+        let load_more = load_more.clone();
+        use_effect_with_deps(move |_| load_more.emit(()), ());
     }
 
+    let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
+    let theme_style = theme.get_theme();
+
+    let load_more_style = use_style!(
+        r#"
+            background-color: ${surface};
+            border-radius: 10px;
+            border: 2px solid ${outline};
+            padding: 5px 10px;
+            color: ${on_surface};
+
+            :hover {
+                background-color: ${primary};
+                color: ${on_primary};
+                cursor: pointer;
+            }
+        "#,
+        outline = theme_style.outline,
+        surface = theme_style.surface,
+        on_surface = theme_style.on_surface,
+        primary = theme_style.primary,
+        on_primary = theme_style.on_primary,
+    );
+
     html! {
         <div>
             {for albums.iter().map(|album| html! {
                 <AlbumView id={album.id} />
             })}
+            if next.is_some() {
+                <button class={ load_more_style } onclick={ load_more.reform(|_| ()) }>{ "Load more" }</button>
+            }
         </div>
     }
 }
@@ -1,6 +1,9 @@
 use std::ops::Deref;
 
-use entities::{artist::Model as Artist, sea_orm_active_enums::Genre};
+use entities::{
+    artist::Model as Artist,
+    validation::{FieldError, Validate},
+};
 use gloo_net::http::Request;
 use stylist::yew::use_style;
 use wasm_bindgen::JsCast;
@@ -8,38 +11,62 @@ use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew_router::prelude::use_navigator;
 
-use crate::theme::Theme;
+use crate::{
+    components::{revalidate, FieldErrorText, GenreSelect},
+    theme::Theme,
+};
 
 #[function_component(ArtistCreate)]
 pub fn artist_create() -> Html {
     let artist = use_state(|| Artist {
         id: 0,
         name: "Artist name".to_string(),
-        genre: None,
         date_formed: "0000-00-00".to_string(),
+        version: 0,
     });
+    let genres = use_state(Vec::<i32>::new);
+    let errors = use_state(Vec::<FieldError>::new);
 
     let create = {
         let artist = artist.clone();
+        let genres = genres.clone();
         let navigator = use_navigator().unwrap();
+        let errors = errors.clone();
         Callback::from(move |_| {
+            if let Err(new_errors) = artist.validate() {
+                errors.set(new_errors);
+                return;
+            }
+
             let artist = artist.clone();
+            let genres = genres.clone();
+            let navigator = navigator.clone();
             wasm_bindgen_futures::spawn_local(async move {
-                Request::post("/api/artist/")
+                let id = Request::post("/api/artist/")
                     .json(&*artist)
                     .expect("Failed to serialize artist")
                     .send()
                     .await
-                    .expect("Failed to send request to save artist");
-            });
+                    .expect("Failed to send request to save artist")
+                    .json::<i32>()
+                    .await
+                    .expect("Failed to parse response into artist id");
 
-            let navigator = navigator.clone();
-            navigator.back();
+                Request::put(&format!("/api/artist/genres/{}", id))
+                    .json(&*genres)
+                    .expect("Failed to serialize genres")
+                    .send()
+                    .await
+                    .expect("Failed to send request to save genres");
+
+                navigator.back();
+            });
         })
     };
 
     let name_onchange = {
         let artist = artist.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let name = event
                 .target()
@@ -47,33 +74,23 @@ pub fn artist_create() -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value();
 
-            artist.set(Artist {
+            let new_artist = Artist {
                 name,
                 ..artist.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_artist, "name", &errors));
+            artist.set(new_artist)
         })
     };
 
-    let genre_onchange = {
-        let artist = artist.clone();
-        Callback::from(move |event: Event| {
-            let genre = event
-                .target()
-                .unwrap()
-                .unchecked_into::<HtmlInputElement>()
-                .value()
-                .parse::<Genre>()
-                .ok();
-
-            artist.set(Artist {
-                genre,
-                ..artist.deref().clone()
-            })
-        })
+    let genres_onchange = {
+        let genres = genres.clone();
+        Callback::from(move |selected: Vec<i32>| genres.set(selected))
     };
 
     let date_onchange = {
         let artist = artist.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let date_formed = event
                 .target()
@@ -81,22 +98,15 @@ pub fn artist_create() -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value();
 
-            artist.set(Artist {
+            let new_artist = Artist {
                 date_formed,
                 ..artist.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_artist, "date_formed", &errors));
+            artist.set(new_artist)
         })
     };
 
-    let genres = vec![
-        None,
-        Some(Genre::Metal),
-        Some(Genre::Classical),
-        Some(Genre::Rock),
-        Some(Genre::Jazz),
-        Some(Genre::Pop),
-    ];
-
     let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
     let theme_style = theme.get_theme();
 
@@ -128,6 +138,10 @@ pub fn artist_create() -> Html {
                 padding: 0 10px;
             }
 
+            select[multiple] {
+                height: 6em;
+            }
+
             button {
                 margin-top: 10px;
                 background-color: ${surface};
@@ -155,24 +169,18 @@ pub fn artist_create() -> Html {
             <div>
                 <label>{ "Name" }</label>
                 <input type="text" onchange={ name_onchange } />
+                <FieldErrorText errors={ (*errors).clone() } field="name" />
             </div>
             <div>
-                <label>{ "Genre" }</label>
-                <select onchange={ genre_onchange }>
-                    {for genres.iter().map(|genre| html! {
-                        <option value={ genre_to_string(genre) } selected={ *genre == None }>{ &*genre_to_string(genre) }</option>
-                    })}
-                </select>
+                <label>{ "Genres" }</label>
+                <GenreSelect selected={ (*genres).clone() } onchange={ genres_onchange } />
             </div>
             <div>
                 <label>{ "Date formed" }</label>
                 <input type="date" onchange={ date_onchange } />
+                <FieldErrorText errors={ (*errors).clone() } field="date_formed" />
             </div>
             <button onclick={ create }>{ "Create" }</button>
         </div>
     }
 }
-
-fn genre_to_string(genre: &Option<Genre>) -> String {
-    genre.as_ref().map_or("None".to_string(), |g| g.to_string())
-}
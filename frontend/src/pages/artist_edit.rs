@@ -1,6 +1,11 @@
 use std::ops::Deref;
 
-use entities::{artist::Model as Artist, sea_orm_active_enums::Genre};
+use entities::{
+    artist::Model as Artist,
+    etag::{etag, ANY},
+    genre::Model as Genre,
+    validation::{FieldError, Validate},
+};
 use gloo_net::http::Request;
 use stylist::yew::use_style;
 use wasm_bindgen::JsCast;
@@ -8,37 +13,87 @@ use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew_router::prelude::use_navigator;
 
-use crate::theme::Theme;
+use crate::{
+    components::{
+        revalidate, save_outcome, ConflictNotice, FieldErrorText, GenreSelect, SaveOutcome,
+    },
+    theme::Theme,
+};
 
 #[derive(PartialEq, Properties)]
 pub struct Props {
     pub id: i32,
 }
 
+/// Fetch the artist being edited
+async fn fetch_artist(id: i32) -> Artist {
+    Request::get(&format!("/api/artist/{}", id))
+        .send()
+        .await
+        .expect("Failed to send request to fetch artist")
+        .json::<Artist>()
+        .await
+        .expect("Failed to parse response into artist")
+}
+
+/// Fetch the ids of the artist's genres
+async fn fetch_genres(id: i32) -> Vec<i32> {
+    Request::get(&format!("/api/artist/genres/{}", id))
+        .send()
+        .await
+        .expect("Failed to send request to fetch genres")
+        .json::<Vec<Genre>>()
+        .await
+        .expect("Failed to parse response into genres")
+        .into_iter()
+        .map(|genre| genre.id)
+        .collect()
+}
+
+/// Replace the artist's genres
+async fn put_genres(id: i32, genres: &[i32]) {
+    Request::put(&format!("/api/artist/genres/{}", id))
+        .json(&genres)
+        .expect("Failed to serialize genres")
+        .send()
+        .await
+        .expect("Failed to send request to save genres");
+}
+
+/// Save the artist unless it no longer matches `if_match`
+async fn put_artist(artist: &Artist, if_match: &str) -> SaveOutcome {
+    let resp = Request::put(&format!("/api/artist/{}", artist.id))
+        .header("If-Match", if_match)
+        .json(artist)
+        .expect("Failed to serialize artist")
+        .send()
+        .await
+        .expect("Failed to send request to save artist");
+
+    save_outcome(resp).await
+}
+
 #[function_component(ArtistEdit)]
 pub fn artist_edit(props: &Props) -> Html {
     let artist = use_state(|| Artist {
         id: props.id,
         name: "Artist name".to_string(),
-        genre: None,
         date_formed: "0000-00-00".to_string(),
+        version: 0,
     });
+    let genres = use_state(Vec::<i32>::new);
+    let errors = use_state(Vec::<FieldError>::new);
+    let conflict = use_state(|| false);
 
     {
         let id = props.id;
         let artist = artist.clone();
+        let genres = genres.clone();
         use_effect_with_deps(
             move |_| {
                 wasm_bindgen_futures::spawn_local(async move {
-                    let resp = Request::get(&format!("/api/artist/{}", id))
-                        .send()
-                        .await
-                        .expect("Failed to send request")
-                        .json::<Artist>()
-                        .await
-                        .expect("Failed to parse response");
-
-                    artist.set(resp);
+                    artist.set(fetch_artist(id).await);
+                    genres.set(fetch_genres(id).await);
                 })
             },
             (),
@@ -46,27 +101,87 @@ pub fn artist_edit(props: &Props) -> Html {
     }
 
     let save = {
-        let id = props.id;
         let artist = artist.clone();
+        let genres = genres.clone();
         let navigator = use_navigator().unwrap();
+        let errors = errors.clone();
+        let conflict = conflict.clone();
         Callback::from(move |_| {
+            if let Err(new_errors) = artist.validate() {
+                errors.set(new_errors);
+                return;
+            }
+
             let artist = artist.clone();
+            let genres = genres.clone();
+            let navigator = navigator.clone();
+            let errors = errors.clone();
+            let conflict = conflict.clone();
             wasm_bindgen_futures::spawn_local(async move {
-                Request::put(&format!("/api/artist/{}", id))
-                    .json(&*artist)
-                    .expect("Failed to serialize artist")
-                    .send()
-                    .await
-                    .expect("Failed to send request to save artist");
+                match put_artist(&artist, &etag(artist.version)).await {
+                    SaveOutcome::Saved => {
+                        put_genres(artist.id, &genres).await;
+                        navigator.back();
+                    }
+                    SaveOutcome::Conflict => conflict.set(true),
+                    SaveOutcome::Rejected(new_errors) => errors.set(new_errors),
+                }
             });
+        })
+    };
+
+    let reload = {
+        let id = props.id;
+        let artist = artist.clone();
+        let genres = genres.clone();
+        let errors = errors.clone();
+        let conflict = conflict.clone();
+        Callback::from(move |_| {
+            let artist = artist.clone();
+            let genres = genres.clone();
+            let errors = errors.clone();
+            let conflict = conflict.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                artist.set(fetch_artist(id).await);
+                genres.set(fetch_genres(id).await);
+                errors.set(Vec::new());
+                conflict.set(false);
+            });
+        })
+    };
 
+    let overwrite = {
+        let artist = artist.clone();
+        let genres = genres.clone();
+        let navigator = use_navigator().unwrap();
+        let errors = errors.clone();
+        let conflict = conflict.clone();
+        Callback::from(move |_| {
+            let artist = artist.clone();
+            let genres = genres.clone();
             let navigator = navigator.clone();
-            navigator.back();
+            let errors = errors.clone();
+            let conflict = conflict.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match put_artist(&artist, ANY).await {
+                    SaveOutcome::Saved => {
+                        put_genres(artist.id, &genres).await;
+                        navigator.back();
+                    }
+                    SaveOutcome::Conflict => conflict.set(true),
+                    // Back to the form, to show why the changes were refused
+                    SaveOutcome::Rejected(new_errors) => {
+                        errors.set(new_errors);
+                        conflict.set(false);
+                    }
+                }
+            });
         })
     };
 
     let name_onchange = {
         let artist = artist.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let name = event
                 .target()
@@ -74,33 +189,23 @@ pub fn artist_edit(props: &Props) -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value();
 
-            artist.set(Artist {
+            let new_artist = Artist {
                 name,
                 ..artist.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_artist, "name", &errors));
+            artist.set(new_artist)
         })
     };
 
-    let genre_onchange = {
-        let artist = artist.clone();
-        Callback::from(move |event: Event| {
-            let genre = event
-                .target()
-                .unwrap()
-                .unchecked_into::<HtmlInputElement>()
-                .value()
-                .parse::<Genre>()
-                .ok();
-
-            artist.set(Artist {
-                genre,
-                ..artist.deref().clone()
-            })
-        })
+    let genres_onchange = {
+        let genres = genres.clone();
+        Callback::from(move |selected: Vec<i32>| genres.set(selected))
     };
 
     let date_onchange = {
         let artist = artist.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let date_formed = event
                 .target()
@@ -108,22 +213,15 @@ pub fn artist_edit(props: &Props) -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value();
 
-            artist.set(Artist {
+            let new_artist = Artist {
                 date_formed,
                 ..artist.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_artist, "date_formed", &errors));
+            artist.set(new_artist)
         })
     };
 
-    let genres = vec![
-        None,
-        Some(Genre::Metal),
-        Some(Genre::Classical),
-        Some(Genre::Rock),
-        Some(Genre::Jazz),
-        Some(Genre::Pop),
-    ];
-
     let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
     let theme_style = theme.get_theme();
 
@@ -155,6 +253,10 @@ pub fn artist_edit(props: &Props) -> Html {
                 padding: 0 10px;
             }
 
+            select[multiple] {
+                height: 6em;
+            }
+
             button {
                 margin-top: 10px;
                 background-color: ${surface};
@@ -177,31 +279,32 @@ pub fn artist_edit(props: &Props) -> Html {
         on_primary = theme_style.on_primary
     );
 
+    if *conflict {
+        return html! {
+            <div class={ style }>
+                <ConflictNotice what="artist" onreload={ reload } onoverwrite={ overwrite } />
+            </div>
+        };
+    }
+
     html! {
         <div class={ style }>
             <div>
                 <label>{ "Name" }</label>
                 <input type="text" value={ artist.name.clone() } onchange={ name_onchange } />
+                <FieldErrorText errors={ (*errors).clone() } field="name" />
             </div>
             <div>
-                <label>{ "Genre" }</label>
-                <select onchange={ genre_onchange }>
-                    {for genres.iter().map(|genre| html! {
-                        <option value={ genre_to_string(genre) } selected={ genre == &artist.genre }>
-                            { &*genre_to_string(genre) }
-                        </option>
-                    })}
-                </select>
+                <label>{ "Genres" }</label>
+                <GenreSelect selected={ (*genres).clone() } onchange={ genres_onchange } />
             </div>
             <div>
                 <label>{ "Date formed" }</label>
                 <input type="date" value={ artist.date_formed.clone() } onchange={ date_onchange } />
+                <FieldErrorText errors={ (*errors).clone() } field="date_formed" />
             </div>
+            <FieldErrorText errors={ (*errors).clone() } field="form" />
             <button onclick={save}>{ "Save" }</button>
         </div>
     }
 }
-
-fn genre_to_string(genre: &Option<Genre>) -> String {
-    genre.as_ref().map_or("None".to_string(), |g| g.to_string())
-}
@@ -1,4 +1,6 @@
-use entities::artist::Model as Artist;
+use entities::{
+    artist::Model as Artist, pagination::Page, sea_orm_active_enums::Role, user::Model as User,
+};
 use gloo_net::http::Request;
 use stylist::yew::use_style;
 use yew::prelude::*;
@@ -9,27 +11,43 @@ use crate::{components::ArtistView, router::Route, theme::Theme};
 #[function_component(ArtistsPage)]
 pub fn artists_page() -> Html {
     let artists = use_state(|| Vec::new());
+    let next = use_state(|| Some("/artist/all".to_string()));
 
-    {
-        let aritsts = artists.clone();
-        use_effect_with_deps(
-            move |_| {
-                wasm_bindgen_futures::spawn_local(async move {
-                    let resp = Request::get("/api/artist/all")
-                        .send()
-                        .await
-                        .expect("Failed to fetch artists")
-                        .json::<Vec<Artist>>()
-                        .await
-                        .expect("Failed to parse artists");
+    let load_more = {
+        let artists = artists.clone();
+        let next = next.clone();
+        Callback::from(move |_| {
+            let Some(link) = (*next).clone() else {
+                return;
+            };
+            let artists = artists.clone();
+            let next = next.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let resp = Request::get(&format!("/api{}", link))
+                    .send()
+                    .await
+                    .expect("Failed to fetch artists")
+                    .json::<Page<Artist>>()
+                    .await
+                    .expect("Failed to parse artists");
+
+                let mut all = (*artists).clone();
+                all.extend(resp.items);
+                artists.set(all);
+                next.set(resp.next);
+            });
+        })
+    };
 
-                    aritsts.set(resp);
-                })
-            },
-            (),
-        );
+    {
+        let load_more = load_more.clone();
+        use_effect_with_deps(move |_| load_more.emit(()), ());
     }
 
+    let role = use_context::<UseStateHandle<Option<User>>>()
+        .expect("No context found")
+        .as_ref()
+        .map(|user| user.role);
     let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
     let theme_style = theme.get_theme();
 
@@ -56,12 +74,39 @@ pub fn artists_page() -> Html {
         on_secondary = theme_style.on_secondary,
     );
 
+    let load_more_style = use_style!(
+        r#"
+            background-color: ${surface};
+            border-radius: 10px;
+            border: 2px solid ${outline};
+            padding: 5px 10px;
+            margin-right: 10px;
+            color: ${on_surface};
+
+            :hover {
+                background-color: ${primary};
+                color: ${on_primary};
+                cursor: pointer;
+            }
+        "#,
+        outline = theme_style.outline,
+        surface = theme_style.surface,
+        on_surface = theme_style.on_surface,
+        primary = theme_style.primary,
+        on_primary = theme_style.on_primary,
+    );
+
     html! {
         <div>
             {for artists.iter().map(|artist| html! {
                 <ArtistView id={ artist.id } />
             })}
-            <Link<Route> classes={ create_artist_style } to={ Route::ArtistCreate }>{ "+" }</Link<Route>>
+            if next.is_some() {
+                <button class={ load_more_style } onclick={ load_more.reform(|_| ()) }>{ "Load more" }</button>
+            }
+            if role >= Some(Role::Editor) {
+                <Link<Route> classes={ create_artist_style } to={ Route::ArtistCreate }>{ "+" }</Link<Route>>
+            }
         </div>
     }
 }
@@ -0,0 +1,243 @@
+use std::ops::Deref;
+
+use entities::{
+    auth::{Credentials, SessionToken},
+    user::Model as User,
+    validation::{FieldError, Validate},
+};
+use gloo_net::http::{Request, Response};
+use serde::Deserialize;
+use stylist::yew::use_style;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_router::prelude::use_navigator;
+
+use crate::{
+    components::{revalidate, FieldErrorText},
+    router::Route,
+    theme::Theme,
+};
+
+/// The parts of an API error body shown on this page
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+    #[serde(default)]
+    details: Vec<FieldError>,
+}
+
+/// Field errors of a failed request, the message goes under the password otherwise
+async fn errors_of(resp: Response) -> Vec<FieldError> {
+    match resp.json::<ErrorBody>().await {
+        Ok(body) if !body.details.is_empty() => body.details,
+        Ok(body) => vec![FieldError::new("password", body.message)],
+        Err(_) => vec![FieldError::new("password", "Something went wrong")],
+    }
+}
+
+/// Open a session, returning the signed in user
+async fn login(credentials: &Credentials) -> Result<User, Vec<FieldError>> {
+    let resp = Request::post("/api/auth/login")
+        .json(credentials)
+        .expect("Failed to serialize credentials")
+        .send()
+        .await
+        .expect("Failed to send request to log in");
+
+    if !resp.ok() {
+        return Err(errors_of(resp).await);
+    }
+
+    let session = resp
+        .json::<SessionToken>()
+        .await
+        .expect("Failed to parse response");
+    Ok(session.user)
+}
+
+#[function_component(LoginPage)]
+pub fn login_page() -> Html {
+    let user = use_context::<UseStateHandle<Option<User>>>().expect("No context found");
+    let credentials = use_state(|| Credentials {
+        username: String::new(),
+        password: String::new(),
+    });
+    let errors = use_state(Vec::<FieldError>::new);
+
+    let log_in = {
+        let user = user.clone();
+        let credentials = credentials.clone();
+        let navigator = use_navigator().unwrap();
+        let errors = errors.clone();
+        Callback::from(move |_| {
+            if let Err(new_errors) = credentials.validate() {
+                errors.set(new_errors);
+                return;
+            }
+
+            let user = user.clone();
+            let credentials = credentials.clone();
+            let navigator = navigator.clone();
+            let errors = errors.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match login(&credentials).await {
+                    Ok(signed_in) => {
+                        user.set(Some(signed_in));
+                        navigator.push(&Route::Home);
+                    }
+                    Err(new_errors) => errors.set(new_errors),
+                }
+            });
+        })
+    };
+
+    let register = {
+        let user = user.clone();
+        let credentials = credentials.clone();
+        let navigator = use_navigator().unwrap();
+        let errors = errors.clone();
+        Callback::from(move |_| {
+            if let Err(new_errors) = credentials.validate() {
+                errors.set(new_errors);
+                return;
+            }
+
+            let user = user.clone();
+            let credentials = credentials.clone();
+            let navigator = navigator.clone();
+            let errors = errors.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let resp = Request::post("/api/auth/register")
+                    .json(&*credentials)
+                    .expect("Failed to serialize credentials")
+                    .send()
+                    .await
+                    .expect("Failed to send request to register");
+
+                if !resp.ok() {
+                    errors.set(errors_of(resp).await);
+                    return;
+                }
+
+                match login(&credentials).await {
+                    Ok(signed_in) => {
+                        user.set(Some(signed_in));
+                        navigator.push(&Route::Home);
+                    }
+                    Err(new_errors) => errors.set(new_errors),
+                }
+            });
+        })
+    };
+
+    let username_onchange = {
+        let credentials = credentials.clone();
+        let errors = errors.clone();
+        Callback::from(move |event: Event| {
+            let username = event
+                .target()
+                .unwrap()
+                .unchecked_into::<HtmlInputElement>()
+                .value();
+
+            let new_credentials = Credentials {
+                username,
+                ..credentials.deref().clone()
+            };
+            errors.set(revalidate(&new_credentials, "username", &errors));
+            credentials.set(new_credentials)
+        })
+    };
+
+    let password_onchange = {
+        let credentials = credentials.clone();
+        let errors = errors.clone();
+        Callback::from(move |event: Event| {
+            let password = event
+                .target()
+                .unwrap()
+                .unchecked_into::<HtmlInputElement>()
+                .value();
+
+            let new_credentials = Credentials {
+                password,
+                ..credentials.deref().clone()
+            };
+            errors.set(revalidate(&new_credentials, "password", &errors));
+            credentials.set(new_credentials)
+        })
+    };
+
+    let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
+    let theme_style = theme.get_theme();
+
+    let style = use_style!(
+        r#"
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            height: 80vh;
+            justify-content: center;
+
+            div {
+                margin: 5px 0;
+            }
+
+            label {
+                display: inline-block;
+                width: 15vw;
+                margin-right: 10px;
+            }
+
+            input {
+                width: 15vw;
+                border-radius: 15px;
+                border: 2px solid ${outline};
+                background-color: ${surface};
+                color: ${on_surface};
+                height: 2em;
+                padding: 0 10px;
+            }
+
+            button {
+                margin: 10px 5px 0;
+                background-color: ${surface};
+                color: ${on_surface};
+                border: 2px solid ${outline};
+                border-radius: 15px;
+                padding: 10px;
+            }
+
+            button:hover {
+                background-color: ${primary};
+                color: ${on_primary};
+                cursor: pointer;
+            }
+        "#,
+        surface = theme_style.surface,
+        on_surface = theme_style.on_surface,
+        outline = theme_style.outline,
+        primary = theme_style.primary,
+        on_primary = theme_style.on_primary
+    );
+
+    html! {
+        <div class={ style }>
+            <div>
+                <label>{ "User name" }</label>
+                <input type="text" onchange={ username_onchange } />
+                <FieldErrorText errors={ (*errors).clone() } field="username" />
+            </div>
+            <div>
+                <label>{ "Password" }</label>
+                <input type="password" onchange={ password_onchange } />
+                <FieldErrorText errors={ (*errors).clone() } field="password" />
+            </div>
+            <div>
+                <button onclick={ log_in }>{ "Log in" }</button>
+                <button onclick={ register }>{ "Register" }</button>
+            </div>
+        </div>
+    }
+}
@@ -6,6 +6,8 @@ mod artist_create;
 mod artist_edit;
 mod artists;
 mod home;
+mod login;
+mod playlists;
 mod song_create;
 mod song_edit;
 mod songs;
@@ -18,6 +20,8 @@ pub use artist_create::ArtistCreate;
 pub use artist_edit::ArtistEdit;
 pub use artists::ArtistsPage;
 pub use home::HomePage;
+pub use login::LoginPage;
+pub use playlists::PlaylistsPage;
 pub use song_create::SongCreate;
 pub use song_edit::SongEdit;
 pub use songs::SongsPage;
@@ -0,0 +1,191 @@
+use std::ops::Deref;
+
+use crate::{
+    components::{revalidate, FieldErrorText},
+    router::Route,
+    theme::Theme,
+};
+use entities::{
+    pagination::Page,
+    playlist::Model as Playlist,
+    user::Model as User,
+    validation::{FieldError, Validate},
+};
+use gloo_net::http::Request;
+use stylist::yew::use_style;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_router::prelude::{use_navigator, Link};
+
+#[function_component(PlaylistsPage)]
+pub fn playlists_page() -> Html {
+    let playlists = use_state(|| Vec::new());
+    let next = use_state(|| Some("/playlist/all".to_string()));
+    let new_playlist = use_state(|| Playlist {
+        id: 0,
+        name: String::new(),
+        user_id: 0,
+    });
+    let errors = use_state(Vec::<FieldError>::new);
+
+    let load_more = {
+        let playlists = playlists.clone();
+        let next = next.clone();
+        Callback::from(move |_| {
+            let Some(link) = (*next).clone() else {
+                return;
+            };
+            let playlists = playlists.clone();
+            let next = next.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let resp = Request::get(&format!("/api{}", link))
+                    .send()
+                    .await
+                    .expect("Failed to fetch playlists")
+                    .json::<Page<Playlist>>()
+                    .await
+                    .expect("Failed to parse playlists");
+
+                let mut all = (*playlists).clone();
+                all.extend(resp.items);
+                playlists.set(all);
+                next.set(resp.next);
+            });
+        })
+    };
+
+    {
+        let load_more = load_more.clone();
+        use_effect_with_deps(move |_| load_more.emit(()), ());
+    }
+
+    let name_onchange = {
+        let new_playlist = new_playlist.clone();
+        let errors = errors.clone();
+        Callback::from(move |event: Event| {
+            let name = event
+                .target()
+                .unwrap()
+                .unchecked_into::<HtmlInputElement>()
+                .value();
+
+            let playlist = Playlist {
+                name,
+                ..new_playlist.deref().clone()
+            };
+            errors.set(revalidate(&playlist, "name", &errors));
+            new_playlist.set(playlist)
+        })
+    };
+
+    let create = {
+        let new_playlist = new_playlist.clone();
+        let errors = errors.clone();
+        let navigator = use_navigator().unwrap();
+        Callback::from(move |_| {
+            if let Err(new_errors) = new_playlist.validate() {
+                errors.set(new_errors);
+                return;
+            }
+
+            let new_playlist = new_playlist.clone();
+            let navigator = navigator.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let id = Request::post("/api/playlist/")
+                    .json(&*new_playlist)
+                    .expect("Failed to serialize playlist")
+                    .send()
+                    .await
+                    .expect("Failed to send request to save playlist")
+                    .text()
+                    .await
+                    .expect("Failed to read the id of the playlist");
+
+                if let Ok(id) = id.parse() {
+                    navigator.push(&Route::Playlist { id });
+                }
+            });
+        })
+    };
+
+    let signed_in = use_context::<UseStateHandle<Option<User>>>()
+        .expect("No context found")
+        .is_some();
+    let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
+    let theme_style = theme.get_theme();
+
+    let style = use_style!(
+        r#"
+            ul {
+                list-style: none;
+                padding: 0;
+            }
+
+            li {
+                background-color: ${surface};
+                border: 1px solid ${outline};
+                border-radius: 50px;
+                margin-bottom: 10px;
+                padding: 10px 20px;
+            }
+
+            a {
+                color: ${on_surface};
+                font-size: 1.25rem;
+                text-decoration: none;
+            }
+
+            input {
+                border-radius: 15px;
+                border: 2px solid ${outline};
+                background-color: ${surface};
+                color: ${on_surface};
+                height: 2em;
+                padding: 0 10px;
+            }
+
+            button {
+                background-color: ${surface};
+                border-radius: 10px;
+                border: 2px solid ${outline};
+                padding: 5px 10px;
+                margin: 0 5px;
+                color: ${on_surface};
+            }
+
+            button:hover {
+                background-color: ${primary};
+                color: ${on_primary};
+                cursor: pointer;
+            }
+        "#,
+        outline = theme_style.outline,
+        surface = theme_style.surface,
+        on_surface = theme_style.on_surface,
+        primary = theme_style.primary,
+        on_primary = theme_style.on_primary,
+    );
+
+    html! {
+        <div class={ style }>
+            if signed_in {
+                <div>
+                    <input type="text" placeholder="Playlist name" onchange={ name_onchange } />
+                    <button onclick={ create }>{ "Create playlist" }</button>
+                    <FieldErrorText errors={ (*errors).clone() } field="name" />
+                </div>
+            }
+            <ul>
+                {for playlists.iter().map(|playlist| html! {
+                    <li>
+                        <Link<Route> to={ Route::Playlist { id: playlist.id } }>{ &playlist.name }</Link<Route>>
+                    </li>
+                })}
+            </ul>
+            if next.is_some() {
+                <button onclick={ load_more.reform(|_| ()) }>{ "Load more" }</button>
+            }
+        </div>
+    }
+}
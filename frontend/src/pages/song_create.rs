@@ -1,6 +1,9 @@
 use std::ops::Deref;
 
-use entities::song::Model as Song;
+use entities::{
+    song::Model as Song,
+    validation::{FieldError, Validate},
+};
 use gloo_net::http::Request;
 use stylist::yew::use_style;
 use wasm_bindgen::JsCast;
@@ -8,7 +11,10 @@ use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew_router::prelude::use_navigator;
 
-use crate::theme::Theme;
+use crate::{
+    components::{revalidate, FieldErrorText},
+    theme::Theme,
+};
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
@@ -22,12 +28,54 @@ pub fn song_create(props: &Props) -> Html {
         name: String::new(),
         length_secs: 0,
         album_id: props.album_id,
+        disc_number: 1,
+        track_number: 1,
+        version: 0,
     });
+    let errors = use_state(Vec::<FieldError>::new);
+
+    // The song goes after the last one on the first disc, unless another track is chosen
+    {
+        let album_id = props.album_id;
+        let song = song.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let songs = Request::get(&format!("/api/album/songs/{}", album_id))
+                        .send()
+                        .await
+                        .expect("Failed to fetch songs")
+                        .json::<Vec<Song>>()
+                        .await
+                        .expect("Failed to parse songs");
+
+                    let track_number = songs
+                        .iter()
+                        .filter(|other| other.disc_number == 1)
+                        .map(|other| other.track_number)
+                        .max()
+                        .unwrap_or(0)
+                        + 1;
+                    song.set(Song {
+                        track_number,
+                        ..song.deref().clone()
+                    });
+                })
+            },
+            (),
+        );
+    }
 
     let create = {
         let song = song.clone();
         let navigator = use_navigator().unwrap();
+        let errors = errors.clone();
         Callback::from(move |_| {
+            if let Err(new_errors) = song.validate() {
+                errors.set(new_errors);
+                return;
+            }
+
             let song = song.clone();
             wasm_bindgen_futures::spawn_local(async move {
                 Request::post("/api/song/")
@@ -45,6 +93,7 @@ pub fn song_create(props: &Props) -> Html {
 
     let name_onchange = {
         let song = song.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let name = event
                 .target()
@@ -52,15 +101,18 @@ pub fn song_create(props: &Props) -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value();
 
-            song.set(Song {
+            let new_song = Song {
                 name,
                 ..song.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_song, "name", &errors));
+            song.set(new_song)
         })
     };
 
     let length_onchange = {
         let song = song.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let length_secs = event
                 .target()
@@ -68,12 +120,56 @@ pub fn song_create(props: &Props) -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value()
                 .parse::<i32>()
-                .unwrap();
+                .unwrap_or(0);
 
-            song.set(Song {
+            let new_song = Song {
                 length_secs,
                 ..song.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_song, "length_secs", &errors));
+            song.set(new_song)
+        })
+    };
+
+    let disc_onchange = {
+        let song = song.clone();
+        let errors = errors.clone();
+        Callback::from(move |event: Event| {
+            let disc_number = event
+                .target()
+                .unwrap()
+                .unchecked_into::<HtmlInputElement>()
+                .value()
+                .parse::<i32>()
+                .unwrap_or(0);
+
+            let new_song = Song {
+                disc_number,
+                ..song.deref().clone()
+            };
+            errors.set(revalidate(&new_song, "disc_number", &errors));
+            song.set(new_song)
+        })
+    };
+
+    let track_onchange = {
+        let song = song.clone();
+        let errors = errors.clone();
+        Callback::from(move |event: Event| {
+            let track_number = event
+                .target()
+                .unwrap()
+                .unchecked_into::<HtmlInputElement>()
+                .value()
+                .parse::<i32>()
+                .unwrap_or(0);
+
+            let new_song = Song {
+                track_number,
+                ..song.deref().clone()
+            };
+            errors.set(revalidate(&new_song, "track_number", &errors));
+            song.set(new_song)
         })
     };
 
@@ -135,10 +231,22 @@ pub fn song_create(props: &Props) -> Html {
             <div>
                 <label>{"Name"}</label>
                 <input type="text" onchange={name_onchange} />
+                <FieldErrorText errors={ (*errors).clone() } field="name" />
             </div>
             <div>
                 <label>{"Length (seconds)"}</label>
                 <input type="text" onchange={length_onchange} />
+                <FieldErrorText errors={ (*errors).clone() } field="length_secs" />
+            </div>
+            <div>
+                <label>{"Disc"}</label>
+                <input type="text" value={song.disc_number.to_string()} onchange={disc_onchange} />
+                <FieldErrorText errors={ (*errors).clone() } field="disc_number" />
+            </div>
+            <div>
+                <label>{"Track"}</label>
+                <input type="text" value={song.track_number.to_string()} onchange={track_onchange} />
+                <FieldErrorText errors={ (*errors).clone() } field="track_number" />
             </div>
             <button onclick={create}>{"Create"}</button>
         </div>
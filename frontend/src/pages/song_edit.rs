@@ -1,4 +1,8 @@
-use entities::song::Model as Song;
+use entities::{
+    etag::{etag, ANY},
+    song::Model as Song,
+    validation::{FieldError, Validate},
+};
 use gloo_net::http::Request;
 use std::ops::Deref;
 use stylist::yew::use_style;
@@ -7,13 +11,40 @@ use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew_router::prelude::use_navigator;
 
-use crate::theme::Theme;
+use crate::{
+    components::{revalidate, save_outcome, ConflictNotice, FieldErrorText, SaveOutcome},
+    theme::Theme,
+};
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
     pub id: i32,
 }
 
+/// Fetch the song being edited
+async fn fetch_song(id: i32) -> Song {
+    Request::get(&format!("/api/song/{}", id))
+        .send()
+        .await
+        .expect("Failed to send request to fetch song")
+        .json::<Song>()
+        .await
+        .expect("Failed to parse response into song")
+}
+
+/// Save the song unless it no longer matches `if_match`
+async fn put_song(song: &Song, if_match: &str) -> SaveOutcome {
+    let resp = Request::put(&format!("/api/song/{}", song.id))
+        .header("If-Match", if_match)
+        .json(song)
+        .expect("Failed to serialize song")
+        .send()
+        .await
+        .expect("Failed to send request to save song");
+
+    save_outcome(resp).await
+}
+
 #[function_component(SongEdit)]
 pub fn song_edit(props: &Props) -> Html {
     let song = use_state(|| Song {
@@ -21,7 +52,12 @@ pub fn song_edit(props: &Props) -> Html {
         name: String::new(),
         length_secs: 0,
         album_id: 0,
+        disc_number: 1,
+        track_number: 0,
+        version: 0,
     });
+    let errors = use_state(Vec::<FieldError>::new);
+    let conflict = use_state(|| false);
 
     {
         let id = props.id;
@@ -29,15 +65,7 @@ pub fn song_edit(props: &Props) -> Html {
         use_effect_with_deps(
             move |_| {
                 wasm_bindgen_futures::spawn_local(async move {
-                    let resp = Request::get(&format!("/api/song/{}", id))
-                        .send()
-                        .await
-                        .expect("Failed to send request to fetch song")
-                        .json::<Song>()
-                        .await
-                        .expect("Failed to parse response into song");
-
-                    song.set(resp);
+                    song.set(fetch_song(id).await);
                 })
             },
             (),
@@ -45,27 +73,78 @@ pub fn song_edit(props: &Props) -> Html {
     }
 
     let save = {
-        let id = props.id;
         let song = song.clone();
         let navigator = use_navigator().unwrap();
+        let errors = errors.clone();
+        let conflict = conflict.clone();
         Callback::from(move |_| {
+            if let Err(new_errors) = song.validate() {
+                errors.set(new_errors);
+                return;
+            }
+
             let song = song.clone();
+            let navigator = navigator.clone();
+            let errors = errors.clone();
+            let conflict = conflict.clone();
             wasm_bindgen_futures::spawn_local(async move {
-                Request::put(&format!("/api/song/{}", id))
-                    .json(&*song)
-                    .expect("Failed to serialize song")
-                    .send()
-                    .await
-                    .expect("Failed to send request to save song");
+                match put_song(&song, &etag(song.version)).await {
+                    SaveOutcome::Saved => {
+                        navigator.back();
+                    }
+                    SaveOutcome::Conflict => conflict.set(true),
+                    SaveOutcome::Rejected(new_errors) => errors.set(new_errors),
+                }
             });
+        })
+    };
 
+    let reload = {
+        let id = props.id;
+        let song = song.clone();
+        let errors = errors.clone();
+        let conflict = conflict.clone();
+        Callback::from(move |_| {
+            let song = song.clone();
+            let errors = errors.clone();
+            let conflict = conflict.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                song.set(fetch_song(id).await);
+                errors.set(Vec::new());
+                conflict.set(false);
+            });
+        })
+    };
+
+    let overwrite = {
+        let song = song.clone();
+        let navigator = use_navigator().unwrap();
+        let errors = errors.clone();
+        let conflict = conflict.clone();
+        Callback::from(move |_| {
+            let song = song.clone();
             let navigator = navigator.clone();
-            navigator.back();
+            let errors = errors.clone();
+            let conflict = conflict.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match put_song(&song, ANY).await {
+                    SaveOutcome::Saved => {
+                        navigator.back();
+                    }
+                    SaveOutcome::Conflict => conflict.set(true),
+                    // Back to the form, to show why the changes were refused
+                    SaveOutcome::Rejected(new_errors) => {
+                        errors.set(new_errors);
+                        conflict.set(false);
+                    }
+                }
+            });
         })
     };
 
     let name_onchange = {
         let song = song.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let name = event
                 .target()
@@ -73,15 +152,18 @@ pub fn song_edit(props: &Props) -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value();
 
-            song.set(Song {
+            let new_song = Song {
                 name,
                 ..song.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_song, "name", &errors));
+            song.set(new_song)
         })
     };
 
     let length_onchange = {
         let song = song.clone();
+        let errors = errors.clone();
         Callback::from(move |event: Event| {
             let length_secs = event
                 .target()
@@ -89,12 +171,56 @@ pub fn song_edit(props: &Props) -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value()
                 .parse::<i32>()
-                .unwrap();
+                .unwrap_or(0);
 
-            song.set(Song {
+            let new_song = Song {
                 length_secs,
                 ..song.deref().clone()
-            })
+            };
+            errors.set(revalidate(&new_song, "length_secs", &errors));
+            song.set(new_song)
+        })
+    };
+
+    let disc_onchange = {
+        let song = song.clone();
+        let errors = errors.clone();
+        Callback::from(move |event: Event| {
+            let disc_number = event
+                .target()
+                .unwrap()
+                .unchecked_into::<HtmlInputElement>()
+                .value()
+                .parse::<i32>()
+                .unwrap_or(0);
+
+            let new_song = Song {
+                disc_number,
+                ..song.deref().clone()
+            };
+            errors.set(revalidate(&new_song, "disc_number", &errors));
+            song.set(new_song)
+        })
+    };
+
+    let track_onchange = {
+        let song = song.clone();
+        let errors = errors.clone();
+        Callback::from(move |event: Event| {
+            let track_number = event
+                .target()
+                .unwrap()
+                .unchecked_into::<HtmlInputElement>()
+                .value()
+                .parse::<i32>()
+                .unwrap_or(0);
+
+            let new_song = Song {
+                track_number,
+                ..song.deref().clone()
+            };
+            errors.set(revalidate(&new_song, "track_number", &errors));
+            song.set(new_song)
         })
     };
 
@@ -107,7 +233,7 @@ pub fn song_edit(props: &Props) -> Html {
                 .unchecked_into::<HtmlInputElement>()
                 .value()
                 .parse::<i32>()
-                .unwrap();
+                .unwrap_or(0);
 
             song.set(Song {
                 album_id,
@@ -169,20 +295,42 @@ pub fn song_edit(props: &Props) -> Html {
         on_primary = theme_style.on_primary
     );
 
+    if *conflict {
+        return html! {
+            <div class={ style }>
+                <ConflictNotice what="song" onreload={ reload } onoverwrite={ overwrite } />
+            </div>
+        };
+    }
+
     html! {
         <div class={ style }>
             <div>
                 <label>{"Name"}</label>
                 <input type="text" value={song.name.clone()} onchange={name_onchange} />
+                <FieldErrorText errors={ (*errors).clone() } field="name" />
             </div>
             <div>
                 <label>{"Length (seconds)"}</label>
                 <input type="text" value={song.length_secs.to_string()} onchange={length_onchange} />
+                <FieldErrorText errors={ (*errors).clone() } field="length_secs" />
             </div>
             <div>
                 <label>{"Album ID"}</label>
                 <input type="text" value={song.album_id.to_string()} onchange={album_onchange} />
+                <FieldErrorText errors={ (*errors).clone() } field="album_id" />
+            </div>
+            <div>
+                <label>{"Disc"}</label>
+                <input type="text" value={song.disc_number.to_string()} onchange={disc_onchange} />
+                <FieldErrorText errors={ (*errors).clone() } field="disc_number" />
+            </div>
+            <div>
+                <label>{"Track"}</label>
+                <input type="text" value={song.track_number.to_string()} onchange={track_onchange} />
+                <FieldErrorText errors={ (*errors).clone() } field="track_number" />
             </div>
+            <FieldErrorText errors={ (*errors).clone() } field="form" />
             <button onclick={save}>{"Save"}</button>
         </div>
     }
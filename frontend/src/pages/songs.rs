@@ -1,37 +1,153 @@
-use crate::components::SongView;
-use entities::song::Model as Song;
+use crate::{components::SongView, theme::Theme};
+use entities::{
+    pagination::Page, sea_orm_active_enums::Role, song::Model as Song, user::Model as User,
+    validation::FieldError,
+};
 use gloo_net::http::Request;
+use serde::Deserialize;
+use stylist::yew::use_style;
+use wasm_bindgen::JsCast;
+use web_sys::{FormData, HtmlInputElement};
 use yew::prelude::*;
 
+/// Outcome of one uploaded file
+#[derive(Deserialize)]
+struct UploadedFile {
+    row: usize,
+    #[serde(default)]
+    errors: Vec<FieldError>,
+}
+
+/// Outcome of an upload of audio files
+#[derive(Deserialize)]
+struct UploadReport {
+    committed: bool,
+    rows: Vec<UploadedFile>,
+}
+
 #[function_component(SongsPage)]
 pub fn songs() -> Html {
     let songs = use_state(|| Vec::new());
+    let next = use_state(|| Some("/song/all".to_string()));
 
-    {
+    let load_more = {
         let songs = songs.clone();
-        use_effect_with_deps(
-            move |_| {
-                wasm_bindgen_futures::spawn_local(async move {
-                    let resp = Request::get("/api/song/all")
-                        .send()
-                        .await
-                        .expect("Failed to fetch songs")
-                        .json::<Vec<Song>>()
-                        .await
-                        .expect("Failed to parse songs");
-
-                    songs.set(resp);
-                })
-            },
-            (),
-        );
+        let next = next.clone();
+        Callback::from(move |_| {
+            let Some(link) = (*next).clone() else {
+                return;
+            };
+            let songs = songs.clone();
+            let next = next.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let resp = Request::get(&format!("/api{}", link))
+                    .send()
+                    .await
+                    .expect("Failed to fetch songs")
+                    .json::<Page<Song>>()
+                    .await
+                    .expect("Failed to parse songs");
+
+                let mut all = (*songs).clone();
+                all.extend(resp.items);
+                songs.set(all);
+                next.set(resp.next);
+            });
+        })
+    };
+
+    {
+        let load_more = load_more.clone();
+        use_effect_with_deps(move |_| load_more.emit(()), ());
     }
 
+    let upload_songs = Callback::from(move |event: Event| {
+        let input = event.target().unwrap().unchecked_into::<HtmlInputElement>();
+        let Some(files) = input.files() else {
+            return;
+        };
+        let form = FormData::new().unwrap();
+        for index in 0..files.length() {
+            form.append_with_blob("files", &files.get(index).unwrap())
+                .unwrap();
+        }
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let resp = Request::post("/api/song/upload")
+                .body(form)
+                .send()
+                .await
+                .expect("Failed to send request to upload songs");
+            let window = web_sys::window().unwrap();
+
+            match resp.json::<UploadReport>().await {
+                Ok(report) if report.committed => {
+                    let _ = window.location().reload();
+                }
+                Ok(report) => {
+                    let rejections = report
+                        .rows
+                        .iter()
+                        .flat_map(|file| {
+                            file.errors.iter().map(move |error| {
+                                format!("File {}: {} {}", file.row, error.field, error.message)
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    let _ = window.alert_with_message(&format!(
+                        "Nothing was uploaded:\n{}",
+                        rejections.join("\n")
+                    ));
+                }
+                Err(_) => {
+                    let _ = window.alert_with_message("The songs could not be uploaded");
+                }
+            }
+        });
+    });
+
+    let role = use_context::<UseStateHandle<Option<User>>>()
+        .expect("No context found")
+        .as_ref()
+        .map(|user| user.role);
+    let theme = use_context::<UseStateHandle<Theme>>().expect("No context found");
+    let theme_style = theme.get_theme();
+
+    let load_more_style = use_style!(
+        r#"
+            background-color: ${surface};
+            border-radius: 10px;
+            border: 2px solid ${outline};
+            padding: 5px 10px;
+            color: ${on_surface};
+
+            :hover {
+                background-color: ${primary};
+                color: ${on_primary};
+                cursor: pointer;
+            }
+        "#,
+        outline = theme_style.outline,
+        surface = theme_style.surface,
+        on_surface = theme_style.on_surface,
+        primary = theme_style.primary,
+        on_primary = theme_style.on_primary,
+    );
+
     html! {
         <div>
+            if role >= Some(Role::Editor) {
+                <label>
+                    { "Upload songs " }
+                    <input type="file" multiple={true} accept=".mp3,.flac,.ogg,audio/mpeg,audio/flac,audio/ogg" onchange={ upload_songs } />
+                </label>
+            }
             {for songs.iter().map(|song| html! {
                 <SongView id={song.id} />
             })}
+            if next.is_some() {
+                <button class={ load_more_style } onclick={ load_more.reform(|_| ()) }>{ "Load more" }</button>
+            }
         </div>
     }
 }
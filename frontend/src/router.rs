@@ -1,4 +1,4 @@
-use crate::components::{AlbumView, ArtistView};
+use crate::components::{AlbumView, ArtistView, LabelView, PlaylistView};
 use crate::pages::*;
 use stylist::css;
 use yew::prelude::*;
@@ -8,6 +8,8 @@ use yew_router::prelude::*;
 pub enum Route {
     #[at("/")]
     Home,
+    #[at("/login")]
+    Login,
 
     #[at("/artist/:id")]
     Artist { id: i32 },
@@ -29,6 +31,9 @@ pub enum Route {
     #[at("/album/artist/:album_id")]
     AlbumArtist { album_id: i32 },
 
+    #[at("/label/:id")]
+    Label { id: i32 },
+
     #[at("/song/create/:album_id")]
     SongCreate { album_id: i32 },
     #[at("/song/edit/:id")]
@@ -36,6 +41,11 @@ pub enum Route {
     #[at("/songs")]
     Songs,
 
+    #[at("/playlist/:id")]
+    Playlist { id: i32 },
+    #[at("/playlists")]
+    Playlists,
+
     #[not_found]
     #[at("/404")]
     NotFound,
@@ -44,6 +54,7 @@ pub enum Route {
 pub fn switch(route: Route) -> Html {
     match route {
         Route::Home => html! { <HomePage /> },
+        Route::Login => html! { <LoginPage /> },
 
         Route::Artist { id } => html! { <ArtistView id={id} full={true} /> },
         Route::ArtistEdit { id } => html! { <ArtistEdit id={id} /> },
@@ -56,10 +67,15 @@ pub fn switch(route: Route) -> Html {
         Route::Albums => html! { <AlbumsPage /> },
         Route::AlbumArtist { album_id } => html! { <AlbumArtistCreate album_id={album_id} />},
 
+        Route::Label { id } => html! { <LabelView id={id} /> },
+
         Route::SongCreate { album_id } => html! { <SongCreate album_id={album_id} /> },
         Route::SongEdit { id } => html! { <SongEdit id={id} /> },
         Route::Songs => html! { <SongsPage /> },
 
+        Route::Playlist { id } => html! { <PlaylistView id={id} /> },
+        Route::Playlists => html! { <PlaylistsPage /> },
+
         Route::NotFound => html! {
             <div class={ css!("margin: 10px;") }>
                 { "Uh-oh... Looks like you took a wrong turn!" }
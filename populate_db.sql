@@ -1,14 +1,44 @@
 -- clear database
+DELETE FROM song_credit;
 DELETE FROM song;
+DELETE FROM artist_membership;
+DELETE FROM person;
+DELETE FROM album_label;
+DELETE FROM label;
+DELETE FROM album_genre;
+DELETE FROM artist_genre;
 DELETE FROM album;
 DELETE FROM album_artist_mtm;
 DELETE FROM artist;
+DELETE FROM genre;
 
 -- populate database
-INSERT INTO artist (id, name, date_formed, genre)
+INSERT INTO genre (id, name, parent_id)
 VALUES
-  (1, 'Awesome Band', '2010-01-01', 'rock'),
-  (2, 'Popular Artist', '2000-01-01', 'pop');
+  (1, 'Rock', NULL),
+  (2, 'Pop', NULL),
+  (3, 'Hard rock', 1);
+
+INSERT INTO artist (id, name, date_formed)
+VALUES
+  (1, 'Awesome Band', '2010-01-01'),
+  (2, 'Popular Artist', '2000-01-01');
+
+INSERT INTO artist_genre (id, artist_id, genre_id)
+VALUES
+  (1, 1, 3),
+  (2, 2, 2);
+
+INSERT INTO person (id, name)
+VALUES
+  (1, 'Jane Doe'),
+  (2, 'John Smith');
+
+INSERT INTO artist_membership (id, artist_id, person_id, role, instrument, start_date, end_date)
+VALUES
+  (1, 1, 1, 'Founder', 'Guitar', '2010-01-01', NULL),
+  (2, 1, 2, 'Member', 'Drums', '2010-01-01', '2016-06-30'),
+  (3, 2, 1, 'Guest', 'Vocals', '2018-03-01', '2019-01-01');
 
 INSERT INTO album (id, name, date_published)
 VALUES
@@ -16,27 +46,45 @@ VALUES
   (2, 'Ipsum', '2001-01-01'),
   (3, 'Dolor', '2020-01-01');
 
-INSERT INTO album_artist_mtm (id, album_id, artist_id)
+INSERT INTO label (id, name, country, founded, parent_id)
+VALUES
+  (1, 'Big Records', 'US', '1990-05-01', NULL),
+  (2, 'Indie Imprint', 'GB', '2008-09-01', 1);
+
+INSERT INTO album_label (id, album_id, label_id, catalog_number)
 VALUES
-  (1, (SELECT id FROM album WHERE name='Lorem'), (SELECT id FROM artist WHERE name='Awesome Band')),
-  (2, (SELECT id FROM album WHERE name='Ipsum'), (SELECT id FROM artist WHERE name='Popular Artist')),
-  (3, (SELECT id FROM album WHERE name='Dolor'), (SELECT id FROM artist WHERE name='Awesome Band')),
-  (4, (SELECT id FROM album WHERE name='Dolor'), (SELECT id FROM artist WHERE name='Popular Artist'));
+  (1, 1, 2, 'II-001'),
+  (2, 2, 1, 'BR-2001'),
+  (3, 3, 1, 'BR-2020'),
+  (4, 3, 2, NULL);
 
-INSERT INTO song (id, name, length_secs, album_id)
+INSERT INTO album_artist_mtm (id, album_id, artist_id, position)
 VALUES
-  (1, 'The Quick', 10, 1),
-  (2, 'Brown Fox', 20, 1),
-  (3, 'Jumps Over', 30, 1),
-  (4, 'The Lazy', 40, 1),
-  (5, 'Dog', 50, 1),
+  (1, (SELECT id FROM album WHERE name='Lorem'), (SELECT id FROM artist WHERE name='Awesome Band'), 0),
+  (2, (SELECT id FROM album WHERE name='Ipsum'), (SELECT id FROM artist WHERE name='Popular Artist'), 0),
+  (3, (SELECT id FROM album WHERE name='Dolor'), (SELECT id FROM artist WHERE name='Awesome Band'), 0),
+  (4, (SELECT id FROM album WHERE name='Dolor'), (SELECT id FROM artist WHERE name='Popular Artist'), 1);
 
-  (6, 'Intro', 60, 2),
-  (7, 'Best Song', 70, 2),
-  (8, 'Interesting...', 80, 2),
-  (9, 'Good Vibes', 90, 2),
-  (10, 'Nice Ballad', 100, 2),
-  (11, 'Outro', 110, 2),
+INSERT INTO song (id, name, length_secs, album_id, disc_number, track_number)
+VALUES
+  (1, 'The Quick', 10, 1, 1, 1),
+  (2, 'Brown Fox', 20, 1, 1, 2),
+  (3, 'Jumps Over', 30, 1, 1, 3),
+  (4, 'The Lazy', 40, 1, 1, 4),
+  (5, 'Dog', 50, 1, 1, 5),
 
-  (12, 'Best Collab Song Ever', 120, 3),
-  (13, 'Best Collab Song Ever (Remix)', 130, 3);
+  (6, 'Intro', 60, 2, 1, 1),
+  (7, 'Best Song', 70, 2, 1, 2),
+  (8, 'Interesting...', 80, 2, 1, 3),
+  (9, 'Good Vibes', 90, 2, 1, 4),
+  (10, 'Nice Ballad', 100, 2, 1, 5),
+  (11, 'Outro', 110, 2, 1, 6),
+
+  (12, 'Best Collab Song Ever', 120, 3, 1, 1),
+  (13, 'Best Collab Song Ever (Remix)', 130, 3, 1, 2);
+
+INSERT INTO song_credit (id, song_id, artist_id, role, position, join_phrase)
+VALUES
+  (1, 12, 2, 'featured', 0, 'feat.'),
+  (2, 13, 1, 'remixer', 0, 'remixed by'),
+  (3, 5, 2, 'composer', 0, NULL);